@@ -65,13 +65,20 @@ use jj_lib::config::ConfigNamePathBuf;
 use jj_lib::config::ConfigSource;
 use jj_lib::config::StackedConfig;
 use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::conflicts::UnparsedConflictHunk;
+use jj_lib::conflicts::UnparsedConflictReason;
+use jj_lib::file_util::expand_home_path;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
+use jj_lib::git_lfs::LfsObjectStore;
 use jj_lib::gitignore::GitIgnoreError;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::id_prefix::IdPrefixContext;
+use jj_lib::mailmap::LazyMailmap;
+use jj_lib::mailmap::Mailmap;
 use jj_lib::matchers::Matcher;
+use jj_lib::merge::Merge;
 use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId as _;
@@ -137,6 +144,7 @@ use jj_lib::workspace::Workspace;
 use jj_lib::workspace::WorkspaceLoadError;
 use jj_lib::workspace::WorkspaceLoader;
 use jj_lib::workspace::WorkspaceLoaderFactory;
+use pollster::FutureExt as _;
 use tracing::instrument;
 use tracing_chrome::ChromeLayerBuilder;
 use tracing_subscriber::prelude::*;
@@ -345,8 +353,9 @@ impl CommandHelper {
     /// Settings for the current command and workspace.
     ///
     /// This may be different from the settings for new workspace created by
-    /// e.g. `jj git init`. There may be conditional variables and repo config
-    /// `.jj/repo/config.toml` loaded for the cwd workspace.
+    /// e.g. `jj git init`. There may be conditional variables, repo config
+    /// `.jj/repo/config.toml`, and workspace config `.jj/workspace-config.toml`
+    /// loaded for the cwd workspace.
     pub fn settings(&self) -> &UserSettings {
         &self.data.settings
     }
@@ -361,6 +370,8 @@ impl CommandHelper {
         let repo_path = workspace_root.join(".jj").join("repo");
         config_env.reset_repo_path(&repo_path);
         config_env.reload_repo_config(&mut raw_config)?;
+        config_env.reset_workspace_path(workspace_root);
+        config_env.reload_workspace_config(&mut raw_config)?;
         let mut config = config_env.resolve_config(&raw_config)?;
         // No migration messages here, which would usually be emitted before.
         jj_lib::config::migrate(&mut config, &self.data.config_migrations)?;
@@ -788,6 +799,9 @@ pub struct WorkspaceCommandEnvironment {
     immutable_heads_expression: Rc<UserRevsetExpression>,
     short_prefixes_expression: Option<Rc<UserRevsetExpression>>,
     conflict_marker_style: ConflictMarkerStyle,
+    show_conflict_labels: bool,
+    mailmap_file: Option<PathBuf>,
+    mailmap: Option<LazyMailmap>,
 }
 
 impl WorkspaceCommandEnvironment {
@@ -810,9 +824,13 @@ impl WorkspaceCommandEnvironment {
             immutable_heads_expression: RevsetExpression::root(),
             short_prefixes_expression: None,
             conflict_marker_style: settings.get("ui.conflict-marker-style")?,
+            show_conflict_labels: settings.get_bool("ui.show-conflict-labels")?,
+            mailmap_file: None,
+            mailmap: None,
         };
         env.immutable_heads_expression = env.load_immutable_heads_expression(ui)?;
         env.short_prefixes_expression = env.load_short_prefixes_expression(ui)?;
+        env.mailmap_file = env.load_mailmap_file(ui, workspace.workspace_root())?;
         Ok(env)
     }
 
@@ -843,6 +861,7 @@ impl WorkspaceCommandEnvironment {
             date_pattern_context: now.into(),
             extensions: self.command.revset_extensions(),
             workspace: Some(workspace_context),
+            mailmap: self.mailmap.as_ref(),
         }
     }
 
@@ -873,6 +892,80 @@ impl WorkspaceCommandEnvironment {
         self.conflict_marker_style
     }
 
+    /// Labels describing where the terms of the commit's conflicts came from,
+    /// if they should be shown
+    pub fn conflict_labels(&self, commit: &Commit) -> Option<Merge<String>> {
+        if self.show_conflict_labels {
+            commit.conflict_labels()
+        } else {
+            None
+        }
+    }
+
+    /// Mailmap applied to author and committer signatures, if any. It's
+    /// loaded when it's first used.
+    pub fn mailmap(&self) -> Option<&Arc<Mailmap>> {
+        self.mailmap.as_ref().and_then(|mailmap| mailmap.get())
+    }
+
+    /// Resolves the `mailmap.file` setting. Warns if the file doesn't exist,
+    /// in which case the mailmap will be disabled.
+    fn load_mailmap_file(
+        &self,
+        ui: &Ui,
+        workspace_root: &Path,
+    ) -> Result<Option<PathBuf>, CommandError> {
+        let Some(path) = self.settings.get_string("mailmap.file").optional()? else {
+            return Ok(None);
+        };
+        // Relative paths are resolved from the workspace root like the
+        // ".mailmap" file.
+        let path = workspace_root.join(expand_home_path(&path));
+        if !path.exists() && self.settings.get_bool("mailmap.enabled")? {
+            writeln!(
+                ui.warning_default(),
+                "Mailmap file {} doesn't exist. The mailmap is disabled.",
+                path.display()
+            )?;
+        }
+        Ok(Some(path))
+    }
+
+    /// Sets up the mailmap to be loaded from the `mailmap.file` setting, or
+    /// from the `.mailmap` file in the working-copy commit, when it's first
+    /// used. Failures to read the mailmap are logged and ignored.
+    fn load_mailmap(&self, repo: &ReadonlyRepo) -> Result<Option<LazyMailmap>, CommandError> {
+        if !self.settings.get_bool("mailmap.enabled")? {
+            return Ok(None);
+        }
+        if let Some(path) = &self.mailmap_file {
+            if !path.exists() {
+                return Ok(None);
+            }
+            let path = path.clone();
+            Ok(Some(LazyMailmap::new(move || {
+                let text = std::fs::read_to_string(&path)
+                    .inspect_err(|err| tracing::warn!(?err, ?path, "failed to read mailmap file"))
+                    .ok()?;
+                Some(Mailmap::parse(&text))
+            })))
+        } else if let Some(wc_commit_id) = repo.view().get_wc_commit_id(&self.workspace_name) {
+            let store = repo.store().clone();
+            let wc_commit_id = wc_commit_id.clone();
+            Ok(Some(LazyMailmap::new(move || {
+                let load = || -> BackendResult<Mailmap> {
+                    let wc_commit = store.get_commit(&wc_commit_id)?;
+                    Mailmap::from_tree(&wc_commit.tree()?).block_on()
+                };
+                load()
+                    .inspect_err(|err| tracing::warn!(?err, "failed to read .mailmap file"))
+                    .ok()
+            })))
+        } else {
+            Ok(None)
+        }
+    }
+
     fn load_immutable_heads_expression(
         &self,
         ui: &Ui,
@@ -1062,6 +1155,8 @@ impl WorkspaceCommandHelper {
             loaded_at_head && !env.command.global_args().ignore_working_copy;
         let working_copy_shared_with_git =
             crate::git_util::is_colocated_git_workspace(&workspace, &repo);
+        let mut env = env;
+        env.mailmap = env.load_mailmap(&repo)?;
 
         let helper = Self {
             workspace,
@@ -1125,6 +1220,12 @@ impl WorkspaceCommandHelper {
         if self.working_copy_shared_with_git {
             self.import_git_refs(ui).map_err(snapshot_command_error)?;
         }
+
+        // The ".mailmap" file may have been updated by the snapshot.
+        self.env.mailmap = self
+            .env
+            .load_mailmap(self.user_repo.repo.as_ref())
+            .map_err(snapshot_command_error)?;
         Ok(stats)
     }
 
@@ -1255,6 +1356,8 @@ impl WorkspaceCommandHelper {
     pub fn checkout_options(&self) -> CheckoutOptions {
         CheckoutOptions {
             conflict_marker_style: self.env.conflict_marker_style(),
+            lfs_store: self.lfs_store(),
+            show_conflict_labels: self.env.show_conflict_labels,
         }
     }
 
@@ -1402,6 +1505,9 @@ to the current parents may contain changes from multiple commits.
             max_new_file_size = u64::MAX;
         }
         let conflict_marker_style = self.env.conflict_marker_style();
+        let validate_conflict_markers = self
+            .settings()
+            .get_bool("snapshot.validate-conflict-markers")?;
         Ok(SnapshotOptions {
             base_ignores,
             fsmonitor_settings,
@@ -1409,9 +1515,29 @@ to the current parents may contain changes from multiple commits.
             start_tracking_matcher,
             max_new_file_size,
             conflict_marker_style,
+            lfs_store: self.lfs_store(),
+            validate_conflict_markers,
         })
     }
 
+    /// Returns the store of Git LFS objects if the repo is backed by Git and
+    /// `git.lfs` is enabled.
+    #[cfg(feature = "git")]
+    pub fn lfs_store(&self) -> Option<Arc<LfsObjectStore>> {
+        if !self.settings().get_bool("git.lfs").unwrap_or(false) {
+            return None;
+        }
+        let git_backend = jj_lib::git::get_git_backend(self.repo().store()).ok()?;
+        Some(Arc::new(LfsObjectStore::for_git_dir(
+            git_backend.git_repo_path(),
+        )))
+    }
+
+    #[cfg(not(feature = "git"))]
+    pub fn lfs_store(&self) -> Option<Arc<LfsObjectStore>> {
+        None
+    }
+
     pub(crate) fn path_converter(&self) -> &RepoPathUiConverter {
         self.env.path_converter()
     }
@@ -1547,20 +1673,26 @@ to the current parents may contain changes from multiple commits.
         tool_name: Option<&str>,
     ) -> Result<MergeEditor, MergeToolConfigError> {
         let conflict_marker_style = self.env.conflict_marker_style();
-        if let Some(name) = tool_name {
+        let editor = if let Some(name) = tool_name {
             MergeEditor::with_name(
                 name,
                 self.settings(),
                 self.path_converter().clone(),
                 conflict_marker_style,
-            )
+            )?
         } else {
             MergeEditor::from_settings(
                 ui,
                 self.settings(),
                 self.path_converter().clone(),
                 conflict_marker_style,
-            )
+            )?
+        };
+        // Only the builtin tool opens the text editor
+        if editor.is_builtin() {
+            Ok(editor.with_text_editor(self.text_editor()?))
+        } else {
+            Ok(editor)
         }
     }
 
@@ -2060,7 +2192,7 @@ See https://jj-vcs.github.io/jj/latest/working-copy/#stale-working-copy \
                         formatter.labeled("warning").with_heading("Warning: "),
                         "There are unresolved conflicts at these paths:"
                     )?;
-                    print_conflicted_paths(conflicts, formatter.as_mut(), self)?;
+                    print_conflicted_paths(conflicts, None, formatter.as_mut(), self)?;
                 }
             }
         }
@@ -2140,6 +2272,10 @@ See https://jj-vcs.github.io/jj/latest/working-copy/#stale-working-copy \
             }
             let stats = jj_lib::git::export_refs(tx.repo_mut())?;
             crate::git_util::print_git_export_stats(ui, &stats)?;
+        } else if jj_lib::git::get_git_backend(tx.repo().store()).is_ok() {
+            // The rewrites are lost once the transaction is committed, so the
+            // notes have to be copied now even if the refs aren't exported.
+            jj_lib::git::copy_notes_of_rewritten_commits(tx.repo_mut())?;
         }
 
         self.user_repo = ReadonlyUserRepo::new(tx.commit(description)?);
@@ -2546,10 +2682,14 @@ jj git init --colocate",
                 user_error(message)
             }
         }
-        WorkspaceLoadError::RepoDoesNotExist(repo_dir) => user_error(format!(
-            "The repository directory at {} is missing. Was it moved?",
-            repo_dir.display(),
-        )),
+        WorkspaceLoadError::RepoDoesNotExist(repo_dir) => user_error_with_hint(
+            format!(
+                "The repository directory at {} is missing. Was it moved?",
+                repo_dir.display(),
+            ),
+            "If the workspace containing the repository was moved, run `jj workspace repair \
+             <path-to-that-workspace>` in this workspace.",
+        ),
         WorkspaceLoadError::StoreLoadError(err @ StoreLoadError::UnsupportedType { .. }) => {
             internal_error_with_message(
                 "This version of the jj binary doesn't support this type of repo",
@@ -2560,6 +2700,7 @@ jj git init --colocate",
             err @ (StoreLoadError::ReadError { .. } | StoreLoadError::Backend(_)),
         ) => internal_error_with_message("The repository appears broken or inaccessible", err),
         WorkspaceLoadError::StoreLoadError(StoreLoadError::Signing(err)) => user_error(err),
+        WorkspaceLoadError::StoreLoadError(StoreLoadError::Config(err)) => err.into(),
         WorkspaceLoadError::WorkingCopyState(err) => internal_error(err),
         WorkspaceLoadError::NonUnicodePath | WorkspaceLoadError::Path(_) => user_error(err),
     }
@@ -2643,6 +2784,7 @@ pub fn print_updated_commits<'a>(
 #[instrument(skip_all)]
 pub fn print_conflicted_paths(
     conflicts: Vec<(RepoPathBuf, BackendResult<MergedTreeValue>)>,
+    conflict_labels: Option<&Merge<String>>,
     formatter: &mut dyn Formatter,
     workspace_command: &WorkspaceCommandHelper,
 ) -> Result<(), CommandError> {
@@ -2658,7 +2800,14 @@ pub fn print_conflicted_paths(
     for ((_, conflict), formatted_path) in std::iter::zip(conflicts, formatted_paths) {
         // TODO: Display the error for the path instead of failing the whole command if
         // `conflict` is an error?
-        let conflict = conflict?.simplify();
+        let conflict = conflict?;
+        let (conflict, labels) = match conflict_labels {
+            Some(labels) if labels.as_slice().len() == conflict.as_slice().len() => {
+                let (conflict, labels) = conflict.simplify_with(labels.clone());
+                (conflict, Some(labels))
+            }
+            _ => (conflict.simplify(), None),
+        };
         let sides = conflict.num_sides();
         let n_adds = conflict.adds().flatten().count();
         let deletions = sides - n_adds;
@@ -2730,6 +2879,17 @@ pub fn print_conflicted_paths(
             io::Result::Ok(())
         })?;
         writeln!(formatter)?;
+        if let Some(labels) = labels.filter(|labels| !labels.is_resolved()) {
+            let num_removes = labels.removes().len();
+            for (index, label) in labels.iter().enumerate() {
+                let term_name = match (index % 2, num_removes) {
+                    (0, _) => format!("side #{}", index / 2 + 1),
+                    (_, 1) => "base".to_owned(),
+                    _ => format!("base #{}", index / 2 + 1),
+                };
+                writeln!(formatter, "  {term_name}: {label}")?;
+            }
+        }
     }
     Ok(())
 }
@@ -2777,12 +2937,53 @@ pub fn print_untracked_files(
     Ok(())
 }
 
+/// Print a warning to the user, listing conflicted files that were left
+/// unchanged because their conflict markers couldn't be parsed
+fn print_unparsed_conflicts(
+    ui: &Ui,
+    unparsed_conflicts: &BTreeMap<RepoPathBuf, Vec<UnparsedConflictHunk>>,
+    path_converter: &RepoPathUiConverter,
+) -> io::Result<()> {
+    if unparsed_conflicts.is_empty() {
+        return Ok(());
+    }
+    writeln!(
+        ui.warning_default(),
+        "Kept some files conflicted because their conflict markers couldn't be parsed:"
+    )?;
+    let mut formatter = ui.stderr_formatter();
+    for (path, hunks) in unparsed_conflicts {
+        let ui_path = path_converter.format_file_path(path);
+        for hunk in hunks {
+            let lines = if hunk.first_line == hunk.last_line {
+                format!("line {}", hunk.first_line)
+            } else {
+                format!("lines {}-{}", hunk.first_line, hunk.last_line)
+            };
+            let message = match hunk.reason {
+                UnparsedConflictReason::MissingEnd => "conflict start marker without end marker",
+                UnparsedConflictReason::MissingStart => "conflict end marker without start marker",
+                UnparsedConflictReason::Malformed => "malformed conflict",
+            };
+            writeln!(formatter, "  {ui_path} ({lines}): {message}")?;
+        }
+    }
+    drop(formatter);
+    writeln!(
+        ui.hint_default(),
+        "Fix or remove the conflict markers. Changes to these files won't be snapshotted until \
+         the markers can be parsed."
+    )?;
+    Ok(())
+}
+
 pub fn print_snapshot_stats(
     ui: &Ui,
     stats: &SnapshotStats,
     path_converter: &RepoPathUiConverter,
 ) -> io::Result<()> {
     print_untracked_files(ui, &stats.untracked_paths, path_converter)?;
+    print_unparsed_conflicts(ui, &stats.unparsed_conflicts, path_converter)?;
 
     let large_files_sizes = stats
         .untracked_paths
@@ -3851,6 +4052,8 @@ impl CliRunner {
         if let Ok(loader) = &maybe_cwd_workspace_loader {
             config_env.reset_repo_path(loader.repo_path());
             config_env.reload_repo_config(&mut raw_config)?;
+            config_env.reset_workspace_path(loader.workspace_root());
+            config_env.reload_workspace_config(&mut raw_config)?;
         }
         let mut config = config_env.resolve_config(&raw_config)?;
         migrate_config(&mut config)?;
@@ -3901,6 +4104,8 @@ impl CliRunner {
                 .map_err(|err| map_workspace_load_error(err, Some(path)))?;
             config_env.reset_repo_path(loader.repo_path());
             config_env.reload_repo_config(&mut raw_config)?;
+            config_env.reset_workspace_path(loader.workspace_root());
+            config_env.reload_workspace_config(&mut raw_config)?;
             Ok(loader)
         } else {
             maybe_cwd_workspace_loader
@@ -33,6 +33,7 @@ use jj_lib::fileset::FilePatternParseError;
 use jj_lib::fileset::FilesetParseError;
 use jj_lib::fileset::FilesetParseErrorKind;
 use jj_lib::fix::FixError;
+use jj_lib::git_lfs::GitLfsError;
 use jj_lib::gitignore::GitIgnoreError;
 use jj_lib::op_heads_store::OpHeadResolutionError;
 use jj_lib::op_heads_store::OpHeadsStoreError;
@@ -57,6 +58,8 @@ use jj_lib::working_copy::ResetError;
 use jj_lib::working_copy::SnapshotError;
 use jj_lib::working_copy::WorkingCopyStateError;
 use jj_lib::workspace::WorkspaceInitError;
+use jj_lib::workspace::WorkspaceRelocateError;
+use jj_lib::workspace_store::WorkspaceStoreError;
 use thiserror::Error;
 
 use crate::cli_util::short_operation_hash;
@@ -354,10 +357,31 @@ impl From<WorkspaceInitError> for CommandError {
                 internal_error_with_message("Failed to access the repository", err)
             }
             WorkspaceInitError::SignInit(err) => user_error(err),
+            WorkspaceInitError::WorkspaceStore(err) => err.into(),
         }
     }
 }
 
+impl From<WorkspaceRelocateError> for CommandError {
+    fn from(err: WorkspaceRelocateError) -> Self {
+        match err {
+            WorkspaceRelocateError::DestinationExists(_)
+            | WorkspaceRelocateError::NoWorkspaceHere(_)
+            | WorkspaceRelocateError::NoRepoHere(_)
+            | WorkspaceRelocateError::RepoInWorkspace(_)
+            | WorkspaceRelocateError::NonUnicodePath
+            | WorkspaceRelocateError::Path(_) => user_error(err),
+            WorkspaceRelocateError::WorkspaceStore(err) => err.into(),
+        }
+    }
+}
+
+impl From<WorkspaceStoreError> for CommandError {
+    fn from(err: WorkspaceStoreError) -> Self {
+        internal_error_with_message("Failed to access the workspace store", err)
+    }
+}
+
 impl From<OpHeadResolutionError> for CommandError {
     fn from(err: OpHeadResolutionError) -> Self {
         match err {
@@ -410,7 +434,10 @@ impl From<ResetError> for CommandError {
 
 impl From<DiffEditError> for CommandError {
     fn from(err: DiffEditError) -> Self {
-        user_error_with_message("Failed to edit diff", err)
+        match err {
+            DiffEditError::Backend(err) => err.into(),
+            _ => user_error_with_message("Failed to edit diff", err),
+        }
     }
 }
 
@@ -421,6 +448,9 @@ impl From<DiffRenderError> for CommandError {
             DiffRenderError::Backend(err) => err.into(),
             DiffRenderError::AccessDenied { .. } => user_error(err),
             DiffRenderError::InvalidRepoPath(_) => user_error(err),
+            DiffRenderError::GitAttributes(_) => {
+                user_error_with_message("Failed to process .gitattributes.", err)
+            }
             DiffRenderError::Io(err) => err.into(),
         }
     }
@@ -481,10 +511,13 @@ impl From<TempTextEditError> for CommandError {
 
 #[cfg(feature = "git")]
 mod git {
+    use jj_lib::git::GitBundleError;
     use jj_lib::git::GitExportError;
     use jj_lib::git::GitFetchError;
     use jj_lib::git::GitFetchPrepareError;
     use jj_lib::git::GitImportError;
+    use jj_lib::git::GitLfsTransportError;
+    use jj_lib::git::GitNotesError;
     use jj_lib::git::GitPushError;
     use jj_lib::git::GitRemoteManagementError;
     use jj_lib::git::GitResetHeadError;
@@ -538,6 +571,8 @@ jj currently does not support partial clones. To use jj with this repository, tr
                 ),
                 GitFetchError::InvalidBranchPattern(_) => user_error(err),
                 #[cfg(feature = "git2")]
+                GitFetchError::UnsupportedDepth(_) => user_error(err),
+                #[cfg(feature = "git2")]
                 GitFetchError::Git2(err) => map_git2_error(err),
                 GitFetchError::Subprocess(_) => user_error(err),
             }
@@ -550,6 +585,7 @@ jj currently does not support partial clones. To use jj with this repository, tr
                 #[cfg(feature = "git2")]
                 GitFetchPrepareError::Git2(err) => map_git2_error(err),
                 GitFetchPrepareError::UnexpectedBackend(_) => user_error(err),
+                GitFetchPrepareError::Backend(err) => err.into(),
             }
         }
     }
@@ -565,6 +601,7 @@ jj currently does not support partial clones. To use jj with this repository, tr
                 #[cfg(feature = "git2")]
                 GitPushError::Git2(err) => map_git2_error(err),
                 GitPushError::Subprocess(_) => user_error(err),
+                GitPushError::Notes(err) => err.into(),
                 GitPushError::UnexpectedBackend(_) => user_error(err),
             }
         }
@@ -576,6 +613,40 @@ jj currently does not support partial clones. To use jj with this repository, tr
         }
     }
 
+    impl From<GitLfsTransportError> for CommandError {
+        fn from(err: GitLfsTransportError) -> Self {
+            match err {
+                GitLfsTransportError::UnsupportedUrl(_) => user_error_with_hint(
+                    err,
+                    "Only Git LFS servers in the local file system are supported. Set \
+                     `remote.<name>.lfsurl` in the Git config to use one.",
+                ),
+                GitLfsTransportError::InternalGitError(_) => internal_error(err),
+                _ => user_error(err),
+            }
+        }
+    }
+
+    impl From<GitBundleError> for CommandError {
+        fn from(err: GitBundleError) -> Self {
+            match err {
+                GitBundleError::Io(_) => user_error(err),
+                GitBundleError::Subprocess(_) => user_error(err),
+                GitBundleError::UnexpectedBackend(_) => user_error(err),
+            }
+        }
+    }
+
+    impl From<GitNotesError> for CommandError {
+        fn from(err: GitNotesError) -> Self {
+            match err {
+                GitNotesError::Git(_) => internal_error(err),
+                GitNotesError::Backend(err) => err.into(),
+                GitNotesError::UnexpectedBackend(_) => user_error(err),
+            }
+        }
+    }
+
     impl From<GitResetHeadError> for CommandError {
         fn from(err: GitResetHeadError) -> Self {
             user_error_with_message("Failed to reset Git HEAD state", err)
@@ -696,6 +767,17 @@ impl From<WorkingCopyStateError> for CommandError {
     }
 }
 
+impl From<GitLfsError> for CommandError {
+    fn from(err: GitLfsError) -> Self {
+        match err {
+            GitLfsError::GitAttributes(_) => {
+                user_error_with_message("Failed to process .gitattributes.", err)
+            }
+            _ => internal_error_with_message("Failed to transfer Git LFS objects", err),
+        }
+    }
+}
+
 impl From<GitIgnoreError> for CommandError {
     fn from(err: GitIgnoreError) -> Self {
         user_error_with_message("Failed to process .gitignore.", err)
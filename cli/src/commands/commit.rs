@@ -25,6 +25,8 @@ use crate::complete;
 use crate::description_util::description_template;
 use crate::description_util::edit_description;
 use crate::description_util::join_message_paragraphs;
+use crate::git_util::run_commit_msg_hook;
+use crate::git_util::run_pre_commit_hook;
 use crate::text_util::parse_author;
 use crate::ui::Ui;
 
@@ -67,6 +69,11 @@ pub(crate) struct CommitArgs {
         value_parser = parse_author
     )]
     author: Option<(String, String)>,
+    /// Don't run the Git `pre-commit` and `commit-msg` hooks
+    ///
+    /// The hooks are only run if enabled by the `git.run-hooks` setting.
+    #[arg(long)]
+    no_verify: bool,
 }
 
 #[instrument(skip_all)]
@@ -81,6 +88,9 @@ pub(crate) fn cmd_commit(
         .get_wc_commit_id()
         .ok_or_else(|| user_error("This command requires a working copy"))?;
     let commit = workspace_command.repo().store().get_commit(commit_id)?;
+    if !args.no_verify {
+        run_pre_commit_hook(ui, &workspace_command)?;
+    }
     let matcher = workspace_command
         .parse_file_patterns(ui, &args.paths)?
         .to_matcher();
@@ -140,6 +150,11 @@ new working-copy commit.
         let template = description_template(ui, &tx, "", &temp_commit)?;
         edit_description(&text_editor, &template)?
     };
+    let description = if args.no_verify {
+        description
+    } else {
+        run_commit_msg_hook(ui, tx.base_workspace_helper(), &description)?
+    };
     commit_builder.set_description(description);
     let new_commit = commit_builder.write(tx.repo_mut())?;
 
@@ -33,6 +33,7 @@ use crate::description_util::edit_description;
 use crate::description_util::edit_multiple_descriptions;
 use crate::description_util::join_message_paragraphs;
 use crate::description_util::ParsedBulkEditMessage;
+use crate::git_util::run_commit_msg_hook;
 use crate::text_util::parse_author;
 use crate::ui::Ui;
 
@@ -104,6 +105,11 @@ pub(crate) struct DescribeArgs {
         value_parser = parse_author
     )]
     author: Option<(String, String)>,
+    /// Don't run the Git `commit-msg` hook
+    ///
+    /// The hook is only run if enabled by the `git.run-hooks` setting.
+    #[arg(long)]
+    no_verify: bool,
 }
 
 #[instrument(skip_all)]
@@ -238,6 +244,19 @@ pub(crate) fn cmd_describe(
         }
     };
 
+    let commit_descriptions: Vec<(_, _)> = if args.no_verify {
+        commit_descriptions
+    } else {
+        commit_descriptions
+            .into_iter()
+            .map(|(commit, description)| -> Result<_, CommandError> {
+                let description =
+                    run_commit_msg_hook(ui, tx.base_workspace_helper(), &description)?;
+                Ok((commit, description))
+            })
+            .try_collect()?
+    };
+
     // Filter out unchanged commits to avoid rebasing descendants in
     // `transform_descendants` below unnecessarily.
     let commit_descriptions: HashMap<_, _> = commit_descriptions
@@ -18,11 +18,15 @@ use std::io::Write as _;
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use jj_lib::backend::BackendResult;
+use jj_lib::conflicts::choose_materialized_conflict_marker_len;
+use jj_lib::conflicts::file_conflict_labels;
+use jj_lib::conflicts::materialize_labeled_merge_result;
 use jj_lib::conflicts::materialize_merge_result;
 use jj_lib::conflicts::materialize_tree_value;
 use jj_lib::conflicts::MaterializedTreeValue;
 use jj_lib::fileset::FilePattern;
 use jj_lib::fileset::FilesetExpression;
+use jj_lib::merge::Merge;
 use jj_lib::merge::MergedTreeValue;
 use jj_lib::repo::Repo as _;
 use jj_lib::repo_path::RepoPath;
@@ -71,6 +75,7 @@ pub(crate) fn cmd_file_show(
     let workspace_command = command.workspace_helper(ui)?;
     let commit = workspace_command.resolve_single_rev(ui, &args.revision)?;
     let tree = commit.tree()?;
+    let conflict_labels = workspace_command.env().conflict_labels(&commit);
     // TODO: No need to add special case for empty paths when switching to
     // parse_union_filesets(). paths = [] should be "none()" if supported.
     let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
@@ -84,7 +89,12 @@ pub(crate) fn cmd_file_show(
         }
         if !value.is_tree() {
             ui.request_pager();
-            write_tree_entries(ui, &workspace_command, [(path, Ok(value))])?;
+            write_tree_entries(
+                ui,
+                &workspace_command,
+                conflict_labels.as_ref(),
+                [(path, Ok(value))],
+            )?;
             return Ok(());
         }
     }
@@ -94,6 +104,7 @@ pub(crate) fn cmd_file_show(
     write_tree_entries(
         ui,
         &workspace_command,
+        conflict_labels.as_ref(),
         tree.entries_matching(matcher.as_ref()),
     )?;
     print_unmatched_explicit_paths(ui, &workspace_command, &fileset_expression, [&tree])?;
@@ -115,11 +126,13 @@ fn get_single_path(expression: &FilesetExpression) -> Option<&RepoPath> {
 fn write_tree_entries<P: AsRef<RepoPath>>(
     ui: &Ui,
     workspace_command: &WorkspaceCommandHelper,
+    conflict_labels: Option<&Merge<String>>,
     entries: impl IntoIterator<Item = (P, BackendResult<MergedTreeValue>)>,
 ) -> Result<(), CommandError> {
     let repo = workspace_command.repo();
     for (path, result) in entries {
         let value = result?;
+        let labels = conflict_labels.and_then(|labels| file_conflict_labels(&value, labels));
         let materialized = materialize_tree_value(repo.store(), path.as_ref(), value).block_on()?;
         match materialized {
             MaterializedTreeValue::Absent => panic!("absent values should be excluded"),
@@ -134,11 +147,25 @@ fn write_tree_entries<P: AsRef<RepoPath>>(
                 io::copy(&mut file.reader, &mut ui.stdout_formatter().as_mut())?;
             }
             MaterializedTreeValue::FileConflict { contents, .. } => {
-                materialize_merge_result(
-                    &contents,
-                    workspace_command.env().conflict_marker_style(),
-                    &mut ui.stdout_formatter(),
-                )?;
+                let conflict_marker_style = workspace_command.env().conflict_marker_style();
+                let merge_options = &repo.store().merge_options().file_merge_options;
+                if let Some(labels) = &labels {
+                    materialize_labeled_merge_result(
+                        &contents,
+                        merge_options,
+                        labels,
+                        conflict_marker_style,
+                        choose_materialized_conflict_marker_len(&contents),
+                        &mut ui.stdout_formatter(),
+                    )?;
+                } else {
+                    materialize_merge_result(
+                        &contents,
+                        merge_options,
+                        conflict_marker_style,
+                        &mut ui.stdout_formatter(),
+                    )?;
+                }
             }
             MaterializedTreeValue::OtherConflict { id } => {
                 ui.stdout_formatter().write_all(id.describe().as_bytes())?;
@@ -0,0 +1,696 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io;
+use std::io::Read as _;
+use std::io::Write as _;
+use std::sync::Arc;
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::backend::MergedTreeId;
+use jj_lib::backend::Timestamp;
+use jj_lib::backend::TreeValue;
+use jj_lib::merge::Merge;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::merged_tree::MergedTreeBuilder;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::store::Store;
+use pollster::FutureExt as _;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Apply patch emails as new revisions
+///
+/// Reads patches in the mbox format written by `jj git format-patch` or `git
+/// format-patch`, and creates a new revision for each of them on top of the
+/// previous one. The author, author date, and description are taken from the
+/// email.
+///
+/// If a patch doesn't apply cleanly, the files it couldn't be applied to are
+/// recorded as conflicts instead of failing.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitAmArgs {
+    /// The mbox or patch files to apply
+    ///
+    /// The patches are read from stdin if no files are given.
+    #[arg(value_name = "FILES", value_hint = clap::ValueHint::FilePath)]
+    paths: Vec<String>,
+    /// The revision to apply the first patch onto
+    #[arg(
+        long,
+        short,
+        default_value = "@",
+        value_name = "REVSET",
+        add = ArgValueCandidates::new(complete::all_revisions)
+    )]
+    destination: RevisionArg,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_git_am(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitAmArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let destination = workspace_command.resolve_single_rev(ui, &args.destination)?;
+    let mut messages = vec![];
+    if args.paths.is_empty() {
+        let mut input = vec![];
+        io::stdin().read_to_end(&mut input)?;
+        messages.extend(parse_mbox(&input)?);
+    } else {
+        for path in &args.paths {
+            let path = command.cwd().join(path);
+            let input = std::fs::read(&path).map_err(|err| {
+                user_error_with_message(format!("Failed to read patch {}", path.display()), err)
+            })?;
+            messages.extend(parse_mbox(&input)?);
+        }
+    }
+    if messages.is_empty() {
+        writeln!(ui.status(), "No patches to apply.")?;
+        return Ok(());
+    }
+
+    let mut tx = workspace_command.start_transaction();
+    let store = tx.repo().store().clone();
+    let mut parent = destination;
+    for message in &messages {
+        let tree_id = apply_file_patches(&store, &parent.tree()?, &message.files)?;
+        let mut commit_builder = tx
+            .repo_mut()
+            .new_commit(vec![parent.id().clone()], tree_id)
+            .set_description(&message.description);
+        let mut author = commit_builder.author().clone();
+        if let Some((name, email)) = &message.author {
+            author.name = name.clone();
+            author.email = email.clone();
+        }
+        if let Some(timestamp) = message.date {
+            author.timestamp = timestamp;
+        }
+        commit_builder = commit_builder.set_author(author);
+        let new_commit = commit_builder.write()?;
+        if let Some(mut formatter) = ui.status_formatter() {
+            write!(formatter, "Applied patch as ")?;
+            tx.write_commit_summary(formatter.as_mut(), &new_commit)?;
+            writeln!(formatter)?;
+        }
+        parent = new_commit;
+    }
+    tx.finish(ui, format!("apply {} patches", messages.len()))?;
+    Ok(())
+}
+
+/// A patch email.
+#[derive(Debug)]
+struct PatchMessage {
+    author: Option<(String, String)>,
+    date: Option<Timestamp>,
+    description: String,
+    files: Vec<FilePatch>,
+}
+
+/// Changes to a single file in Git's diff format.
+#[derive(Debug, Default)]
+struct FilePatch {
+    /// Path of the file before the change, or `None` if the file is added.
+    source: Option<RepoPathBuf>,
+    /// Path of the file after the change, or `None` if the file is deleted.
+    target: Option<RepoPathBuf>,
+    is_copy: bool,
+    new_mode: Option<String>,
+    is_binary: bool,
+    hunks: Vec<Hunk>,
+}
+
+#[derive(Debug)]
+struct Hunk {
+    /// 1-based line number of the first line of the hunk in the old file.
+    old_start: usize,
+    old_lines: Vec<Vec<u8>>,
+    new_lines: Vec<Vec<u8>>,
+}
+
+/// Splits mbox `input` into messages and parses them. Input without a "From "
+/// line is parsed as a single message.
+fn parse_mbox(input: &[u8]) -> Result<Vec<PatchMessage>, CommandError> {
+    let lines = input.split_inclusive(|&b| b == b'\n').collect_vec();
+    let mut starts = (0..lines.len())
+        .filter(|&i| {
+            lines[i].starts_with(b"From ")
+                && (i == 0 || is_blank(lines[i - 1]))
+                && lines.get(i + 1).is_some_and(|line| is_header_line(line))
+        })
+        .collect_vec();
+    if starts.first() != Some(&0) {
+        starts.insert(0, 0);
+    }
+    starts.push(lines.len());
+    starts
+        .iter()
+        .tuple_windows()
+        .map(|(&start, &end)| &lines[start..end])
+        .filter(|lines| lines.iter().any(|line| !is_blank(line)))
+        .map(|lines| {
+            let lines = match lines.first() {
+                Some(line) if line.starts_with(b"From ") => &lines[1..],
+                _ => lines,
+            };
+            parse_message(lines)
+        })
+        .try_collect()
+}
+
+fn parse_message(lines: &[&[u8]]) -> Result<PatchMessage, CommandError> {
+    // Unfold the headers. Patches without headers are accepted too.
+    let mut headers: Vec<(String, String)> = vec![];
+    let mut pos = 0;
+    while let Some(line) = lines.get(pos) {
+        let text = line_to_str(line);
+        if text.is_empty() {
+            pos += 1;
+            break;
+        } else if text.starts_with([' ', '\t']) && !headers.is_empty() {
+            let (_, value) = headers.last_mut().unwrap();
+            value.push(' ');
+            value.push_str(text.trim());
+        } else if let Some((name, value)) = text.split_once(':').filter(|_| is_header_line(line)) {
+            headers.push((name.to_ascii_lowercase(), value.trim().to_owned()));
+        } else {
+            break;
+        }
+        pos += 1;
+    }
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| decode_encoded_words(value))
+    };
+    let author = header("from").map(|value| parse_address(&value));
+    let date = header("date")
+        .map(|value| {
+            chrono::DateTime::parse_from_rfc2822(&value)
+                .map(Timestamp::from_datetime)
+                .map_err(|err| user_error_with_message(format!("Invalid date: {value}"), err))
+        })
+        .transpose()?;
+    let subject = header("subject").map(|value| strip_subject_prefix(&value).to_owned());
+
+    // The message body ends at the "---" line or at the first diff.
+    let body_end = lines[pos..]
+        .iter()
+        .position(|line| line_to_str(line) == "---" || line.starts_with(b"diff --git "))
+        .map_or(lines.len(), |n| pos + n);
+    let body = lines[pos..body_end]
+        .iter()
+        .map(|line| String::from_utf8_lossy(line))
+        .collect::<String>();
+    let body = body.trim();
+    let description = match (subject.as_deref().unwrap_or(""), body) {
+        ("", "") => String::new(),
+        (subject, "") => format!("{subject}\n"),
+        ("", body) => format!("{body}\n"),
+        (subject, body) => format!("{subject}\n\n{body}\n"),
+    };
+    let files = parse_file_patches(&lines[body_end..])?;
+    Ok(PatchMessage {
+        author,
+        date,
+        description,
+        files,
+    })
+}
+
+fn parse_file_patches(lines: &[&[u8]]) -> Result<Vec<FilePatch>, CommandError> {
+    let mut files = vec![];
+    let mut pos = 0;
+    while pos < lines.len() {
+        let Some(paths) = lines[pos].strip_prefix(b"diff --git ") else {
+            pos += 1;
+            continue;
+        };
+        let (source, target) = parse_git_diff_paths(line_to_str(paths))
+            .ok_or_else(|| user_error(format!("Malformed diff header: {}", line_to_str(paths))))?;
+        let mut patch = FilePatch {
+            source: Some(parse_repo_path(source)?),
+            target: Some(parse_repo_path(target)?),
+            ..FilePatch::default()
+        };
+        pos += 1;
+
+        // Extended header lines
+        while let Some(line) = lines.get(pos) {
+            if line.starts_with(b"diff --git ") || line.starts_with(b"@@ ") {
+                break;
+            }
+            let text = line_to_str(line);
+            if let Some(mode) = text.strip_prefix("new file mode ") {
+                patch.source = None;
+                patch.new_mode = Some(mode.to_owned());
+            } else if text.starts_with("deleted file mode ") {
+                patch.target = None;
+            } else if let Some(mode) = text.strip_prefix("new mode ") {
+                patch.new_mode = Some(mode.to_owned());
+            } else if let Some(path) = text.strip_prefix("rename from ") {
+                patch.source = Some(parse_repo_path(path)?);
+            } else if let Some(path) = text.strip_prefix("rename to ") {
+                patch.target = Some(parse_repo_path(path)?);
+            } else if let Some(path) = text.strip_prefix("copy from ") {
+                patch.source = Some(parse_repo_path(path)?);
+                patch.is_copy = true;
+            } else if let Some(path) = text.strip_prefix("copy to ") {
+                patch.target = Some(parse_repo_path(path)?);
+            } else if text.starts_with("Binary files ") || text == "GIT binary patch" {
+                patch.is_binary = true;
+            }
+            pos += 1;
+        }
+
+        while let Some(header) = lines.get(pos).and_then(|line| line.strip_prefix(b"@@ ")) {
+            let header = line_to_str(header);
+            let (old_start, mut old_count, mut new_count) = parse_hunk_header(header)
+                .ok_or_else(|| user_error(format!("Malformed hunk header: @@ {header}")))?;
+            let mut hunk = Hunk {
+                old_start,
+                old_lines: vec![],
+                new_lines: vec![],
+            };
+            let malformed = || user_error(format!("Malformed hunk: @@ {header}"));
+            pos += 1;
+            // The side the last line was added to, for "\ No newline at end of file"
+            let mut last_kind = b' ';
+            while let Some(&line) = lines.get(pos) {
+                let kind = match line.first() {
+                    Some(b'\\') => {
+                        if last_kind != b'+' {
+                            strip_newline(hunk.old_lines.last_mut().ok_or_else(malformed)?);
+                        }
+                        if last_kind != b'-' {
+                            strip_newline(hunk.new_lines.last_mut().ok_or_else(malformed)?);
+                        }
+                        pos += 1;
+                        continue;
+                    }
+                    _ if old_count == 0 && new_count == 0 => break,
+                    // Some mailers strip the trailing space of empty context lines.
+                    Some(b'\n' | b'\r') => b' ',
+                    Some(&kind @ (b' ' | b'-' | b'+')) => kind,
+                    _ => return Err(malformed()),
+                };
+                let content = if kind == b' ' && line[0] != b' ' {
+                    line
+                } else {
+                    &line[1..]
+                };
+                if kind != b'+' {
+                    old_count = old_count.checked_sub(1).ok_or_else(malformed)?;
+                    hunk.old_lines.push(content.to_vec());
+                }
+                if kind != b'-' {
+                    new_count = new_count.checked_sub(1).ok_or_else(malformed)?;
+                    hunk.new_lines.push(content.to_vec());
+                }
+                last_kind = kind;
+                pos += 1;
+            }
+            if old_count != 0 || new_count != 0 {
+                return Err(malformed());
+            }
+            patch.hunks.push(hunk);
+        }
+        files.push(patch);
+    }
+    Ok(files)
+}
+
+/// Parses "a/<path> b/<path>". Unchanged paths are preferred since they may
+/// contain " b/".
+fn parse_git_diff_paths(text: &str) -> Option<(&str, &str)> {
+    let text = text.strip_prefix("a/")?;
+    if text.len() % 2 == 1 {
+        let n = text.len() / 2 - 1;
+        if let (Some(source), Some(" b/"), Some(target)) =
+            (text.get(..n), text.get(n..n + 3), text.get(n + 3..))
+        {
+            if source == target {
+                return Some((source, target));
+            }
+        }
+    }
+    text.split_once(" b/")
+}
+
+/// Parses "-<start>[,<count>] +<start>[,<count>] @@" into the old start line
+/// and the number of old and new lines.
+fn parse_hunk_header(text: &str) -> Option<(usize, usize, usize)> {
+    let parse_range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (ranges, _) = text.split_once(" @@")?;
+    let (old, new) = ranges.split_once(' ')?;
+    let (old_start, old_count) = parse_range(old.strip_prefix('-')?)?;
+    let (_, new_count) = parse_range(new.strip_prefix('+')?)?;
+    Some((old_start, old_count, new_count))
+}
+
+fn parse_repo_path(path: &str) -> Result<RepoPathBuf, CommandError> {
+    RepoPathBuf::from_relative_path(path)
+        .map_err(|err| user_error_with_message(format!("Invalid path in patch: {path}"), err))
+}
+
+/// Parses "Name <email>" into name and email.
+fn parse_address(value: &str) -> (String, String) {
+    match value.rsplit_once('<') {
+        Some((name, email)) => {
+            let name = name.trim();
+            let name = name
+                .strip_prefix('"')
+                .and_then(|name| name.strip_suffix('"'))
+                .unwrap_or(name);
+            let email = email.trim_end().trim_end_matches('>');
+            (name.to_owned(), email.to_owned())
+        }
+        None => (String::new(), value.trim().to_owned()),
+    }
+}
+
+/// Removes "[PATCH ...]" prefixes from the subject.
+fn strip_subject_prefix(mut subject: &str) -> &str {
+    loop {
+        subject = subject.trim_start();
+        match subject.strip_prefix('[').and_then(|s| s.split_once(']')) {
+            Some((_, rest)) => subject = rest,
+            None => return subject.trim_end(),
+        }
+    }
+}
+
+/// Decodes RFC 2047 "Q"-encoded words like `=?UTF-8?q?Jos=C3=A9?=` as written
+/// by `git format-patch`. Other encoded words are left as is.
+fn decode_encoded_words(value: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = value;
+    let mut last_was_encoded = false;
+    while let Some(start) = rest.find("=?") {
+        let (before, word) = rest.split_at(start);
+        let decoded_word = word[2..].split_once('?').and_then(|(charset, word)| {
+            let (encoding, word) = word.split_once('?')?;
+            let (text, after) = word.split_once("?=")?;
+            let is_utf8 = charset.eq_ignore_ascii_case("utf-8");
+            (is_utf8 && encoding.eq_ignore_ascii_case("q")).then(|| (decode_q(text), after))
+        });
+        match decoded_word {
+            Some((text, after)) => {
+                // Whitespace between adjacent encoded words is ignored.
+                if !(last_was_encoded && before.trim().is_empty()) {
+                    decoded.push_str(before);
+                }
+                decoded.push_str(&text);
+                rest = after;
+                last_was_encoded = true;
+            }
+            None => {
+                decoded.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+                last_was_encoded = false;
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_q(text: &str) -> String {
+    let mut bytes = vec![];
+    let mut iter = text.bytes();
+    while let Some(b) = iter.next() {
+        match b {
+            b'_' => bytes.push(b' '),
+            b'=' => {
+                let hex = [iter.next().unwrap_or(b'0'), iter.next().unwrap_or(b'0')];
+                let byte = std::str::from_utf8(&hex)
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                bytes.extend(byte.map_or(hex.to_vec(), |byte| vec![byte]));
+            }
+            _ => bytes.push(b),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn line_to_str(line: &[u8]) -> &str {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    std::str::from_utf8(line).unwrap_or("")
+}
+
+fn is_blank(line: &[u8]) -> bool {
+    line.iter().all(|b| b.is_ascii_whitespace())
+}
+
+fn is_header_line(line: &[u8]) -> bool {
+    let text = line_to_str(line);
+    text.split_once(':').is_some_and(|(name, _)| {
+        !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    })
+}
+
+fn strip_newline(line: &mut Vec<u8>) {
+    if line.last() == Some(&b'\n') {
+        line.pop();
+    }
+}
+
+#[derive(Clone, Debug)]
+struct FileState {
+    content: Vec<u8>,
+    kind: FileKind,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum FileKind {
+    Normal { executable: bool },
+    Symlink,
+}
+
+/// Applies the `patches` to the `tree`. Files the patches can't be applied to
+/// cleanly are recorded as conflicts between the current contents and the
+/// lines changed by the patch.
+fn apply_file_patches(
+    store: &Arc<Store>,
+    tree: &MergedTree,
+    patches: &[FilePatch],
+) -> Result<MergedTreeId, CommandError> {
+    let mut builder = MergedTreeBuilder::new(tree.id());
+    // (path, base, other) of the files that didn't apply cleanly
+    let mut conflicts = vec![];
+    for patch in patches {
+        let path = patch
+            .target
+            .as_ref()
+            .or(patch.source.as_ref())
+            .ok_or_else(|| user_error("Malformed patch: The file is both added and deleted"))?;
+        if patch.is_binary {
+            return Err(user_error(format!(
+                "Cannot apply binary patch to {}",
+                path.as_internal_file_string()
+            )));
+        }
+        let current = read_file_state(tree, patch.source.as_ref().unwrap_or(path))?;
+        let kind = match patch.new_mode.as_deref() {
+            Some("100644") => FileKind::Normal { executable: false },
+            Some("100755") => FileKind::Normal { executable: true },
+            Some("120000") => FileKind::Symlink,
+            Some(mode) => {
+                return Err(user_error(format!(
+                    "Cannot apply patch with mode {mode} to {}",
+                    path.as_internal_file_string()
+                )));
+            }
+            None => current
+                .as_ref()
+                .map_or(FileKind::Normal { executable: false }, |state| state.kind),
+        };
+        let old_content = patch.hunks.iter().flat_map(|hunk| hunk.old_lines.concat());
+        let new_content = patch.hunks.iter().flat_map(|hunk| hunk.new_lines.concat());
+        let base = patch.source.as_ref().map(|_| FileState {
+            content: old_content.collect(),
+            kind: current.as_ref().map_or(kind, |state| state.kind),
+        });
+        let other = patch.target.as_ref().map(|_| FileState {
+            content: new_content.collect(),
+            kind,
+        });
+
+        let applied = match (&patch.source, &current) {
+            (None, None) => Some(other.clone()),
+            (None, Some(_)) | (Some(_), None) => None,
+            (Some(_), Some(current)) => {
+                apply_hunks(&current.content, &patch.hunks).and_then(|content| {
+                    match &patch.target {
+                        Some(_) => Some(Some(FileState { content, kind })),
+                        // The deleted file must not have any other content.
+                        None => content.is_empty().then_some(None),
+                    }
+                })
+            }
+        };
+        if let (Some(source), Some(target)) = (&patch.source, &patch.target) {
+            if source != target && !patch.is_copy {
+                builder.set_or_remove(source.clone(), Merge::absent());
+                if applied.is_none() {
+                    // Move the current contents so they conflict with the patch.
+                    let value = write_file_state(store, target, current.as_ref())?;
+                    builder.set_or_remove(target.clone(), Merge::resolved(value));
+                }
+            }
+        }
+        match applied {
+            Some(state) => {
+                let value = write_file_state(store, path, state.as_ref())?;
+                builder.set_or_remove(path.clone(), Merge::resolved(value));
+            }
+            None => conflicts.push((path.clone(), base, other)),
+        }
+    }
+
+    let tree_id = builder.write_tree(store)?;
+    if conflicts.is_empty() {
+        return Ok(tree_id);
+    }
+    let mut base_builder = MergedTreeBuilder::new(tree_id.clone());
+    let mut other_builder = MergedTreeBuilder::new(tree_id.clone());
+    for (path, base, other) in conflicts {
+        let base_value = write_file_state(store, &path, base.as_ref())?;
+        base_builder.set_or_remove(path.clone(), Merge::resolved(base_value));
+        let other_value = write_file_state(store, &path, other.as_ref())?;
+        other_builder.set_or_remove(path, Merge::resolved(other_value));
+    }
+    let tree = store.get_root_tree(&tree_id)?;
+    let base_tree = store.get_root_tree(&base_builder.write_tree(store)?)?;
+    let other_tree = store.get_root_tree(&other_builder.write_tree(store)?)?;
+    Ok(tree.merge(&base_tree, &other_tree)?.id())
+}
+
+/// Applies the `hunks` to the `content`. Hunks are searched for near their
+/// line numbers in case the lines have moved. Returns `None` if any of the
+/// hunks doesn't match.
+fn apply_hunks(content: &[u8], hunks: &[Hunk]) -> Option<Vec<u8>> {
+    let lines = content.split_inclusive(|&b| b == b'\n').collect_vec();
+    let mut result = vec![];
+    let mut pos = 0;
+    let mut offset: isize = 0;
+    for hunk in hunks {
+        // An insertion-only hunk starts after the given line.
+        let start = if hunk.old_lines.is_empty() {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+        let expected = start.saturating_add_signed(offset).max(pos);
+        let last = lines.len().checked_sub(hunk.old_lines.len())?;
+        let matches = |i: usize| lines[i..i + hunk.old_lines.len()] == *hunk.old_lines;
+        let found = (0..=lines.len())
+            .flat_map(|distance| {
+                [
+                    expected.checked_add(distance),
+                    expected.checked_sub(distance),
+                ]
+            })
+            .flatten()
+            .filter(|&i| pos <= i && i <= last)
+            .find(|&i| matches(i))?;
+        result.extend(lines[pos..found].concat());
+        result.extend(hunk.new_lines.concat());
+        pos = found + hunk.old_lines.len();
+        offset = found as isize - start as isize;
+    }
+    result.extend(lines[pos..].concat());
+    Some(result)
+}
+
+fn read_file_state(tree: &MergedTree, path: &RepoPath) -> Result<Option<FileState>, CommandError> {
+    let store = tree.store();
+    match tree.path_value(path)?.into_resolved() {
+        Ok(None) => Ok(None),
+        Ok(Some(TreeValue::File { id, executable })) => {
+            let mut content = vec![];
+            store
+                .read_file(path, &id)?
+                .read_to_end(&mut content)
+                .map_err(|err| {
+                    user_error_with_message(
+                        format!("Failed to read file {}", path.as_internal_file_string()),
+                        err,
+                    )
+                })?;
+            Ok(Some(FileState {
+                content,
+                kind: FileKind::Normal { executable },
+            }))
+        }
+        Ok(Some(TreeValue::Symlink(id))) => Ok(Some(FileState {
+            content: store.read_symlink(path, &id)?.into_bytes(),
+            kind: FileKind::Symlink,
+        })),
+        Ok(Some(_)) => Err(user_error(format!(
+            "Cannot apply patch to {}: Not a file",
+            path.as_internal_file_string()
+        ))),
+        Err(_) => Err(user_error(format!(
+            "Cannot apply patch to {}: The file has conflicts",
+            path.as_internal_file_string()
+        ))),
+    }
+}
+
+fn write_file_state(
+    store: &Store,
+    path: &RepoPath,
+    state: Option<&FileState>,
+) -> Result<Option<TreeValue>, CommandError> {
+    let Some(state) = state else {
+        return Ok(None);
+    };
+    let value = match state.kind {
+        FileKind::Normal { executable } => {
+            let id = store
+                .write_file(path, &mut state.content.as_slice())
+                .block_on()?;
+            TreeValue::File { id, executable }
+        }
+        FileKind::Symlink => {
+            let target = String::from_utf8_lossy(&state.content);
+            TreeValue::Symlink(store.write_symlink(path, &target).block_on()?)
+        }
+    };
+    Ok(Some(value))
+}
@@ -0,0 +1,122 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+use std::io::Write as _;
+use std::rc::Rc;
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::git;
+use jj_lib::ref_name::GitRefNameBuf;
+use jj_lib::repo::Repo as _;
+use jj_lib::revset::RevsetExpression;
+use jj_lib::revset::UserRevsetExpression;
+
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Create a bundle containing the given revisions
+///
+/// The bundle includes the local bookmarks pointing to the revisions, so every
+/// head of the revisions must have a bookmark. The parents of the revisions
+/// must already exist in the repo the bundle is fetched into.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitBundleCreateArgs {
+    /// Path of the bundle file to create
+    #[arg(value_hint = clap::ValueHint::FilePath)]
+    path: String,
+    /// The revisions to include in the bundle
+    ///
+    /// For example, `::main` includes the whole history of the `main`
+    /// bookmark, and `main@origin..main` includes only the revisions that
+    /// haven't been pushed yet.
+    #[arg(
+        long,
+        short,
+        required = true,
+        value_name = "REVSETS",
+        add = ArgValueCandidates::new(complete::all_revisions)
+    )]
+    revisions: Vec<RevisionArg>,
+}
+
+pub fn cmd_git_bundle_create(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitBundleCreateArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let repo = workspace_command.repo();
+    let expression = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .expression()
+        .minus(&RevsetExpression::root());
+    let evaluate = |expression: Rc<UserRevsetExpression>| -> Result<Vec<CommitId>, CommandError> {
+        Ok(workspace_command
+            .attach_revset_evaluator(expression)
+            .evaluate_to_commit_ids()?
+            .try_collect()?)
+    };
+
+    let commit_ids: HashSet<CommitId> = evaluate(expression.clone())?.into_iter().collect();
+    if commit_ids.is_empty() {
+        return Err(user_error("No revisions to bundle"));
+    }
+    let refs = repo
+        .view()
+        .local_bookmarks()
+        .filter_map(|(name, target)| {
+            let id = target.as_normal().filter(|id| commit_ids.contains(*id))?;
+            let ref_name = GitRefNameBuf::from(format!("refs/heads/{}", name.as_str()));
+            Some((ref_name, id.clone()))
+        })
+        .collect_vec();
+    for head_id in evaluate(expression.heads())? {
+        if !refs.iter().any(|(_, id)| *id == head_id) {
+            return Err(user_error_with_hint(
+                format!(
+                    "Revision {} isn't pointed to by any bookmark",
+                    short_commit_hash(&head_id)
+                ),
+                "Bundles only contain revisions reachable from bookmarks. Use `jj bookmark \
+                 create` to create one.",
+            ));
+        }
+    }
+    let prerequisites = evaluate(
+        expression
+            .parents()
+            .minus(&expression)
+            .minus(&RevsetExpression::root()),
+    )?;
+
+    let path = command.cwd().join(&args.path);
+    let git_settings = workspace_command.settings().git_settings()?;
+    git::create_bundle(repo.store(), &git_settings, &path, &refs, &prerequisites)?;
+    writeln!(
+        ui.status(),
+        "Created bundle with {} bookmarks and {} revisions",
+        refs.len(),
+        commit_ids.len()
+    )?;
+    Ok(())
+}
@@ -0,0 +1,43 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod create;
+
+use clap::Subcommand;
+
+use self::create::cmd_git_bundle_create;
+use self::create::GitBundleCreateArgs;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Manage Git bundles
+///
+/// A bundle is a file containing commits and refs, which can be used to
+/// transfer them without network access. To fetch from a bundle, add its path
+/// as a remote with `jj git remote add`, and run `jj git fetch`.
+#[derive(Subcommand, Clone, Debug)]
+pub enum BundleCommand {
+    Create(GitBundleCreateArgs),
+}
+
+pub fn cmd_git_bundle(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &BundleCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        BundleCommand::Create(args) => cmd_git_bundle_create(ui, command, args),
+    }
+}
@@ -20,6 +20,7 @@ use std::path::Path;
 
 use jj_lib::git;
 use jj_lib::git::GitFetch;
+use jj_lib::git::GitFetchDepth;
 use jj_lib::ref_name::RefNameBuf;
 use jj_lib::ref_name::RemoteName;
 use jj_lib::ref_name::RemoteNameBuf;
@@ -36,6 +37,7 @@ use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::commands::git::maybe_add_gitignore;
 use crate::git_util::absolute_git_url;
+use crate::git_util::fetch_lfs_objects;
 use crate::git_util::print_git_import_stats;
 use crate::git_util::with_remote_git_callbacks;
 use crate::ui::Ui;
@@ -65,6 +67,13 @@ pub struct GitCloneArgs {
     /// Create a shallow clone of the given depth
     #[arg(long)]
     depth: Option<NonZeroU32>,
+    /// Create a partial clone that omits the objects matched by the filter
+    ///
+    /// For example, `--filter=blob:none` omits the contents of all files. The
+    /// omitted objects are fetched from the remote when they're needed, e.g.
+    /// when they're checked out or diffed.
+    #[arg(long, value_name = "FILTER")]
+    filter: Option<String>,
 }
 
 fn clone_destination_for_source(source: &str) -> Option<&str> {
@@ -92,6 +101,13 @@ pub fn cmd_git_clone(
     if command.global_args().at_operation.is_some() {
         return Err(cli_error("--at-op is not respected"));
     }
+    #[cfg(feature = "git2")]
+    if args.filter.is_some() && !command.settings().git_settings()?.subprocess {
+        return Err(user_error(
+            "Partial clones are not supported by the libgit2 backend; set `git.subprocess = \
+             true` to use --filter",
+        ));
+    }
     let source = absolute_git_url(command.cwd(), &args.source)?;
     let wc_path_str = args
         .destination
@@ -118,8 +134,14 @@ pub fn cmd_git_clone(
 
     let clone_result = (|| -> Result<_, CommandError> {
         let workspace_command = init_workspace(ui, command, &canonical_wc_path, args.colocate)?;
-        let mut workspace_command =
-            configure_remote(ui, command, workspace_command, remote_name, &source)?;
+        let mut workspace_command = configure_remote(
+            ui,
+            command,
+            workspace_command,
+            remote_name,
+            &source,
+            args.filter.as_deref(),
+        )?;
         let default_branch = fetch_new_remote(ui, &mut workspace_command, remote_name, args.depth)?;
         Ok((workspace_command, default_branch))
     })();
@@ -190,8 +212,16 @@ fn configure_remote(
     workspace_command: WorkspaceCommandHelper,
     remote_name: &RemoteName,
     source: &str,
+    filter: Option<&str>,
 ) -> Result<WorkspaceCommandHelper, CommandError> {
     git::add_remote(workspace_command.repo().store(), remote_name, source)?;
+    if let Some(filter) = filter {
+        git::set_remote_partial_clone_filter(
+            workspace_command.repo().store(),
+            remote_name,
+            filter,
+        )?;
+    }
     // Reload workspace to apply new remote configuration to
     // gix::ThreadSafeRepository behind the store.
     let workspace = command.load_workspace_at(
@@ -217,15 +247,17 @@ fn fetch_new_remote(
         workspace_command.workspace_root().display()
     )?;
     let git_settings = workspace_command.settings().git_settings()?;
+    let depth = depth.map_or(GitFetchDepth::Unspecified, GitFetchDepth::Depth);
     let mut fetch_tx = workspace_command.start_transaction();
     let mut git_fetch = GitFetch::new(fetch_tx.repo_mut(), &git_settings)?;
     with_remote_git_callbacks(ui, |cb| {
-        git_fetch.fetch(remote_name, &[StringPattern::everything()], cb, depth)
+        git_fetch.fetch(remote_name, &[StringPattern::everything()], cb, &depth)
     })?;
     let default_branch =
         with_remote_git_callbacks(ui, |cb| git_fetch.get_default_branch(remote_name, cb))?;
     let import_stats = git_fetch.import_refs()?;
     print_git_import_stats(ui, fetch_tx.repo(), &import_stats, true)?;
+    fetch_lfs_objects(ui, &fetch_tx, &[remote_name])?;
     fetch_tx.finish(ui, "fetch from git remote into empty repo")?;
     Ok(default_branch)
 }
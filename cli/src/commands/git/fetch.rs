@@ -13,12 +13,15 @@
 // limitations under the License.
 
 use std::collections::HashSet;
+use std::num::NonZeroU32;
 
+use clap::ArgGroup;
 use clap_complete::ArgValueCandidates;
 use itertools::Itertools as _;
 use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::git;
 use jj_lib::git::GitFetch;
+use jj_lib::git::GitFetchDepth;
 use jj_lib::ref_name::RemoteName;
 use jj_lib::repo::Repo as _;
 use jj_lib::str_util::StringPattern;
@@ -31,6 +34,7 @@ use crate::command_error::user_error;
 use crate::command_error::CommandError;
 use crate::commands::git::get_single_remote;
 use crate::complete;
+use crate::git_util::fetch_lfs_objects;
 use crate::git_util::print_git_import_stats;
 use crate::git_util::with_remote_git_callbacks;
 use crate::ui::Ui;
@@ -40,6 +44,7 @@ use crate::ui::Ui;
 /// If a working-copy commit gets abandoned, it will be given a new, empty
 /// commit. This is true in general; it is not specific to this command.
 #[derive(clap::Args, Clone, Debug)]
+#[command(group(ArgGroup::new("shallow").args(&["deepen", "shallow_since", "unshallow"])))]
 pub struct GitFetchArgs {
     /// Fetch only some of the branches
     ///
@@ -76,6 +81,19 @@ pub struct GitFetchArgs {
     /// Fetch from all remotes
     #[arg(long, conflicts_with = "remotes")]
     all_remotes: bool,
+    /// Fetch the given number of additional commits beyond the current
+    /// boundary of a shallow clone
+    #[arg(long, value_name = "N")]
+    deepen: Option<NonZeroU32>,
+    /// Deepen or shorten the history of a shallow clone to include all
+    /// commits after the given date
+    ///
+    /// The date is passed to `git fetch --shallow-since` as is.
+    #[arg(long, value_name = "DATE")]
+    shallow_since: Option<String>,
+    /// Fetch the complete history of a shallow clone
+    #[arg(long)]
+    unshallow: bool,
 }
 
 #[tracing::instrument(skip_all)]
@@ -128,8 +146,18 @@ pub fn cmd_git_fetch(
         .map(|r| r.as_ref())
         .collect_vec();
 
+    let depth = if let Some(n) = args.deepen {
+        GitFetchDepth::Deepen(n)
+    } else if let Some(date) = &args.shallow_since {
+        GitFetchDepth::ShallowSince(date.clone())
+    } else if args.unshallow {
+        GitFetchDepth::Unshallow
+    } else {
+        GitFetchDepth::Unspecified
+    };
+
     let mut tx = workspace_command.start_transaction();
-    do_git_fetch(ui, &mut tx, &remotes, &args.branch)?;
+    do_git_fetch(ui, &mut tx, &remotes, &args.branch, &depth)?;
     tx.finish(
         ui,
         format!(
@@ -179,17 +207,19 @@ fn do_git_fetch(
     tx: &mut WorkspaceCommandTransaction,
     remotes: &[&RemoteName],
     branch_names: &[StringPattern],
+    depth: &GitFetchDepth,
 ) -> Result<(), CommandError> {
     let git_settings = tx.settings().git_settings()?;
     let mut git_fetch = GitFetch::new(tx.repo_mut(), &git_settings)?;
 
     for remote_name in remotes {
         with_remote_git_callbacks(ui, |callbacks| {
-            git_fetch.fetch(remote_name, branch_names, callbacks, None)
+            git_fetch.fetch(remote_name, branch_names, callbacks, depth)
         })?;
     }
     let import_stats = git_fetch.import_refs()?;
     print_git_import_stats(ui, tx.repo(), &import_stats, true)?;
+    fetch_lfs_objects(ui, tx, remotes)?;
     warn_if_branches_not_found(ui, tx, branch_names, remotes)
 }
 
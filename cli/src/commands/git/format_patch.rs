@@ -0,0 +1,287 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use bstr::ByteSlice as _;
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::commit::Commit;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPathUiConverter;
+use once_cell::sync::Lazy;
+use tracing::instrument;
+
+use crate::cli_util::short_change_hash;
+use crate::cli_util::short_commit_hash;
+use crate::cli_util::CommandHelper;
+use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::command_error::internal_error;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::diff_util::DiffFormat;
+use crate::diff_util::DiffRenderer;
+use crate::diff_util::DiffStatOptions;
+use crate::diff_util::UnifiedDiffOptions;
+use crate::formatter::PlainTextFormatter;
+use crate::time_util::format_absolute_timestamp_with;
+use crate::time_util::FormattingItems;
+use crate::ui::Ui;
+
+/// Export revisions as patch emails
+///
+/// Each revision is written to a `NNNN-<subject>.patch` file in the mbox
+/// format used by `git format-patch`. The patches can be sent by email, and
+/// applied with `jj git am` or `git am`.
+///
+/// The change ID of each revision is recorded in the `Change-Id` header.
+#[derive(clap::Args, Clone, Debug)]
+pub struct GitFormatPatchArgs {
+    /// The revisions to export
+    ///
+    /// The revisions are written in topological order. Merge commits and
+    /// revisions with binary changes can't be exported.
+    #[arg(
+        long,
+        short,
+        default_value = "@",
+        value_name = "REVSETS",
+        add = ArgValueCandidates::new(complete::all_revisions)
+    )]
+    revisions: Vec<RevisionArg>,
+    /// Directory to write the patch files to
+    ///
+    /// Defaults to the current directory.
+    #[arg(long, short, value_name = "DIR", value_hint = clap::ValueHint::DirPath)]
+    output_directory: Option<String>,
+    /// Write all patches to stdout instead of creating files
+    #[arg(long, conflicts_with = "output_directory")]
+    stdout: bool,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_git_format_patch(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &GitFormatPatchArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let mut commits: Vec<Commit> = workspace_command
+        .parse_union_revsets(ui, &args.revisions)?
+        .evaluate_to_commits()?
+        .try_collect()?;
+    commits.reverse(); // in topological order
+    if commits.is_empty() {
+        writeln!(ui.status(), "No revisions to export.")?;
+        return Ok(());
+    }
+    let root_commit_id = workspace_command.repo().store().root_commit_id();
+    for commit in &commits {
+        if commit.id() == root_commit_id {
+            return Err(user_error("Cannot export the root commit"));
+        }
+        if commit.parent_ids().len() > 1 {
+            return Err(user_error(format!(
+                "Cannot export merge commit {} as a patch",
+                short_commit_hash(commit.id())
+            )));
+        }
+    }
+
+    let output_dir = args
+        .output_directory
+        .as_ref()
+        .map_or_else(PathBuf::new, PathBuf::from);
+    let total = commits.len();
+    for (index, commit) in commits.iter().enumerate() {
+        let number = index + 1;
+        let patch = format_patch(ui, &workspace_command, commit, number, total)?;
+        if args.stdout {
+            ui.stdout().write_all(&patch)?;
+        } else {
+            let path = output_dir.join(patch_file_name(number, commit));
+            write_patch_file(&command.cwd().join(&path), &patch)?;
+            writeln!(ui.stdout(), "{}", path.display())?;
+        }
+    }
+    Ok(())
+}
+
+/// Renders the `commit` as an mbox message.
+fn format_patch(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    commit: &Commit,
+    number: usize,
+    total: usize,
+) -> Result<Vec<u8>, CommandError> {
+    static DATE_FORMAT: Lazy<FormattingItems> =
+        Lazy::new(|| FormattingItems::parse("%a, %-d %b %Y %H:%M:%S %z").unwrap());
+
+    let author = commit.author();
+    let date =
+        format_absolute_timestamp_with(&author.timestamp, &DATE_FORMAT).map_err(internal_error)?;
+    let (subject, body) = split_description(commit.description());
+    let prefix = if total == 1 {
+        "[PATCH]".to_owned()
+    } else {
+        format!("[PATCH {number}/{total}]")
+    };
+
+    let mut patch = vec![];
+    writeln!(patch, "From {} Mon Sep 17 00:00:00 2001", commit.id().hex())?;
+    let name = encode_header_value(&author.name, "From: ".len());
+    writeln!(patch, "From: {name} <{}>", author.email)?;
+    writeln!(patch, "Date: {date}")?;
+    let subject = encode_header_value(&format!("{prefix} {subject}"), "Subject: ".len());
+    writeln!(patch, "Subject: {subject}")?;
+    writeln!(patch, "Change-Id: {}", commit.change_id().reverse_hex())?;
+    if !(author.name.is_ascii() && commit.description().is_ascii()) {
+        writeln!(patch, "MIME-Version: 1.0")?;
+        writeln!(patch, "Content-Type: text/plain; charset=UTF-8")?;
+        writeln!(patch, "Content-Transfer-Encoding: 8bit")?;
+    }
+    writeln!(patch)?;
+    if !body.is_empty() {
+        writeln!(patch, "{body}")?;
+    }
+    writeln!(patch, "---")?;
+
+    // Paths in patches are relative to the workspace root regardless of the
+    // current directory.
+    let root = workspace_command.workspace_root().to_owned();
+    let path_converter = RepoPathUiConverter::Fs {
+        cwd: root.clone(),
+        base: root,
+    };
+    let settings = workspace_command.settings();
+    let formats = vec![
+        DiffFormat::Stat(Box::new(DiffStatOptions::default())),
+        DiffFormat::Git(Box::new(UnifiedDiffOptions::from_settings(settings)?)),
+    ];
+    let repo = workspace_command.repo().as_ref();
+    let conflict_marker_style = workspace_command.env().conflict_marker_style();
+    let diff_start = patch.len();
+    for format in formats {
+        let renderer =
+            DiffRenderer::new(repo, &path_converter, conflict_marker_style, vec![format]);
+        renderer.show_patch(
+            ui,
+            &mut PlainTextFormatter::new(&mut patch),
+            commit,
+            &EverythingMatcher,
+            72,
+        )?;
+        writeln!(patch)?;
+    }
+    // Binary changes aren't included in the diff, so the patch couldn't be
+    // applied. The lines of the diff stat are indented.
+    if let Some(line) = patch[diff_start..]
+        .lines()
+        .find(|line| line.starts_with(b"Binary files "))
+    {
+        return Err(user_error(format!(
+            "Cannot export commit {} as a patch because of binary changes: {}",
+            short_commit_hash(commit.id()),
+            line.to_str_lossy()
+        )));
+    }
+    Ok(patch)
+}
+
+/// Encodes a header value with non-ASCII characters as RFC 2047 "Q"-encoded
+/// words in the same way as `git format-patch`. The encoded words are folded
+/// so the lines fit in 76 columns, including the `name_len` of the header.
+fn encode_header_value(value: &str, name_len: usize) -> String {
+    const MAX_LINE_LENGTH: usize = 76;
+    const START: &str = "=?UTF-8?q?";
+    const END: &str = "?=";
+    if value.is_ascii() {
+        return value.to_owned();
+    }
+    let mut encoded = START.to_owned();
+    let mut line_len = name_len + START.len();
+    for c in value.chars() {
+        let mut buf = [0; 4];
+        let word: String = c
+            .encode_utf8(&mut buf)
+            .bytes()
+            .map(|b| {
+                if b.is_ascii_graphic() && !b"=?_".contains(&b) {
+                    char::from(b).to_string()
+                } else {
+                    format!("={b:02X}")
+                }
+            })
+            .collect();
+        // Multi-byte characters aren't split across encoded words.
+        if line_len + word.len() + END.len() > MAX_LINE_LENGTH {
+            encoded.push_str(END);
+            encoded.push_str("\n ");
+            encoded.push_str(START);
+            line_len = 1 + START.len();
+        }
+        encoded.push_str(&word);
+        line_len += word.len();
+    }
+    encoded.push_str(END);
+    encoded
+}
+
+/// Splits the description into the subject line and the body.
+fn split_description(description: &str) -> (&str, &str) {
+    let description = description.trim();
+    match description.split_once('\n') {
+        Some((subject, body)) => (subject.trim_end(), body.trim()),
+        None => (description, ""),
+    }
+}
+
+/// Builds a file name like `0001-fix-the-bug.patch` in the same way as `git
+/// format-patch`. The change ID is used if the subject has no usable words.
+fn patch_file_name(number: usize, commit: &Commit) -> String {
+    const MAX_SLUG_LENGTH: usize = 52;
+    let (subject, _) = split_description(commit.description());
+    let slug = subject
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+        .filter(|word| !word.is_empty())
+        .join("-");
+    let slug = slug.get(..MAX_SLUG_LENGTH).unwrap_or(&slug);
+    let slug = slug.trim_end_matches(['-', '.']);
+    if slug.is_empty() {
+        format!(
+            "{number:04}-{}.patch",
+            short_change_hash(commit.change_id())
+        )
+    } else {
+        format!("{number:04}-{slug}.patch")
+    }
+}
+
+fn write_patch_file(path: &Path, patch: &[u8]) -> Result<(), CommandError> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|err| {
+            user_error_with_message(format!("Failed to create directory {}", dir.display()), err)
+        })?;
+    }
+    std::fs::write(path, patch).map_err(|err| {
+        user_error_with_message(format!("Failed to write patch {}", path.display()), err)
+    })
+}
@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
+
 use jj_lib::git;
 
 use crate::cli_util::CommandHelper;
@@ -24,12 +26,21 @@ use crate::ui::Ui;
 /// If a working-copy commit gets abandoned, it will be given a new, empty
 /// commit. This is true in general; it is not specific to this command.
 #[derive(clap::Args, Clone, Debug)]
-pub struct GitImportArgs {}
+pub struct GitImportArgs {
+    /// Also import the entries of `git stash`
+    ///
+    /// Each stash is imported as a new commit on top of the commit it was
+    /// stashed from, including the untracked files stashed by `git stash -u`.
+    /// The imported commits are hidden, and can be selected by the `stashes()`
+    /// revset. Stashes dropped from Git are removed from `stashes()`.
+    #[arg(long)]
+    stash: bool,
+}
 
 pub fn cmd_git_import(
     ui: &mut Ui,
     command: &CommandHelper,
-    _args: &GitImportArgs,
+    args: &GitImportArgs,
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
     let git_settings = workspace_command.settings().git_settings()?;
@@ -39,6 +50,16 @@ pub fn cmd_git_import(
     git::import_head(tx.repo_mut())?;
     let stats = git::import_refs(tx.repo_mut(), &git_settings)?;
     print_git_import_stats(ui, tx.repo(), &stats, true)?;
+    if args.stash {
+        let stash_commits = git::import_stashes(tx.repo_mut())?;
+        if let Some(mut formatter) = ui.status_formatter() {
+            for commit in &stash_commits {
+                write!(formatter, "Imported stash as ")?;
+                tx.write_commit_summary(formatter.as_mut(), commit)?;
+                writeln!(formatter)?;
+            }
+        }
+    }
     tx.finish(ui, "import git refs")?;
     Ok(())
 }
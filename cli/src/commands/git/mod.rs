@@ -12,9 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod am;
+mod bundle;
 mod clone;
 mod export;
 mod fetch;
+mod format_patch;
 mod import;
 mod init;
 mod push;
@@ -32,12 +35,18 @@ use jj_lib::ref_name::RemoteNameBuf;
 use jj_lib::ref_name::RemoteRefSymbol;
 use jj_lib::store::Store;
 
+use self::am::cmd_git_am;
+use self::am::GitAmArgs;
+use self::bundle::cmd_git_bundle;
+use self::bundle::BundleCommand;
 use self::clone::cmd_git_clone;
 use self::clone::GitCloneArgs;
 use self::export::cmd_git_export;
 use self::export::GitExportArgs;
 use self::fetch::cmd_git_fetch;
 use self::fetch::GitFetchArgs;
+use self::format_patch::cmd_git_format_patch;
+use self::format_patch::GitFormatPatchArgs;
 use self::import::cmd_git_import;
 use self::import::GitImportArgs;
 use self::init::cmd_git_init;
@@ -65,9 +74,13 @@ use crate::ui::Ui;
 ///     https://jj-vcs.github.io/jj/latest/git-command-table
 #[derive(Subcommand, Clone, Debug)]
 pub enum GitCommand {
+    Am(GitAmArgs),
+    #[command(subcommand)]
+    Bundle(BundleCommand),
     Clone(GitCloneArgs),
     Export(GitExportArgs),
     Fetch(GitFetchArgs),
+    FormatPatch(GitFormatPatchArgs),
     Import(GitImportArgs),
     Init(GitInitArgs),
     Push(GitPushArgs),
@@ -82,9 +95,12 @@ pub fn cmd_git(
     subcommand: &GitCommand,
 ) -> Result<(), CommandError> {
     match subcommand {
+        GitCommand::Am(args) => cmd_git_am(ui, command, args),
+        GitCommand::Bundle(args) => cmd_git_bundle(ui, command, args),
         GitCommand::Clone(args) => cmd_git_clone(ui, command, args),
         GitCommand::Export(args) => cmd_git_export(ui, command, args),
         GitCommand::Fetch(args) => cmd_git_fetch(ui, command, args),
+        GitCommand::FormatPatch(args) => cmd_git_format_patch(ui, command, args),
         GitCommand::Import(args) => cmd_git_import(ui, command, args),
         GitCommand::Init(args) => cmd_git_init(ui, command, args),
         GitCommand::Push(args) => cmd_git_push(ui, command, args),
@@ -29,6 +29,7 @@ use jj_lib::commit::CommitIteratorExt as _;
 use jj_lib::config::ConfigGetResultExt as _;
 use jj_lib::git;
 use jj_lib::git::GitBranchPushTargets;
+use jj_lib::git::GitPushOptions;
 use jj_lib::git::GitPushStats;
 use jj_lib::op_store::RefTarget;
 use jj_lib::ref_name::RefName;
@@ -62,6 +63,8 @@ use crate::command_error::CommandError;
 use crate::commands::git::get_single_remote;
 use crate::complete;
 use crate::formatter::Formatter;
+use crate::git_util::push_lfs_objects;
+use crate::git_util::run_pre_push_hook;
 use crate::git_util::with_remote_git_callbacks;
 use crate::revset_util::parse_bookmark_name;
 use crate::ui::Ui;
@@ -188,9 +191,28 @@ pub struct GitPushArgs {
         add = ArgValueCompleter::new(complete::branch_name_equals_any_revision)
     )]
     named: Vec<String>,
+    /// Transmit the given string to the server as a push option (can be
+    /// repeated)
+    ///
+    /// The server passes push options to its hooks, e.g. GitLab creates a merge
+    /// request when given `merge_request.create`. This defaults to the
+    /// `remotes.<name>.push-options` setting of the remote.
+    #[arg(long = "option", short = 'o', value_name = "OPTION")]
+    options: Vec<String>,
+    /// Also push the Git notes in `refs/notes/commits`
+    ///
+    /// The notes on the remote are expected to be where they were when last
+    /// fetched with the `git.fetch-notes` setting enabled.
+    #[arg(long)]
+    notes: bool,
     /// Only display what will change on the remote
     #[arg(long)]
     dry_run: bool,
+    /// Don't run the Git `pre-push` hook
+    ///
+    /// The hook is only run if enabled by the `git.run-hooks` setting.
+    #[arg(long)]
+    no_verify: bool,
 }
 
 fn make_bookmark_term(bookmark_names: &[impl fmt::Display]) -> String {
@@ -390,7 +412,11 @@ pub fn cmd_git_push(
         );
     }
     if bookmark_updates.is_empty() {
-        writeln!(ui.status(), "Nothing changed.")?;
+        if args.notes && !args.dry_run {
+            push_notes(ui, &tx, remote, args)?;
+        } else {
+            writeln!(ui.status(), "Nothing changed.")?;
+        }
         return Ok(());
     }
 
@@ -399,7 +425,7 @@ pub fn cmd_git_push(
     } else {
         None
     };
-    let commits_to_sign =
+    let (commits_to_push, commits_to_sign) =
         validate_commits_ready_to_push(ui, &bookmark_updates, remote, &tx, args, sign_behavior)?;
     if !args.dry_run && !commits_to_sign.is_empty() {
         if let Some(sign_behavior) = sign_behavior {
@@ -440,18 +466,98 @@ pub fn cmd_git_push(
         return Ok(());
     }
 
+    if !args.no_verify {
+        run_pre_push_hook(ui, tx.base_workspace_helper(), remote, &bookmark_updates)?;
+    }
+
+    // Like Git LFS's pre-push hook, upload objects before updating the refs so
+    // the remote never references missing objects.
+    push_lfs_objects(ui, &tx, remote, &commits_to_push)?;
+
     let targets = GitBranchPushTargets {
         branch_updates: bookmark_updates,
     };
     let git_settings = tx.settings().git_settings()?;
+    let push_options = get_push_options(tx.settings(), remote, args)?;
     let push_stats = with_remote_git_callbacks(ui, |cb| {
-        git::push_branches(tx.repo_mut(), &git_settings, remote, &targets, cb)
+        git::push_branches(
+            tx.repo_mut(),
+            &git_settings,
+            remote,
+            &targets,
+            &push_options,
+            cb,
+        )
     })?;
+    print_remote_messages(ui, &push_stats)?;
     process_push_stats(&push_stats)?;
+    if args.notes {
+        push_notes(ui, &tx, remote, args)?;
+    }
     tx.finish(ui, tx_description)?;
     Ok(())
 }
 
+fn get_push_options(
+    settings: &UserSettings,
+    remote: &RemoteName,
+    args: &GitPushArgs,
+) -> Result<GitPushOptions, CommandError> {
+    let remote_push_options = if args.options.is_empty() {
+        settings
+            .get(["remotes", remote.as_str(), "push-options"])
+            .optional()?
+            .unwrap_or_default()
+    } else {
+        args.options.clone()
+    };
+    Ok(GitPushOptions {
+        remote_push_options,
+    })
+}
+
+fn push_notes(
+    ui: &Ui,
+    tx: &WorkspaceCommandTransaction,
+    remote: &RemoteName,
+    args: &GitPushArgs,
+) -> Result<(), CommandError> {
+    let git_settings = tx.settings().git_settings()?;
+    let push_options = get_push_options(tx.settings(), remote, args)?;
+    let push_stats = with_remote_git_callbacks(ui, |cb| {
+        git::push_notes(tx.repo(), &git_settings, remote, &push_options, cb)
+    })?;
+    let Some(push_stats) = push_stats else {
+        writeln!(ui.status(), "Notes are already up to date.")?;
+        return Ok(());
+    };
+    print_remote_messages(ui, &push_stats)?;
+    process_push_stats(&push_stats)?;
+    writeln!(
+        ui.status(),
+        "Pushed notes to {remote}",
+        remote = remote.as_symbol()
+    )?;
+    Ok(())
+}
+
+fn print_remote_messages(ui: &Ui, push_stats: &GitPushStats) -> io::Result<()> {
+    if push_stats.remote_messages.is_empty() {
+        return Ok(());
+    }
+    if let Some(mut formatter) = ui.status_formatter() {
+        writeln!(formatter, "Messages from the remote:")?;
+        for message in &push_stats.remote_messages {
+            if message.is_empty() {
+                writeln!(formatter)?;
+            } else {
+                writeln!(formatter, "  {message}")?;
+            }
+        }
+    }
+    Ok(())
+}
+
 fn process_push_stats(push_stats: &GitPushStats) -> Result<(), CommandError> {
     if !push_stats.all_ok() {
         let mut error = user_error("Failed to push some bookmarks");
@@ -508,7 +614,7 @@ fn validate_commits_ready_to_push(
     tx: &WorkspaceCommandTransaction,
     args: &GitPushArgs,
     sign_behavior: Option<SignBehavior>,
-) -> Result<Vec<Commit>, CommandError> {
+) -> Result<(Vec<Commit>, Vec<Commit>), CommandError> {
     let workspace_helper = tx.base_workspace_helper();
     let repo = workspace_helper.repo();
 
@@ -538,6 +644,7 @@ fn validate_commits_ready_to_push(
         sign_settings
     });
 
+    let mut commits_to_push = vec![];
     let mut commits_to_sign = vec![];
 
     for commit in workspace_helper
@@ -587,11 +694,12 @@ fn validate_commits_ready_to_push(
         }
         if let Some(sign_settings) = &sign_settings {
             if !commit.is_signed() && sign_settings.should_sign(commit.store_commit()) {
-                commits_to_sign.push(commit);
+                commits_to_sign.push(commit.clone());
             }
         }
+        commits_to_push.push(commit);
     }
-    Ok(commits_to_sign)
+    Ok((commits_to_push, commits_to_sign))
 }
 
 /// Signs commits before pushing.
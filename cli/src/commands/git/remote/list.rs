@@ -23,6 +23,9 @@ use crate::command_error::CommandError;
 use crate::ui::Ui;
 
 /// List Git remotes
+///
+/// The push URL is shown after the fetch URL if it differs from it, e.g.
+/// because of a `pushurl` setting or `url.<base>.pushInsteadOf` rewriting.
 #[derive(clap::Args, Clone, Debug)]
 pub struct GitRemoteListArgs {}
 
@@ -44,12 +47,19 @@ pub fn cmd_git_remote_list(
             }
             None => continue, // ignore empty [remote "<name>"] section
         };
-        // TODO: print push url (by default or by some flag)?
         let fetch_url = remote
             .url(gix::remote::Direction::Fetch)
             .map(|url| url.to_bstring())
             .unwrap_or_else(|| "<no URL>".into());
-        writeln!(ui.stdout(), "{remote_name} {fetch_url}")?;
+        let push_url = remote
+            .url(gix::remote::Direction::Push)
+            .map(|url| url.to_bstring())
+            .unwrap_or_else(|| "<no URL>".into());
+        if push_url == fetch_url {
+            writeln!(ui.stdout(), "{remote_name} {fetch_url}")?;
+        } else {
+            writeln!(ui.stdout(), "{remote_name} {fetch_url} (push: {push_url})")?;
+        }
     }
     Ok(())
 }
@@ -24,6 +24,10 @@ use crate::git_util::absolute_git_url;
 use crate::ui::Ui;
 
 /// Set the URL of a Git remote
+///
+/// With `--push`, sets the URL used only for pushing, e.g. to push over SSH
+/// while fetching over HTTPS. Otherwise, the push URL defaults to the fetch
+/// URL.
 #[derive(clap::Args, Clone, Debug)]
 pub struct GitRemoteSetUrlArgs {
     /// The remote's name
@@ -34,6 +38,9 @@ pub struct GitRemoteSetUrlArgs {
     /// Local path will be resolved to absolute form.
     #[arg(value_hint = clap::ValueHint::Url)]
     url: String,
+    /// Set the push URL instead of the fetch URL
+    #[arg(long)]
+    push: bool,
 }
 
 pub fn cmd_git_remote_set_url(
@@ -43,6 +50,11 @@ pub fn cmd_git_remote_set_url(
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
     let url = absolute_git_url(command.cwd(), &args.url)?;
-    git::set_remote_url(workspace_command.repo().store(), &args.remote, &url)?;
+    let store = workspace_command.repo().store();
+    if args.push {
+        git::set_remote_push_url(store, &args.remote, &url)?;
+    } else {
+        git::set_remote_url(store, &args.remote, &url)?;
+    }
     Ok(())
 }
@@ -104,5 +104,7 @@ fn view_with_desired_portions_restored(
         git_refs: current_view.git_refs.clone(),
         git_head: current_view.git_head.clone(),
         wc_commit_ids: repo_source.wc_commit_ids.clone(),
+        git_stashes: repo_source.git_stashes.clone(),
+        git_notes: repo_source.git_notes.clone(),
     }
 }
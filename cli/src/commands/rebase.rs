@@ -21,6 +21,7 @@ use itertools::Itertools as _;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
 use jj_lib::commit::CommitIteratorExt as _;
+use jj_lib::files::FileMergeWhitespace;
 use jj_lib::object_id::ObjectId as _;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo as _;
@@ -325,6 +326,21 @@ pub(crate) struct RebaseArgs {
     /// parents.
     #[arg(long)]
     skip_emptied: bool,
+
+    /// Resolve conflicting hunks whose sides only differ in whitespace
+    ///
+    /// The side to take is set by the `merge.whitespace-side` config option,
+    /// which defaults to the destination.
+    #[arg(long)]
+    ignore_all_space: bool,
+
+    /// Resolve conflicting hunks whose sides only differ in the amount of
+    /// whitespace
+    ///
+    /// The side to take is set by the `merge.whitespace-side` config option,
+    /// which defaults to the destination.
+    #[arg(long, conflicts_with = "ignore_all_space")]
+    ignore_space_change: bool,
 }
 
 #[derive(clap::Args, Clone, Debug)]
@@ -384,6 +400,13 @@ pub(crate) fn cmd_rebase(
             delete_abandoned_bookmarks: false,
         },
         simplify_ancestor_merge: false,
+        whitespace: if args.ignore_all_space {
+            FileMergeWhitespace::IgnoreAllSpace
+        } else if args.ignore_space_change {
+            FileMergeWhitespace::IgnoreSpaceChange
+        } else {
+            FileMergeWhitespace::Exact
+        },
     };
     let mut workspace_command = command.workspace_helper(ui)?;
     if !args.revisions.is_empty() {
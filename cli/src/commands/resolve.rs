@@ -12,18 +12,30 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
+
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use itertools::Itertools as _;
+use jj_lib::backend::MergedTreeId;
+use jj_lib::merged_tree::resolve_file_values;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::merged_tree::MergedTreeBuilder;
 use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo_path::RepoPath;
+use pollster::FutureExt as _;
 use tracing::instrument;
 
 use crate::cli_util::print_conflicted_paths;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
 use crate::command_error::cli_error;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
 use crate::command_error::CommandError;
 use crate::complete;
+use crate::merge_tools::take_side_of_conflicts;
+use crate::merge_tools::TakeSide;
 use crate::ui::Ui;
 
 /// Resolve conflicted files with an external merge tool
@@ -34,6 +46,10 @@ use crate::ui::Ui;
 /// resolved. To stop resolving conflicts, exit the merge tool without making
 /// any changes.
 ///
+/// Conflicts that can't be resolved by merging file contents, such as
+/// conflicts between a file and a directory, can be resolved by taking one of
+/// their sides with `--take` or the builtin merge tool.
+///
 /// Note that conflicts can also be resolved without using this command. You may
 /// edit the conflict markers in the conflicted file directly with a text
 /// editor.
@@ -61,6 +77,33 @@ pub(crate) struct ResolveArgs {
     /// Specify 3-way merge tool to be used
     #[arg(long, conflicts_with = "list", value_name = "NAME")]
     tool: Option<String>,
+    /// Resolve conflicts by reusing recorded resolutions instead of running a
+    /// merge tool
+    ///
+    /// Resolutions are recorded when conflicts are resolved while the
+    /// `merge.reuse-resolutions` setting is enabled. Only files where all
+    /// conflicts have a recorded resolution are resolved.
+    #[arg(long, conflicts_with_all = ["list", "tool"])]
+    auto: bool,
+    /// Resolve conflicts by taking one of their sides instead of running a
+    /// merge tool
+    ///
+    /// The side is given as `side-N`, where N is the number of the side as
+    /// shown in the conflict markers, starting from 1. This also works for
+    /// conflicts that can't be merged by a merge tool, such as conflicts
+    /// between a file and a directory, or between a deleted and a modified
+    /// file.
+    #[arg(
+        long,
+        value_name = "SIDE",
+        value_parser = parse_conflict_side,
+        conflicts_with_all = ["list", "tool", "auto"],
+    )]
+    take: Option<usize>,
+    /// Keep the other sides of the conflicts at `PATH~side-N` when taking a
+    /// side with `--take`
+    #[arg(long, requires = "take")]
+    keep_others: bool,
     /// Only resolve conflicts in these paths. You can use the `--list` argument
     /// to find paths to use here.
     #[arg(
@@ -95,8 +138,10 @@ pub(crate) fn cmd_resolve(
         }));
     }
     if args.list {
+        let conflict_labels = workspace_command.env().conflict_labels(&commit);
         return print_conflicted_paths(
             conflicts,
+            conflict_labels.as_ref(),
             ui.stdout_formatter().as_mut(),
             &workspace_command,
         );
@@ -107,10 +152,20 @@ pub(crate) fn cmd_resolve(
         .map(|(path, _)| path.as_ref())
         .collect_vec();
     workspace_command.check_rewritable([commit.id()])?;
-    let merge_editor = workspace_command.merge_editor(ui, args.tool.as_deref())?;
+    let (new_tree_id, partial_resolution_error) = if args.auto {
+        let new_tree_id = resolve_with_recorded_resolutions(ui, &tree, &repo_paths)?;
+        (new_tree_id, None)
+    } else if let Some(index) = args.take {
+        let take = TakeSide {
+            index,
+            keep_others: args.keep_others,
+        };
+        (take_side_of_conflicts(&tree, &repo_paths, take)?, None)
+    } else {
+        let merge_editor = workspace_command.merge_editor(ui, args.tool.as_deref())?;
+        merge_editor.edit_files(ui, &tree, &repo_paths)?
+    };
     let mut tx = workspace_command.start_transaction();
-    let (new_tree_id, partial_resolution_error) =
-        merge_editor.edit_files(ui, &tree, &repo_paths)?;
     let new_commit = tx
         .repo_mut()
         .rewrite_commit(&commit)
@@ -133,7 +188,12 @@ pub(crate) fn cmd_resolve(
                     formatter.labeled("warning").with_heading("Warning: "),
                     "After this operation, some files at this revision still have conflicts:"
                 )?;
-                print_conflicted_paths(new_conflicts, formatter.as_mut(), &workspace_command)?;
+                print_conflicted_paths(
+                    new_conflicts,
+                    None,
+                    formatter.as_mut(),
+                    &workspace_command,
+                )?;
             }
         }
     }
@@ -143,3 +203,48 @@ pub(crate) fn cmd_resolve(
     }
     Ok(())
 }
+
+fn resolve_with_recorded_resolutions(
+    ui: &Ui,
+    tree: &MergedTree,
+    repo_paths: &[&RepoPath],
+) -> Result<MergedTreeId, CommandError> {
+    let store = tree.store();
+    if store.merge_options().resolution_cache.is_none() {
+        return Err(user_error_with_hint(
+            "Reusing recorded conflict resolutions is disabled",
+            "Set `merge.reuse-resolutions = true` to record and reuse resolutions.",
+        ));
+    }
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    let mut resolved_count = 0;
+    for &path in repo_paths {
+        let value = tree.path_value(path)?;
+        let new_value = resolve_file_values(store, path, value).block_on()?;
+        if new_value.is_resolved() {
+            tree_builder.set_or_remove(path.to_owned(), new_value);
+            resolved_count += 1;
+        }
+    }
+    if resolved_count == 0 {
+        return Err(user_error(
+            "No recorded resolutions found for the conflicts",
+        ));
+    }
+    writeln!(
+        ui.status(),
+        "Resolved conflicts in {resolved_count} files using recorded resolutions"
+    )?;
+    Ok(tree_builder.write_tree(store)?)
+}
+
+/// Parses `side-N` into the index of the side.
+fn parse_conflict_side(text: &str) -> Result<usize, String> {
+    text.strip_prefix("side-")
+        .and_then(|number| number.parse::<usize>().ok())
+        .filter(|&number| number > 0)
+        .map(|number| number - 1)
+        .ok_or_else(|| {
+            format!("Expected `side-N` where N is a number starting from 1, got `{text}`")
+        })
+}
@@ -132,7 +132,7 @@ pub(crate) fn cmd_status(
                 formatter.labeled("warning").with_heading("Warning: "),
                 "There are unresolved conflicts at these paths:"
             )?;
-            print_conflicted_paths(conflicts, formatter, &workspace_command)?;
+            print_conflicted_paths(conflicts, None, formatter, &workspace_command)?;
 
             let wc_revset = RevsetExpression::commit(wc_commit.id().clone());
 
@@ -15,6 +15,7 @@
 use clap_complete::ArgValueCandidates;
 use itertools::Itertools as _;
 use jj_lib::ref_name::WorkspaceNameBuf;
+use jj_lib::workspace_store::WorkspaceStore;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -75,5 +76,7 @@ pub fn cmd_workspace_forget(
     };
 
     tx.finish(ui, description)?;
+    let names = wss.iter().map(|ws| &**ws).collect_vec();
+    WorkspaceStore::load(workspace_command.repo_path()).forget(&names)?;
     Ok(())
 }
@@ -0,0 +1,58 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::file_util;
+use jj_lib::workspace::move_workspace;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Move the current workspace to another directory
+///
+/// If the repo is stored in the current workspace, the other workspaces are
+/// updated to point to its new location.
+#[derive(clap::Args, Clone, Debug)]
+pub struct WorkspaceMoveArgs {
+    /// Where to move the workspace
+    destination: String,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_workspace_move(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &WorkspaceMoveArgs,
+) -> Result<(), CommandError> {
+    let workspace = command.load_workspace()?;
+    // The current directory may be moved, so "../" has to be resolved first.
+    let destination_path = file_util::normalize_path(&command.cwd().join(&args.destination));
+    let updated_names = move_workspace(workspace, &destination_path)?;
+    writeln!(
+        ui.status(),
+        "Moved workspace to \"{}\"",
+        file_util::relative_path(command.cwd(), &destination_path).display()
+    )?;
+    for name in &updated_names {
+        writeln!(
+            ui.status(),
+            "Updated repo path of workspace {}",
+            name.as_symbol()
+        )?;
+    }
+    Ok(())
+}
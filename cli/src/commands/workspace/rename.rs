@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use jj_lib::ref_name::WorkspaceNameBuf;
+use jj_lib::workspace_store::WorkspaceStore;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -71,6 +72,7 @@ pub fn cmd_workspace_rename(
         new = new_name.as_symbol()
     ))?;
     locked_ws.finish(repo.op_id().clone())?;
+    WorkspaceStore::load(workspace_command.repo_path()).rename(&old_name, new_name)?;
 
     Ok(())
 }
@@ -0,0 +1,75 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::workspace::set_workspace_repo_path;
+use jj_lib::workspace::update_workspace_repo_paths;
+use jj_lib::workspace_store::WorkspaceStore;
+use tracing::instrument;
+
+use crate::cli_util::find_workspace_dir;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Repair the links between workspaces and the repo after moving them
+///
+/// Run this in a workspace which was moved without `jj workspace move`. The
+/// new location of the workspace is recorded, and if the repo is stored in
+/// this workspace, the other workspaces are updated to point to it.
+///
+/// If this workspace can no longer find the repo because the workspace
+/// containing the repo was moved, specify the new location of that workspace.
+#[derive(clap::Args, Clone, Debug)]
+pub struct WorkspaceRepairArgs {
+    /// Path to the workspace containing the repo
+    repo: Option<String>,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_workspace_repair(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &WorkspaceRepairArgs,
+) -> Result<(), CommandError> {
+    // The workspace loader can't be instantiated if the repo path is stale.
+    let workspace_root = match command.workspace_loader() {
+        Ok(loader) => loader.workspace_root().to_owned(),
+        Err(_) => find_workspace_dir(command.cwd()).to_owned(),
+    };
+    if let Some(repo) = &args.repo {
+        let path = command.cwd().join(repo);
+        let jj_repo_path = path.join(".jj").join("repo");
+        let repo_path = if jj_repo_path.is_dir() {
+            jj_repo_path
+        } else {
+            path
+        };
+        set_workspace_repo_path(&workspace_root, &repo_path)?;
+        writeln!(ui.status(), "Updated repo path of the current workspace")?;
+    }
+
+    let workspace = command.load_workspace_at(&workspace_root, command.settings())?;
+    WorkspaceStore::load(workspace.repo_path())
+        .add(workspace.workspace_name(), workspace.workspace_root())?;
+    for name in update_workspace_repo_paths(workspace.repo_path())? {
+        writeln!(
+            ui.status(),
+            "Updated repo path of workspace {}",
+            name.as_symbol()
+        )?;
+    }
+    Ok(())
+}
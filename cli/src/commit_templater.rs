@@ -13,6 +13,8 @@
 // limitations under the License.
 
 use std::any::Any;
+#[cfg(feature = "git")]
+use std::cell::RefCell;
 use std::cmp::max;
 use std::cmp::Ordering;
 use std::collections::HashMap;
@@ -29,11 +31,14 @@ use jj_lib::backend::ChangeId;
 use jj_lib::backend::CommitId;
 use jj_lib::backend::TreeValue;
 use jj_lib::commit::Commit;
+use jj_lib::conflicts::extract_as_single_hunk;
 use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::copies::CopiesTreeDiffEntry;
 use jj_lib::copies::CopiesTreeDiffEntryPath;
 use jj_lib::copies::CopyRecords;
 use jj_lib::extensions_map::ExtensionsMap;
+use jj_lib::files;
+use jj_lib::files::MergeResult;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
@@ -304,6 +309,18 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
                 let build = template_parser::lookup_method(type_name, table, function)?;
                 build(self, diagnostics, build_ctx, property, function)
             }
+            CommitTemplatePropertyKind::TreeEntryList(property) => {
+                // TODO: migrate to table?
+                template_builder::build_unformattable_list_method(
+                    self,
+                    diagnostics,
+                    build_ctx,
+                    property,
+                    function,
+                    Self::wrap_tree_entry,
+                    Self::wrap_tree_entry_list,
+                )
+            }
             CommitTemplatePropertyKind::DiffStats(property) => {
                 let table = &self.build_fn_table.diff_stats_methods;
                 let build = template_parser::lookup_method(type_name, table, function)?;
@@ -438,6 +455,12 @@ impl<'repo> CommitTemplateLanguage<'repo> {
         CommitTemplatePropertyKind::TreeEntry(Box::new(property))
     }
 
+    pub fn wrap_tree_entry_list(
+        property: impl TemplateProperty<Output = Vec<TreeEntry>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::TreeEntryList(Box::new(property))
+    }
+
     pub fn wrap_diff_stats(
         property: impl TemplateProperty<Output = DiffStatsFormatted<'repo>> + 'repo,
     ) -> CommitTemplatePropertyKind<'repo> {
@@ -473,6 +496,7 @@ pub enum CommitTemplatePropertyKind<'repo> {
     TreeDiffEntry(Box<dyn TemplateProperty<Output = TreeDiffEntry> + 'repo>),
     TreeDiffEntryList(Box<dyn TemplateProperty<Output = Vec<TreeDiffEntry>> + 'repo>),
     TreeEntry(Box<dyn TemplateProperty<Output = TreeEntry> + 'repo>),
+    TreeEntryList(Box<dyn TemplateProperty<Output = Vec<TreeEntry>> + 'repo>),
     DiffStats(Box<dyn TemplateProperty<Output = DiffStatsFormatted<'repo>> + 'repo>),
     CryptographicSignatureOpt(
         Box<dyn TemplateProperty<Output = Option<CryptographicSignature>> + 'repo>,
@@ -498,6 +522,7 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::TreeDiffEntry(_) => "TreeDiffEntry",
             CommitTemplatePropertyKind::TreeDiffEntryList(_) => "List<TreeDiffEntry>",
             CommitTemplatePropertyKind::TreeEntry(_) => "TreeEntry",
+            CommitTemplatePropertyKind::TreeEntryList(_) => "List<TreeEntry>",
             CommitTemplatePropertyKind::DiffStats(_) => "DiffStats",
             CommitTemplatePropertyKind::CryptographicSignatureOpt(_) => {
                 "Option<CryptographicSignature>"
@@ -537,6 +562,9 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
                 Some(Box::new(property.map(|l| !l.is_empty())))
             }
             CommitTemplatePropertyKind::TreeEntry(_) => None,
+            CommitTemplatePropertyKind::TreeEntryList(property) => {
+                Some(Box::new(property.map(|l| !l.is_empty())))
+            }
             CommitTemplatePropertyKind::DiffStats(_) => None,
             CommitTemplatePropertyKind::CryptographicSignatureOpt(property) => {
                 Some(Box::new(property.map(|sig| sig.is_some())))
@@ -583,6 +611,7 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::TreeDiffEntry(_) => None,
             CommitTemplatePropertyKind::TreeDiffEntryList(_) => None,
             CommitTemplatePropertyKind::TreeEntry(_) => None,
+            CommitTemplatePropertyKind::TreeEntryList(_) => None,
             CommitTemplatePropertyKind::DiffStats(property) => Some(property.into_template()),
             CommitTemplatePropertyKind::CryptographicSignatureOpt(_) => None,
             CommitTemplatePropertyKind::AnnotationLine(_) => None,
@@ -609,6 +638,7 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             (CommitTemplatePropertyKind::TreeDiffEntry(_), _) => None,
             (CommitTemplatePropertyKind::TreeDiffEntryList(_), _) => None,
             (CommitTemplatePropertyKind::TreeEntry(_), _) => None,
+            (CommitTemplatePropertyKind::TreeEntryList(_), _) => None,
             (CommitTemplatePropertyKind::DiffStats(_), _) => None,
             (CommitTemplatePropertyKind::CryptographicSignatureOpt(_), _) => None,
             (CommitTemplatePropertyKind::AnnotationLine(_), _) => None,
@@ -638,6 +668,7 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             (CommitTemplatePropertyKind::TreeDiffEntry(_), _) => None,
             (CommitTemplatePropertyKind::TreeDiffEntryList(_), _) => None,
             (CommitTemplatePropertyKind::TreeEntry(_), _) => None,
+            (CommitTemplatePropertyKind::TreeEntryList(_), _) => None,
             (CommitTemplatePropertyKind::DiffStats(_), _) => None,
             (CommitTemplatePropertyKind::CryptographicSignatureOpt(_), _) => None,
             (CommitTemplatePropertyKind::AnnotationLine(_), _) => None,
@@ -749,6 +780,8 @@ pub struct CommitKeywordCache<'repo> {
     tags_index: OnceCell<Rc<CommitRefsIndex>>,
     git_refs_index: OnceCell<Rc<CommitRefsIndex>>,
     is_immutable_fn: OnceCell<Rc<RevsetContainingFn<'repo>>>,
+    #[cfg(feature = "git")]
+    git_notes: RefCell<HashMap<String, Option<Rc<jj_lib::git::GitNotes>>>>,
 }
 
 impl<'repo> CommitKeywordCache<'repo> {
@@ -781,6 +814,31 @@ impl<'repo> CommitKeywordCache<'repo> {
             Ok(revset.containing_fn().into())
         })
     }
+
+    /// Loads the Git notes in `notes_ref`, or returns `None` if the repo isn't
+    /// backed by Git.
+    #[cfg(feature = "git")]
+    pub fn git_notes(
+        &self,
+        repo: &dyn Repo,
+        notes_ref: &str,
+    ) -> Result<Option<Rc<jj_lib::git::GitNotes>>, jj_lib::git::GitNotesError> {
+        if let Some(notes) = self.git_notes.borrow().get(notes_ref) {
+            return Ok(notes.clone());
+        }
+        let notes = if jj_lib::git::get_git_backend(repo.store()).is_ok() {
+            Some(Rc::new(jj_lib::git::GitNotes::load(
+                repo.store(),
+                notes_ref,
+            )?))
+        } else {
+            None
+        };
+        self.git_notes
+            .borrow_mut()
+            .insert(notes_ref.to_owned(), notes.clone());
+        Ok(notes)
+    }
 }
 
 fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Commit> {
@@ -826,6 +884,33 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
     );
     map.insert(
         "author",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            // The mailmap is loaded when the template is evaluated.
+            let mailmap = language.revset_parse_context.mailmap;
+            let out_property =
+                self_property.map(move |commit| match mailmap.and_then(|m| m.get()) {
+                    Some(mailmap) => mailmap.map_signature(commit.author()).into_owned(),
+                    None => commit.author().clone(),
+                });
+            Ok(L::wrap_signature(out_property))
+        },
+    );
+    map.insert(
+        "committer",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let mailmap = language.revset_parse_context.mailmap;
+            let out_property =
+                self_property.map(move |commit| match mailmap.and_then(|m| m.get()) {
+                    Some(mailmap) => mailmap.map_signature(commit.committer()).into_owned(),
+                    None => commit.committer().clone(),
+                });
+            Ok(L::wrap_signature(out_property))
+        },
+    );
+    map.insert(
+        "raw_author",
         |_language, _diagnostics, _build_ctx, self_property, function| {
             function.expect_no_arguments()?;
             let out_property = self_property.map(|commit| commit.author().clone());
@@ -833,7 +918,7 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
         },
     );
     map.insert(
-        "committer",
+        "raw_committer",
         |_language, _diagnostics, _build_ctx, self_property, function| {
             function.expect_no_arguments()?;
             let out_property = self_property.map(|commit| commit.committer().clone());
@@ -844,8 +929,16 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
         "mine",
         |language, _diagnostics, _build_ctx, self_property, function| {
             function.expect_no_arguments()?;
-            let user_email = language.revset_parse_context.user_email.to_owned();
-            let out_property = self_property.map(move |commit| commit.author().email == user_email);
+            let mailmap = language.revset_parse_context.mailmap;
+            let user_email = language.revset_parse_context.user_email;
+            let out_property =
+                self_property.map(move |commit| match mailmap.and_then(|m| m.get()) {
+                    Some(mailmap) => {
+                        let (_, user_email) = mailmap.resolve("", user_email);
+                        mailmap.map_signature(commit.author()).email == user_email
+                    }
+                    None => commit.author().email == user_email,
+                });
             Ok(L::wrap_boolean(out_property))
         },
     );
@@ -964,6 +1057,23 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             Ok(L::wrap_boolean(out_property))
         },
     );
+    map.insert(
+        "notes",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            let ([], [ref_node]) = function.expect_arguments()?;
+            let notes_ref = if let Some(node) = ref_node {
+                template_parser::expect_string_literal_with(
+                    node,
+                    |name, _span| Ok(name.to_owned()),
+                )?
+            } else {
+                "commits".to_owned()
+            };
+            let read_note = git_note_reader(language, &notes_ref, function.name_span)?;
+            let out_property = self_property.and_then(move |commit| read_note(commit.id()));
+            Ok(L::wrap_string(out_property))
+        },
+    );
     map.insert(
         "divergent",
         |language, _diagnostics, _build_ctx, self_property, function| {
@@ -1020,6 +1130,31 @@ fn builtin_commit_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, Comm
             Ok(L::wrap_boolean(out_property))
         },
     );
+    map.insert(
+        "conflicted_files",
+        |language, diagnostics, _build_ctx, self_property, function| {
+            let ([], [files_node]) = function.expect_arguments()?;
+            let files = if let Some(node) = files_node {
+                expect_fileset_literal(diagnostics, node, language.path_converter)?
+            } else {
+                FilesetExpression::all()
+            };
+            let matcher: Rc<dyn Matcher> = files.to_matcher().into();
+            let out_property = self_property.and_then(move |commit| {
+                if !commit.has_conflict()? {
+                    return Ok(vec![]);
+                }
+                let entries: Vec<_> = commit
+                    .tree()?
+                    .conflicts()
+                    .filter(|(path, _)| matcher.matches(path))
+                    .map(|(path, value)| value.map(|value| TreeEntry { path, value }))
+                    .try_collect()?;
+                Ok(entries)
+            });
+            Ok(L::wrap_tree_entry_list(out_property))
+        },
+    );
     map.insert(
         "empty",
         |language, _diagnostics, _build_ctx, self_property, function| {
@@ -1075,6 +1210,37 @@ fn extract_working_copies(repo: &dyn Repo, commit: &Commit) -> String {
     names.join(" ")
 }
 
+/// Returns a function that reads the Git note attached to a commit, or an
+/// empty string if there's none. The notes are loaded once per template.
+#[cfg(feature = "git")]
+fn git_note_reader<'repo>(
+    language: &CommitTemplateLanguage<'repo>,
+    notes_ref: &str,
+    span: pest::Span<'_>,
+) -> TemplateParseResult<impl Fn(&CommitId) -> Result<String, TemplatePropertyError> + 'repo> {
+    let notes = language
+        .keyword_cache
+        .git_notes(language.repo, &jj_lib::git::notes_ref_name(notes_ref))
+        .map_err(|err| {
+            TemplateParseError::expression("Failed to read Git notes", span).with_source(err)
+        })?;
+    Ok(move |id: &CommitId| {
+        let Some(notes) = &notes else {
+            return Ok(String::new());
+        };
+        Ok(notes.get(id)?.unwrap_or_default())
+    })
+}
+
+#[cfg(not(feature = "git"))]
+fn git_note_reader<'repo>(
+    _language: &CommitTemplateLanguage<'repo>,
+    _notes_ref: &str,
+    _span: pest::Span<'_>,
+) -> TemplateParseResult<impl Fn(&CommitId) -> Result<String, TemplatePropertyError> + 'repo> {
+    Ok(|_: &CommitId| Ok(String::new()))
+}
+
 fn expect_fileset_literal(
     diagnostics: &mut TemplateDiagnostics,
     node: &ExpressionNode,
@@ -1774,7 +1940,12 @@ impl TreeDiff {
 
     fn into_formatted<F, E>(self, show: F) -> TreeDiffFormatted<F>
     where
-        F: Fn(&mut dyn Formatter, &Store, BoxStream<CopiesTreeDiffEntry>) -> Result<(), E>,
+        F: Fn(
+            &mut dyn Formatter,
+            &Store,
+            BoxStream<CopiesTreeDiffEntry>,
+            &MergedTree,
+        ) -> Result<(), E>,
         E: Into<TemplatePropertyError>,
     {
         TreeDiffFormatted { diff: self, show }
@@ -1789,14 +1960,15 @@ struct TreeDiffFormatted<F> {
 
 impl<F, E> Template for TreeDiffFormatted<F>
 where
-    F: Fn(&mut dyn Formatter, &Store, BoxStream<CopiesTreeDiffEntry>) -> Result<(), E>,
+    F: Fn(&mut dyn Formatter, &Store, BoxStream<CopiesTreeDiffEntry>, &MergedTree) -> Result<(), E>,
     E: Into<TemplatePropertyError>,
 {
     fn format(&self, formatter: &mut TemplateFormatter) -> io::Result<()> {
         let show = &self.show;
         let store = self.diff.from_tree.store();
         let tree_diff = self.diff.diff_stream();
-        show(formatter.as_mut(), store, tree_diff).or_else(|err| formatter.handle_error(err.into()))
+        show(formatter.as_mut(), store, tree_diff, &self.diff.to_tree)
+            .or_else(|err| formatter.handle_error(err.into()))
     }
 }
 
@@ -1842,11 +2014,12 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
                     if let Some(context) = context {
                         options.context = context;
                     }
-                    diff.into_formatted(move |formatter, store, tree_diff| {
+                    diff.into_formatted(move |formatter, store, tree_diff, to_tree| {
                         diff_util::show_color_words_diff(
                             formatter,
                             store,
                             tree_diff,
+                            to_tree,
                             path_converter,
                             &options,
                             conflict_marker_style,
@@ -1883,11 +2056,12 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
                     if let Some(context) = context {
                         options.context = context;
                     }
-                    diff.into_formatted(move |formatter, store, tree_diff| {
+                    diff.into_formatted(move |formatter, store, tree_diff, to_tree| {
                         diff_util::show_git_diff(
                             formatter,
                             store,
                             tree_diff,
+                            to_tree,
                             &options,
                             conflict_marker_style,
                         )
@@ -1938,7 +2112,7 @@ fn builtin_tree_diff_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, T
             let path_converter = language.path_converter;
             let template = self_property
                 .map(move |diff| {
-                    diff.into_formatted(move |formatter, _store, tree_diff| {
+                    diff.into_formatted(move |formatter, _store, tree_diff, _to_tree| {
                         diff_util::show_diff_summary(formatter, tree_diff, path_converter)
                     })
                 })
@@ -2062,6 +2236,27 @@ fn builtin_tree_entry_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo,
             Ok(L::wrap_boolean(out_property))
         },
     );
+    map.insert(
+        "conflict_side_count",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property
+                .and_then(|entry| Ok(entry.value.simplify().num_sides().try_into()?));
+            Ok(L::wrap_integer(out_property))
+        },
+    );
+    map.insert(
+        "conflict_hunk_count",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let store = language.repo.store().clone();
+            let out_property = self_property.and_then(move |entry| {
+                let count = count_conflict_hunks(&store, &entry).block_on()?;
+                Ok(count.try_into()?)
+            });
+            Ok(L::wrap_integer(out_property))
+        },
+    );
     map.insert(
         "file_type",
         |_language, _diagnostics, _build_ctx, self_property, function| {
@@ -2083,6 +2278,25 @@ fn builtin_tree_entry_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo,
     map
 }
 
+/// Counts the conflicting hunks of the file conflict at the `entry`. Returns 0
+/// if the entry isn't a file conflict.
+async fn count_conflict_hunks(store: &Store, entry: &TreeEntry) -> BackendResult<usize> {
+    if entry.value.is_resolved() {
+        return Ok(0);
+    }
+    let Some(file_merge) = entry.value.to_file_merge() else {
+        return Ok(0);
+    };
+    let content = extract_as_single_hunk(&file_merge, store, &entry.path).await?;
+    // Use the same options as the materialized conflict markers
+    let merge_options = &store.merge_options().file_merge_options;
+    let count = match files::merge_with_options(&content, merge_options) {
+        MergeResult::Resolved(_) => 0,
+        MergeResult::Conflict(hunks) => hunks.iter().filter(|hunk| !hunk.is_resolved()).count(),
+    };
+    Ok(count)
+}
+
 fn describe_file_type(value: &MergedTreeValue) -> &'static str {
     match value.as_resolved() {
         Some(Some(TreeValue::File { .. })) => "file",
@@ -56,6 +56,11 @@ use jj_lib::diff::DiffHunkKind;
 use jj_lib::files::DiffLineHunkSide;
 use jj_lib::files::DiffLineIterator;
 use jj_lib::files::DiffLineNumber;
+use jj_lib::files::FileMergeOptions;
+use jj_lib::gitattributes::AttributeState;
+use jj_lib::gitattributes::GitAttributesError;
+use jj_lib::gitattributes::GitAttributesFile;
+use jj_lib::gitattributes::TreeGitAttributes;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
@@ -281,6 +286,8 @@ pub enum DiffRenderError {
     #[error(transparent)]
     InvalidRepoPath(#[from] InvalidRepoPathError),
     #[error(transparent)]
+    GitAttributes(#[from] GitAttributesError),
+    #[error(transparent)]
     Io(#[from] io::Error),
 }
 
@@ -377,6 +384,7 @@ impl<'a> DiffRenderer<'a> {
                         formatter,
                         store,
                         tree_diff,
+                        to_tree,
                         options,
                         self.conflict_marker_style,
                     )?;
@@ -388,6 +396,7 @@ impl<'a> DiffRenderer<'a> {
                         formatter,
                         store,
                         tree_diff,
+                        to_tree,
                         path_converter,
                         options,
                         self.conflict_marker_style,
@@ -888,9 +897,20 @@ fn file_content_for_diff(
     })
 }
 
+/// Returns true if the file at `path` is marked as `-diff`, in which case its
+/// contents should be treated as binary.
+async fn is_diff_disabled(
+    attributes: &mut TreeGitAttributes,
+    path: &RepoPath,
+) -> Result<bool, GitAttributesError> {
+    let attributes = attributes.for_file(path).await?;
+    Ok(attributes.get(path.as_internal_file_string(), "diff") == AttributeState::Unset)
+}
+
 fn diff_content(
     path: &RepoPath,
     value: MaterializedTreeValue,
+    merge_options: &FileMergeOptions,
     conflict_marker_style: ConflictMarkerStyle,
 ) -> BackendResult<FileContent> {
     match value {
@@ -916,7 +936,11 @@ fn diff_content(
             executable: _,
         } => Ok(FileContent {
             is_binary: false,
-            contents: materialize_merge_result_to_bytes(&contents, conflict_marker_style),
+            contents: materialize_merge_result_to_bytes(
+                &contents,
+                merge_options,
+                conflict_marker_style,
+            ),
         }),
         MaterializedTreeValue::OtherConflict { id } => Ok(FileContent {
             is_binary: false,
@@ -953,10 +977,13 @@ pub fn show_color_words_diff(
     formatter: &mut dyn Formatter,
     store: &Store,
     tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    to_tree: &MergedTree,
     path_converter: &RepoPathUiConverter,
     options: &ColorWordsDiffOptions,
     conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<(), DiffRenderError> {
+    let merge_options = &store.merge_options().file_merge_options;
+    let mut attributes = TreeGitAttributes::new(to_tree.clone(), GitAttributesFile::empty());
     let mut diff_stream = materialized_diff_stream(store, tree_diff);
     async {
         while let Some(MaterializedTreeDiffEntry { path, values }) = diff_stream.next().await {
@@ -991,10 +1018,17 @@ pub fn show_color_words_diff(
                     formatter.labeled("header"),
                     "Added {description} {right_ui_path}:"
                 )?;
-                let right_content = diff_content(right_path, right_value, conflict_marker_style)?;
+                let right_content = diff_content(
+                    right_path,
+                    right_value,
+                    merge_options,
+                    conflict_marker_style,
+                )?;
                 if right_content.is_empty() {
                     writeln!(formatter.labeled("empty"), "    (empty)")?;
-                } else if right_content.is_binary {
+                } else if right_content.is_binary
+                    || is_diff_disabled(&mut attributes, right_path).await?
+                {
                     writeln!(formatter.labeled("binary"), "    (binary)")?;
                 } else {
                     show_color_words_diff_hunks(
@@ -1048,8 +1082,14 @@ pub fn show_color_words_diff(
                         )
                     }
                 };
-                let left_content = diff_content(left_path, left_value, conflict_marker_style)?;
-                let right_content = diff_content(right_path, right_value, conflict_marker_style)?;
+                let left_content =
+                    diff_content(left_path, left_value, merge_options, conflict_marker_style)?;
+                let right_content = diff_content(
+                    right_path,
+                    right_value,
+                    merge_options,
+                    conflict_marker_style,
+                )?;
                 if left_path == right_path {
                     writeln!(
                         formatter.labeled("header"),
@@ -1061,7 +1101,10 @@ pub fn show_color_words_diff(
                         "{description} {right_ui_path} ({left_ui_path} => {right_ui_path}):"
                     )?;
                 }
-                if left_content.is_binary || right_content.is_binary {
+                if left_content.is_binary
+                    || right_content.is_binary
+                    || is_diff_disabled(&mut attributes, right_path).await?
+                {
                     writeln!(formatter.labeled("binary"), "    (binary)")?;
                 } else {
                     show_color_words_diff_hunks(
@@ -1076,10 +1119,13 @@ pub fn show_color_words_diff(
                     formatter.labeled("header"),
                     "Removed {description} {right_ui_path}:"
                 )?;
-                let left_content = diff_content(left_path, left_value, conflict_marker_style)?;
+                let left_content =
+                    diff_content(left_path, left_value, merge_options, conflict_marker_style)?;
                 if left_content.is_empty() {
                     writeln!(formatter.labeled("empty"), "    (empty)")?;
-                } else if left_content.is_binary {
+                } else if left_content.is_binary
+                    || is_diff_disabled(&mut attributes, left_path).await?
+                {
                     writeln!(formatter.labeled("binary"), "    (binary)")?;
                 } else {
                     show_color_words_diff_hunks(
@@ -1104,13 +1150,14 @@ pub fn show_file_by_file_diff(
     tool: &ExternalMergeTool,
     conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<(), DiffRenderError> {
+    let merge_options = &store.merge_options().file_merge_options;
     let create_file = |path: &RepoPath,
                        wc_dir: &Path,
                        value: MaterializedTreeValue|
      -> Result<PathBuf, DiffRenderError> {
         let fs_path = path.to_fs_path(wc_dir)?;
         std::fs::create_dir_all(fs_path.parent().unwrap())?;
-        let content = diff_content(path, value, conflict_marker_style)?;
+        let content = diff_content(path, value, merge_options, conflict_marker_style)?;
         std::fs::write(&fs_path, content.contents)?;
         Ok(fs_path)
     };
@@ -1176,6 +1223,7 @@ struct GitDiffPart {
 fn git_diff_part(
     path: &RepoPath,
     value: MaterializedTreeValue,
+    merge_options: &FileMergeOptions,
     conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<GitDiffPart, DiffRenderError> {
     const DUMMY_HASH: &str = "0000000000";
@@ -1225,7 +1273,11 @@ fn git_diff_part(
             hash = DUMMY_HASH.to_owned();
             content = FileContent {
                 is_binary: false, // TODO: are we sure this is never binary?
-                contents: materialize_merge_result_to_bytes(&contents, conflict_marker_style),
+                contents: materialize_merge_result_to_bytes(
+                    &contents,
+                    merge_options,
+                    conflict_marker_style,
+                ),
             };
         }
         MaterializedTreeValue::OtherConflict { id } => {
@@ -1500,9 +1552,12 @@ pub fn show_git_diff(
     formatter: &mut dyn Formatter,
     store: &Store,
     tree_diff: BoxStream<CopiesTreeDiffEntry>,
+    to_tree: &MergedTree,
     options: &UnifiedDiffOptions,
     conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<(), DiffRenderError> {
+    let merge_options = &store.merge_options().file_merge_options;
+    let mut attributes = TreeGitAttributes::new(to_tree.clone(), GitAttributesFile::empty());
     let mut diff_stream = materialized_diff_stream(store, tree_diff);
     async {
         while let Some(MaterializedTreeDiffEntry { path, values }) = diff_stream.next().await {
@@ -1512,8 +1567,14 @@ pub fn show_git_diff(
             let right_path_string = right_path.as_internal_file_string();
             let (left_value, right_value) = values?;
 
-            let left_part = git_diff_part(left_path, left_value, conflict_marker_style)?;
-            let right_part = git_diff_part(right_path, right_value, conflict_marker_style)?;
+            let left_part =
+                git_diff_part(left_path, left_value, merge_options, conflict_marker_style)?;
+            let right_part = git_diff_part(
+                right_path,
+                right_value,
+                merge_options,
+                conflict_marker_style,
+            )?;
 
             formatter.with_label("file_header", |formatter| {
                 writeln!(
@@ -1568,7 +1629,10 @@ pub fn show_git_diff(
                 Some(_) => format!("b/{right_path_string}"),
                 None => "/dev/null".to_owned(),
             };
-            if left_part.content.is_binary || right_part.content.is_binary {
+            if left_part.content.is_binary
+                || right_part.content.is_binary
+                || is_diff_disabled(&mut attributes, path.target()).await?
+            {
                 // TODO: add option to emit Git binary diff
                 writeln!(
                     formatter,
@@ -1659,11 +1723,14 @@ impl DiffStats {
         options: &DiffStatOptions,
         conflict_marker_style: ConflictMarkerStyle,
     ) -> BackendResult<Self> {
+        let merge_options = &store.merge_options().file_merge_options;
         let entries = materialized_diff_stream(store, tree_diff)
             .map(|MaterializedTreeDiffEntry { path, values }| {
                 let (left, right) = values?;
-                let left_content = diff_content(path.source(), left, conflict_marker_style)?;
-                let right_content = diff_content(path.target(), right, conflict_marker_style)?;
+                let left_content =
+                    diff_content(path.source(), left, merge_options, conflict_marker_style)?;
+                let right_content =
+                    diff_content(path.target(), right, merge_options, conflict_marker_style)?;
                 let stat = get_diff_stat_entry(
                     path,
                     [&left_content.contents, &right_content.contents].map(BStr::new),
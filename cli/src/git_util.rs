@@ -14,7 +14,10 @@
 
 //! Git utilities shared by various commands.
 
+use std::collections::BTreeSet;
 use std::error;
+use std::ffi::OsStr;
+use std::fs;
 use std::io;
 use std::io::Read as _;
 use std::io::Write as _;
@@ -22,31 +25,45 @@ use std::iter;
 use std::mem;
 use std::path::Path;
 use std::path::PathBuf;
+use std::process::Command;
 use std::process::Stdio;
 use std::time::Duration;
 use std::time::Instant;
 
 use crossterm::terminal::Clear;
 use crossterm::terminal::ClearType;
+use indexmap::IndexSet;
 use indoc::writedoc;
 use itertools::Itertools as _;
+use jj_lib::commit::Commit;
 use jj_lib::fmt_util::binary_prefix;
 use jj_lib::git;
 use jj_lib::git::FailedRefExportReason;
 use jj_lib::git::GitExportStats;
 use jj_lib::git::GitImportStats;
+use jj_lib::git::GitLfsTransportError;
 use jj_lib::git::GitRefKind;
+use jj_lib::git_lfs;
+use jj_lib::git_lfs::LfsTransport;
+use jj_lib::object_id::ObjectId as _;
 use jj_lib::op_store::RefTarget;
 use jj_lib::op_store::RemoteRef;
+use jj_lib::ref_name::RefNameBuf;
+use jj_lib::ref_name::RemoteName;
 use jj_lib::ref_name::RemoteRefSymbol;
+use jj_lib::refs::BookmarkPushUpdate;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
 use jj_lib::workspace::Workspace;
 use unicode_width::UnicodeWidthStr as _;
 
 use crate::cleanup_guard::CleanupGuard;
+use crate::cli_util::WorkspaceCommandHelper;
+use crate::cli_util::WorkspaceCommandTransaction;
 use crate::command_error::cli_error;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_hint;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::formatter::Formatter;
 use crate::ui::ProgressOutput;
@@ -280,6 +297,338 @@ pub fn with_remote_git_callbacks<T>(ui: &Ui, f: impl FnOnce(git::RemoteCallbacks
     result
 }
 
+/// Returns the transport to the Git LFS server of the `remote`, or `None` after
+/// printing a warning if the server isn't supported. The Git transfer shouldn't
+/// fail just because the LFS objects can't be transferred.
+fn lfs_transport_or_warn(
+    ui: &Ui,
+    repo: &dyn Repo,
+    remote: &RemoteName,
+    direction: gix::remote::Direction,
+) -> Result<Option<Box<dyn LfsTransport>>, CommandError> {
+    match git::lfs_transport_for_remote(repo.store(), remote, direction) {
+        Ok(transport) => Ok(Some(transport)),
+        Err(
+            err @ (GitLfsTransportError::NoSuchRemote(_) | GitLfsTransportError::UnsupportedUrl(_)),
+        ) => {
+            writeln!(
+                ui.warning_default(),
+                "Skipped transferring Git LFS objects: {err}"
+            )?;
+            writeln!(
+                ui.hint_default(),
+                "Only Git LFS servers in the local file system are supported. Set \
+                 `remote.<name>.lfsurl` in the Git config to use one."
+            )?;
+            Ok(None)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Uploads the Git LFS objects referenced by the `commits` to the LFS server
+/// of the `remote`.
+pub fn push_lfs_objects(
+    ui: &Ui,
+    tx: &WorkspaceCommandTransaction,
+    remote: &RemoteName,
+    commits: &[Commit],
+) -> Result<(), CommandError> {
+    let Some(lfs_store) = tx.base_workspace_helper().lfs_store() else {
+        return Ok(());
+    };
+    let pointers = git_lfs::lfs_pointers_in_commits(tx.repo(), commits)?;
+    if pointers.is_empty() {
+        return Ok(());
+    }
+    let Some(transport) =
+        lfs_transport_or_warn(ui, tx.repo(), remote, gix::remote::Direction::Push)?
+    else {
+        return Ok(());
+    };
+    let stats = transport.upload(&lfs_store, &pointers)?;
+    if !stats.missing.is_empty() {
+        return Err(user_error_with_hint(
+            format!(
+                "Won't push since {} Git LFS objects are missing locally",
+                stats.missing.len()
+            ),
+            format!(
+                "Missing objects: {}",
+                stats.missing.iter().map(|pointer| &pointer.oid).join(", ")
+            ),
+        ));
+    }
+    if stats.transferred > 0 {
+        writeln!(
+            ui.status(),
+            "Uploaded {} Git LFS objects to {}",
+            stats.transferred,
+            remote.as_symbol()
+        )?;
+    }
+    Ok(())
+}
+
+/// Downloads the Git LFS objects of the remote bookmarks updated by the
+/// transaction.
+pub fn fetch_lfs_objects(
+    ui: &Ui,
+    tx: &WorkspaceCommandTransaction,
+    remotes: &[&RemoteName],
+) -> Result<(), CommandError> {
+    let Some(lfs_store) = tx.base_workspace_helper().lfs_store() else {
+        return Ok(());
+    };
+    let old_view = tx.base_repo().view();
+    for &remote in remotes {
+        let new_heads: IndexSet<_> = tx
+            .repo()
+            .view()
+            .remote_bookmarks(remote)
+            .filter(|(name, remote_ref)| {
+                old_view
+                    .get_remote_bookmark(name.to_remote_symbol(remote))
+                    .target
+                    != remote_ref.target
+            })
+            .flat_map(|(_, remote_ref)| remote_ref.target.added_ids())
+            .cloned()
+            .collect();
+        let mut pointers = BTreeSet::new();
+        for id in &new_heads {
+            let commit = tx.repo().store().get_commit(id)?;
+            pointers.extend(git_lfs::lfs_pointers_in_tree(&commit.tree()?)?);
+        }
+        if pointers.is_empty() {
+            continue;
+        }
+        let Some(transport) =
+            lfs_transport_or_warn(ui, tx.repo(), remote, gix::remote::Direction::Fetch)?
+        else {
+            continue;
+        };
+        let stats = transport.download(&lfs_store, &pointers.into_iter().collect_vec())?;
+        if stats.transferred > 0 {
+            writeln!(
+                ui.status(),
+                "Downloaded {} Git LFS objects from {}",
+                stats.transferred,
+                remote.as_symbol()
+            )?;
+        }
+        if !stats.missing.is_empty() {
+            writeln!(
+                ui.warning_default(),
+                "{} Git LFS objects are not available on {}",
+                stats.missing.len(),
+                remote.as_symbol()
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Git hook that can be run by the commands updating the repo.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum GitHook {
+    PreCommit,
+    CommitMsg,
+    PrePush,
+}
+
+impl GitHook {
+    fn name(self) -> &'static str {
+        match self {
+            Self::PreCommit => "pre-commit",
+            Self::CommitMsg => "commit-msg",
+            Self::PrePush => "pre-push",
+        }
+    }
+}
+
+/// Returns the path to the executable `hook` if hooks are enabled by the
+/// `git.run-hooks` setting and the repo has one.
+///
+/// Like Git, hooks are looked up in `core.hooksPath` if configured, and in the
+/// `hooks` directory of the Git repo otherwise.
+fn find_git_hook(
+    workspace_command: &WorkspaceCommandHelper,
+    hook: GitHook,
+) -> Result<Option<PathBuf>, CommandError> {
+    if !workspace_command.settings().get_bool("git.run-hooks")? {
+        return Ok(None);
+    }
+    let Ok(git_backend) = git::get_git_backend(workspace_command.repo().store()) else {
+        return Ok(None);
+    };
+    let hooks_dir = git_backend
+        .git_repo()
+        .config_snapshot()
+        .string("core.hooksPath")
+        .and_then(|value| {
+            let path = std::str::from_utf8(&value)
+                .ok()
+                .map(jj_lib::file_util::expand_home_path)?;
+            // A relative path is resolved from the directory the hooks are run
+            // in.
+            Some(workspace_command.workspace_root().join(path))
+        })
+        .unwrap_or_else(|| git_backend.git_repo_path().join("hooks"));
+    let path = hooks_dir.join(hook.name());
+    if !is_executable_file(&path) {
+        return Ok(None);
+    }
+    Ok(Some(path))
+}
+
+#[cfg(unix)]
+fn is_executable_file(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt as _;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable_file(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Runs the hook at `path` in the workspace root, and forwards its output to
+/// stderr. Fails if the hook exits with a non-zero status.
+fn run_git_hook(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    hook: GitHook,
+    path: &Path,
+    args: &[&OsStr],
+    stdin: &[u8],
+) -> Result<(), CommandError> {
+    let git_backend = git::get_git_backend(workspace_command.repo().store())?;
+    tracing::info!(?path, "running git hook");
+    let mut child = Command::new(path)
+        .args(args)
+        .current_dir(workspace_command.workspace_root())
+        .env("GIT_DIR", git_backend.git_repo_path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| {
+            user_error_with_message(
+                format!("Failed to run the {} hook {}", hook.name(), path.display()),
+                err,
+            )
+        })?;
+    // The hook may exit without reading the input.
+    let mut child_stdin = child.stdin.take().unwrap();
+    _ = child_stdin.write_all(stdin);
+    drop(child_stdin);
+    let output = child.wait_with_output()?;
+    // Like Git, both stdout and stderr of the hook are written to stderr.
+    let mut stderr = ui.stderr();
+    stderr.write_all(&output.stdout)?;
+    stderr.write_all(&output.stderr)?;
+    if !output.status.success() {
+        return Err(user_error_with_hint(
+            format!("The {} hook failed ({})", hook.name(), output.status),
+            "Use --no-verify to skip the Git hooks.",
+        ));
+    }
+    Ok(())
+}
+
+/// Runs the `pre-commit` hook of the Git repo before committing the working
+/// copy.
+pub fn run_pre_commit_hook(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+) -> Result<(), CommandError> {
+    let Some(path) = find_git_hook(workspace_command, GitHook::PreCommit)? else {
+        return Ok(());
+    };
+    run_git_hook(ui, workspace_command, GitHook::PreCommit, &path, &[], b"")
+}
+
+/// Runs the `commit-msg` hook of the Git repo on the `description`, and
+/// returns the description as possibly edited by the hook.
+pub fn run_commit_msg_hook(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    description: &str,
+) -> Result<String, CommandError> {
+    let Some(path) = find_git_hook(workspace_command, GitHook::CommitMsg)? else {
+        return Ok(description.to_owned());
+    };
+    let message_path = tempfile::Builder::new()
+        .prefix("COMMIT_EDITMSG-")
+        .tempfile()?
+        .into_temp_path();
+    fs::write(&message_path, description)?;
+    run_git_hook(
+        ui,
+        workspace_command,
+        GitHook::CommitMsg,
+        &path,
+        &[message_path.as_os_str()],
+        b"",
+    )?;
+    let description = fs::read_to_string(&message_path).map_err(|err| {
+        user_error_with_message(
+            "Failed to read the message edited by the commit-msg hook",
+            err,
+        )
+    })?;
+    Ok(description)
+}
+
+/// Runs the `pre-push` hook of the Git repo before pushing the bookmark
+/// `updates` to the `remote`.
+///
+/// Like Git, the updates are passed to the hook on stdin, one line per ref.
+pub fn run_pre_push_hook(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    remote: &RemoteName,
+    updates: &[(RefNameBuf, BookmarkPushUpdate)],
+) -> Result<(), CommandError> {
+    let Some(path) = find_git_hook(workspace_command, GitHook::PrePush)? else {
+        return Ok(());
+    };
+    let git_repo = git::get_git_repo(workspace_command.repo().store())?;
+    let url = git_repo
+        .try_find_remote(remote.as_str())
+        .and_then(Result::ok)
+        .and_then(|remote| {
+            remote
+                .url(gix::remote::Direction::Push)
+                .map(|url| url.to_bstring().to_string())
+        })
+        .unwrap_or_default();
+    let null_id = "0".repeat(git_repo.object_hash().len_in_hex());
+    let mut stdin = vec![];
+    for (name, update) in updates {
+        let ref_name = format!("refs/heads/{}", name.as_str());
+        let (local_ref, local_id) = match &update.new_target {
+            Some(id) => (ref_name.as_str(), id.hex()),
+            None => ("(delete)", null_id.clone()),
+        };
+        let remote_id = update
+            .old_target
+            .as_ref()
+            .map_or_else(|| null_id.clone(), |id| id.hex());
+        writeln!(stdin, "{local_ref} {local_id} {ref_name} {remote_id}")?;
+    }
+    run_git_hook(
+        ui,
+        workspace_command,
+        GitHook::PrePush,
+        &path,
+        &[OsStr::new(remote.as_str()), OsStr::new(&url)],
+        &stdin,
+    )
+}
+
 pub fn print_git_import_stats(
     ui: &Ui,
     repo: &dyn Repo,
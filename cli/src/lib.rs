@@ -34,9 +34,28 @@ pub mod git_util {
     use jj_lib::repo::ReadonlyRepo;
     use jj_lib::workspace::Workspace;
 
+    use crate::cli_util::WorkspaceCommandHelper;
+    use crate::command_error::CommandError;
+    use crate::ui::Ui;
+
     pub fn is_colocated_git_workspace(_workspace: &Workspace, _repo: &ReadonlyRepo) -> bool {
         false
     }
+
+    pub fn run_pre_commit_hook(
+        _ui: &Ui,
+        _workspace_command: &WorkspaceCommandHelper,
+    ) -> Result<(), CommandError> {
+        Ok(())
+    }
+
+    pub fn run_commit_msg_hook(
+        _ui: &Ui,
+        _workspace_command: &WorkspaceCommandHelper,
+        description: &str,
+    ) -> Result<String, CommandError> {
+        Ok(description.to_owned())
+    }
 }
 pub mod graphlog;
 pub mod merge_tools;
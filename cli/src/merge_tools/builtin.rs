@@ -2,15 +2,20 @@ use std::borrow::Cow;
 use std::path::Path;
 use std::sync::Arc;
 
+use bstr::BString;
 use futures::StreamExt as _;
 use futures::TryFutureExt as _;
 use futures::TryStreamExt as _;
 use itertools::Itertools as _;
 use jj_lib::backend::BackendResult;
+use jj_lib::backend::FileId;
 use jj_lib::backend::MergedTreeId;
 use jj_lib::backend::TreeValue;
+use jj_lib::conflicts::choose_materialized_conflict_marker_len;
 use jj_lib::conflicts::materialize_merge_result_to_bytes;
+use jj_lib::conflicts::materialize_merge_result_to_bytes_with_marker_len;
 use jj_lib::conflicts::materialize_tree_value;
+use jj_lib::conflicts::update_from_content;
 use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::conflicts::MaterializedTreeValue;
 use jj_lib::diff::Diff;
@@ -30,6 +35,8 @@ use pollster::FutureExt as _;
 use thiserror::Error;
 
 use super::MergeToolFile;
+use crate::description_util::TempTextEditError;
+use crate::description_util::TextEditor;
 
 #[derive(Debug, Error)]
 pub enum BuiltinToolError {
@@ -42,8 +49,12 @@ pub enum BuiltinToolError {
     },
     #[error("Rendering {item} {id} is unimplemented for the builtin difftool/mergetool")]
     Unimplemented { item: &'static str, id: String },
+    #[error("Selected lines don't match the conflict (this should not happen)")]
+    MismatchedMergeSections,
     #[error("Backend error")]
     BackendError(#[from] jj_lib::backend::BackendError),
+    #[error(transparent)]
+    TextEdit(#[from] TempTextEditError),
 }
 
 #[derive(Clone, Debug)]
@@ -170,7 +181,12 @@ fn read_file_contents(
             contents,
             executable: _,
         } => {
-            let buf = materialize_merge_result_to_bytes(&contents, conflict_marker_style).into();
+            let buf = materialize_merge_result_to_bytes(
+                &contents,
+                &store.merge_options().file_merge_options,
+                conflict_marker_style,
+            )
+            .into();
             // TODO: Render the ID somehow?
             let contents = buf_to_file_contents(None, buf);
             Ok(FileInfo {
@@ -487,6 +503,26 @@ pub fn edit_diff_builtin(
     Ok(tree_id)
 }
 
+fn make_text_lines(contents: &str) -> Vec<Cow<'static, str>> {
+    contents
+        .split_inclusive('\n')
+        .map(|line| Cow::Owned(line.to_owned()))
+        .collect()
+}
+
+fn make_conflict_label_section(label: String) -> scm_record::Section<'static> {
+    scm_record::Section::Unchanged {
+        lines: vec![Cow::Owned(label)],
+    }
+}
+
+/// Makes the sections of a conflicted file. Each term of a conflicting hunk
+/// gets its own section, preceded by a label, so that a side or the base can
+/// be chosen by selecting its whole section, and the sides can be combined by
+/// selecting several sections. The selected lines are kept in the result.
+///
+/// Unlike removed lines in a diff, selecting a line of the base keeps it
+/// instead of deleting it, which is spelled out in the label of the base.
 fn make_merge_sections(
     merge_result: MergeResult,
 ) -> Result<Vec<scm_record::Section<'static>>, BuiltinToolError> {
@@ -501,10 +537,7 @@ fn make_merge_sections(
                     hash: _,
                     num_bytes: _,
                 } => Some(scm_record::Section::Unchanged {
-                    lines: contents
-                        .split_inclusive('\n')
-                        .map(|line| Cow::Owned(line.to_owned()))
-                        .collect(),
+                    lines: make_text_lines(&contents),
                 }),
                 FileContents::Binary { hash, num_bytes } => Some(scm_record::Section::Binary {
                     is_checked: false,
@@ -517,8 +550,10 @@ fn make_merge_sections(
             }
         }
         MergeResult::Conflict(hunks) => {
+            let num_conflicts = hunks.iter().filter(|hunk| !hunk.is_resolved()).count();
+            let mut conflict_index = 0;
             for hunk in hunks {
-                let section = match hunk.into_resolved() {
+                let merge = match hunk.into_resolved() {
                     Ok(contents) => {
                         let contents = std::str::from_utf8(&contents).map_err(|err| {
                             BuiltinToolError::DecodeUtf8 {
@@ -526,51 +561,104 @@ fn make_merge_sections(
                                 item: "unchanged hunk",
                             }
                         })?;
-                        scm_record::Section::Unchanged {
-                            lines: contents
-                                .split_inclusive('\n')
-                                .map(|line| Cow::Owned(line.to_owned()))
-                                .collect(),
-                        }
-                    }
-                    Err(merge) => {
-                        let lines: Vec<scm_record::SectionChangedLine> = merge
-                            .iter()
-                            .zip(
-                                [
-                                    scm_record::ChangeType::Added,
-                                    scm_record::ChangeType::Removed,
-                                ]
-                                .into_iter()
-                                .cycle(),
-                            )
-                            .map(|(contents, change_type)| -> Result<_, BuiltinToolError> {
-                                let contents = std::str::from_utf8(contents).map_err(|err| {
-                                    BuiltinToolError::DecodeUtf8 {
-                                        source: err,
-                                        item: "conflicting hunk",
-                                    }
-                                })?;
-                                let changed_lines =
-                                    make_section_changed_lines(contents, change_type);
-                                Ok(changed_lines)
-                            })
-                            .flatten_ok()
-                            .try_collect()?;
-                        scm_record::Section::Changed { lines }
+                        sections.push(scm_record::Section::Unchanged {
+                            lines: make_text_lines(contents),
+                        });
+                        continue;
                     }
+                    Err(merge) => merge,
                 };
-                sections.push(section);
+                conflict_index += 1;
+                sections.push(make_conflict_label_section(format!(
+                    "<<<<<<< Conflict {conflict_index} of {num_conflicts}\n"
+                )));
+                for (term_index, contents) in merge.iter().enumerate() {
+                    let contents = std::str::from_utf8(contents).map_err(|err| {
+                        BuiltinToolError::DecodeUtf8 {
+                            source: err,
+                            item: "conflicting hunk",
+                        }
+                    })?;
+                    let (label, change_type) = if term_index % 2 == 0 {
+                        let label = format!("+++++++ Contents of side #{}\n", term_index / 2 + 1);
+                        (label, scm_record::ChangeType::Added)
+                    } else if merge.num_sides() == 2 {
+                        let label =
+                            "------- Contents of base (selected lines are kept)\n".to_owned();
+                        (label, scm_record::ChangeType::Removed)
+                    } else {
+                        let label = format!(
+                            "------- Contents of base #{} (selected lines are kept)\n",
+                            term_index / 2 + 1
+                        );
+                        (label, scm_record::ChangeType::Removed)
+                    };
+                    sections.push(make_conflict_label_section(label));
+                    // Empty terms have no lines to select.
+                    if !contents.is_empty() {
+                        sections.push(scm_record::Section::Changed {
+                            lines: make_section_changed_lines(contents, change_type),
+                        });
+                    }
+                }
+                sections.push(make_conflict_label_section(format!(
+                    ">>>>>>> Conflict {conflict_index} of {num_conflicts} ends\n"
+                )));
             }
         }
     }
     Ok(sections)
 }
 
+/// Resolves the conflicting `hunks` to the lines selected in the `sections`
+/// made by `make_merge_sections()`. Conflicting hunks without selected lines
+/// are left unresolved.
+fn apply_merge_sections(
+    hunks: &[Merge<BString>],
+    sections: &[scm_record::Section],
+) -> Result<Vec<Merge<BString>>, BuiltinToolError> {
+    let mut sections = sections.iter();
+    let mut next_section = || {
+        sections
+            .next()
+            .ok_or(BuiltinToolError::MismatchedMergeSections)
+    };
+    hunks
+        .iter()
+        .map(|hunk| {
+            if hunk.is_resolved() {
+                next_section()?;
+                return Ok(hunk.clone());
+            }
+            next_section()?; // Start of the conflict
+            let mut selected: Option<BString> = None;
+            for contents in hunk.iter() {
+                next_section()?; // Label of the term
+                if contents.is_empty() {
+                    continue;
+                }
+                let scm_record::Section::Changed { lines } = next_section()? else {
+                    return Err(BuiltinToolError::MismatchedMergeSections);
+                };
+                for line in lines.iter().filter(|line| line.is_checked) {
+                    selected
+                        .get_or_insert_with(BString::default)
+                        .extend_from_slice(line.line.as_bytes());
+                }
+            }
+            next_section()?; // End of the conflict
+            Ok(match selected {
+                Some(contents) => Merge::resolved(contents),
+                None => hunk.clone(),
+            })
+        })
+        .try_collect()
+}
+
 fn make_merge_file(
     merge_tool_file: &MergeToolFile,
+    merge_result: MergeResult,
 ) -> Result<scm_record::File<'static>, BuiltinToolError> {
-    let merge_result = files::merge(&merge_tool_file.content);
     let sections = make_merge_sections(merge_result)?;
     Ok(scm_record::File {
         old_path: None,
@@ -585,37 +673,131 @@ fn make_merge_file(
     })
 }
 
+/// Writes the merge result of a file after the user selected lines from the
+/// conflicting hunks. If some hunks are left unresolved, the remaining
+/// conflicts are materialized and opened in the `text_editor`, if any, so they
+/// can be edited by hand.
+fn write_merge_result(
+    store: &Store,
+    merge_tool_file: &MergeToolFile,
+    hunks: &[Merge<BString>],
+    text_editor: Option<&TextEditor>,
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<Merge<Option<FileId>>, BuiltinToolError> {
+    let repo_path = &merge_tool_file.repo_path;
+    if hunks.iter().all(|hunk| hunk.is_resolved()) {
+        let contents = hunks
+            .iter()
+            .map(|hunk| hunk.first().as_slice())
+            .collect_vec()
+            .concat();
+        let file_id = store
+            .write_file(repo_path, &mut contents.as_slice())
+            .block_on()?;
+        return Ok(Merge::normal(file_id));
+    }
+    // Rebuild the terms of the partially resolved file from the hunks.
+    let num_terms = merge_tool_file.content.as_slice().len();
+    let contents = Merge::from_vec(
+        (0..num_terms)
+            .map(|term_index| {
+                hunks
+                    .iter()
+                    .map(|hunk| match hunk.as_resolved() {
+                        Some(contents) => contents.as_slice(),
+                        None => hunk.as_slice()[term_index].as_slice(),
+                    })
+                    .collect_vec()
+                    .concat()
+            })
+            .collect_vec(),
+    );
+    let conflict_marker_len = choose_materialized_conflict_marker_len(&contents);
+    let materialized = materialize_merge_result_to_bytes_with_marker_len(
+        &contents,
+        &store.merge_options().file_merge_options,
+        conflict_marker_style,
+        conflict_marker_len,
+    );
+    let edited: BString = match text_editor {
+        Some(editor) => editor
+            .edit_str(&materialized, None)
+            .map_err(|err| err.with_name("conflicts"))?
+            .into(),
+        None => materialized,
+    };
+    let new_file_ids = update_from_content(
+        &merge_tool_file.file_merge,
+        store,
+        repo_path,
+        &edited,
+        conflict_marker_style,
+        conflict_marker_len,
+    )
+    .block_on()?;
+    Ok(new_file_ids)
+}
+
 pub fn edit_merge_builtin(
     tree: &MergedTree,
     merge_tool_files: &[MergeToolFile],
+    text_editor: Option<&TextEditor>,
+    conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<MergedTreeId, BuiltinToolError> {
+    let merge_options = &tree.store().merge_options().file_merge_options;
+    let merge_results = merge_tool_files
+        .iter()
+        .map(|file| files::merge_with_options(&file.content, merge_options))
+        .collect_vec();
     let mut input = scm_record::helpers::CrosstermInput;
     let recorder = scm_record::Recorder::new(
         scm_record::RecordState {
             is_read_only: false,
-            files: merge_tool_files.iter().map(make_merge_file).try_collect()?,
+            files: merge_tool_files
+                .iter()
+                .zip(&merge_results)
+                .map(|(file, merge_result)| make_merge_file(file, merge_result.clone()))
+                .try_collect()?,
             commits: Default::default(),
         },
         &mut input,
     );
     let state = recorder.run()?;
 
-    apply_diff_builtin(
-        tree.store(),
-        tree,
-        tree,
-        merge_tool_files
-            .iter()
-            .map(|file| file.repo_path.clone())
-            .collect_vec(),
-        &state.files,
-    )
-    .map_err(BuiltinToolError::BackendError)
+    let store = tree.store();
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    for ((merge_tool_file, merge_result), file) in
+        merge_tool_files.iter().zip(merge_results).zip(&state.files)
+    {
+        let hunks = match merge_result {
+            MergeResult::Resolved(contents) => vec![Merge::resolved(contents)],
+            MergeResult::Conflict(hunks) => apply_merge_sections(&hunks, &file.sections)?,
+        };
+        let new_file_ids = write_merge_result(
+            store,
+            merge_tool_file,
+            &hunks,
+            text_editor,
+            conflict_marker_style,
+        )?;
+        // Update the file ids only, leaving the executable flags unchanged
+        let new_file_ids = if let Some(resolved) = new_file_ids.as_resolved() {
+            Merge::from_vec(vec![
+                resolved.clone();
+                merge_tool_file.conflict.as_slice().len()
+            ])
+        } else {
+            new_file_ids
+        };
+        let new_tree_value = merge_tool_file.conflict.with_new_file_ids(&new_file_ids);
+        tree_builder.set_or_remove(merge_tool_file.repo_path.clone(), new_tree_value);
+    }
+    Ok(tree_builder.write_tree(store)?)
 }
 
 #[cfg(test)]
 mod tests {
-    use jj_lib::backend::FileId;
+    use assert_matches::assert_matches;
     use jj_lib::conflicts::extract_as_single_hunk;
     use jj_lib::merge::MergedTreeValue;
     use jj_lib::repo::Repo as _;
@@ -1242,6 +1424,16 @@ mod tests {
         let sections = make_merge_sections(merge_result).unwrap();
         insta::assert_debug_snapshot!(sections, @r#"
         [
+            Unchanged {
+                lines: [
+                    "<<<<<<< Conflict 1 of 2\n",
+                ],
+            },
+            Unchanged {
+                lines: [
+                    "+++++++ Contents of side #1\n",
+                ],
+            },
             Changed {
                 lines: [
                     SectionChangedLine {
@@ -1249,11 +1441,29 @@ mod tests {
                         change_type: Added,
                         line: "left 1\n",
                     },
+                ],
+            },
+            Unchanged {
+                lines: [
+                    "------- Contents of base (selected lines are kept)\n",
+                ],
+            },
+            Changed {
+                lines: [
                     SectionChangedLine {
                         is_checked: false,
                         change_type: Removed,
                         line: "base 1\n",
                     },
+                ],
+            },
+            Unchanged {
+                lines: [
+                    "+++++++ Contents of side #2\n",
+                ],
+            },
+            Changed {
+                lines: [
                     SectionChangedLine {
                         is_checked: false,
                         change_type: Added,
@@ -1261,6 +1471,11 @@ mod tests {
                     },
                 ],
             },
+            Unchanged {
+                lines: [
+                    ">>>>>>> Conflict 1 of 2 ends\n",
+                ],
+            },
             Unchanged {
                 lines: [
                     "base 2\n",
@@ -1268,6 +1483,16 @@ mod tests {
                     "base 4\n",
                 ],
             },
+            Unchanged {
+                lines: [
+                    "<<<<<<< Conflict 2 of 2\n",
+                ],
+            },
+            Unchanged {
+                lines: [
+                    "+++++++ Contents of side #1\n",
+                ],
+            },
             Changed {
                 lines: [
                     SectionChangedLine {
@@ -1275,11 +1500,29 @@ mod tests {
                         change_type: Added,
                         line: "left 5\n",
                     },
+                ],
+            },
+            Unchanged {
+                lines: [
+                    "------- Contents of base (selected lines are kept)\n",
+                ],
+            },
+            Changed {
+                lines: [
                     SectionChangedLine {
                         is_checked: false,
                         change_type: Removed,
                         line: "base 5\n",
                     },
+                ],
+            },
+            Unchanged {
+                lines: [
+                    "+++++++ Contents of side #2\n",
+                ],
+            },
+            Changed {
+                lines: [
                     SectionChangedLine {
                         is_checked: false,
                         change_type: Added,
@@ -1287,7 +1530,126 @@ mod tests {
                     },
                 ],
             },
+            Unchanged {
+                lines: [
+                    ">>>>>>> Conflict 2 of 2 ends\n",
+                ],
+            },
         ]
         "#);
     }
+
+    #[test]
+    fn test_apply_merge_sections() {
+        let conflict = |terms: &[&str]| {
+            Merge::from_vec(terms.iter().copied().map(BString::from).collect::<Vec<_>>())
+        };
+        let hunks = vec![
+            Merge::resolved(BString::from("a\n")),
+            conflict(&["b1\n", "b\n", "b2\n"]),
+            Merge::resolved(BString::from("c\n")),
+            conflict(&["d1\n", "d\n", "d2\n", "", "d3\n"]),
+            conflict(&["e1\n", "e\n", "e2\n"]),
+        ];
+        let mut sections = make_merge_sections(MergeResult::Conflict(hunks.clone())).unwrap();
+        let mut select = |text: &str| {
+            for section in &mut sections {
+                if let scm_record::Section::Changed { lines } = section {
+                    for line in lines.iter_mut().filter(|line| line.line == text) {
+                        line.is_checked = true;
+                    }
+                }
+            }
+        };
+        // Choose a side
+        select("b2\n");
+        // Combine several sides of a 3-sided conflict
+        select("d3\n");
+        select("d1\n");
+        assert_eq!(
+            apply_merge_sections(&hunks, &sections).unwrap(),
+            vec![
+                Merge::resolved(BString::from("a\n")),
+                Merge::resolved(BString::from("b2\n")),
+                Merge::resolved(BString::from("c\n")),
+                Merge::resolved(BString::from("d1\nd3\n")),
+                // Nothing was selected, so the conflict is left unresolved
+                conflict(&["e1\n", "e\n", "e2\n"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_merge_sections_mismatched() {
+        let hunks = vec![Merge::from_vec(vec![
+            BString::from("a1\n"),
+            BString::from("a\n"),
+            BString::from("a2\n"),
+        ])];
+        let sections = make_merge_sections(MergeResult::Conflict(hunks.clone())).unwrap();
+        assert_matches!(
+            apply_merge_sections(&hunks, &sections[..sections.len() - 1]),
+            Err(BuiltinToolError::MismatchedMergeSections)
+        );
+    }
+
+    #[test]
+    fn test_edit_merge_builtin_many_sides() {
+        let test_repo = TestRepo::init();
+        let store = test_repo.repo.store();
+
+        let path = RepoPath::from_internal_string("file");
+        let create_tree =
+            |contents: &str| testutils::create_single_tree(&test_repo.repo, &[(path, contents)]);
+        let base_tree = create_tree("a\nbase\nz\n");
+        let tree = MergedTree::new(Merge::from_vec(vec![
+            create_tree("a\none\nz\n"),
+            base_tree.clone(),
+            create_tree("a\ntwo\nz\n"),
+            base_tree,
+            create_tree("a\nthree\nz\n"),
+        ]));
+        let merge_tool_file = MergeToolFile::from_tree_and_path(&tree, path).unwrap();
+        let merge_options = &store.merge_options().file_merge_options;
+        let MergeResult::Conflict(hunks) =
+            files::merge_with_options(&merge_tool_file.content, merge_options)
+        else {
+            panic!("merge should have conflicted");
+        };
+        let mut sections = make_merge_sections(MergeResult::Conflict(hunks.clone())).unwrap();
+        let write_sections = |sections: &[scm_record::Section]| {
+            let hunks = apply_merge_sections(&hunks, sections).unwrap();
+            write_merge_result(
+                store,
+                &merge_tool_file,
+                &hunks,
+                None,
+                ConflictMarkerStyle::Diff,
+            )
+            .unwrap()
+        };
+
+        // Nothing was selected, so the 3-sided conflict is left as is
+        let file_ids = write_sections(&sections);
+        assert_eq!(file_ids.num_sides(), 3);
+        assert_eq!(file_ids, merge_tool_file.file_merge);
+
+        // Combine the first and the last sides
+        for section in &mut sections {
+            if let scm_record::Section::Changed { lines } = section {
+                for line in lines
+                    .iter_mut()
+                    .filter(|line| line.line == "one\n" || line.line == "three\n")
+                {
+                    line.is_checked = true;
+                }
+            }
+        }
+        let file_ids = write_sections(&sections);
+        let file_id = file_ids.as_resolved().unwrap().as_ref().unwrap();
+        assert_eq!(
+            testutils::read_file(store, path, file_id),
+            b"a\none\nthree\nz\n"
+        );
+    }
 }
@@ -0,0 +1,225 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resolution of conflicts by taking one of their sides, for conflicts that
+//! can't be resolved by merging file contents, such as conflicts between a file
+//! and a directory.
+
+use std::io::Write as _;
+
+use itertools::Itertools as _;
+use jj_lib::backend::BackendResult;
+use jj_lib::backend::MergedTreeId;
+use jj_lib::backend::TreeValue;
+use jj_lib::merge::Merge;
+use jj_lib::merge::MergedTreeValue;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::merged_tree::MergedTreeBuilder;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::repo_path::RepoPathUiConverter;
+use jj_lib::store::Store;
+
+use super::conflict_at;
+use super::ConflictResolveError;
+use crate::ui::Ui;
+
+/// Side of a conflict to resolve the conflict with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TakeSide {
+    /// Index of the side, starting from 0.
+    pub index: usize,
+    /// Whether to keep the other sides at `<path>~side-<N>`.
+    pub keep_others: bool,
+}
+
+/// Resolves the conflicts at `repo_paths` by taking the same side of each
+/// conflict.
+pub fn take_side_of_conflicts(
+    tree: &MergedTree,
+    repo_paths: &[&RepoPath],
+    take: TakeSide,
+) -> Result<MergedTreeId, ConflictResolveError> {
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    for &repo_path in repo_paths {
+        let conflict = conflict_at(tree, repo_path)?;
+        take_side(tree, &mut tree_builder, repo_path, &conflict, take)?;
+    }
+    Ok(tree_builder.write_tree(tree.store())?)
+}
+
+fn take_side(
+    tree: &MergedTree,
+    tree_builder: &mut MergedTreeBuilder,
+    repo_path: &RepoPath,
+    conflict: &MergedTreeValue,
+    take: TakeSide,
+) -> Result<(), ConflictResolveError> {
+    let sides = conflict.clone().simplify().adds().cloned().collect_vec();
+    let Some(value) = sides.get(take.index) else {
+        return Err(ConflictResolveError::SideOutOfRange {
+            path: repo_path.to_owned(),
+            side: take.index + 1,
+            sides: sides.len(),
+        });
+    };
+    if take.keep_others {
+        for (index, other) in sides.iter().enumerate() {
+            if index == take.index || other.is_none() {
+                continue;
+            }
+            let aside_path = RepoPathBuf::from_internal_string(format!(
+                "{}~side-{}",
+                repo_path.as_internal_file_string(),
+                index + 1
+            ));
+            if tree.path_value(&aside_path)?.is_present() {
+                return Err(ConflictResolveError::PathAlreadyExists(aside_path));
+            }
+            tree_builder.set_or_remove(aside_path, Merge::resolved(other.clone()));
+        }
+    }
+    tree_builder.set_or_remove(repo_path.to_owned(), Merge::resolved(value.clone()));
+    Ok(())
+}
+
+/// Asks the user to choose a side of each conflict that can't be resolved by
+/// merging file contents. Conflicts between the executable bits of files are
+/// resolved by choosing the side to take the executable bit from. Returns the
+/// new tree and the paths which still have conflicts between file contents.
+pub(crate) fn choose_sides_of_conflicts(
+    ui: &Ui,
+    path_converter: &RepoPathUiConverter,
+    tree: &MergedTree,
+    repo_paths: &[&RepoPath],
+) -> Result<(MergedTree, Vec<RepoPathBuf>), ConflictResolveError> {
+    let store = tree.store();
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    for &repo_path in repo_paths {
+        let conflict = conflict_at(tree, repo_path)?;
+        let simplified = conflict.clone().simplify();
+        let ui_path = path_converter.format_file_path(repo_path);
+        let sides = simplified.adds().cloned().collect_vec();
+        let is_file_conflict = simplified
+            .to_file_merge()
+            .is_some_and(|file_merge| file_merge.adds().all(Option::is_some));
+        if !is_file_conflict {
+            writeln!(
+                ui.stderr(),
+                "Choose a side to resolve the conflict at {ui_path}:"
+            )?;
+            print_sides(ui, store, repo_path, &sides)?;
+            writeln!(
+                ui.stderr(),
+                "Append \"+\" to the side to keep the other sides at {ui_path}~side-N."
+            )?;
+            let take = prompt_side(ui, "Enter the side to take", sides.len(), true)?;
+            take_side(tree, &mut tree_builder, repo_path, &conflict, take)?;
+            continue;
+        }
+        let executable_merge = simplified.to_executable_merge().unwrap();
+        if executable_merge.resolve_trivial().is_none() {
+            writeln!(
+                ui.stderr(),
+                "The executable bit of {ui_path} differs between the sides:"
+            )?;
+            print_sides(ui, store, repo_path, &sides)?;
+            let take = prompt_side(
+                ui,
+                "Enter the side to take the executable bit from",
+                sides.len(),
+                false,
+            )?;
+            let executable = *executable_merge.adds().nth(take.index).unwrap();
+            let new_conflict = conflict.map(|term| match term {
+                Some(TreeValue::File { id, executable: _ }) => Some(TreeValue::File {
+                    id: id.clone(),
+                    executable,
+                }),
+                other => other.clone(),
+            });
+            tree_builder.set_or_remove(repo_path.to_owned(), new_conflict);
+        }
+    }
+    let new_tree = store.get_root_tree(&tree_builder.write_tree(store)?)?;
+    let mut remaining_paths = vec![];
+    for &repo_path in repo_paths {
+        if !new_tree.path_value(repo_path)?.is_resolved() {
+            remaining_paths.push(repo_path.to_owned());
+        }
+    }
+    Ok((new_tree, remaining_paths))
+}
+
+fn print_sides(
+    ui: &Ui,
+    store: &Store,
+    repo_path: &RepoPath,
+    sides: &[Option<TreeValue>],
+) -> Result<(), ConflictResolveError> {
+    for (index, side) in sides.iter().enumerate() {
+        let description = describe_side(store, repo_path, side.as_ref())?;
+        writeln!(ui.stderr(), "{}: {description}", index + 1)?;
+    }
+    Ok(())
+}
+
+fn describe_side(
+    store: &Store,
+    repo_path: &RepoPath,
+    value: Option<&TreeValue>,
+) -> BackendResult<String> {
+    let description = match value {
+        None => "deleted".to_owned(),
+        Some(TreeValue::File {
+            id: _,
+            executable: false,
+        }) => "file".to_owned(),
+        Some(TreeValue::File {
+            id: _,
+            executable: true,
+        }) => "executable file".to_owned(),
+        Some(TreeValue::Symlink(id)) => {
+            format!("symlink to {}", store.read_symlink(repo_path, id)?)
+        }
+        Some(TreeValue::Tree(_)) => "directory".to_owned(),
+        Some(TreeValue::GitSubmodule(_)) => "Git submodule".to_owned(),
+        Some(TreeValue::Conflict(_)) => "conflict".to_owned(),
+    };
+    Ok(description)
+}
+
+fn prompt_side(
+    ui: &Ui,
+    prompt: &str,
+    num_sides: usize,
+    allow_keep_others: bool,
+) -> Result<TakeSide, ConflictResolveError> {
+    let take = ui.prompt_choice_with(prompt, None, |input| {
+        let (number, keep_others) = match input.strip_suffix('+') {
+            Some(number) if allow_keep_others => (number, true),
+            _ => (input, false),
+        };
+        number
+            .parse::<usize>()
+            .ok()
+            .filter(|number| (1..=num_sides).contains(number))
+            .map(|number| TakeSide {
+                index: number - 1,
+                keep_others,
+            })
+            .ok_or("Invalid side")
+    })?;
+    Ok(take)
+}
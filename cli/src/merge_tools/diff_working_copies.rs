@@ -299,6 +299,8 @@ diff editing in mind and be a little inaccurate.
             start_tracking_matcher: &EverythingMatcher,
             max_new_file_size: u64::MAX,
             conflict_marker_style,
+            lfs_store: None,
+            validate_conflict_markers: false,
         })?;
         Ok(output_tree_state.current_tree_id().clone())
     }
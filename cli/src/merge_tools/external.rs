@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::io;
+use std::io::BufReader;
 use std::io::Write;
 use std::process::Command;
 use std::process::ExitStatus;
@@ -21,6 +22,8 @@ use jj_lib::merged_tree::MergedTree;
 use jj_lib::merged_tree::MergedTreeBuilder;
 use jj_lib::repo_path::RepoPathUiConverter;
 use jj_lib::store::Store;
+use jj_lib::tool_protocol::ToolSession;
+use jj_lib::tool_protocol::ToolSessionState;
 use jj_lib::working_copy::CheckoutOptions;
 use pollster::FutureExt as _;
 use thiserror::Error;
@@ -82,6 +85,9 @@ pub struct ExternalMergeTool {
     /// useful if a tool parses conflict markers, and so it requires a specific
     /// format, or if a certain format is more readable than another.
     pub conflict_marker_style: Option<ConflictMarkerStyle>,
+    /// How the tool gets the files to edit when editing diffs or resolving
+    /// conflicts.
+    pub protocol: MergeToolProtocol,
 }
 
 #[derive(serde::Deserialize, Copy, Clone, Debug, Eq, PartialEq)]
@@ -93,6 +99,17 @@ pub enum DiffToolMode {
     FileByFile,
 }
 
+#[derive(serde::Deserialize, Copy, Clone, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeToolProtocol {
+    /// Pass the files to the tool as paths to temporary files or directories.
+    #[default]
+    Files,
+    /// Serve the files through JSON-RPC requests sent by the tool on its
+    /// stdout. See [`jj_lib::tool_protocol`].
+    JsonRpc,
+}
+
 impl Default for ExternalMergeTool {
     fn default() -> Self {
         Self {
@@ -110,6 +127,7 @@ impl Default for ExternalMergeTool {
             merge_tool_edits_conflict_markers: false,
             conflict_marker_style: None,
             diff_invocation_mode: DiffToolMode::Dir,
+            protocol: MergeToolProtocol::Files,
         }
     }
 }
@@ -169,6 +187,12 @@ pub enum ExternalToolError {
          --debug to see the exact invocation)"
     )]
     InvalidConflictMarkers { exit_status: ExitStatus },
+    #[error(
+        "Tool exited without finishing the session (run with --debug to see the exact invocation)"
+    )]
+    SessionNotFinished,
+    #[error("Canceled by user in the tool")]
+    SessionAborted,
     #[error("I/O error")]
     Io(#[source] std::io::Error),
 }
@@ -205,6 +229,7 @@ fn run_mergetool_external_single_file(
     let initial_output_content = if editor.merge_tool_edits_conflict_markers {
         materialize_merge_result_to_bytes_with_marker_len(
             content,
+            &store.merge_options().file_merge_options,
             conflict_marker_style,
             conflict_marker_len,
         )
@@ -321,6 +346,25 @@ pub fn run_mergetool_external(
     merge_tool_files: &[MergeToolFile],
     default_conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<(MergedTreeId, Option<MergeToolPartialResolutionError>), ConflictResolveError> {
+    if editor.protocol == MergeToolProtocol::JsonRpc {
+        for merge_tool_file in merge_tool_files {
+            writeln!(
+                ui.status(),
+                "Resolving conflicts in: {}",
+                path_converter.format_file_path(&merge_tool_file.repo_path)
+            )?;
+        }
+        let conflict_marker_style = editor
+            .conflict_marker_style
+            .unwrap_or(default_conflict_marker_style);
+        let conflicts = merge_tool_files
+            .iter()
+            .map(|file| (file.repo_path.clone(), file.conflict.clone()));
+        let mut session = ToolSession::for_merge(tree, conflicts, conflict_marker_style);
+        run_tool_session(editor, &editor.merge_args, &mut session)?;
+        return Ok((session.write_tree()?, None));
+    }
+
     // TODO: add support for "dir" invocation mode, similar to the
     // "diff-invocation-mode" config option for diffs
     let mut tree_builder = MergedTreeBuilder::new(tree.id());
@@ -358,6 +402,45 @@ pub fn run_mergetool_external(
     Ok((new_tree, partial_resolution_error))
 }
 
+/// Runs the tool with the `args`, and serves the requests it sends on its
+/// stdout until it finishes the `session`.
+fn run_tool_session(
+    editor: &ExternalMergeTool,
+    args: &[String],
+    session: &mut ToolSession,
+) -> Result<(), ExternalToolError> {
+    let mut cmd = Command::new(&editor.program);
+    cmd.args(args).stdin(Stdio::piped()).stdout(Stdio::piped());
+    tracing::info!(
+        ?cmd,
+        "Invoking the external tool with the JSON-RPC protocol:"
+    );
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| ExternalToolError::FailedToExecute {
+            tool_binary: editor.program.clone(),
+            source: e,
+        })?;
+    let mut reader = BufReader::new(child.stdout.take().unwrap());
+    let mut writer = child.stdin.take().unwrap();
+    let served = session.serve(&mut reader, &mut writer);
+    // Close the pipes so the tool doesn't wait for more responses.
+    drop(writer);
+    drop(reader);
+    let exit_status = child.wait().map_err(ExternalToolError::Io)?;
+    if !exit_status.success() {
+        return Err(ExternalToolError::ToolAborted { exit_status });
+    }
+    served.map_err(ExternalToolError::Io)?;
+    match session.state() {
+        ToolSessionState::Finished => Ok(()),
+        ToolSessionState::Aborted => Err(ExternalToolError::SessionAborted),
+        ToolSessionState::Running | ToolSessionState::Disconnected => {
+            Err(ExternalToolError::SessionNotFinished)
+        }
+    }
+}
+
 pub fn edit_diff_external(
     editor: &ExternalMergeTool,
     left_tree: &MergedTree,
@@ -370,8 +453,16 @@ pub fn edit_diff_external(
     let conflict_marker_style = editor
         .conflict_marker_style
         .unwrap_or(default_conflict_marker_style);
+    if editor.protocol == MergeToolProtocol::JsonRpc {
+        let mut session =
+            ToolSession::for_diff(left_tree, right_tree, matcher, conflict_marker_style)?;
+        run_tool_session(editor, &editor.edit_args, &mut session)?;
+        return Ok(session.write_tree()?);
+    }
     let options = CheckoutOptions {
         conflict_marker_style,
+        lfs_store: None,
+        show_conflict_labels: false,
     };
 
     let got_output_field = find_all_variables(&editor.edit_args).contains(&"output");
@@ -420,6 +511,8 @@ pub fn generate_diff(
         .unwrap_or(default_conflict_marker_style);
     let options = CheckoutOptions {
         conflict_marker_style,
+        lfs_store: None,
+        show_conflict_labels: false,
     };
     let store = left_tree.store();
     let diff_wc = check_out_trees(store, left_tree, right_tree, matcher, None, &options)?;
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 mod builtin;
+mod choose_side;
 mod diff_working_copies;
 mod external;
 
@@ -44,6 +45,9 @@ use thiserror::Error;
 use self::builtin::edit_diff_builtin;
 use self::builtin::edit_merge_builtin;
 use self::builtin::BuiltinToolError;
+use self::choose_side::choose_sides_of_conflicts;
+pub use self::choose_side::take_side_of_conflicts;
+pub use self::choose_side::TakeSide;
 pub(crate) use self::diff_working_copies::new_utf8_temp_dir;
 use self::diff_working_copies::DiffCheckoutError;
 use self::external::edit_diff_external;
@@ -52,7 +56,9 @@ pub use self::external::invoke_external_diff;
 pub use self::external::DiffToolMode;
 pub use self::external::ExternalMergeTool;
 use self::external::ExternalToolError;
+pub use self::external::MergeToolProtocol;
 use crate::config::CommandNameAndArgs;
+use crate::description_util::TextEditor;
 use crate::ui::Ui;
 
 const BUILTIN_EDITOR_NAME: &str = ":builtin";
@@ -69,6 +75,8 @@ pub enum DiffEditError {
     Snapshot(#[from] SnapshotError),
     #[error(transparent)]
     Config(#[from] ConfigGetError),
+    #[error(transparent)]
+    Backend(#[from] jj_lib::backend::BackendError),
 }
 
 #[derive(Debug, Error)]
@@ -98,6 +106,14 @@ pub enum ConflictResolveError {
     NotNormalFiles(RepoPathBuf, String),
     #[error("The conflict at {path:?} has {sides} sides. At most 2 sides are supported.")]
     ConflictTooComplicated { path: RepoPathBuf, sides: usize },
+    #[error("The conflict at {path:?} has no side #{side}, only {sides} sides")]
+    SideOutOfRange {
+        path: RepoPathBuf,
+        side: usize,
+        sides: usize,
+    },
+    #[error("Couldn't keep a side of the conflict at {0:?} since the path already exists")]
+    PathAlreadyExists(RepoPathBuf),
     #[error(
         "The output file is either unchanged or empty after the editor quit (run with --debug to \
          see the exact invocation)."
@@ -287,28 +303,28 @@ struct MergeToolFile {
     content: Merge<BString>,
 }
 
+fn conflict_at(
+    tree: &MergedTree,
+    repo_path: &RepoPath,
+) -> Result<MergedTreeValue, ConflictResolveError> {
+    match tree.path_value(repo_path)?.into_resolved() {
+        Err(conflict) => Ok(conflict),
+        Ok(Some(_)) => Err(ConflictResolveError::NotAConflict(repo_path.to_owned())),
+        Ok(None) => Err(ConflictResolveError::PathNotFound(repo_path.to_owned())),
+    }
+}
+
 impl MergeToolFile {
     fn from_tree_and_path(
         tree: &MergedTree,
         repo_path: &RepoPath,
     ) -> Result<Self, ConflictResolveError> {
-        let conflict = match tree.path_value(repo_path)?.into_resolved() {
-            Err(conflict) => conflict,
-            Ok(Some(_)) => return Err(ConflictResolveError::NotAConflict(repo_path.to_owned())),
-            Ok(None) => return Err(ConflictResolveError::PathNotFound(repo_path.to_owned())),
-        };
+        let conflict = conflict_at(tree, repo_path)?;
         let file_merge = conflict.to_file_merge().ok_or_else(|| {
             let summary = conflict.describe();
             ConflictResolveError::NotNormalFiles(repo_path.to_owned(), summary)
         })?;
         let simplified_file_merge = file_merge.clone().simplify();
-        // We only support conflicts with 2 sides (3-way conflicts)
-        if simplified_file_merge.num_sides() > 2 {
-            return Err(ConflictResolveError::ConflictTooComplicated {
-                path: repo_path.to_owned(),
-                sides: simplified_file_merge.num_sides(),
-            });
-        };
         let content =
             extract_as_single_hunk(&simplified_file_merge, tree.store(), repo_path).block_on()?;
         Ok(MergeToolFile {
@@ -326,6 +342,7 @@ pub struct MergeEditor {
     tool: MergeTool,
     path_converter: RepoPathUiConverter,
     conflict_marker_style: ConflictMarkerStyle,
+    text_editor: Option<TextEditor>,
 }
 
 impl MergeEditor {
@@ -365,7 +382,9 @@ impl MergeEditor {
         path_converter: RepoPathUiConverter,
         conflict_marker_style: ConflictMarkerStyle,
     ) -> Result<Self, MergeToolConfigError> {
-        if matches!(&tool, MergeTool::External(mergetool) if mergetool.merge_args.is_empty()) {
+        if matches!(&tool, MergeTool::External(mergetool)
+            if mergetool.merge_args.is_empty() && mergetool.protocol == MergeToolProtocol::Files)
+        {
             return Err(MergeToolConfigError::MergeArgsNotConfigured {
                 tool_name: name.to_string(),
             });
@@ -374,9 +393,22 @@ impl MergeEditor {
             tool,
             path_converter,
             conflict_marker_style,
+            text_editor: None,
         })
     }
 
+    /// Whether this is the builtin merge editor.
+    pub fn is_builtin(&self) -> bool {
+        matches!(self.tool, MergeTool::Builtin)
+    }
+
+    /// Sets the text editor in which the builtin merge editor opens the
+    /// conflicts that weren't resolved by selecting lines.
+    pub fn with_text_editor(mut self, text_editor: TextEditor) -> Self {
+        self.text_editor = Some(text_editor);
+        self
+    }
+
     /// Starts a merge editor for the specified files.
     pub fn edit_files(
         &self,
@@ -384,24 +416,53 @@ impl MergeEditor {
         tree: &MergedTree,
         repo_paths: &[&RepoPath],
     ) -> Result<(MergedTreeId, Option<MergeToolPartialResolutionError>), ConflictResolveError> {
-        let merge_tool_files: Vec<MergeToolFile> = repo_paths
-            .iter()
-            .map(|&repo_path| MergeToolFile::from_tree_and_path(tree, repo_path))
-            .try_collect()?;
-
         match &self.tool {
             MergeTool::Builtin => {
-                let tree_id = edit_merge_builtin(tree, &merge_tool_files).map_err(Box::new)?;
+                // Conflicts that can't be resolved by merging file contents are
+                // resolved by choosing a side first.
+                let (tree, repo_paths) =
+                    choose_sides_of_conflicts(ui, &self.path_converter, tree, repo_paths)?;
+                let merge_tool_files: Vec<MergeToolFile> = repo_paths
+                    .iter()
+                    .map(|repo_path| MergeToolFile::from_tree_and_path(&tree, repo_path))
+                    .try_collect()?;
+                if merge_tool_files.is_empty() {
+                    return Ok((tree.id(), None));
+                }
+                let tree_id = edit_merge_builtin(
+                    &tree,
+                    &merge_tool_files,
+                    self.text_editor.as_ref(),
+                    self.conflict_marker_style,
+                )
+                .map_err(Box::new)?;
                 Ok((tree_id, None))
             }
-            MergeTool::External(editor) => external::run_mergetool_external(
-                ui,
-                &self.path_converter,
-                editor,
-                tree,
-                &merge_tool_files,
-                self.conflict_marker_style,
-            ),
+            MergeTool::External(editor) => {
+                let merge_tool_files: Vec<MergeToolFile> = repo_paths
+                    .iter()
+                    .map(|&repo_path| MergeToolFile::from_tree_and_path(tree, repo_path))
+                    .try_collect()?;
+                // External tools only support conflicts with 2 sides (3-way
+                // conflicts) unless they get the conflicts through the
+                // protocol
+                if let Some(file) = merge_tool_files.iter().find(|file| {
+                    editor.protocol == MergeToolProtocol::Files && file.content.num_sides() > 2
+                }) {
+                    return Err(ConflictResolveError::ConflictTooComplicated {
+                        path: file.repo_path.clone(),
+                        sides: file.content.num_sides(),
+                    });
+                }
+                external::run_mergetool_external(
+                    ui,
+                    &self.path_converter,
+                    editor,
+                    tree,
+                    &merge_tool_files,
+                    self.conflict_marker_style,
+                )
+            }
         }
     }
 }
@@ -459,6 +520,7 @@ mod tests {
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
+                protocol: Files,
             },
         )
         "#);
@@ -491,6 +553,7 @@ mod tests {
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
+                protocol: Files,
             },
         )
         "#);
@@ -535,6 +598,7 @@ mod tests {
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
+                protocol: Files,
             },
         )
         "#);
@@ -563,6 +627,7 @@ mod tests {
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
+                protocol: Files,
             },
         )
         "#);
@@ -590,6 +655,7 @@ mod tests {
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
+                protocol: Files,
             },
         )
         "#);
@@ -623,6 +689,7 @@ mod tests {
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
+                protocol: Files,
             },
         )
         "#);
@@ -654,6 +721,7 @@ mod tests {
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
+                protocol: Files,
             },
         )
         "#);
@@ -679,6 +747,7 @@ mod tests {
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
+                protocol: Files,
             },
         )
         "#);
@@ -740,6 +809,38 @@ mod tests {
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
+                protocol: Files,
+            },
+        )
+        "#);
+
+        // Tools using the protocol don't need merge-args
+        insta::assert_debug_snapshot!(get(
+            "foo bar", r#"
+        [merge-tools."foo bar"]
+        protocol = "json-rpc"
+        "#,
+        ).unwrap(), @r#"
+        External(
+            ExternalMergeTool {
+                program: "foo bar",
+                diff_args: [
+                    "$left",
+                    "$right",
+                ],
+                diff_expected_exit_codes: [
+                    0,
+                ],
+                diff_invocation_mode: Dir,
+                edit_args: [
+                    "$left",
+                    "$right",
+                ],
+                merge_args: [],
+                merge_conflict_exit_codes: [],
+                merge_tool_edits_conflict_markers: false,
+                conflict_marker_style: None,
+                protocol: JsonRpc,
             },
         )
         "#);
@@ -796,6 +897,7 @@ mod tests {
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
+                protocol: Files,
             },
         )
         "#);
@@ -829,6 +931,7 @@ mod tests {
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
+                protocol: Files,
             },
         )
         "#);
@@ -865,6 +968,7 @@ mod tests {
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
+                protocol: Files,
             },
         )
         "#);
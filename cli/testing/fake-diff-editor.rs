@@ -19,16 +19,24 @@ use std::process::exit;
 
 use clap::Parser;
 use itertools::Itertools as _;
+use jj_lib::tool_protocol::ToolFileSide;
+use testutils::tool_protocol::ToolClient;
 
 /// A fake diff-editor, useful for testing
 #[derive(Parser, Debug)]
 #[clap()]
 struct Args {
     /// Path to the "before" directory
-    before: PathBuf,
+    #[arg(required_unless_present = "json_rpc")]
+    before: Option<PathBuf>,
 
     /// Path to the "after" directory
-    after: PathBuf,
+    #[arg(required_unless_present = "json_rpc")]
+    after: Option<PathBuf>,
+
+    /// Edit the files through JSON-RPC requests on stdout instead
+    #[arg(long)]
+    json_rpc: bool,
 
     /// Ignored argument
     #[arg(long)]
@@ -55,10 +63,80 @@ fn files_recursively(p: &Path) -> HashSet<String> {
     files
 }
 
+/// Runs the edit script on the session served by jj on our stdin and stdout.
+/// Since stdout is used by the protocol, messages are printed to stderr.
+fn run_json_rpc(edit_script_path: &Path, edit_script: &str) {
+    let stdin = std::io::stdin().lock();
+    let stdout = std::io::stdout().lock();
+    let mut client = ToolClient::with_streams(stdin, stdout);
+    let init = client.initialize();
+    for instruction in edit_script.split('\0') {
+        let (command, payload) = instruction.split_once('\n').unwrap_or((instruction, ""));
+        let parts = command.split(' ').collect_vec();
+        match parts.as_slice() {
+            [""] => {}
+            ["fail"] => exit(1),
+            // Exit without finishing the session
+            ["exit"] => exit(0),
+            ["abort"] => {
+                client.abort();
+                return;
+            }
+            ["print", message] => {
+                eprintln!("{message}");
+            }
+            ["print-files"] => {
+                eprintln!("mode: {:?}", init.mode);
+                for file in &init.files {
+                    let read_only = if file.read_only { " (read-only)" } else { "" };
+                    eprintln!("{} ({} sides){read_only}", file.path, file.num_sides);
+                }
+            }
+            ["print-hunks", file] => {
+                for hunk in client.conflict(file).hunks {
+                    match hunk.resolved {
+                        Some(content) => eprint!("resolved: {content}"),
+                        None => eprintln!("conflict: {:?}", hunk.sides),
+                    }
+                }
+            }
+            ["rm", file] => {
+                client.write(file, None);
+            }
+            ["reset", file] => {
+                let left = client.read(file, ToolFileSide::Left);
+                client.write(file, left.content.as_deref());
+            }
+            ["dump", file, dest] => {
+                let dest_path = edit_script_path.parent().unwrap().join(dest);
+                let output = client.read(file, ToolFileSide::Output);
+                std::fs::write(dest_path, output.content.unwrap_or_default()).unwrap();
+            }
+            ["write", file] => {
+                client.write(file, Some(payload));
+            }
+            ["write-with-conflict-markers", file] => {
+                client.write_with_conflict_markers(file, payload);
+            }
+            _ => {
+                eprintln!("fake-diff-editor: unexpected command: {command}");
+                exit(1)
+            }
+        }
+    }
+    client.finish();
+}
+
 fn main() {
     let args: Args = Args::parse();
     let edit_script_path = PathBuf::from(std::env::var_os("DIFF_EDIT_SCRIPT").unwrap());
     let edit_script = String::from_utf8(std::fs::read(&edit_script_path).unwrap()).unwrap();
+    if args.json_rpc {
+        run_json_rpc(&edit_script_path, &edit_script);
+        return;
+    }
+    let before = args.before.unwrap();
+    let after = args.after.unwrap();
     for instruction in edit_script.split('\0') {
         let (command, payload) = instruction.split_once('\n').unwrap_or((instruction, ""));
         let parts = command.split(' ').collect_vec();
@@ -67,7 +145,7 @@ fn main() {
             ["fail"] => exit(1),
             ["files-before", ..] => {
                 let expected = parts[1..].iter().copied().map(str::to_string).collect();
-                let actual = files_recursively(&args.before);
+                let actual = files_recursively(&before);
                 if actual != expected {
                     eprintln!(
                         "fake-diff-editor: unexpected files before. EXPECTED: {:?} ACTUAL: {:?}",
@@ -79,7 +157,7 @@ fn main() {
             }
             ["files-after", ..] => {
                 let expected = parts[1..].iter().copied().map(str::to_string).collect();
-                let actual = files_recursively(&args.after);
+                let actual = files_recursively(&after);
                 if actual != expected {
                     eprintln!(
                         "fake-diff-editor: unexpected files after. EXPECTED: {:?} ACTUAL: {:?}",
@@ -93,31 +171,31 @@ fn main() {
                 println!("{message}");
             }
             ["print-files-before"] => {
-                for base_name in files_recursively(&args.before).iter().sorted() {
+                for base_name in files_recursively(&before).iter().sorted() {
                     println!("{base_name}");
                 }
             }
             ["print-files-after"] => {
-                for base_name in files_recursively(&args.after).iter().sorted() {
+                for base_name in files_recursively(&after).iter().sorted() {
                     println!("{base_name}");
                 }
             }
             ["rm", file] => {
-                std::fs::remove_file(args.after.join(file)).unwrap();
+                std::fs::remove_file(after.join(file)).unwrap();
             }
             ["reset", file] => {
-                if args.before.join(file).exists() {
-                    std::fs::copy(args.before.join(file), args.after.join(file)).unwrap();
+                if before.join(file).exists() {
+                    std::fs::copy(before.join(file), after.join(file)).unwrap();
                 } else {
-                    std::fs::remove_file(args.after.join(file)).unwrap();
+                    std::fs::remove_file(after.join(file)).unwrap();
                 }
             }
             ["dump", file, dest] => {
                 let dest_path = edit_script_path.parent().unwrap().join(dest);
-                std::fs::copy(args.after.join(file), dest_path).unwrap();
+                std::fs::copy(after.join(file), dest_path).unwrap();
             }
             ["write", file] => {
-                std::fs::write(args.after.join(file), payload).unwrap();
+                std::fs::write(after.join(file), payload).unwrap();
             }
             _ => {
                 eprintln!("fake-diff-editor: unexpected command: {command}");
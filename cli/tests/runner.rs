@@ -37,9 +37,12 @@ mod test_file_show_command;
 mod test_file_track_untrack_commands;
 mod test_fix_command;
 mod test_generate_md_cli_help;
+mod test_git_am;
+mod test_git_bundle;
 mod test_git_clone;
 mod test_git_colocated;
 mod test_git_fetch;
+mod test_git_format_patch;
 mod test_git_import_export;
 mod test_git_init;
 mod test_git_private_commits;
@@ -446,6 +446,67 @@ fn test_commit_reset_author() {
     ");
 }
 
+#[cfg(unix)]
+#[test]
+fn test_commit_git_hooks() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let hooks_dir = work_dir.root().join(".jj/repo/store/git/hooks");
+    std::fs::create_dir_all(&hooks_dir).unwrap();
+    for (name, script) in [
+        (
+            "pre-commit",
+            "#!/bin/sh\nif [ -f secret ]; then echo 'secret found'; exit 1; fi\n",
+        ),
+        (
+            "commit-msg",
+            "#!/bin/sh\nprintf '\\nReviewed: yes\\n' >> \"$1\"\n",
+        ),
+    ] {
+        let hook_path = hooks_dir.join(name);
+        std::fs::write(&hook_path, script).unwrap();
+        std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o700)).unwrap();
+    }
+    test_env.add_config("git.run-hooks = true");
+
+    // The pre-commit hook aborts the commit
+    work_dir.write_file("secret", "password");
+    let output = work_dir.run_jj(["commit", "-m=first"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    secret found
+    Error: The pre-commit hook failed (exit status: 1)
+    Hint: Use --no-verify to skip the Git hooks.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // The commit-msg hook can edit the description
+    work_dir.remove_file("secret");
+    work_dir.run_jj(["commit", "-m=first"]).success();
+    let output = work_dir.run_jj(["log", "--no-graph", "-r=@-", "-T=description"]);
+    insta::assert_snapshot!(output, @r"
+    first
+
+    Reviewed: yes
+    [EOF]
+    ");
+
+    // The hooks can be skipped
+    work_dir.write_file("secret", "password");
+    work_dir
+        .run_jj(["commit", "-m=second", "--no-verify"])
+        .success();
+    let output = work_dir.run_jj(["log", "--no-graph", "-r=@-", "-T=description"]);
+    insta::assert_snapshot!(output, @r"
+    second
+    [EOF]
+    ");
+}
+
 #[must_use]
 fn get_log_output(work_dir: &TestWorkDir) -> CommandOutput {
     let template = r#"commit_id.short() ++ " " ++ description"#;
@@ -1466,6 +1466,82 @@ fn test_file_list_entries() {
     ");
 }
 
+#[test]
+fn test_log_conflicted_files() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "a\nb\nc\n");
+    work_dir.write_file("file3", "unchanged\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file1", "a1\nb\nc1\n");
+    work_dir.write_file("file2", "x\n");
+    work_dir.run_jj(["new", "@-"]).success();
+    work_dir.write_file("file1", "a2\nb\nc2\n");
+    work_dir.write_file("file2", "y\n");
+    work_dir.run_jj(["new", "all:visible_heads()"]).success();
+
+    let template = indoc! {r#"
+        conflicted_files().map(|e| separate(" ",
+          e.path(),
+          "sides=" ++ e.conflict_side_count(),
+          "hunks=" ++ e.conflict_hunk_count(),
+        )).join("\n") ++ "\n"
+    "#};
+    let output = work_dir.run_jj(["log", "--no-graph", "-r@", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    file1 sides=2 hunks=2
+    file2 sides=2 hunks=1
+    [EOF]
+    ");
+
+    let template = r#"conflicted_files("file2").map(|e| e.path()) ++ "\n""#;
+    let output = work_dir.run_jj(["log", "--no-graph", "-r@", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    file2
+    [EOF]
+    ");
+
+    // Resolved files and commits without conflicts
+    let template = indoc! {r#"
+        separate(" ",
+          if(conflicted_files(), "conflicted", "resolved"),
+          self.diff().files().map(|e| e.target().conflict_hunk_count()),
+        ) ++ "\n"
+    "#};
+    let output = work_dir.run_jj(["log", "--no-graph", "-r@-", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    resolved 0 0
+    resolved 0 0
+    [EOF]
+    ");
+
+    // conflicts(new) finds the commit where the conflict first appeared
+    work_dir.run_jj(["new"]).success();
+    let output = work_dir.run_jj([
+        "log",
+        "--no-graph",
+        "-r",
+        "conflicts(new)",
+        "-T",
+        r#"self.conflicted_files().len() ++ "\n""#,
+    ]);
+    insta::assert_snapshot!(output, @r"
+    2
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "log",
+        "--no-graph",
+        "-r",
+        "conflicts(file2)",
+        "-T",
+        r#""x""#,
+    ]);
+    insta::assert_snapshot!(output, @"xx[EOF]");
+}
+
 #[cfg(unix)]
 #[test]
 fn test_file_list_symlink() {
@@ -530,11 +530,11 @@ fn test_config_set_bad_opts() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     error: the following required arguments were not provided:
-      <--user|--repo>
+      <--user|--repo|--workspace>
       <NAME>
       <VALUE>
 
-    Usage: jj config set <--user|--repo> <NAME> <VALUE>
+    Usage: jj config set <--user|--repo|--workspace> <NAME> <VALUE>
 
     For more information, try '--help'.
     [EOF]
@@ -669,6 +669,48 @@ fn test_config_set_for_repo() {
     "#);
 }
 
+#[test]
+fn test_config_set_for_workspace() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir
+        .run_jj(["workspace", "add", "--name", "second", "../secondary"])
+        .success();
+    let secondary_dir = test_env.work_dir("secondary");
+    work_dir
+        .run_jj(["config", "set", "--repo", "test-key", "repo-val"])
+        .success();
+    secondary_dir
+        .run_jj(["config", "set", "--workspace", "test-key", "workspace-val"])
+        .success();
+    let workspace_config_toml = secondary_dir.read_file(".jj/workspace-config.toml");
+    insta::assert_snapshot!(workspace_config_toml, @r#"test-key = "workspace-val""#);
+
+    // The workspace config overrides the repo config only in that workspace.
+    let output = secondary_dir.run_jj(["config", "get", "test-key"]);
+    insta::assert_snapshot!(output, @r"
+    workspace-val
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["config", "get", "test-key"]);
+    insta::assert_snapshot!(output, @r"
+    repo-val
+    [EOF]
+    ");
+
+    let output = secondary_dir.run_jj(["config", "list", "--workspace"]);
+    insta::assert_snapshot!(output, @r#"
+    test-key = "workspace-val"
+    [EOF]
+    "#);
+    let output = secondary_dir.run_jj(["config", "path", "--workspace"]);
+    insta::assert_snapshot!(output, @r"
+    $TEST_ENV/secondary/.jj/workspace-config.toml
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_config_set_toml_types() {
     let mut test_env = TestEnvironment::default();
@@ -882,9 +924,9 @@ fn test_config_edit_missing_opt() {
     insta::assert_snapshot!(output, @r"
     ------- stderr -------
     error: the following required arguments were not provided:
-      <--user|--repo>
+      <--user|--repo|--workspace>
 
-    Usage: jj config edit <--user|--repo>
+    Usage: jj config edit <--user|--repo|--workspace>
 
     For more information, try '--help'.
     [EOF]
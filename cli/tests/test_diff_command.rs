@@ -2753,6 +2753,41 @@ fn test_diff_binary() {
     ");
 }
 
+#[test]
+fn test_diff_binary_by_gitattributes() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file(".gitattributes", "*.dat -diff\nsub/*.dat diff\n");
+    work_dir.write_file("file.dat", "a\n");
+    work_dir.write_file("sub/file.dat", "a\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file.dat", "b\n");
+    work_dir.write_file("sub/file.dat", "b\n");
+    work_dir.write_file("file.txt", "c\n");
+
+    // Files marked as -diff are shown as binary
+    let output = work_dir.run_jj(["diff"]);
+    insta::assert_snapshot!(output, @r"
+    Modified regular file file.dat:
+        (binary)
+    Added regular file file.txt:
+            1: c
+    Modified regular file sub/file.dat:
+       1    1: ab
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["diff", "--git", "file.dat"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file.dat b/file.dat
+    index 7898192261..6178079822 100644
+    Binary files a/file.dat and b/file.dat differ
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_diff_revisions() {
     let test_env = TestEnvironment::default();
@@ -247,6 +247,90 @@ fn test_diffedit_new_file() {
     ");
 }
 
+#[test]
+fn test_diffedit_json_rpc() {
+    let mut test_env = TestEnvironment::default();
+    let edit_script = test_env.set_up_fake_diff_editor();
+    test_env.add_config(indoc! {r#"
+        merge-tools.fake-diff-editor.protocol = "json-rpc"
+        merge-tools.fake-diff-editor.edit-args = ["--json-rpc"]
+    "#});
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.write_file("file1", "a\n");
+    work_dir.write_file("file2", "a\n");
+    work_dir.write_file("file3", "a\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.remove_file("file1");
+    work_dir.write_file("file2", "b\n");
+
+    // The changed files are served to the tool; nothing happens if it makes no
+    // changes
+    std::fs::write(&edit_script, "print-files\0dump file2 file2-right").unwrap();
+    let output = work_dir.run_jj(["diffedit"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    mode: Diff
+    file1 (1 sides)
+    file2 (1 sides)
+    Nothing changed.
+    [EOF]
+    ");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("file2-right")).unwrap(), @"b");
+
+    // Files written in the session are kept once it's finished
+    std::fs::write(&edit_script, "reset file1\0write file2\nedited\n").unwrap();
+    work_dir.run_jj(["diffedit"]).success();
+    let output = work_dir.run_jj(["diff", "-s"]);
+    insta::assert_snapshot!(output, @r"
+    M file2
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.read_file("file2"), @"edited");
+
+    // Nothing happens if the tool aborts the session
+    work_dir.run_jj(["undo"]).success();
+    std::fs::write(&edit_script, "rm file2\0abort").unwrap();
+    let output = work_dir.run_jj(["diffedit"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Failed to edit diff
+    Caused by: Canceled by user in the tool
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Nothing happens if the tool exits without finishing the session
+    std::fs::write(&edit_script, "rm file2\0exit").unwrap();
+    let output = work_dir.run_jj(["diffedit"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Failed to edit diff
+    Caused by: Tool exited without finishing the session (run with --debug to see the exact invocation)
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Nothing happens if the tool exits with an error
+    std::fs::write(&edit_script, "rm file2\0fail").unwrap();
+    let output = work_dir.run_jj(["diffedit"]);
+    insta::assert_snapshot!(output.normalize_stderr_exit_status(), @r"
+    ------- stderr -------
+    Error: Failed to edit diff
+    Caused by: Tool exited with exit status: 1 (run with --debug to see the exact invocation)
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["diff", "-s"]);
+    insta::assert_snapshot!(output, @r"
+    D file1
+    M file2
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_diffedit_external_tool_conflict_marker_style() {
     let mut test_env = TestEnvironment::default();
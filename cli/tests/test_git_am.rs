@@ -0,0 +1,145 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+const TWO_PATCHES: &str = "\
+From 1111111111111111111111111111111111111111 Mon Sep 17 00:00:00 2001
+From: =?UTF-8?q?Jos=C3=A9?= <jose@example.com>
+Date: Thu, 1 Jan 2004 12:00:00 +0100
+Subject: [PATCH 1/2] Add file
+
+---
+ file | 2 ++
+ 1 file changed, 2 insertions(+)
+
+diff --git a/file b/file
+new file mode 100644
+index 0000000000..422c2b7ab3
+--- /dev/null
++++ b/file
+@@ -0,0 +1,2 @@
++a
++b
+--
+2.40.0
+
+From 2222222222222222222222222222222222222222 Mon Sep 17 00:00:00 2001
+From: =?UTF-8?q?Jos=C3=A9?= <jose@example.com>
+Date: Thu, 1 Jan 2004 12:01:00 +0100
+Subject: [PATCH 2/2] Change
+ file
+
+With a body.
+---
+ file | 2 +-
+ 1 file changed, 1 insertion(+), 1 deletion(-)
+
+diff --git a/file b/file
+index 422c2b7ab3..0f7bc76605 100644
+--- a/file
++++ b/file
+@@ -1,2 +1,2 @@
+ a
+-b
++c
+\\ No newline at end of file
+--
+2.40.0
+";
+
+#[test]
+fn test_git_am() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    std::fs::write(test_env.env_root().join("patches.mbox"), TWO_PATCHES).unwrap();
+
+    work_dir
+        .run_jj(["git", "am", "../patches.mbox", "-d=root()"])
+        .success();
+    let template = r#"separate(" ", description.first_line(), author, author.timestamp()) ++ "\n""#;
+    let output = work_dir.run_jj(["log", "-r=root()+:: ~ @", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    Change file José <jose@example.com> 2004-01-01 12:01:00.000 +01:00
+    Add file José <jose@example.com> 2004-01-01 12:00:00.000 +01:00
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "log",
+        "-r=heads(root()+:: ~ @)",
+        "--no-graph",
+        "-T=description",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    Change file
+
+    With a body.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r=heads(root()+:: ~ @)", "file"]);
+    insta::assert_snapshot!(output, @r"
+    a
+    c[EOF]
+    ");
+}
+
+#[test]
+fn test_git_am_conflict() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("file", "a\nb\nc\n");
+    work_dir.run_jj(["commit", "-m", "Add file"]).success();
+    std::fs::write(
+        test_env.env_root().join("patch"),
+        "\
+From: Someone <someone@example.com>
+Subject: Change x
+
+diff --git a/file b/file
+--- a/file
++++ b/file
+@@ -1,3 +1,3 @@
+ a
+-x
++y
+ c
+",
+    )
+    .unwrap();
+
+    work_dir
+        .run_jj(["git", "am", "../patch", "-d=@-"])
+        .success();
+    let template = r#"separate(" ", description.first_line(), conflict) ++ "\n""#;
+    let output = work_dir.run_jj(["log", "-r=@-+ ~ @", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    Change x true
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r=@-+ ~ @", "file"]);
+    insta::assert_snapshot!(output, @r"
+    a
+    <<<<<<< Conflict 1 of 1
+    %%%%%%% Changes from base to side #1
+    -x
+    +b
+    +++++++ Contents of side #2
+    y
+    >>>>>>> Conflict 1 of 1 ends
+    c
+    [EOF]
+    ");
+}
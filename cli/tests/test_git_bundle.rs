@@ -0,0 +1,89 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_git_bundle_create_and_fetch() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "origin"]).success();
+    let origin_dir = test_env.work_dir("origin");
+    origin_dir.write_file("file", "a\n");
+    origin_dir.run_jj(["commit", "-m", "first"]).success();
+    origin_dir.write_file("file", "b\n");
+    origin_dir.run_jj(["commit", "-m", "second"]).success();
+    origin_dir
+        .run_jj(["bookmark", "create", "main", "-r=@-"])
+        .success();
+
+    let output = origin_dir.run_jj(["git", "bundle", "create", "../repo.bundle", "-r=::main"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Created bundle with 1 bookmarks and 2 revisions
+    [EOF]
+    ");
+
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir
+        .run_jj(["git", "remote", "add", "bundle", "../repo.bundle"])
+        .success();
+    work_dir
+        .run_jj(["git", "fetch", "--remote=bundle"])
+        .success();
+    let template = r#"separate(" ", description.first_line(), remote_bookmarks) ++ "\n""#;
+    let output = work_dir.run_jj(["log", "-r=::main@bundle", "--no-graph", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    second main@bundle
+    first
+
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r=main@bundle", "file"]);
+    insta::assert_snapshot!(output, @r"
+    b
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_git_bundle_create_without_bookmark() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.run_jj(["commit", "-m", "first"]).success();
+
+    let output = work_dir
+        .run_jj(["git", "bundle", "create", "../repo.bundle", "-r=@-"])
+        .normalize_stderr_with(|stderr| {
+            let re = regex::Regex::new(r"[0-9a-f]{12}").unwrap();
+            re.replace_all(&stderr, "[commit id]").into_owned()
+        });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Revision [commit id] isn't pointed to by any bookmark
+    Hint: Bundles only contain revisions reachable from bookmarks. Use `jj bookmark create` to create one.
+    [EOF]
+    [exit status: 1]
+    ");
+    assert!(!test_env.env_root().join("repo.bundle").exists());
+
+    let output = work_dir.run_jj(["git", "bundle", "create", "../repo.bundle", "-r=none()"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No revisions to bundle
+    [EOF]
+    [exit status: 1]
+    ");
+}
@@ -14,6 +14,7 @@
 
 use std::path;
 
+use bstr::ByteSlice as _;
 use indoc::formatdoc;
 use test_case::test_case;
 use testutils::git;
@@ -984,6 +985,53 @@ fn test_git_clone_with_depth_subprocess() {
     ");
 }
 
+#[test]
+fn test_git_clone_with_filter_subprocess() {
+    let test_env = TestEnvironment::default();
+    let root_dir = test_env.work_dir("");
+    let clone_dir = test_env.work_dir("clone");
+    let git_repo_path = test_env.env_root().join("source");
+    let git_repo = git::init(&git_repo_path);
+    git::set_config(&git_repo_path, "uploadpack.allowFilter", "true");
+    let commit1 = git::add_commit(&git_repo, "refs/heads/main", "file", b"a\n", "first", &[]);
+    git::add_commit(
+        &git_repo,
+        "refs/heads/main",
+        "file",
+        b"b\n",
+        "second",
+        &[commit1.commit_id],
+    );
+    git::set_symbolic_reference(&git_repo, "HEAD", "refs/heads/main");
+
+    root_dir
+        .run_jj(["git", "clone", "--filter=blob:none", "source", "clone"])
+        .success();
+    let git_config = clone_dir.read_file(".jj/repo/store/git/config");
+    assert!(git_config.contains_str("partialclonefilter = blob:none"));
+
+    // The checked-out file contents are fetched on demand
+    assert_eq!(clone_dir.read_file("file"), "b\n");
+
+    // So are the contents in history
+    let output = clone_dir.run_jj(["file", "show", "-r", "main-", "file"]);
+    insta::assert_snapshot!(output, @r"
+    a
+    [EOF]
+    ");
+    let output = clone_dir.run_jj(["diff", "--git", "--from", "main-", "--to", "main"]);
+    insta::assert_snapshot!(output, @r"
+    diff --git a/file b/file
+    index 7898192261..6178079822 100644
+    --- a/file
+    +++ b/file
+    @@ -1,1 +1,1 @@
+    -a
+    +b
+    [EOF]
+    ");
+}
+
 #[cfg_attr(feature = "git2", test_case(false; "use git2 for remote calls"))]
 #[test_case(true; "spawn a git subprocess for remote calls")]
 fn test_git_clone_invalid_immutable_heads(subprocess: bool) {
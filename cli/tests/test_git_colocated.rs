@@ -1632,6 +1632,54 @@ fn test_git_colocated_operation_cleanup() {
     insta::assert_snapshot!(String::from_utf8(output.stdout).unwrap(), @"");
 }
 
+#[test]
+fn test_git_colocated_notes() {
+    let test_env = TestEnvironment::default();
+    test_env
+        .run_jj_in(".", ["git", "init", "--colocate", "repo"])
+        .success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("file", "1");
+    work_dir.run_jj(["commit", "-m", "first"]).success();
+
+    // Notes added by Git are shown by the template method
+    git::add_note(work_dir.root(), "HEAD", "CI: passed");
+    let output = work_dir.run_jj(["log", "--no-graph", "-r@-", "-Tnotes()"]);
+    insta::assert_snapshot!(output, @r"
+    CI: passed
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "--no-graph", "-r@-", "-Tnotes('review')"]);
+    insta::assert_snapshot!(output, @"");
+
+    // The note is carried over to the rewritten commit
+    work_dir
+        .run_jj(["describe", "-r@-", "-m", "first (amended)"])
+        .success();
+    let output = work_dir.run_jj(["log", "--no-graph", "-r@-", "-Tdescription ++ notes()"]);
+    insta::assert_snapshot!(output, @r"
+    first (amended)
+    CI: passed
+    [EOF]
+    ");
+
+    // Undoing the rewrite also undoes the copy of the note
+    let amended_id = work_dir
+        .run_jj(["log", "--no-graph", "-T=commit_id", "-r=@-"])
+        .success()
+        .stdout
+        .into_raw();
+    work_dir.run_jj(["undo"]).success();
+    let output = work_dir.run_jj(["log", "--no-graph", "-r", &amended_id, "-Tnotes()"]);
+    insta::assert_snapshot!(output, @"");
+    let output = work_dir.run_jj(["log", "--no-graph", "-r@-", "-Tdescription ++ notes()"]);
+    insta::assert_snapshot!(output, @r"
+    first
+    CI: passed
+    [EOF]
+    ");
+}
+
 #[must_use]
 fn get_bookmark_output(work_dir: &TestWorkDir) -> CommandOutput {
     // --quiet to suppress deleted bookmarks hint
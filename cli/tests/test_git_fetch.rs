@@ -2106,6 +2106,72 @@ fn test_git_fetch_preserve_commits_across_repos(subprocess: bool) {
     }
 }
 
+#[test]
+fn test_git_fetch_deepen_and_unshallow() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("git.auto-local-bookmark = true");
+    let git_repo = git::init(test_env.env_root().join("source"));
+    let mut parents = vec![];
+    for message in ["first", "second", "third", "fourth"] {
+        let commit_id = git::add_commit(
+            &git_repo,
+            "refs/heads/main",
+            "file",
+            message.as_bytes(),
+            message,
+            &parents,
+        )
+        .commit_id;
+        parents = vec![commit_id];
+    }
+    git::set_symbolic_reference(&git_repo, "HEAD", "refs/heads/main");
+
+    test_env
+        .run_jj_in(".", ["git", "clone", "--depth", "1", "source", "clone"])
+        .success();
+    let work_dir = test_env.work_dir("clone");
+    let get_history = || {
+        work_dir.run_jj([
+            "log",
+            "--no-graph",
+            "-r=root()..main",
+            r#"-T=description.first_line() ++ "\n""#,
+        ])
+    };
+    insta::assert_snapshot!(get_history(), @r"
+    fourth
+    [EOF]
+    ");
+
+    work_dir.run_jj(["git", "fetch", "--deepen", "1"]).success();
+    insta::assert_snapshot!(get_history(), @r"
+    fourth
+    third
+    [EOF]
+    ");
+
+    work_dir.run_jj(["git", "fetch", "--unshallow"]).success();
+    insta::assert_snapshot!(get_history(), @r"
+    fourth
+    third
+    second
+    first
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["git", "fetch", "--deepen", "1", "--unshallow"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: the argument '--deepen <N>' cannot be used with '--unshallow'
+
+    Usage: jj git fetch --deepen <N>
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 // TODO: Remove with the `git.subprocess` setting.
 #[cfg(not(feature = "git2"))]
 #[test]
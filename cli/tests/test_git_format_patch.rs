@@ -0,0 +1,160 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::common::TestEnvironment;
+
+#[test]
+fn test_git_format_patch() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("file", "a\nb\n");
+    work_dir.run_jj(["commit", "-m", "Add file"]).success();
+    work_dir.write_file("file", "a\nc\n");
+    work_dir
+        .run_jj(["commit", "-m", "Change file\n\nWith a body."])
+        .success();
+
+    let output = work_dir.run_jj(["git", "format-patch", "-r=::@- ~ root()", "-o=patches"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r"
+    patches/0001-Add-file.patch
+    patches/0002-Change-file.patch
+    [EOF]
+    ");
+    let patch = work_dir
+        .read_file("patches/0001-Add-file.patch")
+        .to_string();
+    assert!(patch.contains("\nSubject: [PATCH 1/2] Add file\n"));
+
+    // The commit ID and the date depend on the test environment
+    let output = work_dir
+        .run_jj(["git", "format-patch", "-r=@-", "--stdout"])
+        .normalize_stdout_with(|stdout| {
+            stdout
+                .lines()
+                .map(|line| {
+                    if line.starts_with("From ") {
+                        "From [commit id] Mon Sep 17 00:00:00 2001"
+                    } else if line.starts_with("Date: ") {
+                        "Date: [date]"
+                    } else {
+                        line
+                    }
+                })
+                .map(|line| format!("{line}\n"))
+                .collect()
+        });
+    insta::assert_snapshot!(output, @r"
+    From [commit id] Mon Sep 17 00:00:00 2001
+    From: Test User <test.user@example.com>
+    Date: [date]
+    Subject: [PATCH] Change file
+    Change-Id: rlvkpnrzqnoowoytxnquwvuryrwnrmlp
+
+    With a body.
+    ---
+    file | 2 +-
+    1 file changed, 1 insertion(+), 1 deletion(-)
+
+    diff --git a/file b/file
+    index 422c2b7ab3..0f7bc76605 100644
+    --- a/file
+    +++ b/file
+    @@ -1,2 +1,2 @@
+     a
+    -b
+    +c
+
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_git_format_patch_merge() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.run_jj(["new", "root()"]).success();
+    work_dir.run_jj(["new", "@", "@-"]).success();
+
+    let output = work_dir.run_jj(["git", "format-patch", "-r=root()"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Cannot export the root commit
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir
+        .run_jj(["git", "format-patch", "-r=@"])
+        .normalize_stderr_with(|stderr| {
+            let re = regex::Regex::new(r"[0-9a-f]{12}").unwrap();
+            re.replace_all(&stderr, "[commit id]").into_owned()
+        });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Cannot export merge commit [commit id] as a patch
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_git_format_patch_special_cases() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("file", "a\n");
+    work_dir.run_jj(["commit", "-m", ""]).success();
+    work_dir.write_file("file", "b\n");
+    work_dir
+        .run_jj(["commit", "-m", "Répare le café"])
+        .success();
+
+    // The change ID is used as the file name if there's no subject
+    let output = work_dir
+        .run_jj(["git", "format-patch", "-r=::@- ~ root()", "-o=patches"])
+        .normalize_stdout_with(|stdout| {
+            let re = regex::Regex::new(r"-[k-z]{12}\.").unwrap();
+            re.replace_all(&stdout, "-[change id].").into_owned()
+        });
+    insta::assert_snapshot!(output.normalize_backslash(), @r"
+    patches/0001-[change id].patch
+    patches/0002-R-pare-le-caf.patch
+    [EOF]
+    ");
+
+    // Non-ASCII subjects are encoded
+    let patch = work_dir
+        .read_file("patches/0002-R-pare-le-caf.patch")
+        .to_string();
+    assert!(
+        patch.contains("\nSubject: =?UTF-8?q?[PATCH=202/2]=20R=C3=A9pare=20le=20caf=C3=A9?=\n"),
+        "{patch}"
+    );
+
+    // Binary changes can't be exported
+    work_dir.write_file("binary", b"\0\x01\x02");
+    let output = work_dir
+        .run_jj(["git", "format-patch", "-r=@", "--stdout"])
+        .normalize_stderr_with(|stderr| {
+            let re = regex::Regex::new(r"[0-9a-f]{12}").unwrap();
+            re.replace_all(&stderr, "[commit id]").into_owned()
+        });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Cannot export commit [commit id] as a patch because of binary changes: Binary files /dev/null and b/binary differ
+    [EOF]
+    [exit status: 1]
+    ");
+}
@@ -305,6 +305,110 @@ fn test_git_import_move_export_with_default_undo() {
     ");
 }
 
+#[test]
+fn test_git_import_stash() {
+    let test_env = TestEnvironment::default();
+    test_env
+        .run_jj_in(".", ["git", "init", "--colocate", "repo"])
+        .success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file("file", "a\n");
+    work_dir.run_jj(["commit", "-m=initial"]).success();
+    work_dir.write_file("file", "b\n");
+    let output = std::process::Command::new("git")
+        .current_dir(work_dir.root())
+        .args(["stash", "push", "-m", "my stash"])
+        .env("GIT_AUTHOR_NAME", "Test User")
+        .env("GIT_AUTHOR_EMAIL", "test.user@example.com")
+        .env("GIT_AUTHOR_DATE", "2001-02-03T04:05:06+07:00")
+        .env("GIT_COMMITTER_NAME", "Test User")
+        .env("GIT_COMMITTER_EMAIL", "test.user@example.com")
+        .env("GIT_COMMITTER_DATE", "2001-02-03T04:05:06+07:00")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // Stashes aren't imported by default
+    let template = r#"description ++ "\n""#;
+    let output = work_dir.run_jj(["log", "--no-graph", "-r=stashes()", "-T", template]);
+    insta::assert_snapshot!(output, @"");
+
+    let output = work_dir
+        .run_jj(["git", "import", "--stash"])
+        .normalize_stderr_with(|stderr| {
+            let re = regex::Regex::new(r"as [a-z]+ hidden [0-9a-f]+ ").unwrap();
+            re.replace_all(&stderr, "as [change id] hidden [commit id] ")
+                .into_owned()
+        });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Imported stash as [change id] hidden [commit id] On (no branch): my stash
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "--no-graph", "-r=stashes()", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    On (no branch): my stash
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "--no-graph", "-r=stashes()-", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    initial
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r=stashes()", "file"]);
+    insta::assert_snapshot!(output, @r"
+    b
+    [EOF]
+    ");
+
+    // The stash is imported only once
+    let output = work_dir.run_jj(["git", "import", "--stash"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+
+    // The imported commit isn't visible
+    let output = work_dir.run_jj([
+        "log",
+        "--no-graph",
+        "-r=stashes() & ::visible_heads()",
+        "-T",
+        template,
+    ]);
+    insta::assert_snapshot!(output, @"");
+
+    // The stash is still found after being dropped from Git, until the next
+    // import
+    let output = std::process::Command::new("git")
+        .current_dir(work_dir.root())
+        .args(["stash", "drop"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let output = work_dir.run_jj(["log", "--no-graph", "-r=stashes()", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    On (no branch): my stash
+    [EOF]
+    ");
+    work_dir.run_jj(["git", "import", "--stash"]).success();
+    let output = work_dir.run_jj(["log", "--no-graph", "-r=stashes()", "-T", template]);
+    insta::assert_snapshot!(output, @"");
+    let output = work_dir.run_jj([
+        "log",
+        "--no-graph",
+        "--at-op=@-",
+        "-r=stashes()",
+        "-T",
+        template,
+    ]);
+    insta::assert_snapshot!(output, @r"
+    On (no branch): my stash
+    [EOF]
+    ");
+}
+
 #[must_use]
 fn get_bookmark_output(work_dir: &TestWorkDir) -> CommandOutput {
     work_dir.run_jj(["bookmark", "list", "--all-remotes"])
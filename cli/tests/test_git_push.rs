@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use jj_lib::git_lfs::LfsObjectStore;
+use jj_lib::git_lfs::LfsPointer;
 use test_case::test_case;
 use testutils::git;
 
@@ -2601,30 +2603,308 @@ fn test_git_push_rejected_by_remote() {
     // push bookmark
     let output = work_dir.run_jj(["git", "push"]);
 
-    // The git remote sideband adds a dummy suffix of 8 spaces to attempt to clear
-    // any leftover data. This is done to help with cases where the line is
-    // rewritten.
-    //
-    // However, a common option in a lot of editors removes trailing whitespace.
-    // This means that anyone with that option that opens this file would make the
-    // following snapshot fail. Using the insta filter here normalizes the
-    // output.
-    let mut settings = insta::Settings::clone_current();
-    settings.add_filter(r"\s*\n", "\n");
-    settings.bind(|| {
-        insta::assert_snapshot!(output, @r"
-        ------- stderr -------
-        Changes to push to origin:
-          Move forward bookmark bookmark1 from d13ecdbda2a2 to dd5c09b30f9f
-        remote: error: hook declined to update refs/heads/bookmark1
-        Error: Failed to push some bookmarks
-        Hint: The remote rejected the following updates:
-          refs/heads/bookmark1 (reason: hook declined)
-        Hint: Try checking if you have permission to push to all the bookmarks.
-        [EOF]
-        [exit status: 1]
-        ");
-    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from d13ecdbda2a2 to dd5c09b30f9f
+    Messages from the remote:
+      error: hook declined to update refs/heads/bookmark1
+    Error: Failed to push some bookmarks
+    Hint: The remote rejected the following updates:
+      refs/heads/bookmark1 (reason: hook declined)
+    Hint: Try checking if you have permission to push to all the bookmarks.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_git_push_options() {
+    let test_env = TestEnvironment::default();
+    set_up(&test_env);
+    let work_dir = test_env.work_dir("local");
+
+    // create a hook on the remote that reports the push options it receives
+    let origin_git_dir = test_env
+        .env_root()
+        .join("origin")
+        .join(".jj")
+        .join("repo")
+        .join("store")
+        .join("git");
+    git::set_config(&origin_git_dir, "receive.advertisePushOptions", "true");
+    let hook_path = origin_git_dir.join("hooks").join("pre-receive");
+    std::fs::write(
+        &hook_path,
+        "#!/bin/sh\necho \"options: $GIT_PUSH_OPTION_0 $GIT_PUSH_OPTION_1\"",
+    )
+    .unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o700)).unwrap();
+    }
+    test_env.add_config(r#"remotes.origin.push-options = ["ci.skip"]"#);
+
+    // create new commit on top of bookmark1
+    work_dir.run_jj(["new", "bookmark1"]).success();
+    work_dir.write_file("file", "file");
+    work_dir.run_jj(["describe", "-m=update"]).success();
+    work_dir.run_jj(["bookmark", "move", "bookmark1"]).success();
+
+    // The push options default to the remote's setting
+    let output = work_dir.run_jj(["git", "push"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from d13ecdbda2a2 to dd5c09b30f9f
+    Messages from the remote:
+      options: ci.skip
+    [EOF]
+    ");
+
+    // Options given on the command line replace the configured ones
+    work_dir
+        .run_jj(["bookmark", "set", "bookmark2", "-r@", "--allow-backwards"])
+        .success();
+    let output = work_dir.run_jj([
+        "git",
+        "push",
+        "-b=bookmark2",
+        "-o=merge_request.create",
+        "--option=merge_request.target=main",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move sideways bookmark bookmark2 from 8476341eb395 to dd5c09b30f9f
+    Messages from the remote:
+      options: merge_request.create merge_request.target=main
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_git_push_and_clone_lfs_objects() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("git.lfs = true");
+    let remote_path = test_env.env_root().join("remote");
+    git::init_bare(&remote_path);
+    test_env.run_jj_in(".", ["git", "init", "local"]).success();
+    let work_dir = test_env.work_dir("local");
+    work_dir
+        .run_jj([
+            "git",
+            "remote",
+            "add",
+            "origin",
+            remote_path.to_str().unwrap(),
+        ])
+        .success();
+    let lfs_content = "x".repeat(2048);
+    work_dir.write_file(".gitattributes", "*.bin filter=lfs\n");
+    work_dir.write_file("large.bin", &lfs_content);
+    work_dir
+        .run_jj(["commit", "-m", "add large file"])
+        .success();
+
+    // The file is committed as a pointer
+    let output = work_dir
+        .run_jj(["file", "show", "-r@-", "large.bin"])
+        .success();
+    let pointer = LfsPointer::parse(output.stdout.raw().as_bytes()).unwrap();
+    assert_eq!(pointer.size, 2048);
+
+    // The object is uploaded to the remote's LFS store
+    let output = work_dir
+        .run_jj(["git", "push", "--allow-new", "-c@-"])
+        .success();
+    assert!(
+        output
+            .stderr
+            .raw()
+            .contains("Uploaded 1 Git LFS objects to origin"),
+        "{output}"
+    );
+    assert!(LfsObjectStore::for_git_dir(&remote_path).has_object(&pointer));
+
+    // Pushing again doesn't upload anything
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("small.txt", "small\n");
+    let output = work_dir
+        .run_jj(["git", "push", "--allow-new", "-c@"])
+        .success();
+    assert!(!output.stderr.raw().contains("Git LFS"), "{output}");
+
+    // Clone downloads the object before checking out the file
+    let output = test_env
+        .run_jj_in(
+            ".",
+            [
+                "git",
+                "clone",
+                "--config=git.auto-local-bookmark=true",
+                remote_path.to_str().unwrap(),
+                "cloned",
+            ],
+        )
+        .success();
+    assert!(
+        output
+            .stderr
+            .raw()
+            .contains("Downloaded 1 Git LFS objects from origin"),
+        "{output}"
+    );
+    let cloned_dir = test_env.work_dir("cloned");
+    cloned_dir.run_jj(["new", "heads(bookmarks())"]).success();
+    assert_eq!(cloned_dir.read_file("large.bin"), lfs_content);
+}
+
+#[test]
+fn test_git_push_lfs_unsupported_server() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("git.lfs = true");
+    let remote_path = test_env.env_root().join("remote");
+    git::init_bare(&remote_path);
+    test_env.run_jj_in(".", ["git", "init", "local"]).success();
+    let work_dir = test_env.work_dir("local");
+    work_dir
+        .run_jj([
+            "git",
+            "remote",
+            "add",
+            "origin",
+            remote_path.to_str().unwrap(),
+        ])
+        .success();
+    git::set_config(
+        work_dir.root().join(".jj/repo/store/git"),
+        "remote.origin.lfsurl",
+        "https://example.invalid/lfs",
+    );
+    work_dir.write_file(".gitattributes", "*.bin filter=lfs\n");
+    work_dir.write_file("large.bin", "x".repeat(2048));
+    work_dir
+        .run_jj(["commit", "-m", "add large file"])
+        .success();
+
+    // The commits are pushed without the LFS objects
+    let output = work_dir
+        .run_jj(["git", "push", "--allow-new", "-c@-"])
+        .success();
+    assert!(
+        output.stderr.raw().contains(
+            "Warning: Skipped transferring Git LFS objects: Unsupported Git LFS server URL \
+             https://example.invalid/lfs"
+        ),
+        "{output}"
+    );
+    let output = work_dir.run_jj(["bookmark", "list", "--all-remotes"]);
+    assert!(output.stdout.raw().contains("@origin"), "{output}");
+}
+
+#[test]
+fn test_git_push_lfs_disabled() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir.write_file(".gitattributes", "*.bin filter=lfs\n");
+    work_dir.write_file("large.bin", "large\n");
+
+    // Without `git.lfs`, the file is committed as is
+    let output = work_dir.run_jj(["file", "show", "large.bin"]);
+    insta::assert_snapshot!(output, @r"
+    large
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_git_push_notes() {
+    let test_env = TestEnvironment::default();
+    set_up(&test_env);
+    test_env.add_config("git.fetch-notes = true");
+    let work_dir = test_env.work_dir("local");
+    let origin_dir = test_env.work_dir("origin");
+
+    // Without notes, there's nothing to push
+    let output = work_dir.run_jj(["git", "push", "--all", "--notes"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Notes are already up to date.
+    [EOF]
+    ");
+
+    git::add_note(
+        git_repo_dir_for_jj_repo(&work_dir),
+        "refs/remotes/origin/bookmark1",
+        "CI: passed",
+    );
+    let output = work_dir.run_jj(["git", "push", "--all", "--notes"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Pushed notes to origin
+    [EOF]
+    ");
+    let output = origin_dir.run_jj(["log", "--no-graph", "-rbookmark1", "-Tnotes()"]);
+    insta::assert_snapshot!(output, @r"
+    CI: passed
+    [EOF]
+    ");
+
+    // Notes added on the remote are merged into the local notes on fetch
+    git::add_note(
+        git_repo_dir_for_jj_repo(&origin_dir),
+        "refs/heads/bookmark2",
+        "Reviewed-by: Test User",
+    );
+    work_dir.run_jj(["git", "fetch"]).success();
+    let output = work_dir.run_jj([
+        "log",
+        "--no-graph",
+        "-rbookmark1 | bookmark2",
+        "-Tdescription.first_line() ++ ': ' ++ notes()",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    description 2: Reviewed-by: Test User
+    description 1: CI: passed
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_git_push_notes_of_rewritten_commits() {
+    let test_env = TestEnvironment::default();
+    set_up(&test_env);
+    let work_dir = test_env.work_dir("local");
+    let origin_dir = test_env.work_dir("origin");
+    git::add_note(
+        git_repo_dir_for_jj_repo(&work_dir),
+        "refs/remotes/origin/bookmark1",
+        "CI: passed",
+    );
+
+    // The note is copied to the rewritten commit even though the refs aren't
+    // exported to Git until the push
+    work_dir
+        .run_jj(["describe", "bookmark1", "-m", "modified bookmark1 commit"])
+        .success();
+    let output = work_dir.run_jj(["log", "--no-graph", "-rbookmark1", "-Tnotes()"]);
+    insta::assert_snapshot!(output, @r"
+    CI: passed
+    [EOF]
+    ");
+
+    work_dir
+        .run_jj(["git", "push", "--bookmark=bookmark1", "--notes"])
+        .success();
+    let output = origin_dir.run_jj(["log", "--no-graph", "-rbookmark1", "-Tnotes()"]);
+    insta::assert_snapshot!(output, @r"
+    CI: passed
+    [EOF]
+    ");
 }
 
 #[must_use]
@@ -2653,3 +2933,67 @@ fn test_git_push_git2_warning() {
     [EOF]
     "#);
 }
+
+#[cfg(unix)]
+#[test]
+fn test_git_push_pre_push_hook() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let test_env = TestEnvironment::default();
+    set_up(&test_env);
+    let work_dir = test_env.work_dir("local");
+    let hooks_dir = git_repo_dir_for_jj_repo(&work_dir).join("hooks");
+    std::fs::create_dir_all(&hooks_dir).unwrap();
+    let hook_path = hooks_dir.join("pre-push");
+    std::fs::write(
+        &hook_path,
+        indoc::indoc! {r#"
+            #!/bin/sh
+            echo "pre-push $1"
+            while read local_ref local_id remote_ref remote_id; do
+                echo "$local_ref -> $remote_ref"
+            done
+            exit 1
+        "#},
+    )
+    .unwrap();
+    std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o700)).unwrap();
+
+    // create new commit on top of bookmark1
+    work_dir.run_jj(["new", "bookmark1"]).success();
+    work_dir.write_file("file", "file");
+    work_dir.run_jj(["describe", "-m=update"]).success();
+    work_dir.run_jj(["bookmark", "move", "bookmark1"]).success();
+
+    // Hooks aren't run by default
+    let output = work_dir.run_jj(["git", "push", "--dry-run"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from d13ecdbda2a2 to dd5c09b30f9f
+    Dry-run requested, not pushing.
+    [EOF]
+    ");
+
+    test_env.add_config("git.run-hooks = true");
+    let output = work_dir.run_jj(["git", "push"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from d13ecdbda2a2 to dd5c09b30f9f
+    pre-push origin
+    refs/heads/bookmark1 -> refs/heads/bookmark1
+    Error: The pre-push hook failed (exit status: 1)
+    Hint: Use --no-verify to skip the Git hooks.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = work_dir.run_jj(["git", "push", "--no-verify"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark1 from d13ecdbda2a2 to dd5c09b30f9f
+    [EOF]
+    ");
+}
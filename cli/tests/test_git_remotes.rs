@@ -180,6 +180,84 @@ fn test_git_remote_set_url() {
     "#);
 }
 
+#[test]
+fn test_git_remote_set_url_push() {
+    let test_env = TestEnvironment::default();
+
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir
+        .run_jj(["git", "remote", "add", "foo", "http://example.com/repo/foo"])
+        .success();
+    let output = work_dir.run_jj([
+        "git",
+        "remote",
+        "set-url",
+        "--push",
+        "foo",
+        "ssh://git@example.com/repo/foo",
+    ]);
+    insta::assert_snapshot!(output, @"");
+    let output = work_dir.run_jj(["git", "remote", "list"]);
+    insta::assert_snapshot!(output, @r"
+    foo http://example.com/repo/foo (push: ssh://git@example.com/repo/foo)
+    [EOF]
+    ");
+
+    // Setting the fetch URL keeps the push URL
+    let output = work_dir.run_jj([
+        "git",
+        "remote",
+        "set-url",
+        "foo",
+        "http://example.com/repo/bar",
+    ]);
+    insta::assert_snapshot!(output, @"");
+    let output = work_dir.run_jj(["git", "remote", "list"]);
+    insta::assert_snapshot!(output, @r"
+    foo http://example.com/repo/bar (push: ssh://git@example.com/repo/foo)
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["git", "remote", "remove", "foo"]);
+    insta::assert_snapshot!(output, @"");
+    let output = work_dir.run_jj(["git", "remote", "list"]);
+    insta::assert_snapshot!(output, @"");
+}
+
+#[test]
+fn test_git_remote_list_url_rewrite() {
+    let test_env = TestEnvironment::default();
+
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let git_dir = work_dir.root().join(".jj/repo/store/git");
+    git::set_config(
+        &git_dir,
+        "url.https://mirror.example.com/.insteadOf",
+        "https://example.com/",
+    );
+    git::set_config(
+        &git_dir,
+        "url.ssh://git@example.com/.pushInsteadOf",
+        "https://example.com/",
+    );
+    work_dir
+        .run_jj([
+            "git",
+            "remote",
+            "add",
+            "foo",
+            "https://example.com/repo/foo",
+        ])
+        .success();
+    let output = work_dir.run_jj(["git", "remote", "list"]);
+    insta::assert_snapshot!(output, @r"
+    foo https://mirror.example.com/repo/foo (push: ssh://git@example.com/repo/foo)
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_git_remote_relative_path() {
     let test_env = TestEnvironment::default();
@@ -1704,3 +1704,85 @@ fn test_log_full_description_template() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_log_mailmap() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    work_dir
+        .run_jj([
+            "describe",
+            "-m=old identity",
+            "--author=Old Name <old@example.com>",
+        ])
+        .success();
+    work_dir.run_jj(["new", "-m=new identity"]).success();
+    work_dir.write_file(
+        ".mailmap",
+        "Test User <test.user@example.com> Old Name <Old@Example.com>\n",
+    );
+
+    let template = r#"author.name() ++ " <" ++ author.email() ++ "> " ++ description"#;
+    let output = work_dir.run_jj(["log", "--no-graph", "-rmine()", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    Test User <test.user@example.com> new identity
+    Test User <test.user@example.com> old identity
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "log",
+        "--no-graph",
+        "-rauthor_name(exact:'Test User')",
+        "-T",
+        template,
+    ]);
+    insta::assert_snapshot!(output, @r"
+    Test User <test.user@example.com> new identity
+    Test User <test.user@example.com> old identity
+    [EOF]
+    ");
+
+    // The recorded identities are still accessible
+    let template = r#"raw_author.name() ++ " <" ++ raw_author.email() ++ "> " ++ description"#;
+    let output = work_dir.run_jj(["log", "--no-graph", "-rmine()", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    Test User <test.user@example.com> new identity
+    Old Name <old@example.com> old identity
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "log",
+        "--config=mailmap.enabled=false",
+        "--no-graph",
+        "-rmine()",
+        "-T",
+        template,
+    ]);
+    insta::assert_snapshot!(output, @r"
+    Test User <test.user@example.com> new identity
+    [EOF]
+    ");
+
+    // A missing mailmap file disables the mailmap with a warning
+    let output = work_dir.run_jj([
+        "log",
+        "--config=mailmap.file=missing.mailmap",
+        "--no-graph",
+        "-rmine()",
+        "-T",
+        template,
+    ]);
+    assert!(output.status.success(), "{output}");
+    assert!(
+        output
+            .stderr
+            .raw()
+            .contains("doesn't exist. The mailmap is disabled."),
+        "{output}"
+    );
+    insta::assert_snapshot!(output.stdout, @r"
+    Test User <test.user@example.com> new identity
+    [EOF]
+    ");
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use crate::common::create_commit;
+use crate::common::create_commit_with_files;
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 use crate::common::TestWorkDir;
@@ -2842,6 +2843,54 @@ fn test_rebase_skip_emptied_descendants() {
     ");
 }
 
+#[test]
+fn test_rebase_ignore_whitespace() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    // b and c reformatted the same line differently
+    create_commit_with_files(&work_dir, "a", &[], &[("file", "a\nf(x,y)\n")]);
+    create_commit_with_files(&work_dir, "b", &["a"], &[("file", "a\nf(x, y)\n")]);
+    create_commit_with_files(&work_dir, "c", &["a"], &[("file", "a\nf( x, y )\n")]);
+
+    // The sides differ in more than the amount of whitespace
+    work_dir
+        .run_jj(["rebase", "-r=c", "-d=b", "--ignore-space-change"])
+        .success();
+    let output = work_dir.run_jj(["log", "--no-graph", "-r=conflicts()", "-T=bookmarks"]);
+    insta::assert_snapshot!(output, @"c[EOF]");
+    work_dir.run_jj(["undo"]).success();
+
+    work_dir
+        .run_jj(["rebase", "-r=c", "-d=b", "--ignore-all-space"])
+        .success();
+    let output = work_dir.run_jj(["log", "--no-graph", "-r=conflicts()", "-T=bookmarks"]);
+    insta::assert_snapshot!(output, @"");
+    // The destination's side is taken by default
+    let output = work_dir.run_jj(["file", "show", "-r=c", "file"]);
+    insta::assert_snapshot!(output, @r"
+    a
+    f(x, y)
+    [EOF]
+    ");
+    work_dir.run_jj(["undo"]).success();
+
+    test_env.add_config(
+        r#"
+        merge.whitespace = "ignore-all-space"
+        merge.whitespace-side = "theirs"
+        "#,
+    );
+    work_dir.run_jj(["rebase", "-r=c", "-d=b"]).success();
+    let output = work_dir.run_jj(["file", "show", "-r=c", "file"]);
+    insta::assert_snapshot!(output, @r"
+    a
+    f( x, y )
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_rebase_skip_if_on_destination() {
     let test_env = TestEnvironment::default();
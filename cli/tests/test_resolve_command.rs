@@ -17,6 +17,7 @@ use std::path::Path;
 use indoc::indoc;
 
 use crate::common::create_commit_with_files;
+use crate::common::force_interactive;
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 use crate::common::TestWorkDir;
@@ -539,6 +540,104 @@ fn check_resolve_produces_input_file(
     }
 }
 
+#[test]
+fn test_resolution_json_rpc() {
+    let mut test_env = TestEnvironment::default();
+    let edit_script = test_env.set_up_fake_diff_editor();
+    test_env.add_config(indoc! {r#"
+        merge-tools.fake-diff-editor.protocol = "json-rpc"
+        merge-tools.fake-diff-editor.merge-args = ["--json-rpc"]
+    "#});
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(&work_dir, "base", &[], &[("file", "base\n")]);
+    create_commit_with_files(&work_dir, "a", &["base"], &[("file", "a\n")]);
+    create_commit_with_files(&work_dir, "b", &["base"], &[("file", "b\n")]);
+    create_commit_with_files(&work_dir, "conflict", &["a", "b"], &[]);
+
+    // The conflict is served to the tool, which resolves it
+    std::fs::write(
+        &edit_script,
+        [
+            "print-files",
+            "print-hunks file",
+            "dump file output0",
+            "write file\nresolution\n",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let output = work_dir
+        .run_jj(["resolve", "--tool=fake-diff-editor"])
+        .success();
+    let (tool_output, _) = output.stderr.raw().split_once("Working copy").unwrap();
+    insta::assert_snapshot!(tool_output.trim_end(), @r#"
+    Resolving conflicts in: file
+    mode: Merge
+    file (2 sides)
+    conflict: ["a\n", "b\n"]
+    "#);
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("output0")).unwrap(), @r"
+    <<<<<<< Conflict 1 of 1
+    %%%%%%% Changes from base to side #1
+    -base
+    +a
+    +++++++ Contents of side #2
+    b
+    >>>>>>> Conflict 1 of 1 ends
+    ");
+    insta::assert_snapshot!(work_dir.read_file("file"), @"resolution");
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list"]), @r"
+    ------- stderr -------
+    Error: No conflicts found at this revision
+    [EOF]
+    [exit status: 2]
+    ");
+
+    // Conflict markers left in the written content keep the file conflicted
+    work_dir.run_jj(["undo"]).success();
+    std::fs::write(
+        &edit_script,
+        indoc! {"
+            write-with-conflict-markers file
+            <<<<<<<
+            %%%%%%%
+            -base
+            +a
+            +++++++
+            b2
+            >>>>>>>
+        "},
+    )
+    .unwrap();
+    work_dir
+        .run_jj(["resolve", "--tool=fake-diff-editor"])
+        .success();
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list"]), @r"
+    file    2-sided conflict
+    [EOF]
+    ");
+
+    // Nothing happens if the tool aborts the session
+    work_dir.run_jj(["undo"]).success();
+    std::fs::write(&edit_script, "write file\nresolution\n\0abort").unwrap();
+    let output = work_dir.run_jj(["resolve", "--tool=fake-diff-editor"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Resolving conflicts in: file
+    Error: Failed to resolve conflicts
+    Caused by: Canceled by user in the tool
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list"]), @r"
+    file    2-sided conflict
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_normal_conflict_input_files() {
     let mut test_env = TestEnvironment::default();
@@ -620,7 +719,8 @@ fn test_baseless_conflict_input_files() {
 
 #[test]
 fn test_too_many_parents() {
-    let test_env = TestEnvironment::default();
+    let mut test_env = TestEnvironment::default();
+    test_env.set_up_fake_editor();
     test_env.run_jj_in(".", ["git", "init", "repo"]).success();
     let work_dir = test_env.work_dir("repo");
 
@@ -639,10 +739,10 @@ fn test_too_many_parents() {
     [EOF]
     ");
 
+    // External merge tools only support 3-way merges
     let output = work_dir.run_jj(["resolve"]);
     insta::assert_snapshot!(output, @r#"
     ------- stderr -------
-    Hint: Using default editor ':builtin'; run `jj config set --user ui.merge-editor :builtin` to disable this message.
     Error: Failed to resolve conflicts
     Caused by: The conflict at "file" has 3 sides. At most 2 sides are supported.
     [EOF]
@@ -650,6 +750,53 @@ fn test_too_many_parents() {
     "#);
 }
 
+#[test]
+fn test_builtin_many_sides() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(&work_dir, "base", &[], &[("file", "base\n")]);
+    create_commit_with_files(&work_dir, "edit", &["base"], &[("file", "b\n")]);
+    create_commit_with_files(&work_dir, "dir", &["base"], &[]);
+    work_dir.remove_file("file");
+    work_dir.create_dir("file");
+    // Without a placeholder file, `jj` ignores an empty directory
+    work_dir.write_file("file/placeholder", "");
+    create_commit_with_files(&work_dir, "del", &["base"], &[]);
+    work_dir.remove_file("file");
+    create_commit_with_files(&work_dir, "conflict", &["edit", "dir", "del"], &[]);
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list"]), @r"
+    file    3-sided conflict including 1 deletion and a directory
+    [EOF]
+    ");
+
+    // The builtin tool resolves conflicts with more than 2 sides
+    work_dir.run_jj(["new", "root()"]).success();
+    let output = work_dir.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .args([
+                "resolve",
+                "-r=conflict",
+                "--config=ui.merge-editor=:builtin",
+            ])
+            .write_stdin("1\n")
+    });
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Choose a side to resolve the conflict at file:
+    1: file
+    2: directory
+    3: deleted
+    Append "+" to the side to keep the other sides at file~side-N.
+    Enter the side to take: [EOF]
+    "#);
+    insta::assert_snapshot!(work_dir.run_jj(["file", "show", "-r=conflict", "file"]), @r"
+    b
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_simplify_conflict_sides() {
     let mut test_env = TestEnvironment::default();
@@ -839,25 +986,56 @@ fn test_file_vs_dir() {
     file    2-sided conflict including a directory
     [EOF]
     ");
+    // The builtin tool asks which side to take
     let output = work_dir.run_jj(["resolve"]);
     insta::assert_snapshot!(output, @r#"
     ------- stderr -------
     Hint: Using default editor ':builtin'; run `jj config set --user ui.merge-editor :builtin` to disable this message.
+    Choose a side to resolve the conflict at file:
+    1: file
+    2: directory
+    Append "+" to the side to keep the other sides at file~side-N.
     Error: Failed to resolve conflicts
-    Caused by: Only conflicts that involve normal files (not symlinks, not executable, etc.) are supported. Conflict summary for "file":
-    Conflict:
-      Removing file with id df967b96a579e45a18b8251732d16804b2e56a55
-      Adding file with id 78981922613b2afb6025042ff6bd878ac1994e85
-      Adding tree with id 133bb38fc4e4bf6b551f1f04db7e48f04cac2877
-
+    Caused by: Cannot prompt for input since the output is not connected to a terminal
     [EOF]
     [exit status: 1]
     "#);
+
+    // Take the directory and keep the file aside
+    work_dir.run_jj(["new", "root()"]).success();
+    let output = work_dir.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .args([
+                "resolve",
+                "-r=conflict",
+                "--config=ui.merge-editor=:builtin",
+            ])
+            .write_stdin("3\n2+\n")
+    });
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Choose a side to resolve the conflict at file:
+    1: file
+    2: directory
+    Append "+" to the side to keep the other sides at file~side-N.
+    Enter the side to take: Invalid side
+    Enter the side to take: [EOF]
+    "#);
+    insta::assert_snapshot!(work_dir.run_jj(["file", "list", "-r=conflict"]), @r"
+    file/placeholder
+    file~side-1
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["file", "show", "-r=conflict", "file~side-1"]), @r"
+    a
+    [EOF]
+    ");
 }
 
 #[test]
 fn test_description_with_dir_and_deletion() {
-    let test_env = TestEnvironment::default();
+    let mut test_env = TestEnvironment::default();
+    test_env.set_up_fake_editor();
     test_env.run_jj_in(".", ["git", "init", "repo"]).success();
     let work_dir = test_env.work_dir("repo");
 
@@ -893,10 +1071,10 @@ fn test_description_with_dir_and_deletion() {
     file    [38;5;1m3-sided[38;5;3m conflict including 1 deletion and [38;5;1ma directory[39m
     [EOF]
     ");
+    // External merge tools can't resolve the conflict
     let output = work_dir.run_jj(["resolve"]);
     insta::assert_snapshot!(output, @r#"
     ------- stderr -------
-    Hint: Using default editor ':builtin'; run `jj config set --user ui.merge-editor :builtin` to disable this message.
     Error: Failed to resolve conflicts
     Caused by: Only conflicts that involve normal files (not symlinks, not executable, etc.) are supported. Conflict summary for "file":
     Conflict:
@@ -910,6 +1088,63 @@ fn test_description_with_dir_and_deletion() {
     "#);
 }
 
+#[test]
+fn test_resolve_take_side() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(&work_dir, "base", &[], &[("file", "base\n")]);
+    create_commit_with_files(&work_dir, "a", &["base"], &[("file", "a\n")]);
+    create_commit_with_files(&work_dir, "b", &["base"], &[]);
+    work_dir.remove_file("file");
+    work_dir.create_dir("file");
+    work_dir.write_file("file/placeholder", "");
+    create_commit_with_files(&work_dir, "c", &["base"], &[]);
+    work_dir.remove_file("file");
+    create_commit_with_files(&work_dir, "dir", &["a", "b"], &[]);
+    create_commit_with_files(&work_dir, "delete", &["a", "c"], &[]);
+    work_dir.run_jj(["new", "root()"]).success();
+
+    let output = work_dir.run_jj(["resolve", "-r=dir", "--take=2"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: invalid value '2' for '--take <SIDE>': Expected `side-N` where N is a number starting from 1, got `2`
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+    let output = work_dir.run_jj(["resolve", "-r=dir", "--take=side-3"]);
+    insta::assert_snapshot!(output, @r#"
+    ------- stderr -------
+    Error: Failed to resolve conflicts
+    Caused by: The conflict at "file" has no side #3, only 2 sides
+    [EOF]
+    [exit status: 1]
+    "#);
+
+    // Take the file and keep the directory aside
+    work_dir
+        .run_jj(["resolve", "-r=dir", "--take=side-1", "--keep-others"])
+        .success();
+    insta::assert_snapshot!(work_dir.run_jj(["file", "list", "-r=dir"]), @r"
+    file
+    file~side-2/placeholder
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["file", "show", "-r=dir", "file"]), @r"
+    a
+    [EOF]
+    ");
+
+    // Take the side that deleted the file
+    work_dir
+        .run_jj(["resolve", "-r=delete", "--take=side-2"])
+        .success();
+    insta::assert_snapshot!(work_dir.run_jj(["file", "list", "-r=delete"]), @"");
+}
+
 #[test]
 fn test_resolve_conflicts_with_executable() {
     let mut test_env = TestEnvironment::default();
@@ -1661,3 +1896,264 @@ fn test_multiple_conflicts_with_error() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_reuse_recorded_resolutions() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config("merge.reuse-resolutions = true");
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(&work_dir, "base", &[], &[("file", "1\nbase\n3\n")]);
+    create_commit_with_files(&work_dir, "a", &["base"], &[("file", "1\na\n3\n")]);
+    create_commit_with_files(&work_dir, "b1", &["base"], &[("file", "1\nb\n3\n")]);
+    create_commit_with_files(&work_dir, "b2", &["base"], &[("file", "1\nb\n3\n")]);
+    create_commit_with_files(&work_dir, "b3", &["base"], &[("file", "1\nb\n3\n")]);
+
+    // Resolve the conflict after rebasing one of the commits
+    work_dir.run_jj(["rebase", "-r=b1", "-d=a"]).success();
+    work_dir.run_jj(["edit", "b1"]).success();
+    work_dir.write_file("file", "1\na\nb\n3\n");
+    work_dir.run_jj(["status"]).success();
+
+    // The same conflict is resolved automatically when rebasing another commit
+    work_dir.run_jj(["rebase", "-r=b2", "-d=a"]).success();
+    let output = work_dir.run_jj(["file", "show", "-r=b2", "file"]);
+    insta::assert_snapshot!(output, @r"
+    1
+    a
+    b
+    3
+    [EOF]
+    ");
+
+    // Existing conflicts can be resolved with the recorded resolutions
+    work_dir
+        .run_jj([
+            "rebase",
+            "-r=b3",
+            "-d=a",
+            "--config=merge.reuse-resolutions=false",
+        ])
+        .success();
+    let output = work_dir.run_jj(["resolve", "--list", "-r=b3"]);
+    insta::assert_snapshot!(output, @r"
+    file    2-sided conflict
+    [EOF]
+    ");
+    let output = work_dir.run_jj([
+        "resolve",
+        "--auto",
+        "-r=b3",
+        "--config=merge.reuse-resolutions=false",
+    ]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Reusing recorded conflict resolutions is disabled
+    Hint: Set `merge.reuse-resolutions = true` to record and reuse resolutions.
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["resolve", "--auto", "-r=b3"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Resolved conflicts in 1 files using recorded resolutions
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "-r=b3", "file"]);
+    insta::assert_snapshot!(output, @r"
+    1
+    a
+    b
+    3
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_merge_hunk_level_word() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(&work_dir, "base", &[], &[("file", "f(x, y)\n")]);
+    create_commit_with_files(&work_dir, "a", &["base"], &[("file", "g(x, y)\n")]);
+    create_commit_with_files(&work_dir, "b", &["base"], &[("file", "f(x, y, z)\n")]);
+
+    // Different words on the same line conflict by default
+    create_commit_with_files(&work_dir, "line", &["a", "b"], &[]);
+    let output = work_dir.run_jj(["resolve", "--list"]);
+    insta::assert_snapshot!(output, @r"
+    file    2-sided conflict
+    [EOF]
+    ");
+
+    test_env.add_config(r#"merge.hunk-level = "word""#);
+    create_commit_with_files(&work_dir, "word", &["a", "b"], &[]);
+    let output = work_dir.run_jj(["file", "show", "file"]);
+    insta::assert_snapshot!(output, @r"
+    g(x, y, z)
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_merge_drivers() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(indoc! {r#"
+        [merge.drivers.changelog]
+        patterns = ["glob:'**/CHANGELOG.md'"]
+        builtin = "union"
+
+        [merge.drivers.lockfile]
+        patterns = ["Cargo.lock"]
+        builtin = "ours"
+    "#});
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(
+        &work_dir,
+        "base",
+        &[],
+        &[
+            ("CHANGELOG.md", "* old\n"),
+            ("Cargo.lock", "v1\n"),
+            ("file", "base\n"),
+        ],
+    );
+    create_commit_with_files(
+        &work_dir,
+        "a",
+        &["base"],
+        &[
+            ("CHANGELOG.md", "* a\n* old\n"),
+            ("Cargo.lock", "v2\n"),
+            ("file", "a\n"),
+        ],
+    );
+    create_commit_with_files(
+        &work_dir,
+        "b",
+        &["base"],
+        &[
+            ("CHANGELOG.md", "* b\n* old\n"),
+            ("Cargo.lock", "v3\n"),
+            ("file", "b\n"),
+        ],
+    );
+    create_commit_with_files(&work_dir, "conflict", &["a", "b"], &[]);
+
+    // Only the file without a merge driver is conflicted
+    let output = work_dir.run_jj(["resolve", "--list"]);
+    insta::assert_snapshot!(output, @r"
+    file    2-sided conflict
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "CHANGELOG.md"]);
+    insta::assert_snapshot!(output, @r"
+    * a
+    * b
+    * old
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "Cargo.lock"]);
+    insta::assert_snapshot!(output, @r"
+    v2
+    [EOF]
+    ");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_merge_driver_external_command() {
+    let test_env = TestEnvironment::default();
+    test_env.add_config(indoc! {r#"
+        [merge.drivers.sorted]
+        patterns = ["glob:'*.txt'"]
+        command = ["sh", "-c", 'sort -u "$1" "$2" > "$3"', "sh", "$left", "$right", "$output"]
+    "#});
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(&work_dir, "base", &[], &[("list.txt", "b\n")]);
+    create_commit_with_files(&work_dir, "a", &["base"], &[("list.txt", "a\n")]);
+    create_commit_with_files(&work_dir, "c", &["base"], &[("list.txt", "c\n")]);
+    create_commit_with_files(&work_dir, "conflict", &["a", "c"], &[]);
+    let output = work_dir.run_jj(["file", "show", "list.txt"]);
+    insta::assert_snapshot!(output, @r"
+    a
+    c
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_conflict_labels() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    let normalize_ids = |text: String| {
+        let re = regex::Regex::new(r"[k-z]{12} [0-9a-f]{12}").unwrap();
+        re.replace_all(&text, "[ids]").into_owned()
+    };
+
+    create_commit_with_files(&work_dir, "base", &[], &[("file", "base\n")]);
+    create_commit_with_files(&work_dir, "a", &["base"], &[("file", "a\n")]);
+    create_commit_with_files(&work_dir, "b", &["base"], &[("file", "b\n")]);
+    work_dir.run_jj(["rebase", "-r=b", "-d=a"]).success();
+
+    // The labels aren't shown by default
+    let output = work_dir.run_jj(["resolve", "--list"]);
+    insta::assert_snapshot!(output, @r"
+    file    2-sided conflict
+    [EOF]
+    ");
+
+    test_env.add_config("ui.show-conflict-labels = true");
+    let output = work_dir
+        .run_jj(["resolve", "--list"])
+        .normalize_stdout_with(normalize_ids);
+    insta::assert_snapshot!(output, @r#"
+    file    2-sided conflict
+      side #1: [ids] "a"
+      base: [ids] "base"
+      side #2: [ids] "b"
+    [EOF]
+    "#);
+    let output = work_dir
+        .run_jj(["file", "show", "file"])
+        .normalize_stdout_with(normalize_ids);
+    insta::assert_snapshot!(output, @r#"
+    <<<<<<< Conflict 1 of 1
+    %%%%%%% Changes from base ([ids] "base") to side #1 ([ids] "a")
+    -base
+    +a
+    +++++++ Contents of side #2 ([ids] "b")
+    b
+    >>>>>>> Conflict 1 of 1 ends
+    [EOF]
+    "#);
+
+    // The labels are written to the working copy when checking out the commit
+    work_dir.run_jj(["new", "a"]).success();
+    work_dir.run_jj(["edit", "b"]).success();
+    insta::assert_snapshot!(normalize_ids(work_dir.read_file("file").to_string()), @r#"
+    <<<<<<< Conflict 1 of 1
+    %%%%%%% Changes from base ([ids] "base") to side #1 ([ids] "a")
+    -base
+    +a
+    +++++++ Contents of side #2 ([ids] "b")
+    b
+    >>>>>>> Conflict 1 of 1 ends
+    "#);
+
+    // Conflicts in merge commits aren't labeled
+    create_commit_with_files(&work_dir, "c", &["base"], &[("file", "c\n")]);
+    create_commit_with_files(&work_dir, "merge", &["a", "c"], &[]);
+    let output = work_dir.run_jj(["resolve", "--list"]);
+    insta::assert_snapshot!(output, @r"
+    file    2-sided conflict
+    [EOF]
+    ");
+}
@@ -288,6 +288,72 @@ fn test_materialize_and_snapshot_different_conflict_markers() {
     ");
 }
 
+#[test]
+fn test_snapshot_unparsed_conflict_markers() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    test_env.add_config("snapshot.validate-conflict-markers = true");
+
+    // Create a conflict in the working copy
+    let conflict_file = repo_path.join("file");
+    std::fs::write(&conflict_file, "base\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["commit", "-m", "base"])
+        .success();
+    std::fs::write(&conflict_file, "a\n").unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["commit", "-m", "side-a"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["new", "description(base)", "-m", "side-b"])
+        .success();
+    std::fs::write(&conflict_file, "b\n").unwrap();
+    test_env
+        .run_jj_in(
+            &repo_path,
+            ["new", "description(side-a)", "description(side-b)"],
+        )
+        .success();
+
+    // Delete the conflict end marker
+    std::fs::write(
+        &conflict_file,
+        indoc! {"
+            <<<<<<< Conflict 1 of 1
+            %%%%%%% Changes from base to side #1
+            -base
+            +a
+            +++++++ Contents of side #2
+            b
+            resolved
+        "},
+    )
+    .unwrap();
+
+    // The file is kept conflicted
+    let output = test_env.run_jj_in(&repo_path, ["resolve", "--list"]);
+    insta::assert_snapshot!(output, @r"
+    file    2-sided conflict
+    [EOF]
+    ------- stderr -------
+    Warning: Kept some files conflicted because their conflict markers couldn't be parsed:
+      file (line 1): conflict start marker without end marker
+    Hint: Fix or remove the conflict markers. Changes to these files won't be snapshotted until the markers can be parsed.
+    [EOF]
+    ");
+
+    // Once the markers are removed, the file is resolved
+    std::fs::write(&conflict_file, "resolved\n").unwrap();
+    let output = test_env.run_jj_in(&repo_path, ["resolve", "--list"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No conflicts found at this revision
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 #[test]
 fn test_snapshot_invalid_ignore_pattern() {
     let test_env = TestEnvironment::default();
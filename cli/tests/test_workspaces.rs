@@ -1477,6 +1477,140 @@ fn test_workspaces_rename_workspace() {
     ");
 }
 
+#[test]
+fn test_workspaces_move() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    let main_path = test_env.env_root().join("main");
+    test_env
+        .run_jj_in(
+            &main_path,
+            ["workspace", "add", "--name", "second", "../secondary"],
+        )
+        .success();
+    let secondary_path = test_env.env_root().join("secondary");
+    let moved_main_path = test_env.env_root().join("moved-main");
+    let moved_secondary_path = test_env.env_root().join("moved-secondary");
+
+    // Moving the workspace containing the repo updates the other workspaces
+    let output = test_env.run_jj_in(&main_path, ["workspace", "move", "../moved-main"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r#"
+    ------- stderr -------
+    Moved workspace to "../moved-main"
+    Updated repo path of workspace second
+    [EOF]
+    "#);
+    assert!(!main_path.exists());
+    let output = test_env.run_jj_in(&secondary_path, ["workspace", "list"]);
+    insta::assert_snapshot!(output, @r"
+    default: qpvuntsm 230dd059 (empty) (no description set)
+    second: uuqppmxq 57d63245 (empty) (no description set)
+    [EOF]
+    ");
+
+    // Moving a secondary workspace doesn't affect the others
+    let output = test_env.run_jj_in(&secondary_path, ["workspace", "move", "../moved-secondary"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r#"
+    ------- stderr -------
+    Moved workspace to "../moved-secondary"
+    [EOF]
+    "#);
+    let output = test_env.run_jj_in(&moved_secondary_path, ["workspace", "root"]);
+    insta::assert_snapshot!(output, @r"
+    $TEST_ENV/moved-secondary
+    [EOF]
+    ");
+
+    // Moving the main workspace again finds the secondary workspace at its new
+    // location
+    let output = test_env.run_jj_in(&moved_main_path, ["workspace", "move", "../main"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r#"
+    ------- stderr -------
+    Moved workspace to "../main"
+    Updated repo path of workspace second
+    [EOF]
+    "#);
+    let output = test_env.run_jj_in(&moved_secondary_path, ["workspace", "root"]);
+    insta::assert_snapshot!(output, @r"
+    $TEST_ENV/moved-secondary
+    [EOF]
+    ");
+
+    // Cannot move to an existing directory
+    let output = test_env.run_jj_in(&main_path, ["workspace", "move", "../moved-secondary"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: The destination ($TEST_ENV/moved-secondary) already exists
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+#[test]
+fn test_workspaces_repair() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    let main_path = test_env.env_root().join("main");
+    test_env
+        .run_jj_in(
+            &main_path,
+            ["workspace", "add", "--name", "second", "../secondary"],
+        )
+        .success();
+    let secondary_path = test_env.env_root().join("secondary");
+    let moved_main_path = test_env.env_root().join("moved-main");
+
+    // Moving the main workspace without jj leaves a stale repo path behind
+    std::fs::rename(&main_path, &moved_main_path).unwrap();
+    let output = test_env.run_jj_in(&secondary_path, ["workspace", "list"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: The repository directory at $TEST_ENV/main/.jj/repo is missing. Was it moved?
+    Hint: If the workspace containing the repository was moved, run `jj workspace repair <path-to-that-workspace>` in this workspace.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = test_env.run_jj_in(&secondary_path, ["workspace", "repair", "../moved-main"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Updated repo path of the current workspace
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&secondary_path, ["workspace", "list"]);
+    insta::assert_snapshot!(output, @r"
+    default: qpvuntsm 230dd059 (empty) (no description set)
+    second: uuqppmxq 57d63245 (empty) (no description set)
+    [EOF]
+    ");
+
+    // Repairing the main workspace records its new location so it can be
+    // found by later moves
+    let output = test_env.run_jj_in(&moved_main_path, ["workspace", "repair"]);
+    insta::assert_snapshot!(output, @"");
+    let output = test_env.run_jj_in(&moved_main_path, ["workspace", "move", "../main"]);
+    insta::assert_snapshot!(output.normalize_backslash(), @r#"
+    ------- stderr -------
+    Moved workspace to "../main"
+    Updated repo path of workspace second
+    [EOF]
+    "#);
+    let output = test_env.run_jj_in(&secondary_path, ["workspace", "root"]);
+    insta::assert_snapshot!(output, @r"
+    $TEST_ENV/secondary
+    [EOF]
+    ");
+
+    // The repo path must point to a repo
+    let output = test_env.run_jj_in(&secondary_path, ["workspace", "repair", "."]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: There is no Jujutsu repo at $TEST_ENV/secondary
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[must_use]
 fn get_log_output(test_env: &TestEnvironment, cwd: &Path) -> CommandOutput {
     let template = r#"
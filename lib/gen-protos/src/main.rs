@@ -21,6 +21,7 @@ fn main() -> Result<()> {
         "simple_store.proto",
         "op_store.proto",
         "working_copy.proto",
+        "workspace_store.proto",
     ];
 
     let root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
@@ -31,9 +31,18 @@ pub fn generate_hash_impl(data: &Data) -> TokenStream {
                 let hash_statements = fields.named.iter().map(|f| {
                     let field_name = &f.ident;
                     let ty = &f.ty;
-                    quote_spanned! {ty.span()=>
+                    let hash_statement = quote_spanned! {ty.span()=>
                         <#ty as ::jj_lib::content_hash::ContentHash>::hash(
                             &self.#field_name, state);
+                    };
+                    match skips_default(f) {
+                        Ok(false) => hash_statement,
+                        Ok(true) => quote_spanned! {ty.span()=>
+                            if self.#field_name != <#ty as ::std::default::Default>::default() {
+                                #hash_statement
+                            }
+                        },
+                        Err(err) => err.to_compile_error(),
                     }
                 });
                 quote! {
@@ -102,6 +111,26 @@ pub fn generate_hash_impl(data: &Data) -> TokenStream {
     }
 }
 
+/// Returns true if the field is annotated with
+/// `#[content_hash(skip_if_default)]`.
+fn skips_default(field: &Field) -> syn::Result<bool> {
+    let mut skip_if_default = false;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("content_hash") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip_if_default") {
+                skip_if_default = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported content_hash attribute"))
+            }
+        })?;
+    }
+    Ok(skip_if_default)
+}
+
 // The documentation for `ContentHash` specifies that the hash impl for each
 // enum variant should hash the ordinal number of the enum variant as a little
 // endian u32 before hashing the variant's fields, if any.
@@ -11,7 +11,11 @@ use syn::DeriveInput;
 /// Derives the `ContentHash` trait for a struct by calling `ContentHash::hash`
 /// on each of the struct members in the order that they're declared. All
 /// members of the struct must implement the `ContentHash` trait.
-#[proc_macro_derive(ContentHash)]
+///
+/// A named struct member annotated with `#[content_hash(skip_if_default)]`
+/// isn't hashed if it equals its `Default` value, so the member can be added
+/// without changing the hashes of the existing values.
+#[proc_macro_derive(ContentHash, attributes(content_hash))]
 pub fn derive_content_hash(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
@@ -363,9 +363,13 @@ fn get_file_contents(
     let effective_file_value = materialize_tree_value(store, path, file_value).block_on()?;
     match effective_file_value {
         MaterializedTreeValue::File(mut file) => Ok(file.read_all(path)?.into()),
-        MaterializedTreeValue::FileConflict { contents, .. } => Ok(
-            materialize_merge_result_to_bytes(&contents, ConflictMarkerStyle::default()),
-        ),
+        MaterializedTreeValue::FileConflict { contents, .. } => {
+            Ok(materialize_merge_result_to_bytes(
+                &contents,
+                &store.merge_options().file_merge_options,
+                ConflictMarkerStyle::default(),
+            ))
+        }
         _ => Ok(BString::default()),
     }
 }
@@ -144,6 +144,10 @@ pub struct Commit {
     pub parents: Vec<CommitId>,
     pub predecessors: Vec<CommitId>,
     pub root_tree: MergedTreeId,
+    /// Labels of the `root_tree` terms describing where they came from, e.g.
+    /// the commits a conflict was rebased from. Empty if the terms aren't
+    /// labeled.
+    pub conflict_labels: Vec<String>,
     pub change_id: ChangeId,
     pub description: String,
     pub author: Signature,
@@ -383,6 +387,7 @@ pub fn make_root_commit(root_change_id: ChangeId, empty_tree_id: TreeId) -> Comm
         parents: vec![],
         predecessors: vec![],
         root_tree: MergedTreeId::resolved(empty_tree_id),
+        conflict_labels: vec![],
         change_id: root_change_id,
         description: String::new(),
         author: signature.clone(),
@@ -30,6 +30,7 @@ use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::backend::MergedTreeId;
 use crate::backend::Signature;
+use crate::merge::Merge;
 use crate::merged_tree::MergedTree;
 use crate::repo::Repo;
 use crate::rewrite::merge_commit_trees;
@@ -116,6 +117,22 @@ impl Commit {
         &self.data.root_tree
     }
 
+    /// Labels of the terms of the tree conflict describing where they came
+    /// from. Returns `None` if the tree isn't conflicted or if its terms aren't
+    /// labeled.
+    pub fn conflict_labels(&self) -> Option<Merge<String>> {
+        has_conflict_labels(&self.data).then(|| Merge::from_vec(self.data.conflict_labels.clone()))
+    }
+
+    /// Describes this commit in the labels of conflicts that came from it.
+    pub fn conflict_label(&self) -> String {
+        let ids = format!("{:.12} {:.12}", self.change_id(), self.id());
+        match self.description().lines().next() {
+            Some(first_line) if !first_line.is_empty() => format!("{ids} \"{first_line}\""),
+            _ => format!("{ids} (no description set)"),
+        }
+    }
+
     /// Return the parent tree, merging the parent trees if there are multiple
     /// parents.
     pub fn parent_tree(&self, repo: &dyn Repo) -> BackendResult<MergedTree> {
@@ -201,6 +218,16 @@ pub(crate) fn is_backend_commit_empty(
     Ok(commit.root_tree == parent_tree.id())
 }
 
+/// Whether the commit's tree is conflicted and has one label per term.
+pub(crate) fn has_conflict_labels(commit: &backend::Commit) -> bool {
+    match &commit.root_tree {
+        MergedTreeId::Legacy(_) => false,
+        MergedTreeId::Merge(tree_ids) => {
+            !tree_ids.is_resolved() && commit.conflict_labels.len() == tree_ids.as_slice().len()
+        }
+    }
+}
+
 pub trait CommitIteratorExt<'c, I> {
     fn ids(self) -> impl Iterator<Item = &'c CommitId>;
 }
@@ -24,6 +24,7 @@ use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::backend::MergedTreeId;
 use crate::backend::Signature;
+use crate::commit::has_conflict_labels;
 use crate::commit::is_backend_commit_empty;
 use crate::commit::Commit;
 use crate::repo::MutableRepo;
@@ -74,6 +75,17 @@ impl CommitBuilder<'_> {
         self
     }
 
+    pub fn conflict_labels(&self) -> &[String] {
+        self.inner.conflict_labels()
+    }
+
+    /// Sets the labels of the terms of the tree conflict. The labels are
+    /// dropped when writing the commit unless there's one label per term.
+    pub fn set_conflict_labels(mut self, labels: Vec<String>) -> Self {
+        self.inner.set_conflict_labels(labels);
+        self
+    }
+
     /// [`Commit::is_empty()`] for the new commit.
     pub fn is_empty(&self) -> BackendResult<bool> {
         self.inner.is_empty(self.mut_repo)
@@ -182,6 +194,7 @@ impl DetachedCommitBuilder {
             parents,
             predecessors: vec![],
             root_tree: tree_id,
+            conflict_labels: vec![],
             change_id,
             description: String::new(),
             author: signature.clone(),
@@ -276,6 +289,17 @@ impl DetachedCommitBuilder {
         self
     }
 
+    pub fn conflict_labels(&self) -> &[String] {
+        &self.commit.conflict_labels
+    }
+
+    /// Sets the labels of the terms of the tree conflict. The labels are
+    /// dropped when writing the commit unless there's one label per term.
+    pub fn set_conflict_labels(&mut self, labels: Vec<String>) -> &mut Self {
+        self.commit.conflict_labels = labels;
+        self
+    }
+
     /// [`Commit::is_empty()`] for the new commit.
     pub fn is_empty(&self, repo: &dyn Repo) -> BackendResult<bool> {
         is_backend_commit_empty(repo, &self.store, &self.commit)
@@ -350,6 +374,7 @@ impl DetachedCommitBuilder {
     pub fn write(self, mut_repo: &mut MutableRepo) -> BackendResult<Commit> {
         let commit = write_to_store(&self.store, self.commit, &self.sign_settings)?;
         mut_repo.add_head(&commit)?;
+        mut_repo.add_written_commit(commit.clone(), self.rewrite_source.clone());
         if let Some(rewrite_source) = self.rewrite_source {
             if rewrite_source.change_id() == commit.change_id() {
                 mut_repo.set_rewritten_commit(rewrite_source.id().clone(), commit.id().clone());
@@ -396,6 +421,11 @@ fn write_to_store(
     // if we're rewriting a signed commit
     commit.secure_sig = None;
 
+    // Labels are only meaningful for the terms of a conflict.
+    if !has_conflict_labels(&commit) {
+        commit.conflict_labels.clear();
+    }
+
     store
         .write_commit(commit, should_sign.then_some(&mut &sign_fn))
         .block_on()
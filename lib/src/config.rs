@@ -288,6 +288,8 @@ pub enum ConfigSource {
     User,
     /// Repo configuration files.
     Repo,
+    /// Workspace configuration files.
+    Workspace,
     /// Override environment variables.
     EnvOverrides,
     /// Command-line arguments (which has the highest precedence.)
@@ -301,6 +303,7 @@ impl Display for ConfigSource {
             Default => "default",
             User => "user",
             Repo => "repo",
+            Workspace => "workspace",
             CommandArg => "cli",
             EnvBase | EnvOverrides => "env",
         };
@@ -1078,6 +1081,10 @@ mod tests {
         ));
         config.add_layer(ConfigLayer::with_data(ConfigSource::EnvBase, empty_data()));
         config.add_layer(ConfigLayer::with_data(ConfigSource::User, empty_data()));
+        config.add_layer(ConfigLayer::with_data(
+            ConfigSource::Workspace,
+            empty_data(),
+        ));
         assert_eq!(
             layer_sources(&config),
             vec![
@@ -1086,6 +1093,7 @@ mod tests {
                 ConfigSource::User,
                 ConfigSource::User,
                 ConfigSource::Repo,
+                ConfigSource::Workspace,
                 ConfigSource::CommandArg,
             ]
         );
@@ -1094,6 +1102,7 @@ mod tests {
         config.remove_layers(ConfigSource::CommandArg);
         config.remove_layers(ConfigSource::Default);
         config.remove_layers(ConfigSource::User);
+        config.remove_layers(ConfigSource::Workspace);
         assert_eq!(
             layer_sources(&config),
             vec![ConfigSource::EnvBase, ConfigSource::Repo]
@@ -18,6 +18,7 @@ use std::io;
 use std::io::Read;
 use std::io::Write;
 use std::iter::zip;
+use std::ops::Range;
 
 use bstr::BString;
 use bstr::ByteSlice as _;
@@ -41,7 +42,9 @@ use crate::copies::CopiesTreeDiffEntryPath;
 use crate::diff::Diff;
 use crate::diff::DiffHunk;
 use crate::diff::DiffHunkKind;
+use crate::diff::DiffHunkRange;
 use crate::files;
+use crate::files::FileMergeOptions;
 use crate::files::MergeResult;
 use crate::merge::Merge;
 use crate::merge::MergeBuilder;
@@ -256,6 +259,14 @@ pub enum ConflictMarkerStyle {
     Snapshot,
     /// Style which replicates Git's "diff3" style to support external tools.
     Git,
+    /// Like the "git" style, but lines which all sides have in common at the
+    /// start and end of a conflict are moved out of it, like Git's "zdiff3"
+    /// style.
+    Zdiff3,
+    /// Like the "diff" style, but all lines which the sides have in common are
+    /// moved out of the conflicts, so only the smallest conflicting hunks are
+    /// shown.
+    Minimal,
 }
 
 /// Characters which can be repeated to form a conflict marker line when
@@ -357,41 +368,57 @@ pub fn choose_materialized_conflict_marker_len<T: AsRef<[u8]>>(single_hunk: &Mer
         .max(MIN_CONFLICT_MARKER_LEN)
 }
 
+/// Writes the merge of `single_hunk` to the `output`, with conflict markers
+/// around the conflicting hunks. The `merge_options` should match the ones used
+/// for parsing the markers back, which are the options of the store.
 pub fn materialize_merge_result<T: AsRef<[u8]>>(
     single_hunk: &Merge<T>,
+    merge_options: &FileMergeOptions,
     conflict_marker_style: ConflictMarkerStyle,
     output: &mut dyn Write,
 ) -> io::Result<()> {
-    let merge_result = files::merge(single_hunk);
+    let merge_result = files::merge_with_options(single_hunk, merge_options);
     match &merge_result {
         MergeResult::Resolved(content) => output.write_all(content),
         MergeResult::Conflict(hunks) => {
             let conflict_marker_len = choose_materialized_conflict_marker_len(single_hunk);
-            materialize_conflict_hunks(hunks, conflict_marker_style, conflict_marker_len, output)
+            materialize_conflict_hunks(
+                hunks,
+                None,
+                conflict_marker_style,
+                conflict_marker_len,
+                output,
+            )
         }
     }
 }
 
 pub fn materialize_merge_result_with_marker_len<T: AsRef<[u8]>>(
     single_hunk: &Merge<T>,
+    merge_options: &FileMergeOptions,
     conflict_marker_style: ConflictMarkerStyle,
     conflict_marker_len: usize,
     output: &mut dyn Write,
 ) -> io::Result<()> {
-    let merge_result = files::merge(single_hunk);
+    let merge_result = files::merge_with_options(single_hunk, merge_options);
     match &merge_result {
         MergeResult::Resolved(content) => output.write_all(content),
-        MergeResult::Conflict(hunks) => {
-            materialize_conflict_hunks(hunks, conflict_marker_style, conflict_marker_len, output)
-        }
+        MergeResult::Conflict(hunks) => materialize_conflict_hunks(
+            hunks,
+            None,
+            conflict_marker_style,
+            conflict_marker_len,
+            output,
+        ),
     }
 }
 
 pub fn materialize_merge_result_to_bytes<T: AsRef<[u8]>>(
     single_hunk: &Merge<T>,
+    merge_options: &FileMergeOptions,
     conflict_marker_style: ConflictMarkerStyle,
 ) -> BString {
-    let merge_result = files::merge(single_hunk);
+    let merge_result = files::merge_with_options(single_hunk, merge_options);
     match merge_result {
         MergeResult::Resolved(content) => content,
         MergeResult::Conflict(hunks) => {
@@ -399,6 +426,7 @@ pub fn materialize_merge_result_to_bytes<T: AsRef<[u8]>>(
             let mut output = Vec::new();
             materialize_conflict_hunks(
                 &hunks,
+                None,
                 conflict_marker_style,
                 conflict_marker_len,
                 &mut output,
@@ -411,16 +439,18 @@ pub fn materialize_merge_result_to_bytes<T: AsRef<[u8]>>(
 
 pub fn materialize_merge_result_to_bytes_with_marker_len<T: AsRef<[u8]>>(
     single_hunk: &Merge<T>,
+    merge_options: &FileMergeOptions,
     conflict_marker_style: ConflictMarkerStyle,
     conflict_marker_len: usize,
 ) -> BString {
-    let merge_result = files::merge(single_hunk);
+    let merge_result = files::merge_with_options(single_hunk, merge_options);
     match merge_result {
         MergeResult::Resolved(content) => content,
         MergeResult::Conflict(hunks) => {
             let mut output = Vec::new();
             materialize_conflict_hunks(
                 &hunks,
+                None,
                 conflict_marker_style,
                 conflict_marker_len,
                 &mut output,
@@ -431,12 +461,63 @@ pub fn materialize_merge_result_to_bytes_with_marker_len<T: AsRef<[u8]>>(
     }
 }
 
+/// Like [`materialize_merge_result_with_marker_len()`], but describes the terms
+/// of the conflict with `labels` in the conflict markers. `labels` must have
+/// one label per term of `single_hunk`.
+pub fn materialize_labeled_merge_result<T: AsRef<[u8]>>(
+    single_hunk: &Merge<T>,
+    merge_options: &FileMergeOptions,
+    labels: &Merge<String>,
+    conflict_marker_style: ConflictMarkerStyle,
+    conflict_marker_len: usize,
+    output: &mut dyn Write,
+) -> io::Result<()> {
+    assert_eq!(single_hunk.as_slice().len(), labels.as_slice().len());
+    let merge_result = files::merge_with_options(single_hunk, merge_options);
+    match &merge_result {
+        MergeResult::Resolved(content) => output.write_all(content),
+        MergeResult::Conflict(hunks) => materialize_conflict_hunks(
+            hunks,
+            Some(labels),
+            conflict_marker_style,
+            conflict_marker_len,
+            output,
+        ),
+    }
+}
+
+/// Returns the labels of the terms of the file conflict materialized from
+/// `value` by [`materialize_tree_value()`], given the `labels` of the terms of
+/// `value`. Returns `None` if `value` isn't a file conflict.
+pub fn file_conflict_labels(
+    value: &MergedTreeValue,
+    labels: &Merge<String>,
+) -> Option<Merge<String>> {
+    let file_merge = value.to_file_merge()?;
+    if file_merge.is_resolved() || file_merge.as_slice().len() != labels.as_slice().len() {
+        return None;
+    }
+    let (_, labels) = file_merge.simplify_with(labels.clone());
+    Some(labels)
+}
+
 fn materialize_conflict_hunks(
     hunks: &[Merge<BString>],
+    labels: Option<&Merge<String>>,
     conflict_marker_style: ConflictMarkerStyle,
     conflict_marker_len: usize,
     output: &mut dyn Write,
 ) -> io::Result<()> {
+    let split_hunks;
+    let hunks = match conflict_marker_style {
+        ConflictMarkerStyle::Zdiff3 | ConflictMarkerStyle::Minimal => {
+            split_hunks = split_conflict_hunks(hunks, conflict_marker_style);
+            &split_hunks[..]
+        }
+        ConflictMarkerStyle::Diff | ConflictMarkerStyle::Snapshot | ConflictMarkerStyle::Git => {
+            hunks
+        }
+    };
     let num_conflicts = hunks
         .iter()
         .filter(|hunk| hunk.as_resolved().is_none())
@@ -451,11 +532,12 @@ fn materialize_conflict_hunks(
 
             match (conflict_marker_style, hunk.as_slice()) {
                 // 2-sided conflicts can use Git-style conflict markers
-                (ConflictMarkerStyle::Git, [left, base, right]) => {
+                (ConflictMarkerStyle::Git | ConflictMarkerStyle::Zdiff3, [left, base, right]) => {
                     materialize_git_style_conflict(
                         left,
                         base,
                         right,
+                        labels,
                         &conflict_info,
                         conflict_marker_len,
                         output,
@@ -464,6 +546,7 @@ fn materialize_conflict_hunks(
                 _ => {
                     materialize_jj_style_conflict(
                         hunk,
+                        labels,
                         &conflict_info,
                         conflict_marker_style,
                         conflict_marker_len,
@@ -476,19 +559,138 @@ fn materialize_conflict_hunks(
     Ok(())
 }
 
+/// Moves the lines which all sides of a conflict have in common out of the
+/// conflict. For the "zdiff3" style, only the lines at the start and end of the
+/// conflict are moved. For the "minimal" style, the conflict is also split at
+/// the common lines in between.
+///
+/// The bases are split where they align with the first side. Since the moved
+/// lines are resolved, parsing the materialized conflict back includes them in
+/// every term, so the bases may differ from the original ones after the
+/// conflict is edited, but the result of the merge is the same.
+fn split_conflict_hunks(
+    hunks: &[Merge<BString>],
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Vec<Merge<BString>> {
+    let mut new_hunks = vec![];
+    for hunk in hunks {
+        // Conflicts at the end of a file without a trailing newline are kept
+        // whole so the missing newline can be restored when parsing.
+        if hunk.is_resolved() || hunk.iter().any(|term| has_no_eol(term)) {
+            new_hunks.push(hunk.clone());
+            continue;
+        }
+        let sides = hunk.adds().collect_vec();
+        let common_ranges = Diff::by_line(sides.iter().copied())
+            .hunk_ranges()
+            .filter(|range| range.kind == DiffHunkKind::Matching)
+            .map(|range| range.ranges)
+            .filter(|ranges| {
+                conflict_marker_style == ConflictMarkerStyle::Minimal
+                    || ranges.iter().all(|range| range.start == 0)
+                    || zip(ranges, &sides).all(|(range, side)| range.end == side.len())
+            })
+            .filter(|ranges| !ranges[0].is_empty())
+            .collect_vec();
+        if common_ranges.is_empty() {
+            new_hunks.push(hunk.clone());
+            continue;
+        }
+        let bases = hunk.removes().collect_vec();
+        let base_diffs = bases
+            .iter()
+            .map(|base| Diff::by_line([*base, sides[0]]).hunk_ranges().collect_vec())
+            .collect_vec();
+        let make_conflict = |side_ranges: Vec<Range<usize>>, base_ranges: Vec<Range<usize>>| {
+            let adds = zip(&sides, side_ranges).map(|(side, range)| BString::from(&side[range]));
+            let removes = zip(&bases, base_ranges).map(|(base, range)| BString::from(&base[range]));
+            let conflict = Merge::from_removes_adds(removes, adds);
+            // The sides may agree on the lines between the common lines, e.g.
+            // if they all removed the same lines from the bases.
+            match conflict.adds().all_equal_value() {
+                Ok(content) if content.is_empty() => None,
+                Ok(content) => Some(Merge::resolved(content.clone())),
+                Err(_) => Some(conflict),
+            }
+        };
+        let mut side_starts = vec![0; sides.len()];
+        let mut base_starts = vec![0; bases.len()];
+        for ranges in &common_ranges {
+            let common = &sides[0][ranges[0].clone()];
+            let base_ends = zip(&bases, &base_diffs)
+                .map(|(base, diff)| map_to_base_pos(diff, ranges[0].start, base.len()))
+                .collect_vec();
+            new_hunks.extend(make_conflict(
+                zip(&side_starts, ranges)
+                    .map(|(&start, range)| start..range.start)
+                    .collect(),
+                zip(&base_starts, &base_ends)
+                    .map(|(&start, &end)| start..end)
+                    .collect(),
+            ));
+            new_hunks.push(Merge::resolved(BString::from(common)));
+            side_starts = ranges.iter().map(|range| range.end).collect();
+            // Skip the common lines in the bases which have them too, so the
+            // bases are unchanged when the conflict is parsed back.
+            base_starts = zip(&bases, base_ends)
+                .map(|(base, end)| {
+                    if base[end..].starts_with(common) {
+                        end + common.len()
+                    } else {
+                        end
+                    }
+                })
+                .collect();
+        }
+        new_hunks.extend(make_conflict(
+            zip(&side_starts, &sides)
+                .map(|(&start, side)| start..side.len())
+                .collect(),
+            zip(&base_starts, &bases)
+                .map(|(&start, base)| start..base.len())
+                .collect(),
+        ));
+    }
+    new_hunks
+}
+
+/// Maps a position in the first side of a conflict to the position in a base,
+/// given the diff ranges between the base and the side.
+fn map_to_base_pos(diff_ranges: &[DiffHunkRange], side_pos: usize, base_len: usize) -> usize {
+    for range in diff_ranges {
+        let [base_range, side_range] = &range.ranges[..] else {
+            panic!("diff should have two inputs");
+        };
+        if side_pos <= side_range.end {
+            return match range.kind {
+                DiffHunkKind::Matching => base_range.start + (side_pos - side_range.start),
+                DiffHunkKind::Different if side_pos == side_range.start => base_range.start,
+                DiffHunkKind::Different => base_range.end,
+            };
+        }
+    }
+    base_len
+}
+
 fn materialize_git_style_conflict(
     left: &[u8],
     base: &[u8],
     right: &[u8],
+    labels: Option<&Merge<String>>,
     conflict_info: &str,
     conflict_marker_len: usize,
     output: &mut dyn Write,
 ) -> io::Result<()> {
+    // Like Git, use the labels in place of the names of the terms
+    let (left_name, base_name, right_name) = match labels.map(|labels| labels.as_slice()) {
+        Some([left, base, right]) => (left.as_str(), base.as_str(), right.as_str()),
+        _ => ("Side #1", "Base", "Side #2"),
+    };
     write_conflict_marker(
         output,
         ConflictMarkerLineChar::ConflictStart,
         conflict_marker_len,
-        &format!("Side #1 ({conflict_info})"),
+        &format!("{left_name} ({conflict_info})"),
     )?;
     write_and_ensure_newline(output, left)?;
 
@@ -496,7 +698,7 @@ fn materialize_git_style_conflict(
         output,
         ConflictMarkerLineChar::GitAncestor,
         conflict_marker_len,
-        "Base",
+        base_name,
     )?;
     write_and_ensure_newline(output, base)?;
 
@@ -513,7 +715,7 @@ fn materialize_git_style_conflict(
         output,
         ConflictMarkerLineChar::ConflictEnd,
         conflict_marker_len,
-        &format!("Side #2 ({conflict_info} ends)"),
+        &format!("{right_name} ({conflict_info} ends)"),
     )?;
 
     Ok(())
@@ -521,11 +723,17 @@ fn materialize_git_style_conflict(
 
 fn materialize_jj_style_conflict(
     hunk: &Merge<BString>,
+    labels: Option<&Merge<String>>,
     conflict_info: &str,
     conflict_marker_style: ConflictMarkerStyle,
     conflict_marker_len: usize,
     output: &mut dyn Write,
 ) -> io::Result<()> {
+    let side_name = |add_index: usize| match labels.and_then(|labels| labels.get_add(add_index)) {
+        Some(label) => format!("side #{} ({label})", add_index + 1),
+        None => format!("side #{}", add_index + 1),
+    };
+
     // Write a positive snapshot (side) of a conflict
     let write_side = |add_index: usize, data: &[u8], output: &mut dyn Write| {
         write_conflict_marker(
@@ -533,8 +741,8 @@ fn materialize_jj_style_conflict(
             ConflictMarkerLineChar::Add,
             conflict_marker_len,
             &format!(
-                "Contents of side #{}{}",
-                add_index + 1,
+                "Contents of {}{}",
+                side_name(add_index),
                 maybe_no_eol_comment(data)
             ),
         )?;
@@ -572,8 +780,8 @@ fn materialize_jj_style_conflict(
                 ConflictMarkerLineChar::Diff,
                 conflict_marker_len,
                 &format!(
-                    "Changes from {base_str} to side #{}{no_eol_comment}",
-                    add_index + 1
+                    "Changes from {base_str} to {}{no_eol_comment}",
+                    side_name(add_index)
                 ),
             )?;
             write_diff_hunks(diff, output)
@@ -594,6 +802,10 @@ fn materialize_jj_style_conflict(
         } else {
             format!("base #{}", base_index + 1)
         };
+        let base_str = match labels.and_then(|labels| labels.get_remove(base_index)) {
+            Some(label) => format!("{base_str} ({label})"),
+            None => base_str,
+        };
 
         let Some(right1) = hunk.get_add(add_index) else {
             // If we have no more positive terms, emit the remaining negative terms as
@@ -602,8 +814,12 @@ fn materialize_jj_style_conflict(
             continue;
         };
 
-        // For any style other than "diff", always emit sides and bases separately
-        if conflict_marker_style != ConflictMarkerStyle::Diff {
+        // For any style other than "diff" and "minimal", always emit sides and
+        // bases separately
+        if !matches!(
+            conflict_marker_style,
+            ConflictMarkerStyle::Diff | ConflictMarkerStyle::Minimal
+        ) {
             write_side(add_index, right1, output)?;
             write_base(&base_str, left, output)?;
             add_index += 1;
@@ -762,6 +978,88 @@ pub fn parse_conflict(
     }
 }
 
+/// Reason why conflict markers couldn't be parsed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UnparsedConflictReason {
+    /// A conflict start marker has no matching end marker.
+    MissingEnd,
+    /// A conflict end marker has no matching start marker.
+    MissingStart,
+    /// The markers between the start and end markers are malformed, or the
+    /// conflict doesn't have the expected number of sides.
+    Malformed,
+}
+
+/// Range of lines with conflict markers which couldn't be parsed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnparsedConflictHunk {
+    /// 1-based line number of the first marker.
+    pub first_line: usize,
+    /// 1-based line number of the last marker (inclusive).
+    pub last_line: usize,
+    /// Why the markers couldn't be parsed.
+    pub reason: UnparsedConflictReason,
+}
+
+/// Finds conflict markers which `parse_conflict()` would leave in the resolved
+/// contents because they are partially edited or malformed.
+///
+/// Only conflict start and end markers are considered outside of conflict
+/// hunks, since lines like `=======` are common in regular text files.
+pub fn find_unparsed_conflict_hunks(
+    input: &[u8],
+    num_sides: usize,
+    expected_marker_len: usize,
+) -> Vec<UnparsedConflictHunk> {
+    let mut unparsed = vec![];
+    let mut pos = 0;
+    // (line number, byte offset after the start marker)
+    let mut conflict_start: Option<(usize, usize)> = None;
+    for (line_num, line) in (1..).zip(input.lines_with_terminator()) {
+        match parse_conflict_marker(line, expected_marker_len) {
+            Some(ConflictMarkerLineChar::ConflictStart) => {
+                if let Some((start_line, _)) = conflict_start {
+                    unparsed.push(UnparsedConflictHunk {
+                        first_line: start_line,
+                        last_line: start_line,
+                        reason: UnparsedConflictReason::MissingEnd,
+                    });
+                }
+                conflict_start = Some((line_num, pos + line.len()));
+            }
+            Some(ConflictMarkerLineChar::ConflictEnd) => match conflict_start.take() {
+                Some((start_line, body_start)) => {
+                    let hunk = parse_conflict_hunk(&input[body_start..pos], expected_marker_len);
+                    if hunk.num_sides() != num_sides {
+                        unparsed.push(UnparsedConflictHunk {
+                            first_line: start_line,
+                            last_line: line_num,
+                            reason: UnparsedConflictReason::Malformed,
+                        });
+                    }
+                }
+                None => {
+                    unparsed.push(UnparsedConflictHunk {
+                        first_line: line_num,
+                        last_line: line_num,
+                        reason: UnparsedConflictReason::MissingStart,
+                    });
+                }
+            },
+            _ => {}
+        }
+        pos += line.len();
+    }
+    if let Some((start_line, _)) = conflict_start {
+        unparsed.push(UnparsedConflictHunk {
+            first_line: start_line,
+            last_line: start_line,
+            reason: UnparsedConflictReason::MissingEnd,
+        });
+    }
+    unparsed
+}
+
 /// This method handles parsing both JJ-style and Git-style conflict markers,
 /// meaning that switching conflict marker styles won't prevent existing files
 /// with other conflict marker styles from being parsed successfully. The
@@ -932,6 +1230,7 @@ pub async fn update_from_content(
     let merge_hunk = extract_as_single_hunk(&simplified_file_ids, store, path).await?;
     materialize_merge_result_with_marker_len(
         &merge_hunk,
+        &store.merge_options().file_merge_options,
         conflict_marker_style,
         conflict_marker_len,
         &mut old_content,
@@ -964,6 +1263,19 @@ pub async fn update_from_content(
         }
     }
 
+    // If the user edited the empty placeholder for an absent side, we consider the
+    // conflict resolved. Only the conflicting hunks are checked since resolved
+    // hunks are included in every term, e.g. the lines moved out of the
+    // conflicts by the "zdiff3" style.
+    let edited_absent_side = hunks.iter().filter(|hunk| !hunk.is_resolved()).any(|hunk| {
+        zip(hunk.iter(), simplified_file_ids.iter())
+            .any(|(content, file_id)| file_id.is_none() && !content.is_empty())
+    });
+    if edited_absent_side {
+        let file_id = store.write_file(path, &mut &content[..]).await?;
+        return Ok(Merge::normal(file_id));
+    }
+
     let mut contents = simplified_file_ids.map(|_| vec![]);
     for hunk in hunks {
         if let Some(slice) = hunk.as_resolved() {
@@ -977,15 +1289,6 @@ pub async fn update_from_content(
         }
     }
 
-    // If the user edited the empty placeholder for an absent side, we consider the
-    // conflict resolved.
-    if zip(contents.iter(), simplified_file_ids.iter())
-        .any(|(content, file_id)| file_id.is_none() && !content.is_empty())
-    {
-        let file_id = store.write_file(path, &mut &content[..]).await?;
-        return Ok(Merge::normal(file_id));
-    }
-
     // Now write the new files contents we found by parsing the file with conflict
     // markers.
     // TODO: Write these concurrently
@@ -15,7 +15,9 @@ pub use jj_lib_proc_macros::ContentHash;
 /// hash a 32-bit little-endian encoding of the ordinal number of the enum
 /// variant, then the variant's fields in lexical order.
 ///
-/// Structs can implement `ContentHash` by using `#[derive(ContentHash)]`.
+/// Structs can implement `ContentHash` by using `#[derive(ContentHash)]`. Fields
+/// added later can be marked as `#[content_hash(skip_if_default)]` so the
+/// existing hashes are preserved.
 pub trait ContentHash {
     /// Update the hasher state with this object's content
     fn hash(&self, state: &mut impl DigestUpdate);
@@ -245,6 +247,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_skip_if_default() {
+        #[derive(ContentHash)]
+        struct Foo {
+            x: i32,
+        }
+        #[derive(ContentHash)]
+        struct FooWithY {
+            x: i32,
+            #[content_hash(skip_if_default)]
+            y: Option<i32>,
+        }
+        assert_eq!(hash(&FooWithY { x: 42, y: None }), hash(&Foo { x: 42 }));
+        assert_ne!(hash(&FooWithY { x: 42, y: Some(0) }), hash(&Foo { x: 42 }));
+    }
+
     // Test that the derived version of `ContentHash` matches the that's
     // manually implemented for `std::Option`.
     #[test]
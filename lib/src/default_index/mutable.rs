@@ -47,6 +47,7 @@ use super::readonly::OVERFLOW_FLAG;
 use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::commit::Commit;
+use crate::dag_walk;
 use crate::file_util::persist_content_addressed_temp_file;
 use crate::index::AllHeadsForGcUnsupported;
 use crate::index::ChangeIdIndex;
@@ -166,6 +167,43 @@ impl MutableIndexSegment {
         }
     }
 
+    /// Rebuilds this segment from scratch, replacing the parents of the
+    /// commits in `new_parents`. The new parents must be indexed.
+    pub(super) fn reparent_commits(&mut self, new_parents: &HashMap<CommitId, Vec<CommitId>>) {
+        let index = self.as_composite();
+        let entries = (0..index.num_commits())
+            .map(|pos| {
+                let entry = index.entry_by_pos(IndexPosition(pos));
+                let commit_id = entry.commit_id();
+                let parent_ids = match new_parents.get(&commit_id) {
+                    Some(parent_ids) => parent_ids.clone(),
+                    None => entry.parents().map(|entry| entry.commit_id()).collect(),
+                };
+                (commit_id, entry.change_id(), parent_ids)
+            })
+            .collect_vec();
+        let positions: HashMap<&CommitId, usize> = entries
+            .iter()
+            .enumerate()
+            .map(|(pos, (commit_id, _, _))| (commit_id, pos))
+            .collect();
+        // Parents of the reparented commits may have been indexed after them.
+        let sorted_positions = dag_walk::topo_order_forward(
+            0..entries.len(),
+            |&pos| pos,
+            |&pos| {
+                let (_, _, parent_ids) = &entries[pos];
+                parent_ids.iter().map(|id| positions[id]).collect_vec()
+            },
+        );
+        let mut segment = MutableIndexSegment::full(self.commit_id_length, self.change_id_length);
+        for pos in sorted_positions {
+            let (commit_id, change_id, parent_ids) = &entries[pos];
+            segment.add_commit_data(commit_id.clone(), change_id.clone(), parent_ids);
+        }
+        *self = segment;
+    }
+
     pub(super) fn merge_in(&mut self, other: Arc<ReadonlyIndexSegment>) {
         let mut maybe_own_ancestor = self.parent_file.clone();
         let mut maybe_other_ancestor = Some(other);
@@ -558,6 +596,14 @@ impl MutableIndex for DefaultMutableIndex {
         self.0.add_commit(commit);
     }
 
+    fn reparent_commits(&mut self, commits: &[Commit]) {
+        let new_parents = commits
+            .iter()
+            .map(|commit| (commit.id().clone(), commit.parent_ids().to_vec()))
+            .collect();
+        self.0.reparent_commits(&new_parents);
+    }
+
     fn merge_in(&mut self, other: &dyn ReadonlyIndex) {
         let other = other
             .as_any()
@@ -60,6 +60,7 @@ use crate::revset::Revset;
 use crate::revset::RevsetContainingFn;
 use crate::revset::RevsetEvaluationError;
 use crate::revset::RevsetFilterPredicate;
+use crate::revset::SignatureField;
 use crate::revset::GENERATION_RANGE_FULL;
 use crate::rewrite;
 use crate::store::Store;
@@ -1220,6 +1221,35 @@ fn build_predicate_fn(
                 Ok(expression.matches(committer_date))
             })
         }
+        RevsetFilterPredicate::MappedSignature {
+            field,
+            pattern,
+            mailmap,
+        } => {
+            let field = *field;
+            let pattern = pattern.clone();
+            let mailmap = mailmap.clone();
+            box_pure_predicate_fn(move |index, pos| {
+                let entry = index.entry_by_pos(pos);
+                let commit = store.get_commit(&entry.commit_id())?;
+                let (name, email) = match field {
+                    SignatureField::AuthorName | SignatureField::AuthorEmail => {
+                        mailmap.resolve(&commit.author().name, &commit.author().email)
+                    }
+                    SignatureField::CommitterName | SignatureField::CommitterEmail => {
+                        mailmap.resolve(&commit.committer().name, &commit.committer().email)
+                    }
+                };
+                Ok(match field {
+                    SignatureField::AuthorName | SignatureField::CommitterName => {
+                        pattern.matches(name)
+                    }
+                    SignatureField::AuthorEmail | SignatureField::CommitterEmail => {
+                        pattern.matches(email)
+                    }
+                })
+            })
+        }
         RevsetFilterPredicate::File(expr) => {
             let matcher: Rc<dyn Matcher> = expr.to_matcher().into();
             box_pure_predicate_fn(move |index, pos| {
@@ -1248,6 +1278,15 @@ fn build_predicate_fn(
             let commit = store.get_commit(&entry.commit_id())?;
             Ok(commit.has_conflict()?)
         }),
+        RevsetFilterPredicate::HasConflictIn { files, new_only } => {
+            let matcher: Rc<dyn Matcher> = files.to_matcher().into();
+            let new_only = *new_only;
+            box_pure_predicate_fn(move |index, pos| {
+                let entry = index.entry_by_pos(pos);
+                let commit = store.get_commit(&entry.commit_id())?;
+                Ok(has_conflict_in(&commit, &*matcher, new_only)?)
+            })
+        }
         RevsetFilterPredicate::Extension(ext) => {
             let ext = ext.clone();
             box_pure_predicate_fn(move |index, pos| {
@@ -1296,6 +1335,39 @@ fn has_diff_from_parent(
     .block_on()
 }
 
+/// Whether the `commit` has conflicts at the paths matched by the `matcher`.
+/// If `new_only` is set, conflicts at paths where any of the parents is also
+/// conflicted are ignored.
+fn has_conflict_in(commit: &Commit, matcher: &dyn Matcher, new_only: bool) -> BackendResult<bool> {
+    if !commit.has_conflict()? {
+        return Ok(false);
+    }
+    let tree = commit.tree()?;
+    let mut conflicts = tree.conflicts().filter(|(path, _)| matcher.matches(path));
+    if !new_only {
+        return Ok(conflicts.next().is_some());
+    }
+
+    // A conflict is new unless it was inherited from one of the parents. The
+    // parents are checked one by one since their merge may conflict even if
+    // none of them does, e.g. in a merge commit.
+    let parent_trees: Vec<_> = commit
+        .parents()
+        .map(|parent| parent?.tree())
+        .try_collect()?;
+    for (path, value) in conflicts {
+        value?;
+        let inherited = parent_trees
+            .iter()
+            .map(|tree| tree.path_value(&path))
+            .process_results(|mut values| values.any(|value| !value.is_resolved()))?;
+        if !inherited {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
 fn matches_diff_from_parent(
     store: &Arc<Store>,
     index: &CompositeIndex,
@@ -1322,8 +1394,8 @@ fn matches_diff_from_parent(
             let left_future = materialize_tree_value(store, &entry.path, left_value);
             let right_future = materialize_tree_value(store, &entry.path, right_value);
             let (left_value, right_value) = futures::try_join!(left_future, right_future)?;
-            let left_content = to_file_content(&entry.path, left_value)?;
-            let right_content = to_file_content(&entry.path, right_value)?;
+            let left_content = to_file_content(store, &entry.path, left_value)?;
+            let right_content = to_file_content(store, &entry.path, right_value)?;
             // Filter lines prior to comparison. This might produce inferior
             // hunks due to lack of contexts, but is way faster than full diff.
             let left_lines = match_lines(&left_content, text_pattern);
@@ -1350,7 +1422,11 @@ fn match_lines<'a, 'b>(
     })
 }
 
-fn to_file_content(path: &RepoPath, value: MaterializedTreeValue) -> BackendResult<Vec<u8>> {
+fn to_file_content(
+    store: &Store,
+    path: &RepoPath,
+    value: MaterializedTreeValue,
+) -> BackendResult<Vec<u8>> {
     match value {
         MaterializedTreeValue::Absent => Ok(vec![]),
         MaterializedTreeValue::AccessDenied(_) => Ok(vec![]),
@@ -1358,7 +1434,13 @@ fn to_file_content(path: &RepoPath, value: MaterializedTreeValue) -> BackendResu
         MaterializedTreeValue::Symlink { id: _, target } => Ok(target.into_bytes()),
         MaterializedTreeValue::GitSubmodule(_) => Ok(vec![]),
         MaterializedTreeValue::FileConflict { contents, .. } => {
-            Ok(materialize_merge_result_to_bytes(&contents, ConflictMarkerStyle::default()).into())
+            let merge_options = &store.merge_options().file_merge_options;
+            Ok(materialize_merge_result_to_bytes(
+                &contents,
+                merge_options,
+                ConflictMarkerStyle::default(),
+            )
+            .into())
         }
         MaterializedTreeValue::OtherConflict { .. } => Ok(vec![]),
         MaterializedTreeValue::Tree(id) => {
@@ -0,0 +1,233 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Line ending conversion driven by the `text` and `eol` Git attributes.
+
+use std::borrow::Cow;
+
+use crate::gitattributes::AttributeState;
+use crate::gitattributes::GitAttributesFile;
+use crate::repo_path::RepoPath;
+
+/// Number of bytes to look at when guessing if the contents are binary. This
+/// is the same heuristic as Git's.
+const PEEK_SIZE: usize = 8000;
+
+/// Line ending used in the working copy.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    Crlf,
+}
+
+impl LineEnding {
+    /// Returns the line ending native to the current platform.
+    pub fn native() -> Self {
+        if cfg!(windows) {
+            LineEnding::Crlf
+        } else {
+            LineEnding::Lf
+        }
+    }
+}
+
+/// Conversion between the normalized (LF) contents in the store and the
+/// contents in the working copy.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EolConversion {
+    /// Line ending to use in the working copy.
+    pub line_ending: LineEnding,
+    /// If true, contents that look binary are left alone (`text=auto`).
+    pub auto: bool,
+}
+
+impl EolConversion {
+    /// Determines the conversion for the file at `path`, or returns `None` if
+    /// the file shouldn't be converted.
+    ///
+    /// As in Git, `-text` disables the conversion, and setting `eol` implies
+    /// `text` unless `text` is specified otherwise.
+    pub fn from_attributes(attributes: &GitAttributesFile, path: &RepoPath) -> Option<Self> {
+        let path = path.as_internal_file_string();
+        let eol = attributes.get(path, "eol");
+        let auto = match attributes.get(path, "text") {
+            AttributeState::Set => false,
+            AttributeState::Unset => return None,
+            AttributeState::Value(value) if value == "auto" => true,
+            AttributeState::Value(_) => return None,
+            AttributeState::Unspecified => {
+                if eol == AttributeState::Unspecified {
+                    return None;
+                }
+                false
+            }
+        };
+        let line_ending = if eol.is_value("crlf") {
+            LineEnding::Crlf
+        } else if eol.is_value("lf") {
+            LineEnding::Lf
+        } else {
+            LineEnding::native()
+        };
+        Some(EolConversion { line_ending, auto })
+    }
+
+    /// Converts working-copy contents to be written to the store.
+    pub fn convert_to_store<'a>(&self, contents: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.skips(contents) || !contents.contains(&b'\r') {
+            return Cow::Borrowed(contents);
+        }
+        let mut converted = Vec::with_capacity(contents.len());
+        let mut rest = contents;
+        while let Some(pos) = rest.windows(2).position(|pair| pair == b"\r\n") {
+            converted.extend_from_slice(&rest[..pos]);
+            converted.push(b'\n');
+            rest = &rest[pos + 2..];
+        }
+        converted.extend_from_slice(rest);
+        Cow::Owned(converted)
+    }
+
+    /// Converts contents read from the store to be written to the working
+    /// copy.
+    pub fn convert_to_working_copy<'a>(&self, contents: &'a [u8]) -> Cow<'a, [u8]> {
+        if self.line_ending == LineEnding::Lf || self.skips(contents) {
+            return Cow::Borrowed(contents);
+        }
+        let mut converted = Vec::with_capacity(contents.len() + contents.len() / 16);
+        let mut prev = None;
+        for &b in contents {
+            if b == b'\n' && prev != Some(b'\r') {
+                converted.push(b'\r');
+            }
+            converted.push(b);
+            prev = Some(b);
+        }
+        Cow::Owned(converted)
+    }
+
+    fn skips(&self, contents: &[u8]) -> bool {
+        self.auto && contents[..PEEK_SIZE.min(contents.len())].contains(&b'\0')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn conversion(input: &[u8], path: &str) -> Option<EolConversion> {
+        let attributes = GitAttributesFile::empty()
+            .chain("", Path::new(""), input)
+            .unwrap();
+        EolConversion::from_attributes(&attributes, RepoPath::from_internal_string(path))
+    }
+
+    #[test]
+    fn test_eol_conversion_from_attributes() {
+        let input = b"*.txt text\n*.bat eol=crlf\n*.sh text eol=lf\n*.bin binary\n* text=auto\n";
+        assert_eq!(
+            conversion(input, "a.txt"),
+            Some(EolConversion {
+                line_ending: LineEnding::native(),
+                auto: true,
+            })
+        );
+        let input = b"* text=auto\n*.txt text\n*.bat eol=crlf\n*.sh text eol=lf\n*.bin binary\n";
+        assert_eq!(
+            conversion(input, "a.txt"),
+            Some(EolConversion {
+                line_ending: LineEnding::native(),
+                auto: false,
+            })
+        );
+        assert_eq!(
+            conversion(input, "a.bat"),
+            Some(EolConversion {
+                line_ending: LineEnding::Crlf,
+                auto: true,
+            })
+        );
+        assert_eq!(
+            conversion(input, "a.sh"),
+            Some(EolConversion {
+                line_ending: LineEnding::Lf,
+                auto: false,
+            })
+        );
+        assert_eq!(conversion(input, "a.bin"), None);
+        assert!(!conversion(b"*.bat eol=crlf\n", "a.bat").unwrap().auto);
+        assert_eq!(conversion(b"*.bat eol=crlf\n", "a.txt"), None);
+        assert_eq!(conversion(b"*.txt -text eol=crlf\n", "a.txt"), None);
+    }
+
+    #[test]
+    fn test_eol_conversion_crlf() {
+        let conversion = EolConversion {
+            line_ending: LineEnding::Crlf,
+            auto: false,
+        };
+        assert_eq!(
+            conversion.convert_to_working_copy(b"a\nb\r\nc"),
+            b"a\r\nb\r\nc".as_slice()
+        );
+        assert_eq!(
+            conversion.convert_to_store(b"a\r\nb\nc\r"),
+            b"a\nb\nc\r".as_slice()
+        );
+        assert!(matches!(
+            conversion.convert_to_store(b"a\nb\n"),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_eol_conversion_lf() {
+        let conversion = EolConversion {
+            line_ending: LineEnding::Lf,
+            auto: false,
+        };
+        assert_eq!(
+            conversion.convert_to_working_copy(b"a\nb\n"),
+            b"a\nb\n".as_slice()
+        );
+        assert_eq!(
+            conversion.convert_to_store(b"a\r\nb\r\n"),
+            b"a\nb\n".as_slice()
+        );
+    }
+
+    #[test]
+    fn test_eol_conversion_auto_skips_binary() {
+        let conversion = EolConversion {
+            line_ending: LineEnding::Crlf,
+            auto: true,
+        };
+        assert_eq!(
+            conversion.convert_to_working_copy(b"a\0\nb\n"),
+            b"a\0\nb\n".as_slice()
+        );
+        assert_eq!(
+            conversion.convert_to_store(b"a\0\r\nb\r\n"),
+            b"a\0\r\nb\r\n".as_slice()
+        );
+        assert_eq!(
+            conversion.convert_to_working_copy(b"a\nb\n"),
+            b"a\r\nb\r\n".as_slice()
+        );
+    }
+}
@@ -23,6 +23,9 @@ use bstr::BStr;
 use bstr::BString;
 use itertools::Itertools as _;
 
+use crate::diff::CompareBytes;
+use crate::diff::CompareBytesIgnoreAllWhitespace;
+use crate::diff::CompareBytesIgnoreWhitespaceAmount;
 use crate::diff::Diff;
 use crate::diff::DiffHunk;
 use crate::diff::DiffHunkKind;
@@ -193,13 +196,179 @@ pub enum MergeResult {
     Conflict(Vec<Merge<BString>>),
 }
 
+/// Granularity of hunks when merging files.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileMergeHunkLevel {
+    /// Merges line by line.
+    #[default]
+    Line,
+    /// Merges line by line, then merges the conflicting line hunks again word
+    /// by word.
+    Word,
+}
+
+/// How whitespace is compared when resolving conflicting hunks.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileMergeWhitespace {
+    /// Only resolves hunks whose sides are the same.
+    #[default]
+    Exact,
+    /// Also resolves hunks whose sides only differ in whitespace.
+    IgnoreAllSpace,
+    /// Also resolves hunks whose sides only differ in the amount of
+    /// whitespace.
+    IgnoreSpaceChange,
+}
+
+/// Side of a conflicting hunk to take when its sides are considered equal.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileMergeSide {
+    /// The first side. When rebasing, that's the destination.
+    #[default]
+    Ours,
+    /// The last side. When rebasing, that's the commit being rebased.
+    Theirs,
+}
+
+impl FileMergeSide {
+    /// Returns the term of the `merge` for this side.
+    pub fn select<T>(self, merge: &Merge<T>) -> &T {
+        match self {
+            FileMergeSide::Ours => merge.first(),
+            FileMergeSide::Theirs => merge.adds().last().unwrap(),
+        }
+    }
+}
+
+/// Options for merging file contents.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FileMergeOptions {
+    /// Granularity of the hunks.
+    pub hunk_level: FileMergeHunkLevel,
+    /// How whitespace is compared when resolving conflicting hunks.
+    pub whitespace: FileMergeWhitespace,
+    /// Side to take when the sides of a conflicting hunk only differ in
+    /// whitespace.
+    pub whitespace_side: FileMergeSide,
+}
+
 pub fn merge<T: AsRef<[u8]>>(slices: &Merge<T>) -> MergeResult {
+    merge_with_hunk_level(slices, FileMergeHunkLevel::Line)
+}
+
+/// Merges `slices` line by line. With [`FileMergeHunkLevel::Word`], the hunks
+/// which conflict at line level are merged again word by word, and are only
+/// reported as conflicts if that fails too.
+pub fn merge_with_hunk_level<T: AsRef<[u8]>>(
+    slices: &Merge<T>,
+    hunk_level: FileMergeHunkLevel,
+) -> MergeResult {
+    let options = FileMergeOptions {
+        hunk_level,
+        ..Default::default()
+    };
+    merge_with_options(slices, &options)
+}
+
+/// Merges `slices` like [`merge_with_hunk_level()`]. Then the remaining
+/// conflicting hunks whose sides only differ in whitespace are resolved by
+/// taking one of their sides, unless whitespace is compared exactly.
+pub fn merge_with_options<T: AsRef<[u8]>>(
+    slices: &Merge<T>,
+    options: &FileMergeOptions,
+) -> MergeResult {
     // TODO: Using the first remove as base (first in the inputs) is how it's
     // usually done for 3-way conflicts. Are there better heuristics when there are
     // more than 3 parts?
     let num_diffs = slices.removes().len();
     let diff_inputs = slices.removes().chain(slices.adds());
-    merge_hunks(&Diff::by_line(diff_inputs), num_diffs)
+    let result = match merge_hunks(&Diff::by_line(diff_inputs), num_diffs) {
+        MergeResult::Conflict(hunks) if options.hunk_level == FileMergeHunkLevel::Word => {
+            merge_conflicting_hunks_by_word(hunks)
+        }
+        result => result,
+    };
+    match (result, options.whitespace) {
+        (MergeResult::Conflict(hunks), FileMergeWhitespace::IgnoreAllSpace) => {
+            resolve_hunks_equal_by(
+                hunks,
+                CompareBytesIgnoreAllWhitespace,
+                options.whitespace_side,
+            )
+        }
+        (MergeResult::Conflict(hunks), FileMergeWhitespace::IgnoreSpaceChange) => {
+            resolve_hunks_equal_by(
+                hunks,
+                CompareBytesIgnoreWhitespaceAmount,
+                options.whitespace_side,
+            )
+        }
+        (result, _) => result,
+    }
+}
+
+fn merge_conflicting_hunks_by_word(hunks: Vec<Merge<BString>>) -> MergeResult {
+    resolve_conflicting_hunks(hunks, |hunk| {
+        let num_diffs = hunk.removes().len();
+        let diff_inputs = hunk.removes().chain(hunk.adds());
+        match merge_hunks(&Diff::by_word(diff_inputs), num_diffs) {
+            MergeResult::Resolved(content) => Some(content),
+            MergeResult::Conflict(_) => None,
+        }
+    })
+}
+
+/// Resolves the conflicting hunks whose sides are all equal according to
+/// `compare` by taking the given `side`.
+fn resolve_hunks_equal_by(
+    hunks: Vec<Merge<BString>>,
+    compare: impl CompareBytes,
+    side: FileMergeSide,
+) -> MergeResult {
+    resolve_conflicting_hunks(hunks, |hunk| {
+        let first = hunk.first();
+        if !hunk.adds().all(|add| compare.eq(first, add)) {
+            return None;
+        }
+        Some(side.select(hunk).clone())
+    })
+}
+
+/// Resolves the conflicting `hunks` which `resolve` returns content for, and
+/// joins adjacent resolved hunks.
+fn resolve_conflicting_hunks(
+    hunks: Vec<Merge<BString>>,
+    mut resolve: impl FnMut(&Merge<BString>) -> Option<BString>,
+) -> MergeResult {
+    let mut resolved_hunk = BString::new(vec![]);
+    let mut new_hunks: Vec<Merge<BString>> = vec![];
+    for hunk in hunks {
+        let resolved = if let Some(content) = hunk.as_resolved() {
+            Some(content.clone())
+        } else {
+            resolve(&hunk)
+        };
+        if let Some(content) = resolved {
+            resolved_hunk.extend_from_slice(&content);
+        } else {
+            if !resolved_hunk.is_empty() {
+                new_hunks.push(Merge::resolved(mem::take(&mut resolved_hunk)));
+            }
+            new_hunks.push(hunk);
+        }
+    }
+
+    if new_hunks.is_empty() {
+        MergeResult::Resolved(resolved_hunk)
+    } else {
+        if !resolved_hunk.is_empty() {
+            new_hunks.push(Merge::resolved(resolved_hunk));
+        }
+        MergeResult::Conflict(new_hunks)
+    }
 }
 
 fn merge_hunks(diff: &Diff, num_diffs: usize) -> MergeResult {
@@ -440,6 +609,134 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_merge_by_word() {
+        let merge_by_word = |removes: &[&[u8]], adds: &[&[u8]]| {
+            merge_with_hunk_level(
+                &Merge::from_removes_adds(removes, adds),
+                FileMergeHunkLevel::Word,
+            )
+        };
+        // Two sides changed different words on the same line
+        assert_eq!(
+            merge_by_word(
+                &[b"a\nf(x, y)\nb\n"],
+                &[b"a\ng(x, y)\nb\n", b"a\nf(x, y, z)\nb\n"]
+            ),
+            MergeResult::Resolved(hunk(b"a\ng(x, y, z)\nb\n"))
+        );
+        // The line-level merge reports a conflict
+        assert_eq!(
+            merge(
+                &[b"a\nf(x, y)\nb\n"],
+                &[b"a\ng(x, y)\nb\n", b"a\nf(x, y, z)\nb\n"]
+            ),
+            MergeResult::Conflict(vec![
+                Merge::resolved(hunk(b"a\n")),
+                Merge::from_removes_adds(
+                    vec![hunk(b"f(x, y)\n")],
+                    vec![hunk(b"g(x, y)\n"), hunk(b"f(x, y, z)\n")]
+                ),
+                Merge::resolved(hunk(b"b\n"))
+            ])
+        );
+        // Two sides changed the same word differently, while another line
+        // hunk could be resolved
+        assert_eq!(
+            merge_by_word(
+                &[b"f(x)\na\ng(y)\n"],
+                &[b"f(x1)\na\nh(y)\n", b"f(x2)\na\ng(y, z)\n"]
+            ),
+            MergeResult::Conflict(vec![
+                Merge::from_removes_adds(
+                    vec![hunk(b"f(x)\n")],
+                    vec![hunk(b"f(x1)\n"), hunk(b"f(x2)\n")]
+                ),
+                Merge::resolved(hunk(b"a\nh(y, z)\n"))
+            ])
+        );
+    }
+
+    #[test]
+    fn test_merge_ignoring_whitespace() {
+        let merge_with = |whitespace, whitespace_side, removes: &[&[u8]], adds: &[&[u8]]| {
+            let options = FileMergeOptions {
+                hunk_level: FileMergeHunkLevel::Line,
+                whitespace,
+                whitespace_side,
+            };
+            merge_with_options(&Merge::from_removes_adds(removes, adds), &options)
+        };
+        // Both sides reformatted the same line differently
+        let removes: &[&[u8]] = &[b"a\nf(x,y)\nb\n"];
+        let adds: &[&[u8]] = &[b"a\nf(x, y)\nb\n", b"a\nf( x,  y )\nb\n"];
+        assert_eq!(
+            merge_with(
+                FileMergeWhitespace::IgnoreAllSpace,
+                FileMergeSide::Ours,
+                removes,
+                adds
+            ),
+            MergeResult::Resolved(hunk(b"a\nf(x, y)\nb\n"))
+        );
+        assert_eq!(
+            merge_with(
+                FileMergeWhitespace::IgnoreAllSpace,
+                FileMergeSide::Theirs,
+                removes,
+                adds
+            ),
+            MergeResult::Resolved(hunk(b"a\nf( x,  y )\nb\n"))
+        );
+        // The sides differ in whether there's whitespace, not only in its amount
+        assert_eq!(
+            merge_with(
+                FileMergeWhitespace::IgnoreSpaceChange,
+                FileMergeSide::Ours,
+                removes,
+                adds
+            ),
+            MergeResult::Conflict(vec![
+                Merge::resolved(hunk(b"a\n")),
+                Merge::from_removes_adds(
+                    vec![hunk(b"f(x,y)\n")],
+                    vec![hunk(b"f(x, y)\n"), hunk(b"f( x,  y )\n")]
+                ),
+                Merge::resolved(hunk(b"b\n"))
+            ])
+        );
+        // The sides only differ in the amount of indentation
+        assert_eq!(
+            merge_with(
+                FileMergeWhitespace::IgnoreSpaceChange,
+                FileMergeSide::Ours,
+                &[b"a\nx\n"],
+                &[b"a\n  x  y\n", b"a\n    x y\n"]
+            ),
+            MergeResult::Resolved(hunk(b"a\n  x  y\n"))
+        );
+        // Whitespace is compared exactly by default
+        assert_eq!(
+            merge_with(
+                FileMergeWhitespace::Exact,
+                FileMergeSide::Ours,
+                &[b"a\nx\n"],
+                &[b"a\n  x  y\n", b"a\n    x y\n"]
+            ),
+            merge(&[b"a\nx\n"], &[b"a\n  x  y\n", b"a\n    x y\n"])
+        );
+        // The sides differ in more than whitespace
+        assert_eq!(
+            merge_with(
+                FileMergeWhitespace::IgnoreAllSpace,
+                FileMergeSide::Ours,
+                &[b"x\n"],
+                &[b"x y\n", b"x z\n"]
+            ),
+            merge(&[b"x\n"], &[b"x y\n", b"x z\n"])
+        );
+    }
+
     #[test]
     fn test_merge_multi_hunk() {
         // Two sides left one line unchanged, and added conflicting additional lines
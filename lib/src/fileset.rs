@@ -307,19 +307,25 @@ impl FilesetExpression {
     pub fn to_matcher(&self) -> Box<dyn Matcher> {
         build_union_matcher(self.as_union_all())
     }
+
+    /// Transforms the expression tree to `Matcher` object which can be sent
+    /// to other threads, e.g. to be stored in the `Store`.
+    pub fn to_send_matcher(&self) -> Box<dyn Matcher + Send> {
+        build_union_matcher(self.as_union_all())
+    }
 }
 
 /// Transforms the union `expressions` to `Matcher` object.
 ///
 /// Since `Matcher` typically accepts a set of patterns to be OR-ed, this
 /// function takes a list of union `expressions` as input.
-fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher> {
+fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher + Send> {
     let mut file_paths = Vec::new();
     let mut prefix_paths = Vec::new();
     let mut file_globs = Vec::new();
-    let mut matchers: Vec<Option<Box<dyn Matcher>>> = Vec::new();
+    let mut matchers: Vec<Option<Box<dyn Matcher + Send>>> = Vec::new();
     for expr in expressions {
-        let matcher: Box<dyn Matcher> = match expr {
+        let matcher: Box<dyn Matcher + Send> = match expr {
             // None and All are supposed to be simplified by caller.
             FilesetExpression::None => Box::new(NothingMatcher),
             FilesetExpression::All => Box::new(EverythingMatcher),
@@ -365,7 +371,7 @@ fn build_union_matcher(expressions: &[FilesetExpression]) -> Box<dyn Matcher> {
 ///
 /// Each matcher element must be wrapped in `Some` so the matchers can be moved
 /// in arbitrary order.
-fn union_all_matchers(matchers: &mut [Option<Box<dyn Matcher>>]) -> Box<dyn Matcher> {
+fn union_all_matchers(matchers: &mut [Option<Box<dyn Matcher + Send>>]) -> Box<dyn Matcher + Send> {
     match matchers {
         [] => Box::new(NothingMatcher),
         [matcher] => matcher.take().expect("matcher should still be available"),
@@ -16,11 +16,15 @@
 
 use std::borrow::Borrow;
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::default::Default;
 use std::fs::File;
+use std::io::Write as _;
+use std::mem;
 use std::num::NonZeroU32;
+use std::path::Path;
 use std::path::PathBuf;
 use std::str;
 use std::sync::Arc;
@@ -34,12 +38,17 @@ use thiserror::Error;
 
 use crate::backend::BackendError;
 use crate::backend::BackendResult;
+use crate::backend::ChangeId;
 use crate::backend::CommitId;
+use crate::backend::Signature;
 use crate::backend::TreeValue;
 use crate::commit::Commit;
 use crate::file_util::IoResultExt as _;
 use crate::file_util::PathError;
+use crate::git_backend::signature_to_git;
 use crate::git_backend::GitBackend;
+use crate::git_lfs::LfsTransport;
+use crate::git_lfs::LocalLfsTransport;
 use crate::git_subprocess::GitSubprocessContext;
 use crate::git_subprocess::GitSubprocessError;
 #[cfg(feature = "git2")]
@@ -119,6 +128,8 @@ pub struct GitPushStats {
     pub rejected: Vec<(GitRefNameBuf, Option<String>)>,
     /// reference rejected by the remote, with an optional reason
     pub remote_rejected: Vec<(GitRefNameBuf, Option<String>)>,
+    /// messages sent by the remote, e.g. a link to create a merge request
+    pub remote_messages: Vec<String>,
 }
 
 impl GitPushStats {
@@ -127,6 +138,65 @@ impl GitPushStats {
     }
 }
 
+/// Collects the messages a remote sends through the sideband channel.
+///
+/// Lines terminated by `\r` are progress updates, which are forwarded to the
+/// `sideband_progress` callback along with the line completing them. The other
+/// lines are recorded so they can be reported once the operation finishes.
+#[derive(Debug, Default)]
+pub(crate) struct RemoteMessageCollector {
+    line: Vec<u8>,
+    in_progress: bool,
+    messages: Vec<String>,
+}
+
+impl RemoteMessageCollector {
+    pub(crate) fn feed(&mut self, data: &[u8], forward: &mut Option<&mut dyn FnMut(&[u8])>) {
+        for chunk in data.split_inclusive(|&b| b == b'\r' || b == b'\n') {
+            self.line.extend_from_slice(chunk);
+            let is_progress = match self.line.last() {
+                Some(b'\r') => true,
+                Some(b'\n') => mem::take(&mut self.in_progress),
+                _ => continue,
+            };
+            if is_progress {
+                if let Some(cb) = forward.as_mut() {
+                    cb(&self.line);
+                }
+                self.in_progress = self.line.ends_with(b"\r");
+            } else {
+                self.push_message();
+            }
+            self.line.clear();
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> Vec<String> {
+        if !self.line.is_empty() {
+            self.push_message();
+        }
+        let start = self.messages.iter().position(|m| !m.is_empty());
+        let end = self.messages.iter().rposition(|m| !m.is_empty());
+        match (start, end) {
+            (Some(start), Some(end)) => self.messages.drain(start..=end).collect(),
+            _ => vec![],
+        }
+    }
+
+    fn push_message(&mut self) {
+        let message = String::from_utf8_lossy(&self.line);
+        self.messages.push(message.trim_end().to_owned());
+    }
+}
+
+/// Options for pushing to a Git remote.
+#[derive(Clone, Debug, Default)]
+pub struct GitPushOptions {
+    /// Strings transmitted to the server as `git push -o` options, e.g. to
+    /// drive merge request creation on GitLab.
+    pub remote_push_options: Vec<String>,
+}
+
 /// Newtype to look up `HashMap` entry by key of shorter lifetime.
 ///
 /// https://users.rust-lang.org/t/unexpected-lifetime-issue-with-hashmap-remove/113961/6
@@ -520,6 +590,7 @@ pub fn import_some_refs(
         }
         // TODO: If we add Git-tracking tag, it will be updated here.
     }
+    import_notes(mut_repo, &git_repo).map_err(GitImportError::from_git)?;
 
     let abandoned_commits = if git_settings.abandon_unreachable_commits {
         abandon_unreachable_commits(mut_repo, &changed_remote_bookmarks, &changed_remote_tags)
@@ -536,6 +607,156 @@ pub fn import_some_refs(
     Ok(stats)
 }
 
+/// Updates the parents of commits that were at the shallow boundary of the
+/// underlying Git repo, but no longer are because the repo was deepened or
+/// unshallowed.
+///
+/// `previously_shallow` should be the result of
+/// [`GitBackend::shallow_commits()`] before the repo was deepened. Returns the
+/// commits that got their parents back.
+pub fn import_unshallowed_commits(
+    mut_repo: &mut MutableRepo,
+    previously_shallow: &HashSet<CommitId>,
+) -> Result<Vec<CommitId>, GitImportError> {
+    let store = mut_repo.store().clone();
+    let git_backend = get_git_backend(&store)?;
+    let shallow_commits = git_backend
+        .shallow_commits()
+        .map_err(GitImportError::Backend)?;
+    let unshallowed_ids = previously_shallow
+        .difference(&shallow_commits)
+        .filter(|&id| mut_repo.index().has_id(id))
+        .cloned()
+        .collect_vec();
+    if unshallowed_ids.is_empty() {
+        return Ok(vec![]);
+    }
+    tracing::debug!(?unshallowed_ids, "import unshallowed commits");
+    // The cached commits were read without parents.
+    store.forget_commits(&unshallowed_ids);
+    let commits: Vec<_> = unshallowed_ids
+        .iter()
+        .map(|id| store.get_commit(id))
+        .try_collect()
+        .map_err(GitImportError::Backend)?;
+    git_backend
+        .import_head_commits(commits.iter().flat_map(|commit| commit.parent_ids()))
+        .map_err(GitImportError::Backend)?;
+    mut_repo
+        .reparent_commits_in_index(&commits)
+        .map_err(GitImportError::Backend)?;
+    Ok(unshallowed_ids)
+}
+
+/// Git ref whose reflog records the entries made by `git stash`.
+const GIT_STASH_REF: &str = "refs/stash";
+
+/// Returns the commits of the `git stash` entries, newest first. That is, the
+/// `n`-th commit is `stash@{n}`.
+pub fn read_stash_commit_ids(store: &Store) -> Result<Vec<CommitId>, GitImportError> {
+    let git_repo = get_git_repo(store)?;
+    let Some(mut reference) = git_repo
+        .try_find_reference(GIT_STASH_REF)
+        .map_err(GitImportError::from_git)?
+    else {
+        return Ok(vec![]);
+    };
+    let mut log_iter = reference.log_iter();
+    let Some(lines) = log_iter.all().map_err(GitImportError::from_git)? else {
+        // The reflog of the stash shouldn't be missing, but if it is, the ref
+        // still points to the latest entry.
+        let ids = reference
+            .try_id()
+            .map(|id| CommitId::from_bytes(id.as_bytes()));
+        return Ok(ids.into_iter().collect());
+    };
+    let mut commit_ids: Vec<_> = lines
+        .map_ok(|line| CommitId::from_bytes(line.new_oid().as_bytes()))
+        .try_collect()
+        .map_err(GitImportError::from_git)?;
+    commit_ids.reverse();
+    Ok(commit_ids)
+}
+
+/// Imports the `git stash` entries as new commits on top of the commits they
+/// were stashed from.
+///
+/// The imported commits aren't visible. They are recorded in the view instead,
+/// which is how the `stashes()` revset finds them. The imported commit of an
+/// entry takes over the change id of the stash commit, so entries that are
+/// already imported are kept as is, and entries that have been dropped from the
+/// Git stash are removed from the view. Returns the newly imported commits.
+pub fn import_stashes(mut_repo: &mut MutableRepo) -> Result<Vec<Commit>, GitImportError> {
+    let store = mut_repo.store().clone();
+    let git_backend = get_git_backend(&store)?;
+    let empty_tree = store
+        .get_root_tree(&store.empty_merged_tree_id())
+        .map_err(GitImportError::Backend)?;
+    let old_stash_ids: HashMap<ChangeId, CommitId> = mut_repo
+        .view()
+        .git_stashes()
+        .iter()
+        .map(|id| {
+            let commit = store.get_commit(id)?;
+            Ok::<_, BackendError>((commit.change_id().clone(), id.clone()))
+        })
+        .try_collect()
+        .map_err(GitImportError::Backend)?;
+    let mut stash_ids = vec![];
+    let mut imported_commits = vec![];
+    // Import the oldest entry first so the commits are created in stash order.
+    for stash_id in read_stash_commit_ids(&store)?.iter().rev() {
+        let stash_commit = store
+            .get_commit(stash_id)
+            .map_err(GitImportError::Backend)?;
+        if let Some(id) = old_stash_ids.get(stash_commit.change_id()) {
+            stash_ids.push(id.clone());
+            continue;
+        }
+        // A stash commit is a merge of the commit it was stashed from, the
+        // commit of the staged changes, and optionally the commit of the
+        // untracked files.
+        let (base_id, untracked_id) = match stash_commit.parent_ids() {
+            [base_id, _] => (base_id, None),
+            [base_id, _, untracked_id] => (base_id, Some(untracked_id)),
+            _ => {
+                return Err(GitImportError::from_git(format!(
+                    "Stash commit {stash_id} has unexpected parents"
+                )));
+            }
+        };
+        git_backend
+            .import_head_commits([base_id])
+            .map_err(GitImportError::Backend)?;
+        let mut tree = stash_commit.tree().map_err(GitImportError::Backend)?;
+        if let Some(untracked_id) = untracked_id {
+            let untracked_tree = store
+                .get_commit(untracked_id)
+                .and_then(|commit| commit.tree())
+                .map_err(GitImportError::Backend)?;
+            tree = tree
+                .merge(&empty_tree, &untracked_tree)
+                .map_err(GitImportError::Backend)?;
+        }
+        let commit = mut_repo
+            .new_commit(vec![base_id.clone()], tree.id())
+            .set_change_id(stash_commit.change_id().clone())
+            .set_author(stash_commit.author().clone())
+            .set_description(stash_commit.description())
+            .detach()
+            .write_hidden()
+            .map_err(GitImportError::Backend)?;
+        stash_ids.push(commit.id().clone());
+        imported_commits.push(commit);
+    }
+    mut_repo
+        .add_hidden_commits(&imported_commits)
+        .map_err(GitImportError::Backend)?;
+    stash_ids.reverse();
+    mut_repo.set_git_stashes(stash_ids);
+    Ok(imported_commits)
+}
+
 /// Finds commits that used to be reachable in git that no longer are reachable.
 /// Those commits will be recorded as abandoned in the `MutableRepo`.
 fn abandon_unreachable_commits(
@@ -895,7 +1116,8 @@ struct RefsToExport {
 ///
 /// We do not export tags and other refs at the moment, since these aren't
 /// supposed to be modified by JJ. For them, the Git state is considered
-/// authoritative.
+/// authoritative. The exception is `refs/notes/commits`, to which the notes of
+/// the rewritten commits are added.
 pub fn export_refs(mut_repo: &mut MutableRepo) -> Result<GitExportStats, GitExportError> {
     export_some_refs(mut_repo, |_, _| true)
 }
@@ -996,6 +1218,7 @@ pub fn export_some_refs(
             git_ref_filter(GitRefKind::Bookmark, symbol) && get(&failed_bookmarks, symbol).is_none()
         },
     );
+    export_notes(mut_repo, &git_repo).map_err(GitExportError::from_git)?;
 
     Ok(GitExportStats { failed_bookmarks })
 }
@@ -1750,7 +1973,9 @@ fn remove_remote_git_config_sections(
         })
         .map(|section| {
             if section.value_names().any(|name| {
-                !name.eq_ignore_ascii_case(b"url") && !name.eq_ignore_ascii_case(b"fetch")
+                !["url", "pushurl", "fetch", "promisor", "partialclonefilter"]
+                    .iter()
+                    .any(|known| name.eq_ignore_ascii_case(known.as_bytes()))
             }) {
                 return Err(GitRemoteManagementError::NonstandardConfiguration(
                     remote_name.to_owned(),
@@ -1817,6 +2042,37 @@ pub fn add_remote(
     Ok(())
 }
 
+/// Makes the remote the promisor remote of a partial clone.
+///
+/// Objects matched by the `filter` (e.g. `blob:none`) will be omitted when
+/// fetching from the remote, and fetched on demand when they're needed. The
+/// repo must be reloaded for the change to take effect.
+pub fn set_remote_partial_clone_filter(
+    store: &Store,
+    remote_name: &RemoteName,
+    filter: &str,
+) -> Result<(), GitRemoteManagementError> {
+    let git_repo = get_git_repo(store)?;
+
+    if git_repo.try_find_remote(remote_name.as_str()).is_none() {
+        return Err(GitRemoteManagementError::NoSuchRemote(
+            remote_name.to_owned(),
+        ));
+    }
+
+    let mut config = git_repo.config_snapshot().clone();
+    let subsection = Some(BStr::new(remote_name.as_str()));
+    config
+        .set_raw_value_by("remote", subsection, "promisor", "true")
+        .map_err(GitRemoteManagementError::from_git)?;
+    config
+        .set_raw_value_by("remote", subsection, "partialclonefilter", filter)
+        .map_err(GitRemoteManagementError::from_git)?;
+    save_git_config(&config).map_err(GitRemoteManagementError::GitConfigSaveError)?;
+
+    Ok(())
+}
+
 pub fn remove_remote(
     mut_repo: &mut MutableRepo,
     remote_name: &RemoteName,
@@ -1848,11 +2104,18 @@ fn remove_remote_git_refs(
     git_repo: &mut gix::Repository,
     remote: &RemoteName,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
-    let edits: Vec<_> = git_repo
+    let mut edits: Vec<_> = git_repo
         .references()?
         .prefixed(format!("refs/remotes/{remote}/", remote = remote.as_str()))?
         .map_ok(remove_ref)
         .try_collect()?;
+    // Notes fetched from the remote
+    for reference in git_repo.references()?.prefixed(format!(
+        "refs/notes/remotes/{remote}/",
+        remote = remote.as_str()
+    ))? {
+        edits.push(remove_ref(reference?));
+    }
     git_repo.edit_references(edits)?;
     Ok(())
 }
@@ -1989,8 +2252,8 @@ where
     // Copy the existing data from `remote`.
     //
     // We don’t copy the push URL, as there does not seem to be any way to reliably
-    // detect whether one is present with the current API, and `jj git remote
-    // set-url` refuses to work with them anyway.
+    // detect whether one is present with the current API. `set_remote_url()`
+    // restores it from the config instead.
     new_remote = new_remote.with_fetch_tags(remote.fetch_tags());
     for direction in [gix::remote::Direction::Fetch, gix::remote::Direction::Push] {
         new_remote
@@ -2022,24 +2285,140 @@ pub fn set_remote_url(
     };
     let mut remote = result.map_err(GitRemoteManagementError::from_git)?;
 
-    if remote.url(gix::remote::Direction::Push) != remote.url(gix::remote::Direction::Fetch) {
-        return Err(GitRemoteManagementError::NonstandardConfiguration(
-            remote_name.to_owned(),
-        ));
-    }
+    let mut config = git_repo.config_snapshot().clone();
+    let push_url = git_config_remote_push_url(&config, remote_name)?;
 
     remote = gix_remote_with_fetch_url(remote, new_remote_url)
         .map_err(GitRemoteManagementError::from_git)?;
 
-    let mut config = git_repo.config_snapshot().clone();
     remote
         .save_as_to(remote_name.as_str(), &mut config)
         .map_err(GitRemoteManagementError::from_git)?;
+    if let Some(push_url) = push_url {
+        config
+            .set_raw_value_by(
+                "remote",
+                Some(BStr::new(remote_name.as_str())),
+                "pushurl",
+                BStr::new(&push_url),
+            )
+            .map_err(GitRemoteManagementError::from_git)?;
+    }
     save_git_config(&config).map_err(GitRemoteManagementError::GitConfigSaveError)?;
 
     Ok(())
 }
 
+/// Sets the `url` to be used when pushing data to a remote, which otherwise
+/// defaults to the fetch URL.
+pub fn set_remote_push_url(
+    store: &Store,
+    remote_name: &RemoteName,
+    new_push_url: &str,
+) -> Result<(), GitRemoteManagementError> {
+    let git_repo = get_git_repo(store)?;
+
+    validate_remote_name(remote_name)?;
+
+    if git_repo
+        .try_find_remote_without_url_rewrite(remote_name.as_str())
+        .is_none()
+    {
+        return Err(GitRemoteManagementError::NoSuchRemote(
+            remote_name.to_owned(),
+        ));
+    }
+    gix::url::parse(new_push_url.into()).map_err(GitRemoteManagementError::from_git)?;
+
+    let mut config = git_repo.config_snapshot().clone();
+    // Fails if there are multiple push URLs, which we can't represent.
+    git_config_remote_push_url(&config, remote_name)?;
+    config
+        .set_raw_value_by(
+            "remote",
+            Some(BStr::new(remote_name.as_str())),
+            "pushurl",
+            new_push_url,
+        )
+        .map_err(GitRemoteManagementError::from_git)?;
+    save_git_config(&config).map_err(GitRemoteManagementError::GitConfigSaveError)?;
+
+    Ok(())
+}
+
+/// Returns the `remote.<name>.pushurl` value without URL rewriting applied.
+fn git_config_remote_push_url(
+    config: &gix::config::File,
+    remote_name: &RemoteName,
+) -> Result<Option<BString>, GitRemoteManagementError> {
+    let key = format!("remote.{}.pushurl", remote_name.as_str());
+    match config.strings(key.as_str()).unwrap_or_default().as_slice() {
+        [] => Ok(None),
+        [url] => Ok(Some(url.clone().into_owned())),
+        _ => Err(GitRemoteManagementError::NonstandardConfiguration(
+            remote_name.to_owned(),
+        )),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum GitLfsTransportError {
+    #[error("No git remote named '{}'", .0.as_symbol())]
+    NoSuchRemote(RemoteNameBuf),
+    #[error("Unsupported Git LFS server URL {0}")]
+    UnsupportedUrl(String),
+    #[error("Unexpected Git error when looking up the Git LFS server")]
+    InternalGitError(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error(transparent)]
+    UnexpectedBackend(#[from] UnexpectedGitBackendError),
+}
+
+/// Returns the transport to the Git LFS server of the remote.
+///
+/// The server is looked up from the `remote.<name>.lfsurl` and `lfs.url` Git
+/// config, and defaults to the remote's fetch or push URL depending on the
+/// `direction`. Only servers in the local file system are supported, in which
+/// case the objects are stored in the `lfs/objects` directory of the server's
+/// Git repository.
+pub fn lfs_transport_for_remote(
+    store: &Store,
+    remote_name: &RemoteName,
+    direction: gix::remote::Direction,
+) -> Result<Box<dyn LfsTransport>, GitLfsTransportError> {
+    let git_repo = get_git_repo(store)?;
+    let config = git_repo.config_snapshot();
+    let lfs_url_key = format!("remote.{}.lfsurl", remote_name.as_str());
+    let url = if let Some(url) = config
+        .string(lfs_url_key.as_str())
+        .or_else(|| config.string("lfs.url"))
+    {
+        gix::url::parse(&url).map_err(|_| GitLfsTransportError::UnsupportedUrl(url.to_string()))?
+    } else {
+        let Some(result) = git_repo.try_find_remote(remote_name.as_str()) else {
+            return Err(GitLfsTransportError::NoSuchRemote(remote_name.to_owned()));
+        };
+        let remote = result.map_err(|err| GitLfsTransportError::InternalGitError(err.into()))?;
+        let Some(url) = remote.url(direction) else {
+            return Err(GitLfsTransportError::NoSuchRemote(remote_name.to_owned()));
+        };
+        url.clone()
+    };
+    if url.scheme != gix::url::Scheme::File {
+        return Err(GitLfsTransportError::UnsupportedUrl(
+            url.to_bstring().to_string(),
+        ));
+    }
+    let server_path = gix::path::from_bstr(url.path.as_ref()).into_owned();
+    let git_dir = if server_path.join(".git").is_dir() {
+        server_path.join(".git")
+    } else {
+        server_path
+    };
+    Ok(Box::new(LocalLfsTransport::new(
+        git_dir.join("lfs").join("objects"),
+    )))
+}
+
 fn rename_remote_refs(
     mut_repo: &mut MutableRepo,
     old_remote_name: &RemoteName,
@@ -2079,12 +2458,33 @@ pub enum GitFetchError {
     #[error(transparent)]
     RemoteName(#[from] GitRemoteNameError),
     #[cfg(feature = "git2")]
+    #[error("{0} is not supported by the libgit2 backend; set `git.subprocess = true` to use it")]
+    UnsupportedDepth(&'static str),
+    #[cfg(feature = "git2")]
     #[error(transparent)]
     Git2(#[from] git2::Error),
     #[error(transparent)]
     Subprocess(#[from] GitSubprocessError),
 }
 
+/// How much history to fetch.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum GitFetchDepth {
+    /// Fetch all history, or keep the current shallow boundaries if the repo
+    /// is already shallow.
+    #[default]
+    Unspecified,
+    /// Limit the history to the given number of commits from the tips.
+    Depth(NonZeroU32),
+    /// Deepen the history of a shallow repo by the given number of commits.
+    Deepen(NonZeroU32),
+    /// Deepen or shorten the history of a shallow repo to include all commits
+    /// after the given date.
+    ShallowSince(String),
+    /// Fetch the complete history of a shallow repo.
+    Unshallow,
+}
+
 // TODO: If Git2 implementation is removed, this can be replaced with
 // UnexpectedGitBackendError.
 #[derive(Debug, Error)]
@@ -2094,13 +2494,15 @@ pub enum GitFetchPrepareError {
     Git2(#[from] git2::Error),
     #[error(transparent)]
     UnexpectedBackend(#[from] UnexpectedGitBackendError),
+    #[error(transparent)]
+    Backend(#[from] BackendError),
 }
 
 #[cfg(feature = "git2")]
 fn git2_fetch_options(
     mut callbacks: RemoteCallbacks<'_>,
-    depth: Option<NonZeroU32>,
-) -> git2::FetchOptions<'_> {
+    depth: &GitFetchDepth,
+) -> Result<git2::FetchOptions<'_>, GitFetchError> {
     let mut proxy_options = git2::ProxyOptions::new();
     proxy_options.auto();
 
@@ -2113,11 +2515,22 @@ fn git2_fetch_options(
         callbacks.sideband_progress = None;
     }
     fetch_options.remote_callbacks(callbacks.into_git());
-    if let Some(depth) = depth {
-        fetch_options.depth(depth.get().try_into().unwrap_or(i32::MAX));
+    match depth {
+        GitFetchDepth::Unspecified => {}
+        GitFetchDepth::Depth(depth) => {
+            fetch_options.depth(depth.get().try_into().unwrap_or(i32::MAX));
+        }
+        GitFetchDepth::Deepen(_) => return Err(GitFetchError::UnsupportedDepth("Deepening")),
+        GitFetchDepth::ShallowSince(_) => {
+            return Err(GitFetchError::UnsupportedDepth("Fetching since a date"));
+        }
+        // Same as GIT_FETCH_DEPTH_UNSHALLOW
+        GitFetchDepth::Unshallow => {
+            fetch_options.depth(i32::MAX);
+        }
     }
 
-    fetch_options
+    Ok(fetch_options)
 }
 
 struct FetchedBranches {
@@ -2131,6 +2544,8 @@ pub struct GitFetch<'a> {
     fetch_impl: GitFetchImpl<'a>,
     git_settings: &'a GitSettings,
     fetched: Vec<FetchedBranches>,
+    /// Shallow commits as of the last import.
+    previously_shallow: HashSet<CommitId>,
 }
 
 impl<'a> GitFetch<'a> {
@@ -2139,11 +2554,13 @@ impl<'a> GitFetch<'a> {
         git_settings: &'a GitSettings,
     ) -> Result<Self, GitFetchPrepareError> {
         let fetch_impl = GitFetchImpl::new(mut_repo.store(), git_settings)?;
+        let previously_shallow = get_git_backend(mut_repo.store())?.shallow_commits()?;
         Ok(GitFetch {
             mut_repo,
             fetch_impl,
             git_settings,
             fetched: vec![],
+            previously_shallow,
         })
     }
 
@@ -2158,11 +2575,16 @@ impl<'a> GitFetch<'a> {
         remote_name: &RemoteName,
         branch_names: &[StringPattern],
         callbacks: RemoteCallbacks<'_>,
-        depth: Option<NonZeroU32>,
+        depth: &GitFetchDepth,
     ) -> Result<(), GitFetchError> {
         validate_remote_name(remote_name)?;
-        self.fetch_impl
-            .fetch(remote_name, branch_names, callbacks, depth)?;
+        self.fetch_impl.fetch(
+            remote_name,
+            branch_names,
+            self.git_settings.fetch_notes,
+            callbacks,
+            depth,
+        )?;
         self.fetched.push(FetchedBranches {
             remote: remote_name.to_owned(),
             branches: branch_names.to_vec(),
@@ -2187,9 +2609,16 @@ impl<'a> GitFetch<'a> {
     /// Clears all yet-to-be-imported {branch_names, remote_name} pairs after
     /// the import. If `fetch()` has not been called since the last time
     /// `import_refs()` was called then this will be a no-op.
+    ///
+    /// If the fetch deepened a shallow repo, the commits at the previous
+    /// shallow boundary get their parents back.
     #[tracing::instrument(skip(self))]
     pub fn import_refs(&mut self) -> Result<GitImportStats, GitImportError> {
         tracing::debug!("import_refs");
+        import_unshallowed_commits(self.mut_repo, &self.previously_shallow)?;
+        self.previously_shallow = get_git_backend(self.mut_repo.store())?
+            .shallow_commits()
+            .map_err(GitImportError::Backend)?;
         let import_stats =
             import_some_refs(
                 self.mut_repo,
@@ -2209,6 +2638,15 @@ impl<'a> GitFetch<'a> {
                 },
             )?;
 
+        if self.git_settings.fetch_notes {
+            let git_repo = get_git_repo(self.mut_repo.store())?;
+            let signature = self.mut_repo.settings().signature();
+            for remote in self.fetched.iter().map(|fetched| &fetched.remote).unique() {
+                import_remote_notes(&git_repo, remote, &signature)
+                    .map_err(GitImportError::from_git)?;
+            }
+        }
+
         self.fetched.clear();
 
         Ok(import_stats)
@@ -2242,7 +2680,10 @@ fn expand_fetch_refspecs(
 
 enum GitFetchImpl<'a> {
     #[cfg(feature = "git2")]
-    Git2 { git_repo: git2::Repository },
+    Git2 {
+        git_repo: git2::Repository,
+        gix_repo: Box<gix::Repository>,
+    },
     Subprocess {
         git_repo: Box<gix::Repository>,
         git_ctx: GitSubprocessContext<'a>,
@@ -2255,7 +2696,8 @@ impl<'a> GitFetchImpl<'a> {
         #[cfg(feature = "git2")]
         if !git_settings.subprocess {
             let git_repo = git2::Repository::open(git_backend.git_repo_path())?;
-            return Ok(GitFetchImpl::Git2 { git_repo });
+            let gix_repo = Box::new(git_backend.git_repo());
+            return Ok(GitFetchImpl::Git2 { git_repo, gix_repo });
         }
         let git_repo = Box::new(git_backend.git_repo());
         let git_ctx =
@@ -2267,19 +2709,27 @@ impl<'a> GitFetchImpl<'a> {
         &self,
         remote_name: &RemoteName,
         branch_names: &[StringPattern],
+        fetch_notes: bool,
         callbacks: RemoteCallbacks<'_>,
-        depth: Option<NonZeroU32>,
+        depth: &GitFetchDepth,
     ) -> Result<(), GitFetchError> {
         match self {
             #[cfg(feature = "git2")]
-            GitFetchImpl::Git2 { git_repo } => {
-                git2_fetch(git_repo, remote_name, branch_names, callbacks, depth)
-            }
+            GitFetchImpl::Git2 { git_repo, gix_repo } => git2_fetch(
+                git_repo,
+                gix_repo,
+                remote_name,
+                branch_names,
+                fetch_notes,
+                callbacks,
+                depth,
+            ),
             GitFetchImpl::Subprocess { git_repo, git_ctx } => subprocess_fetch(
                 git_repo,
                 git_ctx,
                 remote_name,
                 branch_names,
+                fetch_notes,
                 callbacks,
                 depth,
             ),
@@ -2293,8 +2743,8 @@ impl<'a> GitFetchImpl<'a> {
     ) -> Result<Option<RefNameBuf>, GitFetchError> {
         match self {
             #[cfg(feature = "git2")]
-            GitFetchImpl::Git2 { git_repo } => {
-                git2_get_default_branch(git_repo, remote_name, callbacks)
+            GitFetchImpl::Git2 { git_repo, gix_repo } => {
+                git2_get_default_branch(git_repo, gix_repo, remote_name, callbacks)
             }
             GitFetchImpl::Subprocess { git_repo, git_ctx } => {
                 subprocess_get_default_branch(git_repo, git_ctx, remote_name, callbacks)
@@ -2303,15 +2753,57 @@ impl<'a> GitFetchImpl<'a> {
     }
 }
 
+/// Looks up the remote, making sure that its URL for the `direction` is
+/// rewritten by the `url.<base>.insteadOf` and `url.<base>.pushInsteadOf`
+/// config in the same way as the other remote operations, which use gix.
+///
+/// libgit2 applies its own rewriting when loading the remote, which doesn't
+/// agree with Git in some cases (e.g. `pushInsteadOf` without `pushurl`). If
+/// the URLs differ, an anonymous remote for the rewritten URL is returned.
+#[cfg(feature = "git2")]
+fn git2_find_remote<'repo>(
+    git_repo: &'repo git2::Repository,
+    gix_repo: &gix::Repository,
+    remote_name: &RemoteName,
+    direction: gix::remote::Direction,
+) -> Result<git2::Remote<'repo>, git2::Error> {
+    let remote = git_repo.find_remote(remote_name.as_str())?;
+    let rewritten_url = match gix_repo.try_find_remote(remote_name.as_str()) {
+        Some(Ok(gix_remote)) => gix_remote.url(direction).map(|url| url.to_bstring()),
+        Some(Err(err)) => return Err(git2::Error::from_str(&err.to_string())),
+        None => None,
+    };
+    let git2_url = match direction {
+        gix::remote::Direction::Fetch => remote.url_bytes(),
+        gix::remote::Direction::Push => remote.pushurl_bytes().unwrap_or(remote.url_bytes()),
+    };
+    match rewritten_url {
+        Some(url) if url != git2_url => {
+            let url = str::from_utf8(&url)
+                .map_err(|_| git2::Error::from_str("Remote URL is not valid UTF-8"))?;
+            git_repo.remote_anonymous(url)
+        }
+        _ => Ok(remote),
+    }
+}
+
 #[cfg(feature = "git2")]
 fn git2_fetch(
     git_repo: &git2::Repository,
+    gix_repo: &gix::Repository,
     remote_name: &RemoteName,
     branch_names: &[StringPattern],
+    fetch_notes: bool,
     callbacks: RemoteCallbacks<'_>,
-    depth: Option<NonZeroU32>,
+    depth: &GitFetchDepth,
 ) -> Result<(), GitFetchError> {
-    let mut remote = git_repo.find_remote(remote_name.as_str()).map_err(|err| {
+    let mut remote = git2_find_remote(
+        git_repo,
+        gix_repo,
+        remote_name,
+        gix::remote::Direction::Fetch,
+    )
+    .map_err(|err| {
         if is_remote_not_found_err(&err) {
             GitFetchError::NoSuchRemote(remote_name.to_owned())
         } else {
@@ -2320,7 +2812,7 @@ fn git2_fetch(
     })?;
     // At this point, we are only updating Git's remote tracking branches, not the
     // local branches.
-    let refspecs: Vec<String> = expand_fetch_refspecs(remote_name, branch_names)?
+    let mut refspecs: Vec<String> = expand_fetch_refspecs(remote_name, branch_names)?
         .iter()
         .map(|refspec| refspec.to_git_format())
         .collect();
@@ -2329,9 +2821,13 @@ fn git2_fetch(
         // Don't fall back to the base refspecs.
         return Ok(());
     }
+    if fetch_notes {
+        refspecs.push(notes_fetch_refspec(remote_name).to_git_format());
+    }
 
     tracing::debug!("remote.download");
-    remote.download(&refspecs, Some(&mut git2_fetch_options(callbacks, depth)))?;
+    let mut fetch_options = git2_fetch_options(callbacks, depth)?;
+    remote.download(&refspecs, Some(&mut fetch_options))?;
     tracing::debug!("remote.prune");
     remote.prune(None)?;
     tracing::debug!("remote.update_tips");
@@ -2349,10 +2845,17 @@ fn git2_fetch(
 #[cfg(feature = "git2")]
 fn git2_get_default_branch(
     git_repo: &git2::Repository,
+    gix_repo: &gix::Repository,
     remote_name: &RemoteName,
     callbacks: RemoteCallbacks<'_>,
 ) -> Result<Option<RefNameBuf>, GitFetchError> {
-    let mut remote = git_repo.find_remote(remote_name.as_str()).map_err(|err| {
+    let mut remote = git2_find_remote(
+        git_repo,
+        gix_repo,
+        remote_name,
+        gix::remote::Direction::Fetch,
+    )
+    .map_err(|err| {
         if is_remote_not_found_err(&err) {
             GitFetchError::NoSuchRemote(remote_name.to_owned())
         } else {
@@ -2392,8 +2895,9 @@ fn subprocess_fetch(
     git_ctx: &GitSubprocessContext,
     remote_name: &RemoteName,
     branch_names: &[StringPattern],
+    fetch_notes: bool,
     mut callbacks: RemoteCallbacks<'_>,
-    depth: Option<NonZeroU32>,
+    depth: &GitFetchDepth,
 ) -> Result<(), GitFetchError> {
     // check the remote exists
     if git_repo.try_find_remote(remote_name.as_str()).is_none() {
@@ -2406,6 +2910,9 @@ fn subprocess_fetch(
         // Don't fall back to the base refspecs.
         return Ok(());
     }
+    if fetch_notes {
+        remaining_refspecs.push(notes_fetch_refspec(remote_name));
+    }
 
     let mut branches_to_prune = Vec::new();
     // git unfortunately errors out if one of the many refspecs is not found
@@ -2461,6 +2968,8 @@ pub enum GitPushError {
     #[error(transparent)]
     Subprocess(#[from] GitSubprocessError),
     #[error(transparent)]
+    Notes(#[from] GitNotesError),
+    #[error(transparent)]
     UnexpectedBackend(#[from] UnexpectedGitBackendError),
 }
 
@@ -2485,6 +2994,7 @@ pub fn push_branches(
     git_settings: &GitSettings,
     remote: &RemoteName,
     targets: &GitBranchPushTargets,
+    options: &GitPushOptions,
     callbacks: RemoteCallbacks<'_>,
 ) -> Result<GitPushStats, GitPushError> {
     validate_remote_name(remote)?;
@@ -2499,7 +3009,14 @@ pub fn push_branches(
         })
         .collect_vec();
 
-    let push_stats = push_updates(mut_repo, git_settings, remote, &ref_updates, callbacks)?;
+    let push_stats = push_updates(
+        mut_repo,
+        git_settings,
+        remote,
+        &ref_updates,
+        options,
+        callbacks,
+    )?;
     tracing::debug!(?push_stats);
 
     // TODO: add support for partially pushed refs? we could update the view
@@ -2531,6 +3048,7 @@ pub fn push_updates(
     git_settings: &GitSettings,
     remote_name: &RemoteName,
     updates: &[GitRefUpdate],
+    options: &GitPushOptions,
     callbacks: RemoteCallbacks<'_>,
 ) -> Result<GitPushStats, GitPushError> {
     let mut qualified_remote_refs_expected_locations = HashMap::new();
@@ -2559,13 +3077,16 @@ pub fn push_updates(
     #[cfg(feature = "git2")]
     if !git_settings.subprocess {
         let git_repo = git2::Repository::open(git_backend.git_repo_path())?;
+        let gix_repo = git_backend.git_repo();
         let refspecs: Vec<String> = refspecs.iter().map(RefSpec::to_git_format).collect();
         return git2_push_refs(
             repo,
             &git_repo,
+            &gix_repo,
             remote_name,
             &qualified_remote_refs_expected_locations,
             &refspecs,
+            options,
             callbacks,
         );
     }
@@ -2578,6 +3099,7 @@ pub fn push_updates(
         remote_name,
         &qualified_remote_refs_expected_locations,
         &refspecs,
+        options,
         callbacks,
     )
 }
@@ -2586,12 +3108,20 @@ pub fn push_updates(
 fn git2_push_refs(
     repo: &dyn Repo,
     git_repo: &git2::Repository,
+    gix_repo: &gix::Repository,
     remote_name: &RemoteName,
     qualified_remote_refs_expected_locations: &HashMap<&GitRefName, Option<&CommitId>>,
     refspecs: &[String],
-    callbacks: RemoteCallbacks<'_>,
+    options: &GitPushOptions,
+    mut callbacks: RemoteCallbacks<'_>,
 ) -> Result<GitPushStats, GitPushError> {
-    let mut remote = git_repo.find_remote(remote_name.as_str()).map_err(|err| {
+    let mut remote = git2_find_remote(
+        git_repo,
+        gix_repo,
+        remote_name,
+        gix::remote::Direction::Push,
+    )
+    .map_err(|err| {
         if is_remote_not_found_err(&err) {
             GitPushError::NoSuchRemote(remote_name.to_owned())
         } else {
@@ -2605,13 +3135,26 @@ fn git2_push_refs(
         .collect();
     let mut failed_push_negotiations = vec![];
     let mut pushed_refs = vec![];
+    let mut remote_rejected_reasons = HashMap::new();
+    let mut remote_messages = RemoteMessageCollector::default();
 
     let push_result = {
         let mut push_options = git2::PushOptions::new();
         let mut proxy_options = git2::ProxyOptions::new();
         proxy_options.auto();
         push_options.proxy_options(proxy_options);
+        let remote_push_options = options
+            .remote_push_options
+            .iter()
+            .map(String::as_str)
+            .collect_vec();
+        push_options.remote_push_options(&remote_push_options);
+        let mut sideband_progress = callbacks.sideband_progress.take();
         let mut callbacks = callbacks.into_git();
+        callbacks.sideband_progress(|data| {
+            remote_messages.feed(data, &mut sideband_progress);
+            true
+        });
         callbacks.push_negotiation(|updates| {
             for update in updates {
                 let dst_refname: &GitRefName = update
@@ -2676,7 +3219,9 @@ fn git2_push_refs(
         callbacks.push_update_reference(|refname, status| {
             let refname = GitRefName::new(refname);
             // The status is Some if the ref update was rejected by the remote
-            if status.is_none() {
+            if let Some(reason) = status {
+                remote_rejected_reasons.insert(refname.to_owned(), reason.to_owned());
+            } else {
                 remaining_remote_refs.remove(refname);
                 pushed_refs.push(refname.to_owned());
             }
@@ -2697,9 +3242,10 @@ fn git2_push_refs(
     let remote_rejected: Vec<_> = remaining_remote_refs
         .into_iter()
         .sorted()
-        .map(|name| (name.to_owned(), None))
+        .map(|name| (name.to_owned(), remote_rejected_reasons.remove(name)))
         .collect();
     pushed_refs.sort();
+    let remote_messages = remote_messages.finish();
 
     let push_stats = if !rejected.is_empty() {
         // If the push negotiation returned an error, `remote.push` would not
@@ -2713,6 +3259,7 @@ fn git2_push_refs(
         GitPushStats {
             rejected,
             remote_rejected,
+            remote_messages,
             ..Default::default()
         }
     } else {
@@ -2720,6 +3267,7 @@ fn git2_push_refs(
         GitPushStats {
             pushed: pushed_refs,
             remote_rejected,
+            remote_messages,
             ..Default::default()
         }
     };
@@ -2733,6 +3281,7 @@ fn subprocess_push_refs(
     remote_name: &RemoteName,
     qualified_remote_refs_expected_locations: &HashMap<&GitRefName, Option<&CommitId>>,
     refspecs: &[RefSpec],
+    options: &GitPushOptions,
     mut callbacks: RemoteCallbacks<'_>,
 ) -> Result<GitPushStats, GitPushError> {
     // check the remote exists
@@ -2745,7 +3294,7 @@ fn subprocess_push_refs(
         .map(|full_refspec| RefToPush::new(full_refspec, qualified_remote_refs_expected_locations))
         .collect();
 
-    let mut push_stats = git_ctx.spawn_push(remote_name, &refs_to_push, &mut callbacks)?;
+    let mut push_stats = git_ctx.spawn_push(remote_name, &refs_to_push, options, &mut callbacks)?;
     push_stats.pushed.sort();
     push_stats.rejected.sort();
     push_stats.remote_rejected.sort();
@@ -2812,6 +3361,516 @@ fn allow_push(
     }
 }
 
+/// Ref that `git notes` reads and writes by default.
+pub const GIT_NOTES_REF: &str = "refs/notes/commits";
+
+#[derive(Debug, Error)]
+pub enum GitNotesError {
+    #[error("Failed to access Git notes")]
+    Git(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+    #[error(transparent)]
+    UnexpectedBackend(#[from] UnexpectedGitBackendError),
+}
+
+impl GitNotesError {
+    fn from_git(source: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        GitNotesError::Git(source.into())
+    }
+}
+
+/// Returns the full name of the notes ref, e.g. `refs/notes/commits` for
+/// `commits`.
+pub fn notes_ref_name(name: &str) -> String {
+    if name.starts_with("refs/notes/") {
+        name.to_owned()
+    } else {
+        format!("refs/notes/{name}")
+    }
+}
+
+/// Notes in a notes ref, loaded once to look up the notes of many commits.
+pub struct GitNotes {
+    git_repo: gix::Repository,
+    notes: BTreeMap<String, gix::ObjectId>,
+}
+
+impl GitNotes {
+    /// Loads the notes in `notes_ref`. There are no notes if the ref doesn't
+    /// exist.
+    pub fn load(store: &Store, notes_ref: &str) -> Result<Self, GitNotesError> {
+        let git_repo = get_git_repo(store)?;
+        let notes = match resolve_notes_commit(&git_repo, notes_ref)? {
+            Some(notes_commit_id) => {
+                read_notes(&git_repo, notes_tree_id(&git_repo, notes_commit_id)?)?
+            }
+            None => BTreeMap::new(),
+        };
+        Ok(GitNotes { git_repo, notes })
+    }
+
+    /// Reads the note attached to the commit `id`.
+    pub fn get(&self, id: &CommitId) -> Result<Option<String>, GitNotesError> {
+        let Some(&blob_id) = self.notes.get(&id.hex()) else {
+            return Ok(None);
+        };
+        let blob = self
+            .git_repo
+            .find_blob(blob_id)
+            .map_err(GitNotesError::from_git)?;
+        Ok(Some(String::from_utf8_lossy(&blob.data).into_owned()))
+    }
+}
+
+/// Records the position of `refs/notes/commits` in the view, so the notes
+/// can be restored by `jj undo`.
+fn import_notes(
+    mut_repo: &mut MutableRepo,
+    git_repo: &gix::Repository,
+) -> Result<(), GitNotesError> {
+    let commit_id = resolve_notes_commit(git_repo, GIT_NOTES_REF)?
+        .map(|id| CommitId::from_bytes(id.as_bytes()));
+    if mut_repo.view().git_notes() != commit_id.as_ref() {
+        mut_repo.set_git_notes(commit_id);
+    }
+    Ok(())
+}
+
+/// Copies the notes of the commits rewritten in `mut_repo` to their new
+/// versions, and updates `refs/notes/commits` accordingly.
+///
+/// [`export_refs()`] does this as well. This should be called before
+/// committing transactions which don't export the refs, e.g. in non-colocated
+/// repos, since the rewrites aren't known afterwards.
+pub fn copy_notes_of_rewritten_commits(mut_repo: &mut MutableRepo) -> Result<(), GitNotesError> {
+    let git_repo = get_git_repo(mut_repo.store())?;
+    export_notes(mut_repo, &git_repo)
+}
+
+/// Updates `refs/notes/commits` to the position recorded in the view, with the
+/// notes of the commits rewritten in `mut_repo` copied to their new versions.
+///
+/// If the ref has been moved in Git since the notes were last synced, or if the
+/// view has no notes recorded, the Git state wins, and the notes of the
+/// rewritten commits are added to it.
+fn export_notes(
+    mut_repo: &mut MutableRepo,
+    git_repo: &gix::Repository,
+) -> Result<(), GitNotesError> {
+    let to_git_id = |id: &CommitId| gix::ObjectId::from_bytes_or_panic(id.as_bytes());
+    let current_commit_id = resolve_notes_commit(git_repo, GIT_NOTES_REF)?;
+    let base_commit_id = mut_repo.base_repo().view().git_notes().map(to_git_id);
+    let old_commit_id = if current_commit_id == base_commit_id {
+        // The notes commit recorded in an old operation may have been
+        // garbage-collected by Git.
+        mut_repo
+            .view()
+            .git_notes()
+            .map(to_git_id)
+            .filter(|&id| git_repo.has_object(id))
+            .or(current_commit_id)
+    } else {
+        current_commit_id
+    };
+    let Some(old_commit_id) = old_commit_id else {
+        return Ok(());
+    };
+    let new_commit_id = copy_notes_for_rewritten_commits(mut_repo, git_repo, old_commit_id)?;
+    if current_commit_id != Some(new_commit_id) {
+        update_git_ref(
+            git_repo,
+            GitRefName::new(GIT_NOTES_REF),
+            current_commit_id,
+            new_commit_id,
+        )
+        .map_err(GitNotesError::from_git)?;
+    }
+    let new_commit_id = CommitId::from_bytes(new_commit_id.as_bytes());
+    if mut_repo.view().git_notes() != Some(&new_commit_id) {
+        mut_repo.set_git_notes(Some(new_commit_id));
+    }
+    Ok(())
+}
+
+/// Attaches the notes of the commits rewritten in `mut_repo` to their new
+/// versions, like `git notes copy` does. Returns the new notes commit.
+///
+/// The rewrites are followed to the latest versions, which inherit the note
+/// of the commit they were rewritten from unless they already have one.
+fn copy_notes_for_rewritten_commits(
+    mut_repo: &MutableRepo,
+    git_repo: &gix::Repository,
+    notes_commit_id: gix::ObjectId,
+) -> Result<gix::ObjectId, GitNotesError> {
+    let rewritten_commits = mut_repo.rewritten_commits();
+    if rewritten_commits.is_empty() {
+        return Ok(notes_commit_id);
+    }
+    let tree_id = notes_tree_id(git_repo, notes_commit_id)?;
+    let base_index = mut_repo.base_repo().index();
+    let mut copied_notes = BTreeMap::new();
+    // Commits created in this transaction have no notes to copy.
+    for (old_id, new_ids) in rewritten_commits
+        .iter()
+        .filter(|(old_id, _)| base_index.has_id(old_id))
+    {
+        let Some(blob_id) = find_note(git_repo, tree_id, &old_id.hex())? else {
+            continue;
+        };
+        let mut pending = new_ids.iter().collect_vec();
+        let mut seen = HashSet::new();
+        while let Some(id) = pending.pop() {
+            if !seen.insert(id) {
+                continue;
+            }
+            if let Some(new_ids) = rewritten_commits.get(id) {
+                pending.extend(new_ids);
+            } else if find_note(git_repo, tree_id, &id.hex())?.is_none() {
+                copied_notes.entry(id.hex()).or_insert(blob_id);
+            }
+        }
+    }
+    if copied_notes.is_empty() {
+        return Ok(notes_commit_id);
+    }
+
+    let mut notes = read_notes(git_repo, tree_id)?;
+    notes.extend(copied_notes);
+    write_notes_commit(
+        git_repo,
+        &notes,
+        [notes_commit_id],
+        &mut_repo.settings().signature(),
+        "Notes added by 'jj' for rewritten commits\n",
+    )
+}
+
+/// Pushes the local `refs/notes/commits` to the remote.
+///
+/// The remote is expected to be at the position of the notes fetched last
+/// time. Returns `None` if there's nothing to push.
+pub fn push_notes(
+    repo: &dyn Repo,
+    git_settings: &GitSettings,
+    remote_name: &RemoteName,
+    options: &GitPushOptions,
+    callbacks: RemoteCallbacks<'_>,
+) -> Result<Option<GitPushStats>, GitPushError> {
+    validate_remote_name(remote_name)?;
+    let git_repo = get_git_repo(repo.store())?;
+    let Some(local_commit_id) = resolve_notes_commit(&git_repo, GIT_NOTES_REF)? else {
+        return Ok(None);
+    };
+    let tracking_ref = remote_notes_ref_name(remote_name, GIT_NOTES_REF);
+    let remote_commit_id = resolve_notes_commit(&git_repo, &tracking_ref)?;
+    if remote_commit_id == Some(local_commit_id) {
+        return Ok(None);
+    }
+
+    let to_commit_id = |id: gix::ObjectId| CommitId::from_bytes(id.as_bytes());
+    let update = GitRefUpdate {
+        qualified_name: GIT_NOTES_REF.into(),
+        expected_current_target: remote_commit_id.map(to_commit_id),
+        new_target: Some(to_commit_id(local_commit_id)),
+    };
+    let push_stats = push_updates(
+        repo,
+        git_settings,
+        remote_name,
+        &[update],
+        options,
+        callbacks,
+    )?;
+    if push_stats.all_ok() {
+        update_notes_ref(
+            &git_repo,
+            &tracking_ref,
+            remote_commit_id,
+            local_commit_id,
+            "jj: push notes",
+        )?;
+    }
+    Ok(Some(push_stats))
+}
+
+/// Returns the ref the notes `notes_ref` fetched from the remote are stored
+/// in, e.g. `refs/notes/remotes/origin/commits`.
+fn remote_notes_ref_name(remote: &RemoteName, notes_ref: &str) -> String {
+    let name = notes_ref.strip_prefix("refs/notes/").unwrap_or(notes_ref);
+    format!(
+        "refs/notes/remotes/{remote}/{name}",
+        remote = remote.as_str()
+    )
+}
+
+fn notes_fetch_refspec(remote: &RemoteName) -> RefSpec {
+    RefSpec::forced(
+        "refs/notes/*",
+        format!("refs/notes/remotes/{remote}/*", remote = remote.as_str()),
+    )
+}
+
+/// Merges the notes fetched from the `remote` into the local notes refs.
+///
+/// Fast-forwards the local notes if possible. Otherwise, a merge commit is
+/// created, in which local notes take precedence over the remote ones.
+fn import_remote_notes(
+    git_repo: &gix::Repository,
+    remote: &RemoteName,
+    signature: &Signature,
+) -> Result<(), GitNotesError> {
+    let prefix = format!("refs/notes/remotes/{remote}/", remote = remote.as_str());
+    let remote_notes: Vec<(String, gix::ObjectId)> = git_repo
+        .references()
+        .map_err(GitNotesError::from_git)?
+        .prefixed(prefix.clone())
+        .map_err(GitNotesError::from_git)?
+        .map(|reference| {
+            let reference = reference.map_err(GitNotesError::from_git)?;
+            let name = reference.name().as_bstr().to_string();
+            let id = reference
+                .into_fully_peeled_id()
+                .map_err(GitNotesError::from_git)?;
+            Ok::<_, GitNotesError>((name, id.detach()))
+        })
+        .try_collect()?;
+    for (remote_ref, remote_commit_id) in remote_notes {
+        let local_ref = notes_ref_name(&remote_ref[prefix.len()..]);
+        let local_commit_id = resolve_notes_commit(git_repo, &local_ref)?;
+        let new_commit_id = match local_commit_id {
+            None => remote_commit_id,
+            Some(local_commit_id) => {
+                if is_notes_ancestor(git_repo, remote_commit_id, local_commit_id)? {
+                    continue;
+                } else if is_notes_ancestor(git_repo, local_commit_id, remote_commit_id)? {
+                    remote_commit_id
+                } else {
+                    let mut notes =
+                        read_notes(git_repo, notes_tree_id(git_repo, remote_commit_id)?)?;
+                    notes.extend(read_notes(
+                        git_repo,
+                        notes_tree_id(git_repo, local_commit_id)?,
+                    )?);
+                    write_notes_commit(
+                        git_repo,
+                        &notes,
+                        [local_commit_id, remote_commit_id],
+                        signature,
+                        &format!("Notes merged from {remote_ref}\n"),
+                    )?
+                }
+            }
+        };
+        update_notes_ref(
+            git_repo,
+            &local_ref,
+            local_commit_id,
+            new_commit_id,
+            "jj: import notes",
+        )?;
+    }
+    Ok(())
+}
+
+fn resolve_notes_commit(
+    git_repo: &gix::Repository,
+    notes_ref: &str,
+) -> Result<Option<gix::ObjectId>, GitNotesError> {
+    let Some(reference) = git_repo
+        .try_find_reference(notes_ref)
+        .map_err(GitNotesError::from_git)?
+    else {
+        return Ok(None);
+    };
+    let id = reference
+        .into_fully_peeled_id()
+        .map_err(GitNotesError::from_git)?;
+    Ok(Some(id.detach()))
+}
+
+fn notes_tree_id(
+    git_repo: &gix::Repository,
+    notes_commit_id: gix::ObjectId,
+) -> Result<gix::ObjectId, GitNotesError> {
+    let commit = git_repo
+        .find_commit(notes_commit_id)
+        .map_err(GitNotesError::from_git)?;
+    let tree_id = commit.tree_id().map_err(GitNotesError::from_git)?;
+    Ok(tree_id.detach())
+}
+
+/// Looks up the note blob for the object `hex`. Git splits large notes trees
+/// into subtrees named after the leading digits of the annotated objects.
+fn find_note(
+    git_repo: &gix::Repository,
+    tree_id: gix::ObjectId,
+    hex: &str,
+) -> Result<Option<gix::ObjectId>, GitNotesError> {
+    let tree = git_repo
+        .find_tree(tree_id)
+        .map_err(GitNotesError::from_git)?;
+    for entry in tree.iter() {
+        let entry = entry.map_err(GitNotesError::from_git)?;
+        let Ok(name) = str::from_utf8(entry.filename()) else {
+            continue;
+        };
+        if entry.mode().is_tree() {
+            if let Some(rest) = hex.strip_prefix(name) {
+                return find_note(git_repo, entry.oid().to_owned(), rest);
+            }
+        } else if name == hex {
+            return Ok(Some(entry.oid().to_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// Reads all notes in the tree, keyed by the hex of the annotated objects.
+fn read_notes(
+    git_repo: &gix::Repository,
+    tree_id: gix::ObjectId,
+) -> Result<BTreeMap<String, gix::ObjectId>, GitNotesError> {
+    let mut notes = BTreeMap::new();
+    let mut pending = vec![(String::new(), tree_id)];
+    while let Some((prefix, tree_id)) = pending.pop() {
+        let tree = git_repo
+            .find_tree(tree_id)
+            .map_err(GitNotesError::from_git)?;
+        for entry in tree.iter() {
+            let entry = entry.map_err(GitNotesError::from_git)?;
+            let Ok(name) = str::from_utf8(entry.filename()) else {
+                continue;
+            };
+            if !name.bytes().all(|b| b.is_ascii_hexdigit()) {
+                continue;
+            }
+            let path = format!("{prefix}{name}");
+            if entry.mode().is_tree() {
+                pending.push((path, entry.oid().to_owned()));
+            } else {
+                notes.insert(path, entry.oid().to_owned());
+            }
+        }
+    }
+    Ok(notes)
+}
+
+/// Writes a notes commit with all `notes` in a flat tree.
+fn write_notes_commit(
+    git_repo: &gix::Repository,
+    notes: &BTreeMap<String, gix::ObjectId>,
+    parents: impl IntoIterator<Item = gix::ObjectId>,
+    signature: &Signature,
+    message: &str,
+) -> Result<gix::ObjectId, GitNotesError> {
+    // Entries of blobs sort by their names, which the map is ordered by.
+    let entries = notes
+        .iter()
+        .map(|(name, oid)| gix::objs::tree::Entry {
+            mode: gix::object::tree::EntryKind::Blob.into(),
+            filename: name.as_str().into(),
+            oid: *oid,
+        })
+        .collect();
+    let tree_id = git_repo
+        .write_object(gix::objs::Tree { entries })
+        .map_err(GitNotesError::from_git)?;
+    let signature: gix::actor::Signature = signature_to_git(signature).into();
+    let commit = gix::objs::Commit {
+        tree: tree_id.detach(),
+        parents: parents.into_iter().collect(),
+        author: signature.clone(),
+        committer: signature,
+        encoding: None,
+        message: message.into(),
+        extra_headers: vec![],
+    };
+    let commit_id = git_repo
+        .write_object(&commit)
+        .map_err(GitNotesError::from_git)?;
+    Ok(commit_id.detach())
+}
+
+fn update_notes_ref(
+    git_repo: &gix::Repository,
+    notes_ref: &str,
+    old_commit_id: Option<gix::ObjectId>,
+    new_commit_id: gix::ObjectId,
+    log_message: &str,
+) -> Result<(), GitNotesError> {
+    let constraint = match old_commit_id {
+        Some(id) => gix::refs::transaction::PreviousValue::MustExistAndMatch(id.into()),
+        None => gix::refs::transaction::PreviousValue::MustNotExist,
+    };
+    git_repo
+        .reference(notes_ref, new_commit_id, constraint, log_message)
+        .map_err(GitNotesError::from_git)?;
+    Ok(())
+}
+
+fn is_notes_ancestor(
+    git_repo: &gix::Repository,
+    ancestor_id: gix::ObjectId,
+    descendant_id: gix::ObjectId,
+) -> Result<bool, GitNotesError> {
+    let mut pending = vec![descendant_id];
+    let mut seen = HashSet::new();
+    while let Some(id) = pending.pop() {
+        if id == ancestor_id {
+            return Ok(true);
+        }
+        if !seen.insert(id) {
+            continue;
+        }
+        let commit = git_repo.find_commit(id).map_err(GitNotesError::from_git)?;
+        pending.extend(commit.parent_ids().map(|id| id.detach()));
+    }
+    Ok(false)
+}
+
+#[derive(Debug, Error)]
+pub enum GitBundleError {
+    #[error(transparent)]
+    Io(#[from] PathError),
+    #[error(transparent)]
+    Subprocess(#[from] GitSubprocessError),
+    #[error(transparent)]
+    UnexpectedBackend(#[from] UnexpectedGitBackendError),
+}
+
+/// Writes a Git bundle to `path` with the given `refs`. The bundle contains the
+/// commits reachable from the refs but not from the `prerequisites`, which the
+/// receiving repo must already have.
+///
+/// The bundle can be fetched from by adding its path as a remote.
+pub fn create_bundle(
+    store: &Store,
+    git_settings: &GitSettings,
+    path: &Path,
+    refs: &[(GitRefNameBuf, CommitId)],
+    prerequisites: &[CommitId],
+) -> Result<(), GitBundleError> {
+    let git_backend = get_git_backend(store)?;
+    let mut file = File::create(path).context(path)?;
+    let mut header = "# v2 git bundle\n".to_owned();
+    for id in prerequisites {
+        header.push_str(&format!("-{}\n", id.hex()));
+    }
+    for (name, id) in refs {
+        header.push_str(&format!("{} {}\n", id.hex(), name.as_str()));
+    }
+    header.push('\n');
+    file.write_all(header.as_bytes()).context(path)?;
+    // libgit2 doesn't support bundles, so the pack is always written by the
+    // Git subprocess.
+    let git_ctx =
+        GitSubprocessContext::from_git_backend(git_backend, &git_settings.executable_path);
+    let heads = refs.iter().map(|(_, id)| id.clone()).collect_vec();
+    git_ctx.spawn_pack_objects(&heads, prerequisites, file)?;
+    Ok(())
+}
+
 #[non_exhaustive]
 #[derive(Default)]
 #[expect(clippy::type_complexity)]
@@ -20,6 +20,7 @@ use std::fmt::Debug;
 use std::fmt::Error;
 use std::fmt::Formatter;
 use std::fs;
+use std::future;
 use std::io;
 use std::io::Cursor;
 use std::io::Read;
@@ -29,13 +30,16 @@ use std::process::Command;
 use std::process::ExitStatus;
 use std::str;
 use std::sync::Arc;
+use std::sync::Condvar;
 use std::sync::Mutex;
 use std::sync::MutexGuard;
+use std::task::Poll;
 use std::time::SystemTime;
 
 use async_trait::async_trait;
 use futures::stream::BoxStream;
 use gix::bstr::BString;
+use gix::bstr::ByteSlice as _;
 use gix::objs::CommitRef;
 use gix::objs::CommitRefIter;
 use gix::objs::WriteTo as _;
@@ -71,6 +75,8 @@ use crate::backend::TreeId;
 use crate::backend::TreeValue;
 use crate::file_util::IoResultExt as _;
 use crate::file_util::PathError;
+use crate::git_subprocess::GitSubprocessContext;
+use crate::git_subprocess::GitSubprocessError;
 use crate::index::Index;
 use crate::lock::FileLock;
 use crate::merge::Merge;
@@ -91,6 +97,9 @@ const CHANGE_ID_LENGTH: usize = 16;
 /// Ref namespace used only for preventing GC.
 const NO_GC_REF_NAMESPACE: &str = "refs/jj/keep/";
 const CONFLICT_SUFFIX: &str = ".jjconflict";
+/// Number of objects to read concurrently from a partial clone, which is also
+/// the maximum number of missing objects fetched at once.
+const PARTIAL_CLONE_CONCURRENCY: usize = 64;
 
 pub const JJ_TREES_COMMIT_HEADER: &[u8] = b"jj:trees";
 
@@ -159,6 +168,8 @@ pub struct GitBackend {
     empty_tree_id: TreeId,
     extra_metadata_store: TableStore,
     cached_extra_metadata: Mutex<Option<Arc<ReadonlyTable>>>,
+    /// Set if the repo is a partial clone.
+    promisor_fetcher: Option<PromisorFetcher>,
 }
 
 impl GitBackend {
@@ -166,8 +177,20 @@ impl GitBackend {
         "git"
     }
 
-    fn new(base_repo: gix::ThreadSafeRepository, extra_metadata_store: TableStore) -> Self {
-        let repo = Mutex::new(base_repo.to_thread_local());
+    fn new(
+        base_repo: gix::ThreadSafeRepository,
+        extra_metadata_store: TableStore,
+        git_executable_path: PathBuf,
+    ) -> Self {
+        let repo = base_repo.to_thread_local();
+        let promisor_fetcher = find_promisor_remote(&repo).map(|remote_name| PromisorFetcher {
+            git_dir: repo.path().to_owned(),
+            git_executable_path,
+            remote_name,
+            state: Mutex::new(PromisorFetchState::default()),
+            fetched: Condvar::new(),
+        });
+        let repo = Mutex::new(repo);
         let root_commit_id = CommitId::from_bytes(&[0; HASH_LENGTH]);
         let root_change_id = ChangeId::from_bytes(&[0; CHANGE_ID_LENGTH]);
         let empty_tree_id = TreeId::from_hex("4b825dc642cb6eb9a060e54bf8d69288fbee4904");
@@ -179,6 +202,7 @@ impl GitBackend {
             empty_tree_id,
             extra_metadata_store,
             cached_extra_metadata: Mutex::new(None),
+            promisor_fetcher,
         }
     }
 
@@ -194,7 +218,8 @@ impl GitBackend {
             gix_open_opts_from_settings(settings),
         )
         .map_err(GitBackendInitError::InitRepository)?;
-        Self::init_with_repo(store_path, git_repo_path, git_repo)
+        let git_executable_path = git_executable_path_from_settings(settings);
+        Self::init_with_repo(store_path, git_repo_path, git_repo, git_executable_path)
     }
 
     /// Initializes backend by creating a new Git repo at the specified
@@ -218,7 +243,8 @@ impl GitBackend {
         )
         .map_err(GitBackendInitError::InitRepository)?;
         let git_repo_path = workspace_root.join(".git");
-        Self::init_with_repo(store_path, &git_repo_path, git_repo)
+        let git_executable_path = git_executable_path_from_settings(settings);
+        Self::init_with_repo(store_path, &git_repo_path, git_repo, git_executable_path)
     }
 
     /// Initializes backend with an existing Git repo at the specified path.
@@ -238,13 +264,15 @@ impl GitBackend {
             gix_open_opts_from_settings(settings),
         )
         .map_err(GitBackendInitError::OpenRepository)?;
-        Self::init_with_repo(store_path, git_repo_path, git_repo)
+        let git_executable_path = git_executable_path_from_settings(settings);
+        Self::init_with_repo(store_path, git_repo_path, git_repo, git_executable_path)
     }
 
     fn init_with_repo(
         store_path: &Path,
         git_repo_path: &Path,
         git_repo: gix::ThreadSafeRepository,
+        git_executable_path: PathBuf,
     ) -> Result<Self, Box<GitBackendInitError>> {
         let extra_path = store_path.join("extra");
         fs::create_dir(&extra_path)
@@ -271,7 +299,11 @@ impl GitBackend {
                 .map_err(GitBackendInitError::Path)?;
         };
         let extra_metadata_store = TableStore::init(extra_path, HASH_LENGTH);
-        Ok(GitBackend::new(git_repo, extra_metadata_store))
+        Ok(GitBackend::new(
+            git_repo,
+            extra_metadata_store,
+            git_executable_path,
+        ))
     }
 
     pub fn load(
@@ -294,13 +326,32 @@ impl GitBackend {
         )
         .map_err(GitBackendLoadError::OpenRepository)?;
         let extra_metadata_store = TableStore::load(store_path.join("extra"), HASH_LENGTH);
-        Ok(GitBackend::new(repo, extra_metadata_store))
+        let git_executable_path = git_executable_path_from_settings(settings);
+        Ok(GitBackend::new(
+            repo,
+            extra_metadata_store,
+            git_executable_path,
+        ))
     }
 
     fn lock_git_repo(&self) -> MutexGuard<'_, gix::Repository> {
         self.repo.lock().unwrap()
     }
 
+    /// Returns the commits at the boundary of the shallow Git repo. Their
+    /// parents aren't present in the repo.
+    pub fn shallow_commits(&self) -> BackendResult<HashSet<CommitId>> {
+        let locked_repo = self.lock_git_repo();
+        let shallow_commits = locked_repo
+            .shallow_commits()
+            .map_err(|err| BackendError::Other(err.into()))?;
+        Ok(shallow_commits
+            .iter()
+            .flat_map(|commits| commits.iter())
+            .map(|oid| CommitId::from_bytes(oid.as_bytes()))
+            .collect())
+    }
+
     /// Returns new thread-local instance to access to the underlying Git repo.
     pub fn git_repo(&self) -> gix::Repository {
         self.base_repo.to_thread_local()
@@ -395,6 +446,22 @@ impl GitBackend {
         self.save_extra_metadata_table(mut_table, &table_lock)
     }
 
+    /// Fetches the object from the promisor remote if this is a partial clone
+    /// and the object is missing locally.
+    async fn fetch_if_missing(&self, id: &impl ObjectId) -> BackendResult<()> {
+        let Some(fetcher) = &self.promisor_fetcher else {
+            return Ok(());
+        };
+        let git_object_id = validate_git_object_id(id)?;
+        if self.lock_git_repo().has_object(git_object_id) {
+            return Ok(());
+        }
+        fetcher
+            .fetch(git_object_id)
+            .await
+            .map_err(|err| to_read_object_err(err, id))
+    }
+
     fn read_file_sync(&self, id: &FileId) -> BackendResult<Box<dyn Read>> {
         let git_blob_id = validate_git_object_id(id)?;
         let locked_repo = self.lock_git_repo();
@@ -464,6 +531,101 @@ pub fn canonicalize_git_repo_path(path: &Path) -> io::Result<PathBuf> {
     }
 }
 
+fn git_executable_path_from_settings(settings: &UserSettings) -> PathBuf {
+    // The Git executable is only needed to fetch missing objects of a partial
+    // clone, so don't fail to load the backend because of a bad config.
+    settings
+        .get("git.executable-path")
+        .unwrap_or_else(|_| PathBuf::from("git"))
+}
+
+/// Returns the name of the remote from which missing objects can be fetched if
+/// the repo is a partial clone.
+fn find_promisor_remote(repo: &gix::Repository) -> Option<String> {
+    let config = repo.config_snapshot();
+    if let Some(name) = config.string("extensions.partialClone") {
+        return Some(name.to_str_lossy().into_owned());
+    }
+    repo.remote_names()
+        .into_iter()
+        .find(|name| {
+            config
+                .boolean(format!("remote.{name}.promisor").as_str())
+                .unwrap_or(false)
+        })
+        .map(|name| name.to_str_lossy().into_owned())
+}
+
+/// Fetches objects missing from a partial clone from the promisor remote.
+///
+/// Concurrent requests are batched so that up to `Backend::concurrency()`
+/// objects are fetched by a single `git fetch`.
+struct PromisorFetcher {
+    git_dir: PathBuf,
+    git_executable_path: PathBuf,
+    remote_name: String,
+    state: Mutex<PromisorFetchState>,
+    fetched: Condvar,
+}
+
+#[derive(Default)]
+struct PromisorFetchState {
+    /// Objects requested, but not being fetched yet.
+    pending: Vec<gix::ObjectId>,
+    /// Objects being fetched.
+    in_flight: HashSet<gix::ObjectId>,
+}
+
+impl PromisorFetcher {
+    async fn fetch(&self, id: gix::ObjectId) -> Result<(), GitSubprocessError> {
+        {
+            let mut state = self.state.lock().unwrap();
+            if !state.in_flight.contains(&id) && !state.pending.contains(&id) {
+                state.pending.push(id);
+            }
+        }
+        // Let the other concurrent reads request their objects first.
+        yield_now().await;
+        let mut state = self.state.lock().unwrap();
+        if let Some(pos) = state.pending.iter().position(|&pending| pending == id) {
+            let mut batch = vec![state.pending.swap_remove(pos)];
+            let rest = state.pending.len().min(PARTIAL_CLONE_CONCURRENCY - 1);
+            batch.extend(state.pending.drain(..rest));
+            state.in_flight.extend(&batch);
+            drop(state);
+            let git_ctx = GitSubprocessContext::new(&self.git_dir, &self.git_executable_path);
+            let result = git_ctx.spawn_fetch_objects(&self.remote_name, &batch);
+            let mut state = self.state.lock().unwrap();
+            for id in &batch {
+                state.in_flight.remove(id);
+            }
+            self.fetched.notify_all();
+            result
+        } else {
+            // Fetched by another request, or being fetched.
+            while state.in_flight.contains(&id) {
+                state = self.fetched.wait(state).unwrap();
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Returns `Poll::Pending` once to let the other futures make progress.
+async fn yield_now() {
+    let mut yielded = false;
+    future::poll_fn(|cx| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await;
+}
+
 fn gix_open_opts_from_settings(settings: &UserSettings) -> gix::open::Options {
     let user_name = settings.user_name();
     let user_email = settings.user_email();
@@ -532,8 +694,9 @@ fn commit_from_git_without_root_parent(
             .collect(),
     );
     // shallow commits don't have parents their parents actually fetched, so we
-    // discard them here
-    // TODO: This causes issues when a shallow repository is deepened/unshallowed
+    // discard them here. When the repository is deepened, the parents will be
+    // read again, and the index has to be updated accordingly. See
+    // git::import_unshallowed_commits().
     let parents = if is_shallow {
         vec![]
     } else {
@@ -586,6 +749,7 @@ fn commit_from_git_without_root_parent(
         predecessors: vec![],
         // If this commit has associated extra metadata, we may reset this later.
         root_tree,
+        conflict_labels: vec![],
         change_id,
         description,
         author,
@@ -621,7 +785,7 @@ fn signature_from_git(signature: gix::actor::SignatureRef) -> Signature {
     }
 }
 
-fn signature_to_git(signature: &Signature) -> gix::actor::SignatureRef<'_> {
+pub(crate) fn signature_to_git(signature: &Signature) -> gix::actor::SignatureRef<'_> {
     // git does not support empty names or emails
     let name = if !signature.name.is_empty() {
         &signature.name
@@ -656,6 +820,7 @@ fn serialize_extras(commit: &Commit) -> Vec<u8> {
             // f7b14be) being able to read the repo. At some point in the
             // future, we can stop doing it.
             proto.root_tree = tree_ids.iter().map(|r| r.to_bytes()).collect();
+            proto.conflict_labels = commit.conflict_labels.clone();
         }
     }
     for predecessor in &commit.predecessors {
@@ -684,6 +849,7 @@ fn deserialize_extras(commit: &mut Commit, bytes: &[u8]) {
             }
         }
     }
+    commit.conflict_labels = proto.conflict_labels;
     for predecessor in &proto.predecessors {
         commit.predecessors.push(CommitId::from_bytes(predecessor));
     }
@@ -925,10 +1091,15 @@ impl Backend for GitBackend {
     }
 
     fn concurrency(&self) -> usize {
-        1
+        if self.promisor_fetcher.is_some() {
+            PARTIAL_CLONE_CONCURRENCY
+        } else {
+            1
+        }
     }
 
     async fn read_file(&self, _path: &RepoPath, id: &FileId) -> BackendResult<Box<dyn Read>> {
+        self.fetch_if_missing(id).await?;
         self.read_file_sync(id)
     }
 
@@ -950,6 +1121,7 @@ impl Backend for GitBackend {
     }
 
     async fn read_symlink(&self, _path: &RepoPath, id: &SymlinkId) -> BackendResult<String> {
+        self.fetch_if_missing(id).await?;
         let git_blob_id = validate_git_object_id(id)?;
         let locked_repo = self.lock_git_repo();
         let mut blob = locked_repo
@@ -978,6 +1150,7 @@ impl Backend for GitBackend {
         if id == &self.empty_tree_id {
             return Ok(Tree::default());
         }
+        self.fetch_if_missing(id).await?;
         let git_tree_id = validate_git_object_id(id)?;
 
         let locked_repo = self.lock_git_repo();
@@ -1878,6 +2051,7 @@ mod tests {
             parents: vec![],
             predecessors: vec![],
             root_tree: MergedTreeId::Legacy(backend.empty_tree_id().clone()),
+            conflict_labels: vec![],
             change_id: ChangeId::from_hex("abc123"),
             description: "".to_string(),
             author: create_signature(),
@@ -1964,6 +2138,9 @@ mod tests {
             parents: vec![backend.root_commit_id().clone()],
             predecessors: vec![],
             root_tree: MergedTreeId::Merge(root_tree.clone()),
+            conflict_labels: ["side 0", "base 0", "side 1", "base 1", "side 2"]
+                .map(str::to_owned)
+                .to_vec(),
             change_id: ChangeId::from_hex("abc123"),
             description: "".to_string(),
             author: create_signature(),
@@ -2030,6 +2207,7 @@ mod tests {
         // When writing a single tree using the new format, it's represented by a
         // regular git tree.
         commit.root_tree = MergedTreeId::resolved(create_tree(5));
+        commit.conflict_labels = vec![];
         let read_commit_id = write_commit(commit.clone()).unwrap().0;
         let read_commit = backend.read_commit(&read_commit_id).block_on().unwrap();
         assert_eq!(read_commit, commit);
@@ -2062,6 +2240,7 @@ mod tests {
             parents: vec![backend.root_commit_id().clone()],
             predecessors: vec![],
             root_tree: MergedTreeId::Legacy(backend.empty_tree_id().clone()),
+            conflict_labels: vec![],
             change_id: ChangeId::new(vec![]),
             description: "initial".to_string(),
             author: signature.clone(),
@@ -2140,6 +2319,7 @@ mod tests {
             parents: vec![backend.root_commit_id().clone()],
             predecessors: vec![],
             root_tree: MergedTreeId::Legacy(backend.empty_tree_id().clone()),
+            conflict_labels: vec![],
             change_id: ChangeId::new(vec![]),
             description: "initial".to_string(),
             author: create_signature(),
@@ -2182,6 +2362,7 @@ mod tests {
             parents: vec![backend.root_commit_id().clone()],
             predecessors: vec![],
             root_tree: MergedTreeId::Legacy(backend.empty_tree_id().clone()),
+            conflict_labels: vec![],
             change_id: ChangeId::new(vec![]),
             description: "initial".to_string(),
             author: create_signature(),
@@ -0,0 +1,521 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Git LFS (Large File Storage) support.
+//!
+//! Files whose `filter` attribute is `lfs` are committed as small pointer
+//! files. Their actual contents live in an object store addressed by SHA-256
+//! hash, which is `.git/lfs/objects` for the Git backend. Contents are "cleaned"
+//! into the object store when the working copy is snapshotted, and pointers are
+//! "smudged" back into contents when files are checked out.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use futures::StreamExt as _;
+use pollster::FutureExt as _;
+use sha2::Digest as _;
+use sha2::Sha256;
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+use crate::backend::BackendError;
+use crate::backend::TreeValue;
+use crate::commit::Commit;
+use crate::file_util::persist_content_addressed_temp_file;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::gitattributes::GitAttributesError;
+use crate::gitattributes::GitAttributesFile;
+use crate::gitattributes::TreeGitAttributes;
+use crate::matchers::EverythingMatcher;
+use crate::merged_tree::MergedTree;
+use crate::merged_tree::TreeDiffEntry;
+use crate::repo::Repo;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::tree::Tree;
+
+/// Version line of the pointer files written by this module.
+pub const POINTER_VERSION: &str = "https://git-lfs.github.com/spec/v1";
+
+/// Pointer files larger than this are never parsed as pointers.
+const MAX_POINTER_SIZE: u64 = 1024;
+
+/// Error that may occur while handling LFS objects.
+#[derive(Debug, Error)]
+pub enum GitLfsError {
+    /// Failed to access the object store.
+    #[error(transparent)]
+    Path(#[from] PathError),
+    /// Failed to read the file contents to clean or smudge.
+    #[error("Failed to read file contents")]
+    ReadContent(#[source] io::Error),
+    /// The object store doesn't have the object.
+    #[error("LFS object {oid} not found in {}", location.display())]
+    ObjectNotFound {
+        /// SHA-256 hash of the object.
+        oid: String,
+        /// Location of the object store.
+        location: PathBuf,
+    },
+    /// The transferred object doesn't match its pointer.
+    #[error("LFS object {oid} is corrupt")]
+    CorruptObject {
+        /// SHA-256 hash of the object.
+        oid: String,
+    },
+    /// Failed to read `.gitattributes` files.
+    #[error(transparent)]
+    GitAttributes(#[from] GitAttributesError),
+    /// Failed to read the pointer files.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
+/// Content of an LFS pointer file.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct LfsPointer {
+    /// Hex-encoded SHA-256 hash of the object.
+    pub oid: String,
+    /// Size of the object in bytes.
+    pub size: u64,
+}
+
+impl LfsPointer {
+    /// Parses pointer file content. Returns `None` if the content isn't a
+    /// valid pointer.
+    pub fn parse(content: &[u8]) -> Option<Self> {
+        if content.len() as u64 > MAX_POINTER_SIZE {
+            return None;
+        }
+        let text = std::str::from_utf8(content).ok()?;
+        let mut lines = text.lines();
+        let version = lines.next()?.strip_prefix("version ")?;
+        if version != POINTER_VERSION && version != "https://hawser.github.com/spec/v1" {
+            return None;
+        }
+        let mut oid = None;
+        let mut size = None;
+        for line in lines {
+            let (key, value) = line.split_once(' ')?;
+            match key {
+                "oid" => oid = Some(value.strip_prefix("sha256:")?),
+                "size" => size = Some(value.parse().ok()?),
+                _ => {}
+            }
+        }
+        let oid = oid?;
+        if oid.len() != 64 || !oid.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+            return None;
+        }
+        Some(LfsPointer {
+            oid: oid.to_owned(),
+            size: size?,
+        })
+    }
+
+    /// Formats the pointer file content.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        format!(
+            "version {POINTER_VERSION}\noid sha256:{}\nsize {}\n",
+            self.oid, self.size
+        )
+        .into_bytes()
+    }
+}
+
+/// Returns true if the file at `path` should be stored in LFS according to
+/// the `attributes`.
+pub fn is_lfs_file(attributes: &GitAttributesFile, path: &RepoPath) -> bool {
+    attributes
+        .get(path.as_internal_file_string(), "filter")
+        .is_value("lfs")
+}
+
+/// Store of LFS objects in the local file system.
+///
+/// Objects are stored at `<objects_dir>/<oid[0..2]>/<oid[2..4]>/<oid>` as Git
+/// LFS does.
+#[derive(Debug)]
+pub struct LfsObjectStore {
+    objects_dir: PathBuf,
+}
+
+impl LfsObjectStore {
+    /// Creates a store backed by the given directory.
+    pub fn new(objects_dir: PathBuf) -> Self {
+        LfsObjectStore { objects_dir }
+    }
+
+    /// Returns the store of the Git repository at `git_dir`.
+    pub fn for_git_dir(git_dir: &Path) -> Self {
+        Self::new(git_dir.join("lfs").join("objects"))
+    }
+
+    /// Directory where objects are stored.
+    pub fn objects_dir(&self) -> &Path {
+        &self.objects_dir
+    }
+
+    fn object_path(&self, oid: &str) -> PathBuf {
+        self.objects_dir.join(&oid[0..2]).join(&oid[2..4]).join(oid)
+    }
+
+    /// Returns true if the object referenced by the `pointer` exists.
+    pub fn has_object(&self, pointer: &LfsPointer) -> bool {
+        self.object_path(&pointer.oid).is_file()
+    }
+
+    /// Opens the object referenced by the `pointer`.
+    pub fn open_object(&self, pointer: &LfsPointer) -> Result<File, GitLfsError> {
+        let path = self.object_path(&pointer.oid);
+        match File::open(&path) {
+            Ok(file) => Ok(file),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Err(GitLfsError::ObjectNotFound {
+                oid: pointer.oid.clone(),
+                location: self.objects_dir.clone(),
+            }),
+            Err(err) => Err(PathError { path, error: err }.into()),
+        }
+    }
+
+    /// Writes the `contents` as a new object, and returns the pointer to it.
+    pub fn write_object(&self, contents: &mut dyn Read) -> Result<LfsPointer, GitLfsError> {
+        self.write_object_with_head(&[], contents)
+    }
+
+    fn write_object_with_head(
+        &self,
+        head: &[u8],
+        contents: &mut dyn Read,
+    ) -> Result<LfsPointer, GitLfsError> {
+        let temp_dir = self.objects_dir.join("tmp");
+        fs::create_dir_all(&temp_dir).context(&temp_dir)?;
+        let mut temp_file = NamedTempFile::new_in(&temp_dir).context(&temp_dir)?;
+        let mut hasher = Sha256::new();
+        let mut size = 0;
+        let mut write_chunk = |chunk: &[u8]| -> Result<(), GitLfsError> {
+            hasher.update(chunk);
+            size += chunk.len() as u64;
+            temp_file
+                .as_file_mut()
+                .write_all(chunk)
+                .context(temp_file.path())?;
+            Ok(())
+        };
+        write_chunk(head)?;
+        let mut buf = vec![0; 64 * 1024];
+        loop {
+            let n = match contents.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(GitLfsError::ReadContent(err)),
+            };
+            write_chunk(&buf[..n])?;
+        }
+        let pointer = LfsPointer {
+            oid: hex::encode(hasher.finalize()),
+            size,
+        };
+        let object_path = self.object_path(&pointer.oid);
+        let object_dir = object_path.parent().unwrap();
+        fs::create_dir_all(object_dir).context(object_dir)?;
+        persist_content_addressed_temp_file(temp_file, &object_path).context(&object_path)?;
+        Ok(pointer)
+    }
+
+    /// Moves the `contents` of a file into the store, and returns the pointer
+    /// to be committed instead.
+    ///
+    /// If the `contents` is already a pointer, it's returned as is.
+    pub fn clean(&self, contents: &mut dyn Read) -> Result<LfsPointer, GitLfsError> {
+        let head = read_head(contents)?;
+        if let Some(pointer) = LfsPointer::parse(&head) {
+            return Ok(pointer);
+        }
+        self.write_object_with_head(&head, contents)
+    }
+
+    /// Replaces the `contents` of a pointer file with the object it points to.
+    ///
+    /// If the `contents` isn't a pointer, or if the object doesn't exist in
+    /// the store, the `contents` is returned as is.
+    pub fn smudge<'a>(
+        &self,
+        contents: &'a mut dyn Read,
+    ) -> Result<Box<dyn Read + 'a>, GitLfsError> {
+        let head = read_head(contents)?;
+        if let Some(pointer) = LfsPointer::parse(&head) {
+            match self.open_object(&pointer) {
+                Ok(file) => return Ok(Box::new(file)),
+                Err(GitLfsError::ObjectNotFound { .. }) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(Box::new(Cursor::new(head).chain(contents)))
+    }
+}
+
+/// Reads up to one byte more than the largest pointer file.
+fn read_head(contents: &mut dyn Read) -> Result<Vec<u8>, GitLfsError> {
+    let mut head = vec![];
+    contents
+        .take(MAX_POINTER_SIZE + 1)
+        .read_to_end(&mut head)
+        .map_err(GitLfsError::ReadContent)?;
+    Ok(head)
+}
+
+/// Stats about an LFS transfer.
+#[derive(Clone, Debug, Default)]
+pub struct LfsTransferStats {
+    /// Number of objects transferred.
+    pub transferred: usize,
+    /// Objects that couldn't be transferred because the source doesn't have
+    /// them.
+    pub missing: Vec<LfsPointer>,
+}
+
+/// Transfers LFS objects between the local store and an LFS server.
+pub trait LfsTransport {
+    /// Uploads the objects that the server doesn't have yet.
+    fn upload(
+        &self,
+        store: &LfsObjectStore,
+        pointers: &[LfsPointer],
+    ) -> Result<LfsTransferStats, GitLfsError>;
+
+    /// Downloads the objects that the local `store` doesn't have yet.
+    fn download(
+        &self,
+        store: &LfsObjectStore,
+        pointers: &[LfsPointer],
+    ) -> Result<LfsTransferStats, GitLfsError>;
+}
+
+/// Transport to an LFS object store in the local file system. Used for remotes
+/// that are local paths.
+#[derive(Debug)]
+pub struct LocalLfsTransport {
+    server: LfsObjectStore,
+}
+
+impl LocalLfsTransport {
+    /// Creates a transport that stores objects in `objects_dir`.
+    pub fn new(objects_dir: PathBuf) -> Self {
+        LocalLfsTransport {
+            server: LfsObjectStore::new(objects_dir),
+        }
+    }
+
+    /// Returns the directory where objects are stored.
+    pub fn objects_dir(&self) -> &Path {
+        self.server.objects_dir()
+    }
+}
+
+impl LfsTransport for LocalLfsTransport {
+    fn upload(
+        &self,
+        store: &LfsObjectStore,
+        pointers: &[LfsPointer],
+    ) -> Result<LfsTransferStats, GitLfsError> {
+        copy_objects(store, &self.server, pointers)
+    }
+
+    fn download(
+        &self,
+        store: &LfsObjectStore,
+        pointers: &[LfsPointer],
+    ) -> Result<LfsTransferStats, GitLfsError> {
+        copy_objects(&self.server, store, pointers)
+    }
+}
+
+fn copy_objects(
+    source: &LfsObjectStore,
+    destination: &LfsObjectStore,
+    pointers: &[LfsPointer],
+) -> Result<LfsTransferStats, GitLfsError> {
+    let mut stats = LfsTransferStats::default();
+    for pointer in pointers {
+        if destination.has_object(pointer) {
+            continue;
+        }
+        let mut file = match source.open_object(pointer) {
+            Ok(file) => file,
+            Err(GitLfsError::ObjectNotFound { .. }) => {
+                stats.missing.push(pointer.clone());
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        let written = destination.write_object(&mut file)?;
+        if written != *pointer {
+            return Err(GitLfsError::CorruptObject {
+                oid: pointer.oid.clone(),
+            });
+        }
+        stats.transferred += 1;
+    }
+    Ok(stats)
+}
+
+/// Collects the pointers of LFS files added or modified between `from_tree`
+/// and `to_tree`. Attributes are read from `to_tree`.
+pub async fn lfs_pointers_in_diff(
+    from_tree: &MergedTree,
+    to_tree: &MergedTree,
+) -> Result<BTreeSet<LfsPointer>, GitLfsError> {
+    let store = to_tree.store();
+    let mut attributes = TreeGitAttributes::new(to_tree.clone(), GitAttributesFile::empty());
+    let mut pointers = BTreeSet::new();
+    let mut diff_stream = from_tree.diff_stream(to_tree, &EverythingMatcher);
+    while let Some(TreeDiffEntry { path, values }) = diff_stream.next().await {
+        let (_before, after) = values?;
+        let Some(Some(TreeValue::File { id, .. })) = after.as_resolved() else {
+            continue;
+        };
+        if !is_lfs_file(&attributes.for_file(&path).await?, &path) {
+            continue;
+        }
+        let mut reader = store.read_file_async(&path, id).await?;
+        if let Some(pointer) = LfsPointer::parse(&read_head(&mut reader)?) {
+            pointers.insert(pointer);
+        }
+    }
+    Ok(pointers)
+}
+
+/// Collects the pointers of all LFS files in the `tree`.
+pub fn lfs_pointers_in_tree(tree: &MergedTree) -> Result<Vec<LfsPointer>, GitLfsError> {
+    let empty_tree = MergedTree::resolved(Tree::empty(tree.store().clone(), RepoPathBuf::root()));
+    let pointers = lfs_pointers_in_diff(&empty_tree, tree).block_on()?;
+    Ok(pointers.into_iter().collect())
+}
+
+/// Collects the pointers of LFS files introduced by the `commits`.
+pub fn lfs_pointers_in_commits(
+    repo: &dyn Repo,
+    commits: &[Commit],
+) -> Result<Vec<LfsPointer>, GitLfsError> {
+    let mut pointers = BTreeSet::new();
+    for commit in commits {
+        let parent_tree = commit.parent_tree(repo)?;
+        let tree = commit.tree()?;
+        pointers.extend(lfs_pointers_in_diff(&parent_tree, &tree).block_on()?);
+    }
+    Ok(pointers.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::new_temp_dir;
+
+    #[test]
+    fn test_parse_pointer() {
+        let oid = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+        let content = format!("version {POINTER_VERSION}\noid sha256:{oid}\nsize 12345\n");
+        let pointer = LfsPointer::parse(content.as_bytes()).unwrap();
+        assert_eq!(pointer.oid, oid);
+        assert_eq!(pointer.size, 12345);
+        assert_eq!(pointer.to_bytes(), content.as_bytes());
+
+        // Unknown keys are allowed
+        let content =
+            format!("version {POINTER_VERSION}\next-0-foo sha256:abc\noid sha256:{oid}\nsize 1\n");
+        assert!(LfsPointer::parse(content.as_bytes()).is_some());
+
+        // Invalid pointers
+        assert!(LfsPointer::parse(b"").is_none());
+        assert!(LfsPointer::parse(b"hello\n").is_none());
+        let content = format!("version {POINTER_VERSION}\noid sha256:{oid}\n");
+        assert!(LfsPointer::parse(content.as_bytes()).is_none());
+        let content = format!("version {POINTER_VERSION}\noid sha1:{oid}\nsize 1\n");
+        assert!(LfsPointer::parse(content.as_bytes()).is_none());
+        let content = format!("version {POINTER_VERSION}\noid sha256:xyz\nsize 1\n");
+        assert!(LfsPointer::parse(content.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn test_clean_smudge() {
+        let temp_dir = new_temp_dir();
+        let store = LfsObjectStore::new(temp_dir.path().join("objects"));
+        let contents = vec![b'x'; 2000];
+
+        let pointer = store.clean(&mut contents.as_slice()).unwrap();
+        assert_eq!(pointer.size, 2000);
+        assert_eq!(pointer.oid, hex::encode(Sha256::digest(&contents)));
+        assert!(store.has_object(&pointer));
+
+        // Cleaning a pointer returns the pointer as is
+        let pointer_bytes = pointer.to_bytes();
+        assert_eq!(store.clean(&mut pointer_bytes.as_slice()).unwrap(), pointer);
+
+        let mut smudged = vec![];
+        let mut reader = pointer_bytes.as_slice();
+        store
+            .smudge(&mut reader)
+            .unwrap()
+            .read_to_end(&mut smudged)
+            .unwrap();
+        assert_eq!(smudged, contents);
+
+        // Non-pointer contents are passed through
+        let mut smudged = vec![];
+        let mut reader = contents.as_slice();
+        store
+            .smudge(&mut reader)
+            .unwrap()
+            .read_to_end(&mut smudged)
+            .unwrap();
+        assert_eq!(smudged, contents);
+    }
+
+    #[test]
+    fn test_local_transport() {
+        let temp_dir = new_temp_dir();
+        let local = LfsObjectStore::new(temp_dir.path().join("local"));
+        let transport = LocalLfsTransport::new(temp_dir.path().join("server"));
+        let pointer1 = local.clean(&mut [b'1'; 2000].as_slice()).unwrap();
+        let pointer2 = LfsPointer {
+            oid: "0".repeat(64),
+            size: 1,
+        };
+
+        let stats = transport
+            .upload(&local, &[pointer1.clone(), pointer2.clone()])
+            .unwrap();
+        assert_eq!(stats.transferred, 1);
+        assert_eq!(stats.missing, [pointer2]);
+        // Objects already on the server aren't uploaded again
+        let stats = transport.upload(&local, &[pointer1.clone()]).unwrap();
+        assert_eq!(stats.transferred, 0);
+
+        let other = LfsObjectStore::new(temp_dir.path().join("other"));
+        let stats = transport.download(&other, &[pointer1.clone()]).unwrap();
+        assert_eq!(stats.transferred, 1);
+        assert!(other.has_object(&pointer1));
+    }
+}
@@ -12,10 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::fs::File;
 use std::io;
 use std::io::BufReader;
 use std::io::Read;
-use std::num::NonZeroU32;
+use std::io::Write as _;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Child;
@@ -28,12 +29,17 @@ use bstr::ByteSlice as _;
 use itertools::Itertools as _;
 use thiserror::Error;
 
+use crate::backend::CommitId;
+use crate::git::GitFetchDepth;
+use crate::git::GitPushOptions;
 use crate::git::GitPushStats;
 use crate::git::Progress;
 use crate::git::RefSpec;
 use crate::git::RefToPush;
 use crate::git::RemoteCallbacks;
+use crate::git::RemoteMessageCollector;
 use crate::git_backend::GitBackend;
+use crate::object_id::ObjectId as _;
 use crate::ref_name::GitRefNameBuf;
 use crate::ref_name::RefNameBuf;
 use crate::ref_name::RemoteName;
@@ -65,6 +71,8 @@ pub enum GitSubprocessError {
     },
     #[error("Failed to wait for the git process")]
     Wait(std::io::Error),
+    #[error("Failed to write to the git process")]
+    Write(std::io::Error),
     #[error(
         "Git does not recognize required option: {0} (note: supported version is \
          {MINIMUM_GIT_VERSION})"
@@ -149,7 +157,7 @@ impl<'a> GitSubprocessContext<'a> {
         remote_name: &RemoteName,
         refspecs: &[RefSpec],
         callbacks: &mut RemoteCallbacks<'_>,
-        depth: Option<NonZeroU32>,
+        depth: &GitFetchDepth,
     ) -> Result<Option<String>, GitSubprocessError> {
         if refspecs.is_empty() {
             return Ok(None);
@@ -162,8 +170,20 @@ impl<'a> GitSubprocessContext<'a> {
         if callbacks.progress.is_some() {
             command.arg("--progress");
         }
-        if let Some(d) = depth {
-            command.arg(format!("--depth={d}"));
+        match depth {
+            GitFetchDepth::Unspecified => {}
+            GitFetchDepth::Depth(d) => {
+                command.arg(format!("--depth={d}"));
+            }
+            GitFetchDepth::Deepen(d) => {
+                command.arg(format!("--deepen={d}"));
+            }
+            GitFetchDepth::ShallowSince(date) => {
+                command.arg(format!("--shallow-since={date}"));
+            }
+            GitFetchDepth::Unshallow => {
+                command.arg("--unshallow");
+            }
         }
         command.arg("--").arg(remote_name.as_str());
         command.args(refspecs.iter().map(|x| x.to_git_format()));
@@ -173,6 +193,76 @@ impl<'a> GitSubprocessContext<'a> {
         parse_git_fetch_output(output)
     }
 
+    /// Fetch particular objects from the promisor remote of a partial clone
+    ///
+    /// This is the same command Git uses to fetch missing objects on demand.
+    pub(crate) fn spawn_fetch_objects(
+        &self,
+        remote_name: &str,
+        ids: &[gix::ObjectId],
+    ) -> Result<(), GitSubprocessError> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        tracing::debug!(count = ids.len(), "fetching missing objects");
+        let mut command = self.create_command();
+        command.stdin(Stdio::piped());
+        command.stdout(Stdio::null());
+        command.args(["-c", "fetch.negotiationAlgorithm=noop", "fetch"]);
+        command.args([
+            "--no-tags",
+            "--no-write-fetch-head",
+            "--recurse-submodules=no",
+            "--filter=blob:none",
+            "--stdin",
+        ]);
+        command.args(["--", remote_name]);
+
+        let mut child = self.spawn_cmd(command)?;
+        let mut stdin = child.stdin.take().unwrap();
+        for id in ids {
+            writeln!(stdin, "{id}").map_err(GitSubprocessError::Write)?;
+        }
+        drop(stdin);
+        let output = wait_with_output(child)?;
+
+        parse_git_fetch_objects_output(output)
+    }
+
+    /// Write a thin pack of the objects reachable from `heads` but not from
+    /// `bases` to `output`, as `git bundle create` does.
+    pub(crate) fn spawn_pack_objects(
+        &self,
+        heads: &[CommitId],
+        bases: &[CommitId],
+        output: File,
+    ) -> Result<(), GitSubprocessError> {
+        let mut command = self.create_command();
+        command.stdin(Stdio::piped());
+        command.stdout(output);
+        command.args([
+            "pack-objects",
+            "--stdout",
+            "--thin",
+            "--delta-base-offset",
+            "--revs",
+            "--quiet",
+        ]);
+
+        let mut child = self.spawn_cmd(command)?;
+        let mut stdin = child.stdin.take().unwrap();
+        for id in heads {
+            writeln!(stdin, "{}", id.hex()).map_err(GitSubprocessError::Write)?;
+        }
+        for id in bases {
+            writeln!(stdin, "^{}", id.hex()).map_err(GitSubprocessError::Write)?;
+        }
+        drop(stdin);
+        let output = wait_with_output(child)?;
+
+        parse_git_pack_objects_output(output)
+    }
+
     /// Prune particular branches
     pub(crate) fn spawn_branch_prune(
         &self,
@@ -225,10 +315,14 @@ impl<'a> GitSubprocessContext<'a> {
     /// Return tuple with
     ///     1. refs that failed to push
     ///     2. refs that succeeded to push
+    ///
+    /// Messages sent by the remote are collected in the returned stats instead
+    /// of being passed to the sideband callback.
     pub(crate) fn spawn_push(
         &self,
         remote_name: &RemoteName,
         references: &[RefToPush],
+        options: &GitPushOptions,
         callbacks: &mut RemoteCallbacks<'_>,
     ) -> Result<GitPushStats, GitSubprocessError> {
         let mut command = self.create_command();
@@ -247,6 +341,12 @@ impl<'a> GitSubprocessContext<'a> {
                 .iter()
                 .map(|reference| format!("--force-with-lease={}", reference.to_git_lease())),
         );
+        command.args(
+            options
+                .remote_push_options
+                .iter()
+                .map(|option| format!("--push-option={option}")),
+        );
         command.args(["--", remote_name.as_str()]);
         // with --force-with-lease we cannot have the forced refspec,
         // as it ignores the lease
@@ -256,9 +356,23 @@ impl<'a> GitSubprocessContext<'a> {
                 .map(|r| r.refspec.to_git_format_not_forced()),
         );
 
-        let output = wait_with_progress(self.spawn_cmd(command)?, callbacks)?;
+        let mut sideband_progress = callbacks.sideband_progress.take();
+        let mut remote_messages = RemoteMessageCollector::default();
+        let mut collect_message = |data: &[u8]| remote_messages.feed(data, &mut sideband_progress);
+        let mut push_callbacks = RemoteCallbacks {
+            progress: callbacks
+                .progress
+                .as_deref_mut()
+                .map(|cb| cb as &mut dyn FnMut(&Progress)),
+            sideband_progress: Some(&mut collect_message),
+            ..Default::default()
+        };
+        let output = wait_with_progress(self.spawn_cmd(command)?, &mut push_callbacks)?;
+        callbacks.sideband_progress = sideband_progress;
 
-        parse_git_push_output(output)
+        let mut push_stats = parse_git_push_output(output)?;
+        push_stats.remote_messages = remote_messages.finish();
+        Ok(push_stats)
     }
 }
 
@@ -377,6 +491,36 @@ fn parse_git_fetch_output(output: Output) -> Result<Option<String>, GitSubproces
     Err(external_git_error(&output.stderr))
 }
 
+fn parse_git_fetch_objects_output(output: Output) -> Result<(), GitSubprocessError> {
+    if output.status.success() {
+        return Ok(());
+    }
+
+    // There are some git errors we want to parse out
+    if let Some(option) = parse_unknown_option(&output.stderr) {
+        return Err(GitSubprocessError::UnsupportedGitOption(option));
+    }
+
+    if let Some(remote) = parse_no_such_remote(&output.stderr) {
+        return Err(GitSubprocessError::NoSuchRepository(remote));
+    }
+
+    Err(external_git_error(&output.stderr))
+}
+
+fn parse_git_pack_objects_output(output: Output) -> Result<(), GitSubprocessError> {
+    if output.status.success() {
+        return Ok(());
+    }
+
+    // There are some git errors we want to parse out
+    if let Some(option) = parse_unknown_option(&output.stderr) {
+        return Err(GitSubprocessError::UnsupportedGitOption(option));
+    }
+
+    Err(external_git_error(&output.stderr))
+}
+
 fn parse_git_branch_prune_output(output: Output) -> Result<(), GitSubprocessError> {
     if output.status.success() {
         return Ok(());
@@ -0,0 +1,391 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing and matching of `.gitattributes` files.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::io::Read as _;
+use std::iter;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use ignore::gitignore;
+use thiserror::Error;
+
+use crate::backend::BackendError;
+use crate::backend::TreeValue;
+use crate::merged_tree::MergedTree;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::repo_path::RepoPathComponent;
+
+/// Error that may occur while reading `.gitattributes` files.
+#[derive(Debug, Error)]
+pub enum GitAttributesError {
+    /// Failed to read the file from disk.
+    #[error("Failed to read attributes from file {path}")]
+    ReadFile {
+        /// Path to the `.gitattributes` file.
+        path: PathBuf,
+        /// The underlying error.
+        source: io::Error,
+    },
+    /// A line of the file isn't valid UTF-8.
+    #[error("Invalid UTF-8 for attributes in {path} on line #{line_num_for_display}: {line}")]
+    InvalidUtf8 {
+        /// Path to the `.gitattributes` file.
+        path: PathBuf,
+        /// 1-based line number.
+        line_num_for_display: usize,
+        /// The line, lossily decoded.
+        line: String,
+        /// The underlying error.
+        source: std::str::Utf8Error,
+    },
+    /// A pattern couldn't be parsed.
+    #[error("Failed to parse attribute patterns from file {path}")]
+    Underlying {
+        /// Path to the `.gitattributes` file.
+        path: PathBuf,
+        /// The underlying error.
+        source: ignore::Error,
+    },
+    /// Failed to read the file from the tree.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
+/// State of an attribute for a path.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AttributeState {
+    /// The attribute is set (`name`).
+    Set,
+    /// The attribute is unset (`-name`).
+    Unset,
+    /// The attribute is set to a value (`name=value`).
+    Value(String),
+    /// No pattern specifies the attribute, or it was reset (`!name`).
+    Unspecified,
+}
+
+impl AttributeState {
+    /// Returns true if the attribute is set to the given `value`.
+    pub fn is_value(&self, value: &str) -> bool {
+        matches!(self, AttributeState::Value(v) if v == value)
+    }
+}
+
+#[derive(Debug)]
+struct AttributeRule {
+    matcher: gitignore::Gitignore,
+    attributes: Vec<(String, AttributeState)>,
+}
+
+/// Models the effective contents of multiple `.gitattributes` files.
+#[derive(Debug)]
+pub struct GitAttributesFile {
+    parent: Option<Arc<GitAttributesFile>>,
+    prefix: String,
+    rules: Vec<AttributeRule>,
+}
+
+impl GitAttributesFile {
+    /// Returns an instance with no patterns.
+    pub fn empty() -> Arc<GitAttributesFile> {
+        Arc::new(GitAttributesFile {
+            parent: None,
+            prefix: String::new(),
+            rules: vec![],
+        })
+    }
+
+    /// Concatenates new `.gitattributes` content at the `prefix` directory.
+    ///
+    /// The `prefix` should be a slash-separated path relative to the workspace
+    /// root.
+    pub fn chain(
+        self: &Arc<GitAttributesFile>,
+        prefix: &str,
+        attributes_path: &Path,
+        input: &[u8],
+    ) -> Result<Arc<GitAttributesFile>, GitAttributesError> {
+        let mut rules = vec![];
+        for (i, input_line) in input.split(|b| *b == b'\n').enumerate() {
+            let line =
+                std::str::from_utf8(input_line).map_err(|err| GitAttributesError::InvalidUtf8 {
+                    path: attributes_path.to_path_buf(),
+                    line_num_for_display: i + 1,
+                    line: String::from_utf8_lossy(input_line).to_string(),
+                    source: err,
+                })?;
+            let line = line.trim();
+            // Macro definitions and quoted patterns aren't supported. Negative
+            // patterns are forbidden by Git.
+            if line.is_empty() || line.starts_with(['#', '[', '"', '!']) {
+                continue;
+            }
+            let mut tokens = line.split_ascii_whitespace();
+            let pattern = tokens.next().unwrap();
+            let attributes: Vec<_> = tokens.flat_map(parse_attribute).collect();
+            if attributes.is_empty() {
+                continue;
+            }
+            let mut builder = gitignore::GitignoreBuilder::new(prefix);
+            builder
+                .add_line(None, pattern)
+                .map_err(|err| GitAttributesError::Underlying {
+                    path: attributes_path.to_path_buf(),
+                    source: err,
+                })?;
+            let matcher = builder
+                .build()
+                .map_err(|err| GitAttributesError::Underlying {
+                    path: attributes_path.to_path_buf(),
+                    source: err,
+                })?;
+            rules.push(AttributeRule {
+                matcher,
+                attributes,
+            });
+        }
+        if rules.is_empty() {
+            return Ok(self.clone());
+        }
+        let parent = if self.rules.is_empty() {
+            self.parent.clone() // omit the empty root
+        } else {
+            Some(self.clone())
+        };
+        Ok(Arc::new(GitAttributesFile {
+            parent,
+            prefix: prefix.to_owned(),
+            rules,
+        }))
+    }
+
+    /// Concatenates new `.gitattributes` file at the `prefix` directory.
+    ///
+    /// The `prefix` should be a slash-separated path relative to the workspace
+    /// root.
+    pub fn chain_with_file(
+        self: &Arc<GitAttributesFile>,
+        prefix: &str,
+        file: PathBuf,
+    ) -> Result<Arc<GitAttributesFile>, GitAttributesError> {
+        if file.is_file() {
+            let buf = fs::read(&file).map_err(|err| GitAttributesError::ReadFile {
+                path: file.clone(),
+                source: err,
+            })?;
+            self.chain(prefix, &file, &buf)
+        } else {
+            Ok(self.clone())
+        }
+    }
+
+    /// Returns the state of the attribute `name` for the file at `path`.
+    ///
+    /// Patterns in deeper directories take precedence over the ones in their
+    /// parents, and later lines take precedence over earlier lines.
+    pub fn get(&self, path: &str, name: &str) -> AttributeState {
+        iter::successors(Some(self), |file| file.parent.as_deref())
+            .filter(|file| path.starts_with(file.prefix.trim_start_matches("./")))
+            .flat_map(|file| file.rules.iter().rev())
+            .filter(|rule| rule.matcher.matched(path, false).is_ignore())
+            .find_map(|rule| {
+                rule.attributes
+                    .iter()
+                    .rev()
+                    .find(|(attr_name, _)| attr_name == name)
+                    .map(|(_, state)| state.clone())
+            })
+            .unwrap_or(AttributeState::Unspecified)
+    }
+}
+
+/// Parses an attribute token, expanding the builtin `binary` macro.
+fn parse_attribute(token: &str) -> Vec<(String, AttributeState)> {
+    if token == "binary" {
+        // Git defines `[attr]binary -diff -merge -text`
+        return vec![
+            ("binary".to_owned(), AttributeState::Set),
+            ("diff".to_owned(), AttributeState::Unset),
+            ("merge".to_owned(), AttributeState::Unset),
+            ("text".to_owned(), AttributeState::Unset),
+        ];
+    }
+    let attribute = if let Some(name) = token.strip_prefix('-') {
+        (name.to_owned(), AttributeState::Unset)
+    } else if let Some(name) = token.strip_prefix('!') {
+        (name.to_owned(), AttributeState::Unspecified)
+    } else if let Some((name, value)) = token.split_once('=') {
+        (name.to_owned(), AttributeState::Value(value.to_owned()))
+    } else {
+        (token.to_owned(), AttributeState::Set)
+    };
+    vec![attribute]
+}
+
+/// Loads `.gitattributes` files from a tree on demand.
+///
+/// This is used when the attributes of the files to be written have to be
+/// determined before the `.gitattributes` files exist on disk.
+pub struct TreeGitAttributes {
+    tree: MergedTree,
+    base: Arc<GitAttributesFile>,
+    dirs: HashMap<RepoPathBuf, Arc<GitAttributesFile>>,
+}
+
+impl TreeGitAttributes {
+    /// Creates a loader that reads the `.gitattributes` files from `tree`, on
+    /// top of the `base` attributes.
+    pub fn new(tree: MergedTree, base: Arc<GitAttributesFile>) -> Self {
+        TreeGitAttributes {
+            tree,
+            base,
+            dirs: HashMap::new(),
+        }
+    }
+
+    /// Returns the attributes that apply to the file at `path`.
+    pub async fn for_file(
+        &mut self,
+        path: &RepoPath,
+    ) -> Result<Arc<GitAttributesFile>, GitAttributesError> {
+        let mut missing_dirs = vec![];
+        let mut attributes = self.base.clone();
+        let mut dir = path.parent();
+        while let Some(cur) = dir {
+            if let Some(cached) = self.dirs.get(cur) {
+                attributes = cached.clone();
+                break;
+            }
+            missing_dirs.push(cur);
+            dir = cur.parent();
+        }
+        for dir in missing_dirs.into_iter().rev() {
+            let file_path = dir.join(RepoPathComponent::new(".gitattributes"));
+            if let Ok(Some(TreeValue::File { id, .. })) =
+                self.tree.path_value(&file_path)?.into_resolved()
+            {
+                let mut buf = vec![];
+                let store = self.tree.store();
+                store
+                    .read_file_async(&file_path, &id)
+                    .await?
+                    .read_to_end(&mut buf)
+                    .map_err(|err| GitAttributesError::ReadFile {
+                        path: PathBuf::from(file_path.as_internal_file_string()),
+                        source: err,
+                    })?;
+                attributes = attributes.chain(
+                    &dir.to_internal_dir_string(),
+                    Path::new(file_path.as_internal_file_string()),
+                    &buf,
+                )?;
+            }
+            self.dirs.insert(dir.to_owned(), attributes.clone());
+        }
+        Ok(attributes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(input: &[u8], path: &str, name: &str) -> AttributeState {
+        let file = GitAttributesFile::empty()
+            .chain("", Path::new(""), input)
+            .unwrap();
+        file.get(path, name)
+    }
+
+    #[test]
+    fn test_gitattributes_empty_file() {
+        let file = GitAttributesFile::empty();
+        assert_eq!(file.get("foo", "filter"), AttributeState::Unspecified);
+    }
+
+    #[test]
+    fn test_gitattributes_states() {
+        let input = b"*.bin filter=lfs -text diff !merge\n";
+        assert_eq!(
+            get(input, "a.bin", "filter"),
+            AttributeState::Value("lfs".to_owned())
+        );
+        assert_eq!(get(input, "a.bin", "text"), AttributeState::Unset);
+        assert_eq!(get(input, "a.bin", "diff"), AttributeState::Set);
+        assert_eq!(get(input, "a.bin", "merge"), AttributeState::Unspecified);
+        assert_eq!(get(input, "a.txt", "filter"), AttributeState::Unspecified);
+    }
+
+    #[test]
+    fn test_gitattributes_binary_macro() {
+        let input = b"*.png binary\n*.svg binary diff\n";
+        assert_eq!(get(input, "a.png", "binary"), AttributeState::Set);
+        assert_eq!(get(input, "a.png", "diff"), AttributeState::Unset);
+        assert_eq!(get(input, "a.png", "merge"), AttributeState::Unset);
+        assert_eq!(get(input, "a.png", "text"), AttributeState::Unset);
+        // Later attributes on the same line override the expansion
+        assert_eq!(get(input, "a.svg", "diff"), AttributeState::Set);
+        assert_eq!(get(input, "a.svg", "text"), AttributeState::Unset);
+    }
+
+    #[test]
+    fn test_gitattributes_basename_and_anchored() {
+        let input = b"*.bin filter=lfs\n/top.dat filter=lfs\n";
+        assert!(get(input, "dir/sub/a.bin", "filter").is_value("lfs"));
+        assert!(get(input, "top.dat", "filter").is_value("lfs"));
+        assert!(!get(input, "dir/top.dat", "filter").is_value("lfs"));
+    }
+
+    #[test]
+    fn test_gitattributes_comments_and_unsupported_lines() {
+        let input = b"# comment\n\n[attr]binary -diff\n!*.bin filter=lfs\n*.bin\n";
+        assert_eq!(get(input, "a.bin", "filter"), AttributeState::Unspecified);
+    }
+
+    #[test]
+    fn test_gitattributes_crlf_line_ending() {
+        let input = b"*.bin filter=lfs\r\n";
+        assert!(get(input, "a.bin", "filter").is_value("lfs"));
+    }
+
+    #[test]
+    fn test_gitattributes_line_ordering() {
+        let input = b"*.bin filter=lfs\nfoo.bin -filter\n";
+        assert!(get(input, "a.bin", "filter").is_value("lfs"));
+        assert_eq!(get(input, "foo.bin", "filter"), AttributeState::Unset);
+        // An unrelated attribute on a later line doesn't reset earlier ones
+        let input = b"*.bin filter=lfs\nfoo.bin diff\n";
+        assert!(get(input, "foo.bin", "filter").is_value("lfs"));
+    }
+
+    #[test]
+    fn test_gitattributes_file_ordering() {
+        let file1 = GitAttributesFile::empty()
+            .chain("", Path::new(""), b"*.bin filter=lfs\n")
+            .unwrap();
+        let file2 = file1
+            .chain("dir/", Path::new(""), b"*.bin -filter\n")
+            .unwrap();
+        assert!(file2.get("a.bin", "filter").is_value("lfs"));
+        assert_eq!(file2.get("dir/a.bin", "filter"), AttributeState::Unset);
+        assert_eq!(file2.get("dir/sub/a.bin", "filter"), AttributeState::Unset);
+    }
+}
@@ -163,6 +163,13 @@ pub trait MutableIndex {
 
     fn add_commit(&mut self, commit: &Commit);
 
+    /// Updates the parents of the already indexed `commits` to
+    /// `commit.parent_ids()`. The new parents must be indexed.
+    ///
+    /// This is needed when the backend starts reporting different parents for
+    /// existing commits, e.g. when a shallow Git repository is deepened.
+    fn reparent_commits(&mut self, commits: &[Commit]);
+
     fn merge_in(&mut self, other: &dyn ReadonlyIndex);
 }
 
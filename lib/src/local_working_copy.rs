@@ -16,6 +16,7 @@
 #![allow(clippy::let_unit_value)]
 
 use std::any::Any;
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::collections::HashSet;
 use std::error::Error;
@@ -67,11 +68,15 @@ use crate::backend::TreeValue;
 use crate::commit::Commit;
 use crate::conflicts;
 use crate::conflicts::choose_materialized_conflict_marker_len;
+use crate::conflicts::file_conflict_labels;
+use crate::conflicts::materialize_labeled_merge_result;
 use crate::conflicts::materialize_merge_result_to_bytes_with_marker_len;
 use crate::conflicts::materialize_tree_value;
 use crate::conflicts::ConflictMarkerStyle;
 use crate::conflicts::MaterializedTreeValue;
+use crate::conflicts::UnparsedConflictHunk;
 use crate::conflicts::MIN_CONFLICT_MARKER_LEN;
+use crate::eol::EolConversion;
 use crate::file_util::check_symlink_support;
 use crate::file_util::try_symlink;
 #[cfg(feature = "watchman")]
@@ -79,6 +84,11 @@ use crate::fsmonitor::watchman;
 use crate::fsmonitor::FsmonitorSettings;
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::WatchmanConfig;
+use crate::git_lfs::is_lfs_file;
+use crate::git_lfs::LfsObjectStore;
+use crate::gitattributes::GitAttributesError;
+use crate::gitattributes::GitAttributesFile;
+use crate::gitattributes::TreeGitAttributes;
 use crate::gitignore::GitIgnoreFile;
 use crate::lock::FileLock;
 use crate::matchers::DifferenceMatcher;
@@ -956,6 +966,8 @@ impl TreeState {
             start_tracking_matcher,
             max_new_file_size,
             conflict_marker_style,
+            ref lfs_store,
+            validate_conflict_markers,
         } = options;
 
         let sparse_matcher = self.sparse_matcher();
@@ -981,6 +993,7 @@ impl TreeState {
         let (tree_entries_tx, tree_entries_rx) = channel();
         let (file_states_tx, file_states_rx) = channel();
         let (untracked_paths_tx, untracked_paths_rx) = channel();
+        let (unparsed_conflicts_tx, unparsed_conflicts_rx) = channel();
         let (deleted_files_tx, deleted_files_rx) = channel();
 
         trace_span!("traverse filesystem").in_scope(|| -> Result<(), SnapshotError> {
@@ -993,16 +1006,20 @@ impl TreeState {
                 tree_entries_tx,
                 file_states_tx,
                 untracked_paths_tx,
+                unparsed_conflicts_tx,
                 deleted_files_tx,
                 error: OnceLock::new(),
                 progress,
                 max_new_file_size,
                 conflict_marker_style,
+                lfs_store: lfs_store.as_deref(),
+                validate_conflict_markers,
             };
             let directory_to_visit = DirectoryToVisit {
                 dir: RepoPathBuf::root(),
                 disk_dir: self.working_copy_path.clone(),
                 git_ignore: base_ignores.clone(),
+                git_attributes: GitAttributesFile::empty(),
                 file_states: self.file_states.all(),
             };
             // Here we use scope as a queue of per-directory jobs.
@@ -1016,6 +1033,7 @@ impl TreeState {
 
         let stats = SnapshotStats {
             untracked_paths: untracked_paths_rx.into_iter().collect(),
+            unparsed_conflicts: unparsed_conflicts_rx.into_iter().collect(),
         };
         let mut tree_builder = MergedTreeBuilder::new(self.tree_id.clone());
         trace_span!("process tree entries").in_scope(|| {
@@ -1112,10 +1130,20 @@ impl TreeState {
     }
 }
 
+/// Conversions applied to file contents between the store and the working
+/// copy, as specified by `.gitattributes`.
+#[derive(Clone, Copy, Debug)]
+struct ContentFilter<'a> {
+    /// Set if the file is stored as an LFS pointer.
+    lfs_store: Option<&'a LfsObjectStore>,
+    eol: Option<EolConversion>,
+}
+
 struct DirectoryToVisit<'a> {
     dir: RepoPathBuf,
     disk_dir: PathBuf,
     git_ignore: Arc<GitIgnoreFile>,
+    git_attributes: Arc<GitAttributesFile>,
     file_states: FileStates<'a>,
 }
 
@@ -1140,11 +1168,14 @@ struct FileSnapshotter<'a> {
     tree_entries_tx: Sender<(RepoPathBuf, MergedTreeValue)>,
     file_states_tx: Sender<(RepoPathBuf, FileState)>,
     untracked_paths_tx: Sender<(RepoPathBuf, UntrackedReason)>,
+    unparsed_conflicts_tx: Sender<(RepoPathBuf, Vec<UnparsedConflictHunk>)>,
     deleted_files_tx: Sender<RepoPathBuf>,
     error: OnceLock<SnapshotError>,
     progress: Option<&'a SnapshotProgress<'a>>,
     max_new_file_size: u64,
     conflict_marker_style: ConflictMarkerStyle,
+    lfs_store: Option<&'a LfsObjectStore>,
+    validate_conflict_markers: bool,
 }
 
 impl FileSnapshotter<'_> {
@@ -1182,11 +1213,16 @@ impl FileSnapshotter<'_> {
             dir,
             disk_dir,
             git_ignore,
+            git_attributes,
             file_states,
         } = directory_to_visit;
 
         let git_ignore = git_ignore
             .chain_with_file(&dir.to_internal_dir_string(), disk_dir.join(".gitignore"))?;
+        let git_attributes = git_attributes.chain_with_file(
+            &dir.to_internal_dir_string(),
+            disk_dir.join(".gitattributes"),
+        )?;
         let dir_entries: Vec<_> = disk_dir
             .read_dir()
             .and_then(|entries| entries.try_collect())
@@ -1200,8 +1236,15 @@ impl FileSnapshotter<'_> {
             // sequential scan should be fast enough.
             .with_min_len(100)
             .filter_map(|entry| {
-                self.process_dir_entry(&dir, &git_ignore, file_states, &entry, scope)
-                    .transpose()
+                self.process_dir_entry(
+                    &dir,
+                    &git_ignore,
+                    &git_attributes,
+                    file_states,
+                    &entry,
+                    scope,
+                )
+                .transpose()
             })
             .map(|item| match item {
                 Ok((PresentDirEntryKind::Dir, name)) => Ok(Either::Left(name)),
@@ -1218,6 +1261,7 @@ impl FileSnapshotter<'_> {
         &'scope self,
         dir: &RepoPath,
         git_ignore: &Arc<GitIgnoreFile>,
+        git_attributes: &Arc<GitAttributesFile>,
         file_states: FileStates<'scope>,
         entry: &DirEntry,
         scope: &rayon::Scope<'scope>,
@@ -1248,12 +1292,16 @@ impl FileSnapshotter<'_> {
                 // ignored directory must be ignored. It's also more efficient.
                 // start_tracking_matcher is NOT tested here because we need to
                 // scan directory entries to report untracked paths.
-                self.spawn_ok(scope, move |_| self.visit_tracked_files(file_states));
+                let git_attributes = git_attributes.clone();
+                self.spawn_ok(scope, move |_| {
+                    self.visit_tracked_files(file_states, &git_attributes)
+                });
             } else if !self.matcher.visit(&path).is_nothing() {
                 let directory_to_visit = DirectoryToVisit {
                     dir: path,
                     disk_dir: entry.path(),
                     git_ignore: git_ignore.clone(),
+                    git_attributes: git_attributes.clone(),
                     file_states,
                 };
                 self.spawn_ok(scope, |scope| {
@@ -1286,7 +1334,11 @@ impl FileSnapshotter<'_> {
                     message: format!("Failed to stat file {}", entry.path().display()),
                     err: err.into(),
                 })?;
-                if maybe_current_file_state.is_none() && metadata.len() > self.max_new_file_size {
+                let filter = self.content_filter_for(git_attributes, &path);
+                if maybe_current_file_state.is_none()
+                    && metadata.len() > self.max_new_file_size
+                    && filter.lfs_store.is_none()
+                {
                     // Leave the large file untracked
                     let reason = UntrackedReason::FileTooLarge {
                         size: metadata.len(),
@@ -1300,6 +1352,7 @@ impl FileSnapshotter<'_> {
                         &entry.path(),
                         maybe_current_file_state.as_ref(),
                         new_file_state,
+                        filter,
                     )?;
                     Ok(Some((PresentDirEntryKind::File, name_string)))
                 } else {
@@ -1312,8 +1365,24 @@ impl FileSnapshotter<'_> {
         }
     }
 
+    /// Returns how the contents of the file at `path` should be converted.
+    fn content_filter_for(
+        &self,
+        git_attributes: &GitAttributesFile,
+        path: &RepoPath,
+    ) -> ContentFilter<'_> {
+        ContentFilter {
+            lfs_store: self.lfs_store.filter(|_| is_lfs_file(git_attributes, path)),
+            eol: EolConversion::from_attributes(git_attributes, path),
+        }
+    }
+
     /// Visits only paths we're already tracking.
-    fn visit_tracked_files(&self, file_states: FileStates<'_>) -> Result<(), SnapshotError> {
+    fn visit_tracked_files(
+        &self,
+        file_states: FileStates<'_>,
+        git_attributes: &GitAttributesFile,
+    ) -> Result<(), SnapshotError> {
         for (tracked_path, current_file_state) in file_states {
             if current_file_state.file_type == FileType::GitSubmodule {
                 continue;
@@ -1338,6 +1407,7 @@ impl FileSnapshotter<'_> {
                     &disk_path,
                     Some(&current_file_state),
                     new_file_state,
+                    self.content_filter_for(git_attributes, tracked_path),
                 )?;
             } else {
                 self.deleted_files_tx.send(tracked_path.to_owned()).ok();
@@ -1352,13 +1422,20 @@ impl FileSnapshotter<'_> {
         disk_path: &Path,
         maybe_current_file_state: Option<&FileState>,
         mut new_file_state: FileState,
+        filter: ContentFilter<'_>,
     ) -> Result<(), SnapshotError> {
-        let update = self.get_updated_tree_value(
+        let Some(update) = self.get_updated_tree_value(
             &path,
             disk_path,
             maybe_current_file_state,
             &new_file_state,
-        )?;
+            filter,
+        )?
+        else {
+            // Keep the old file state so the file is examined again by the
+            // next snapshot.
+            return Ok(());
+        };
         // Preserve materialized conflict data for normal, non-resolved files
         if matches!(new_file_state.file_type, FileType::Normal { .. })
             && !update.as_ref().is_some_and(|update| update.is_resolved())
@@ -1408,13 +1485,17 @@ impl FileSnapshotter<'_> {
             .ok();
     }
 
+    /// Returns the new tree value if the file changed, or `Some(None)` if it
+    /// didn't. Returns `None` if the file couldn't be snapshotted because of
+    /// conflict markers that can't be parsed.
     fn get_updated_tree_value(
         &self,
         repo_path: &RepoPath,
         disk_path: &Path,
         maybe_current_file_state: Option<&FileState>,
         new_file_state: &FileState,
-    ) -> Result<Option<MergedTreeValue>, SnapshotError> {
+        filter: ContentFilter<'_>,
+    ) -> Result<Option<Option<MergedTreeValue>>, SnapshotError> {
         let clean = match maybe_current_file_state {
             None => {
                 // untracked
@@ -1428,7 +1509,7 @@ impl FileSnapshotter<'_> {
             }
         };
         if clean {
-            Ok(None)
+            Ok(Some(None))
         } else {
             let current_tree_values = self.current_tree.path_value(repo_path)?;
             let new_file_type = if !self.tree_state.symlink_support {
@@ -1443,15 +1524,23 @@ impl FileSnapshotter<'_> {
                 new_file_state.file_type.clone()
             };
             let new_tree_values = match new_file_type {
-                FileType::Normal { executable } => self
-                    .write_path_to_store(
-                        repo_path,
-                        disk_path,
-                        &current_tree_values,
-                        executable,
-                        maybe_current_file_state.and_then(|state| state.materialized_conflict_data),
-                    )
-                    .block_on()?,
+                FileType::Normal { executable } => {
+                    let Some(new_tree_values) = self
+                        .write_path_to_store(
+                            repo_path,
+                            disk_path,
+                            &current_tree_values,
+                            executable,
+                            maybe_current_file_state
+                                .and_then(|state| state.materialized_conflict_data),
+                            filter,
+                        )
+                        .block_on()?
+                    else {
+                        return Ok(None);
+                    };
+                    new_tree_values
+                }
                 FileType::Symlink => {
                     let id = self
                         .write_symlink_to_store(repo_path, disk_path)
@@ -1461,9 +1550,9 @@ impl FileSnapshotter<'_> {
                 FileType::GitSubmodule => panic!("git submodule cannot be written to store"),
             };
             if new_tree_values != current_tree_values {
-                Ok(Some(new_tree_values))
+                Ok(Some(Some(new_tree_values)))
             } else {
-                Ok(None)
+                Ok(Some(None))
             }
         }
     }
@@ -1472,6 +1561,8 @@ impl FileSnapshotter<'_> {
         &self.tree_state.store
     }
 
+    /// Writes the file to the store. Returns `None` if the file is kept
+    /// conflicted because its conflict markers can't be parsed.
     async fn write_path_to_store(
         &self,
         repo_path: &RepoPath,
@@ -1479,11 +1570,14 @@ impl FileSnapshotter<'_> {
         current_tree_values: &MergedTreeValue,
         executable: FileExecutableFlag,
         materialized_conflict_data: Option<MaterializedConflictData>,
-    ) -> Result<MergedTreeValue, SnapshotError> {
+        filter: ContentFilter<'_>,
+    ) -> Result<Option<MergedTreeValue>, SnapshotError> {
         if let Some(current_tree_value) = current_tree_values.as_resolved() {
             #[cfg(unix)]
             let _ = current_tree_value; // use the variable
-            let id = self.write_file_to_store(repo_path, disk_path).await?;
+            let id = self
+                .write_file_to_store(repo_path, disk_path, filter)
+                .await?;
             // On Windows, we preserve the executable bit from the current tree.
             #[cfg(windows)]
             let executable = {
@@ -1494,7 +1588,7 @@ impl FileSnapshotter<'_> {
                     false
                 }
             };
-            Ok(Merge::normal(TreeValue::File { id, executable }))
+            Ok(Some(Merge::normal(TreeValue::File { id, executable })))
         } else if let Some(old_file_ids) = current_tree_values.to_file_merge() {
             // If the file contained a conflict before and is a normal file on
             // disk, we try to parse any conflict markers in the file into a
@@ -1503,15 +1597,37 @@ impl FileSnapshotter<'_> {
                 message: format!("Failed to open file {}", disk_path.display()),
                 err: err.into(),
             })?;
+            let content = match filter.eol {
+                Some(eol) => eol.convert_to_store(&content),
+                None => Cow::Borrowed(content.as_slice()),
+            };
+            let conflict_marker_len = materialized_conflict_data
+                .map_or(MIN_CONFLICT_MARKER_LEN, |data| {
+                    data.conflict_marker_len as usize
+                });
+            if self.validate_conflict_markers {
+                let num_sides = old_file_ids.clone().simplify().num_sides();
+                let unparsed = conflicts::find_unparsed_conflict_hunks(
+                    &content,
+                    num_sides,
+                    conflict_marker_len,
+                );
+                if !unparsed.is_empty() {
+                    // Keep the file conflicted rather than committing the
+                    // marker text as resolved contents.
+                    self.unparsed_conflicts_tx
+                        .send((repo_path.to_owned(), unparsed))
+                        .ok();
+                    return Ok(None);
+                }
+            }
             let new_file_ids = conflicts::update_from_content(
                 &old_file_ids,
                 self.store(),
                 repo_path,
                 &content,
                 self.conflict_marker_style,
-                materialized_conflict_data.map_or(MIN_CONFLICT_MARKER_LEN, |data| {
-                    data.conflict_marker_len as usize
-                }),
+                conflict_marker_len,
             )
             .block_on()?;
             match new_file_ids.into_resolved() {
@@ -1526,21 +1642,21 @@ impl FileSnapshotter<'_> {
                             false
                         }
                     };
-                    Ok(Merge::normal(TreeValue::File {
+                    Ok(Some(Merge::normal(TreeValue::File {
                         id: file_id.unwrap(),
                         executable,
-                    }))
+                    })))
                 }
                 Err(new_file_ids) => {
                     if new_file_ids != old_file_ids {
-                        Ok(current_tree_values.with_new_file_ids(&new_file_ids))
+                        Ok(Some(current_tree_values.with_new_file_ids(&new_file_ids)))
                     } else {
-                        Ok(current_tree_values.clone())
+                        Ok(Some(current_tree_values.clone()))
                     }
                 }
             }
         } else {
-            Ok(current_tree_values.clone())
+            Ok(Some(current_tree_values.clone()))
         }
     }
 
@@ -1548,12 +1664,39 @@ impl FileSnapshotter<'_> {
         &self,
         path: &RepoPath,
         disk_path: &Path,
+        filter: ContentFilter<'_>,
     ) -> Result<FileId, SnapshotError> {
         let mut file = File::open(disk_path).map_err(|err| SnapshotError::Other {
             message: format!("Failed to open file {}", disk_path.display()),
             err: err.into(),
         })?;
-        Ok(self.store().write_file(path, &mut file).await?)
+        if let Some(lfs_store) = filter.lfs_store {
+            let pointer = lfs_store
+                .clean(&mut file)
+                .map_err(|err| SnapshotError::GitLfsError {
+                    path: disk_path.to_path_buf(),
+                    source: err,
+                })?;
+            let contents = pointer.to_bytes();
+            Ok(self
+                .store()
+                .write_file(path, &mut contents.as_slice())
+                .await?)
+        } else if let Some(eol) = filter.eol {
+            let mut contents = vec![];
+            file.read_to_end(&mut contents)
+                .map_err(|err| SnapshotError::Other {
+                    message: format!("Failed to read file {}", disk_path.display()),
+                    err: err.into(),
+                })?;
+            let contents = eol.convert_to_store(&contents);
+            Ok(self
+                .store()
+                .write_file(path, &mut contents.as_ref())
+                .await?)
+        } else {
+            Ok(self.store().write_file(path, &mut file).await?)
+        }
     }
 
     async fn write_symlink_to_store(
@@ -1682,6 +1825,17 @@ impl TreeState {
         &mut self,
         new_tree: &MergedTree,
         options: &CheckoutOptions,
+    ) -> Result<CheckoutStats, CheckoutError> {
+        self.check_out_with_conflict_labels(new_tree, None, options)
+    }
+
+    /// Like [`TreeState::check_out()`], but describes the terms of the
+    /// materialized conflicts with the labels of the terms of `new_tree`.
+    pub fn check_out_with_conflict_labels(
+        &mut self,
+        new_tree: &MergedTree,
+        conflict_labels: Option<&Merge<String>>,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         let old_tree = self.current_tree().map_err(|err| match err {
             err @ BackendError::ObjectNotFound { .. } => CheckoutError::SourceNotFound {
@@ -1693,8 +1847,9 @@ impl TreeState {
             .update(
                 &old_tree,
                 new_tree,
+                conflict_labels,
                 self.sparse_matcher().as_ref(),
-                options.conflict_marker_style,
+                options,
             )
             .block_on()?;
         self.tree_id = new_tree.id();
@@ -1718,20 +1873,10 @@ impl TreeState {
         let removed_matcher = DifferenceMatcher::new(&old_matcher, &new_matcher);
         let empty_tree = MergedTree::resolved(Tree::empty(self.store.clone(), RepoPathBuf::root()));
         let added_stats = self
-            .update(
-                &empty_tree,
-                &tree,
-                &added_matcher,
-                options.conflict_marker_style,
-            )
+            .update(&empty_tree, &tree, None, &added_matcher, options)
             .block_on()?;
         let removed_stats = self
-            .update(
-                &tree,
-                &empty_tree,
-                &removed_matcher,
-                options.conflict_marker_style,
-            )
+            .update(&tree, &empty_tree, None, &removed_matcher, options)
             .block_on()?;
         self.sparse_patterns = sparse_patterns;
         assert_eq!(added_stats.updated_files, 0);
@@ -1751,9 +1896,17 @@ impl TreeState {
         &mut self,
         old_tree: &MergedTree,
         new_tree: &MergedTree,
+        conflict_labels: Option<&Merge<String>>,
         matcher: &dyn Matcher,
-        conflict_marker_style: ConflictMarkerStyle,
+        options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
+        let CheckoutOptions {
+            conflict_marker_style,
+            lfs_store,
+            show_conflict_labels: _,
+        } = options;
+        let mut git_attributes =
+            TreeGitAttributes::new(new_tree.clone(), GitAttributesFile::empty());
         // TODO: maybe it's better not include the skipped counts in the "intended"
         // counts
         let mut stats = CheckoutStats {
@@ -1769,15 +1922,17 @@ impl TreeState {
             .map(|TreeDiffEntry { path, values }| async {
                 match values {
                     Ok((before, after)) => {
+                        let labels =
+                            conflict_labels.and_then(|labels| file_conflict_labels(&after, labels));
                         let result = materialize_tree_value(&self.store, &path, after).await;
-                        (path, result.map(|value| (before, value)))
+                        (path, result.map(|value| (before, value, labels)))
                     }
                     Err(err) => (path, Err(err)),
                 }
             })
             .buffered(self.store.concurrency().max(1));
         while let Some((path, data)) = diff_stream.next().await {
-            let (before, after) = data?;
+            let (before, after, labels) = data?;
             if after.is_absent() {
                 stats.removed_files += 1;
             } else if before.is_absent() {
@@ -1832,7 +1987,31 @@ impl TreeState {
                     continue;
                 }
                 MaterializedTreeValue::File(mut file) => {
-                    self.write_file(&disk_path, &mut file.reader, file.executable)?
+                    let attributes = git_attributes
+                        .for_file(&path)
+                        .await
+                        .map_err(checkout_error_for_git_attributes_error)?;
+                    let lfs_store = lfs_store
+                        .as_deref()
+                        .filter(|_| is_lfs_file(&attributes, &path));
+                    if let Some(lfs_store) = lfs_store {
+                        let mut reader = lfs_store.smudge(&mut file.reader).map_err(|err| {
+                            CheckoutError::Other {
+                                message: format!(
+                                    "Failed to read LFS object for {}",
+                                    disk_path.display()
+                                ),
+                                err: err.into(),
+                            }
+                        })?;
+                        self.write_file(&disk_path, &mut reader, file.executable)?
+                    } else if let Some(eol) = EolConversion::from_attributes(&attributes, &path) {
+                        let contents = file.read_all(&path)?;
+                        let contents = eol.convert_to_working_copy(&contents);
+                        self.write_file(&disk_path, &mut contents.as_ref(), file.executable)?
+                    } else {
+                        self.write_file(&disk_path, &mut file.reader, file.executable)?
+                    }
                 }
                 MaterializedTreeValue::Symlink { id: _, target } => {
                     if self.symlink_support {
@@ -1854,12 +2033,35 @@ impl TreeState {
                     executable,
                 } => {
                     let conflict_marker_len = choose_materialized_conflict_marker_len(&contents);
-                    let data = materialize_merge_result_to_bytes_with_marker_len(
-                        &contents,
-                        conflict_marker_style,
-                        conflict_marker_len,
-                    )
-                    .into();
+                    let data: Vec<u8> = if let Some(labels) = &labels {
+                        let mut data = vec![];
+                        materialize_labeled_merge_result(
+                            &contents,
+                            &self.store.merge_options().file_merge_options,
+                            labels,
+                            *conflict_marker_style,
+                            conflict_marker_len,
+                            &mut data,
+                        )
+                        .expect("writing to an in-memory buffer should never fail");
+                        data
+                    } else {
+                        materialize_merge_result_to_bytes_with_marker_len(
+                            &contents,
+                            &self.store.merge_options().file_merge_options,
+                            *conflict_marker_style,
+                            conflict_marker_len,
+                        )
+                        .into()
+                    };
+                    let attributes = git_attributes
+                        .for_file(&path)
+                        .await
+                        .map_err(checkout_error_for_git_attributes_error)?;
+                    let data = match EolConversion::from_attributes(&attributes, &path) {
+                        Some(eol) => eol.convert_to_working_copy(&data).into_owned(),
+                        None => data,
+                    };
                     let materialized_conflict_data = MaterializedConflictData {
                         conflict_marker_len: conflict_marker_len.try_into().unwrap_or(u32::MAX),
                     };
@@ -1956,6 +2158,13 @@ fn checkout_error_for_stat_error(err: io::Error, path: &Path) -> CheckoutError {
     }
 }
 
+fn checkout_error_for_git_attributes_error(err: GitAttributesError) -> CheckoutError {
+    CheckoutError::Other {
+        message: "Failed to read .gitattributes".to_string(),
+        err: err.into(),
+    }
+}
+
 /// Working copy state stored in "checkout" file.
 #[derive(Clone, Debug)]
 struct CheckoutState {
@@ -2273,7 +2482,15 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
                 err: err.into(),
             })?;
         if tree_state.tree_id != *commit.tree_id() {
-            let stats = tree_state.check_out(&new_tree, options)?;
+            let conflict_labels = options
+                .show_conflict_labels
+                .then(|| commit.conflict_labels())
+                .flatten();
+            let stats = tree_state.check_out_with_conflict_labels(
+                &new_tree,
+                conflict_labels.as_ref(),
+                options,
+            )?;
             self.tree_state_dirty = true;
             Ok(stats)
         } else {
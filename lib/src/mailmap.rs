@@ -0,0 +1,287 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing and applying `.mailmap` files.
+//!
+//! A mailmap maps the names and emails recorded in commits to canonical ones,
+//! e.g. for people who changed their names or email addresses. See
+//! <https://git-scm.com/docs/gitmailmap> for the format.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::fmt::Debug;
+use std::io::Read as _;
+use std::sync::Arc;
+use std::sync::OnceLock;
+
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::Signature;
+use crate::backend::TreeValue;
+use crate::merged_tree::MergedTree;
+use crate::repo_path::RepoPath;
+
+/// Parsed mailmap entries.
+#[derive(Clone, Debug, Default)]
+pub struct Mailmap {
+    entries: Vec<MailmapEntry>,
+}
+
+#[derive(Clone, Debug)]
+struct MailmapEntry {
+    proper_name: Option<String>,
+    proper_email: Option<String>,
+    commit_name: Option<String>,
+    commit_email: String,
+}
+
+impl Mailmap {
+    /// Parses the contents of a mailmap file. Malformed lines are ignored like
+    /// Git does.
+    pub fn parse(text: &str) -> Self {
+        let entries = text.lines().filter_map(parse_line).collect();
+        Mailmap { entries }
+    }
+
+    /// Reads the `.mailmap` file at the root of the `tree`. Returns an empty
+    /// mailmap if there's no such file.
+    pub async fn from_tree(tree: &MergedTree) -> BackendResult<Self> {
+        let path = RepoPath::from_internal_string(".mailmap");
+        let Ok(Some(TreeValue::File { id, .. })) = tree.path_value(path)?.into_resolved() else {
+            return Ok(Mailmap::default());
+        };
+        let mut content = vec![];
+        tree.store()
+            .read_file_async(path, &id)
+            .await?
+            .read_to_end(&mut content)
+            .map_err(|err| BackendError::ReadFile {
+                path: path.to_owned(),
+                id: id.clone(),
+                source: err.into(),
+            })?;
+        Ok(Mailmap::parse(&String::from_utf8_lossy(&content)))
+    }
+
+    /// Returns true if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Maps the `name` and `email` recorded in a commit to the canonical ones.
+    pub fn resolve<'a>(&'a self, name: &'a str, email: &'a str) -> (&'a str, &'a str) {
+        // Entries matching both the name and the email take precedence. Later
+        // entries override earlier ones.
+        let matches_email = |entry: &&MailmapEntry| entry.commit_email.eq_ignore_ascii_case(email);
+        let entry = self
+            .entries
+            .iter()
+            .rev()
+            .filter(matches_email)
+            .find(|entry| {
+                entry
+                    .commit_name
+                    .as_ref()
+                    .is_some_and(|commit_name| commit_name.eq_ignore_ascii_case(name))
+            })
+            .or_else(|| {
+                self.entries
+                    .iter()
+                    .rev()
+                    .filter(matches_email)
+                    .find(|entry| entry.commit_name.is_none())
+            });
+        match entry {
+            Some(entry) => (
+                entry.proper_name.as_deref().unwrap_or(name),
+                entry.proper_email.as_deref().unwrap_or(email),
+            ),
+            None => (name, email),
+        }
+    }
+
+    /// Returns the `signature` with the name and email mapped to the
+    /// canonical ones.
+    pub fn map_signature<'a>(&self, signature: &'a Signature) -> Cow<'a, Signature> {
+        let (name, email) = self.resolve(&signature.name, &signature.email);
+        if name == signature.name && email == signature.email {
+            Cow::Borrowed(signature)
+        } else {
+            Cow::Owned(Signature {
+                name: name.to_owned(),
+                email: email.to_owned(),
+                timestamp: signature.timestamp,
+            })
+        }
+    }
+}
+
+/// Mailmap which is loaded when it's first used, so commands that don't map
+/// any signatures don't have to read it.
+pub struct LazyMailmap {
+    mailmap: OnceLock<Option<Arc<Mailmap>>>,
+    load: Box<dyn Fn() -> Option<Mailmap> + Send + Sync>,
+}
+
+impl LazyMailmap {
+    /// Creates a mailmap which will be loaded by the `load` function. The
+    /// function may return `None` if there's no mailmap to apply.
+    pub fn new(load: impl Fn() -> Option<Mailmap> + Send + Sync + 'static) -> Self {
+        LazyMailmap {
+            mailmap: OnceLock::new(),
+            load: Box::new(load),
+        }
+    }
+
+    /// Returns the loaded mailmap, or `None` if there's no mailmap or it is
+    /// empty.
+    pub fn get(&self) -> Option<&Arc<Mailmap>> {
+        self.mailmap
+            .get_or_init(|| {
+                (self.load)()
+                    .filter(|mailmap| !mailmap.is_empty())
+                    .map(Arc::new)
+            })
+            .as_ref()
+    }
+}
+
+impl Debug for LazyMailmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyMailmap")
+            .field("mailmap", &self.mailmap.get())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Parses a line of the form `[Proper Name] [<proper@email>] [Commit Name]
+/// <commit@email>`.
+fn parse_line(line: &str) -> Option<MailmapEntry> {
+    let line = line.trim_start();
+    if line.starts_with('#') {
+        return None;
+    }
+    let (name1, email1, rest) = split_name_and_email(line)?;
+    match split_name_and_email(rest) {
+        Some((name2, email2, _)) => Some(MailmapEntry {
+            proper_name: non_empty(name1),
+            proper_email: non_empty(email1),
+            commit_name: non_empty(name2),
+            commit_email: email2.to_owned(),
+        }),
+        None => Some(MailmapEntry {
+            proper_name: Some(non_empty(name1)?),
+            proper_email: None,
+            commit_name: None,
+            commit_email: email1.to_owned(),
+        }),
+    }
+}
+
+fn split_name_and_email(text: &str) -> Option<(&str, &str, &str)> {
+    let (name, rest) = text.split_once('<')?;
+    let (email, rest) = rest.split_once('>')?;
+    Some((name.trim(), email.trim(), rest))
+}
+
+fn non_empty(text: &str) -> Option<String> {
+    (!text.is_empty()).then(|| text.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mailmap_resolve() {
+        let mailmap = Mailmap::parse(
+            "\
+# comment
+Proper Name <proper@example.com> <old@example.com>
+Only Name <name-only@example.com>
+<email-only@example.com> <Old-Email@Example.com>
+Specific <specific@example.com> Commit Name <shared@example.com>
+malformed line
+",
+        );
+        assert_eq!(
+            mailmap.resolve("Old", "old@example.com"),
+            ("Proper Name", "proper@example.com")
+        );
+        assert_eq!(
+            mailmap.resolve("Someone", "name-only@example.com"),
+            ("Only Name", "name-only@example.com")
+        );
+        // Emails are matched case-insensitively
+        assert_eq!(
+            mailmap.resolve("Old", "old-email@example.com"),
+            ("Old", "email-only@example.com")
+        );
+        // Entries with a commit name only match that name
+        assert_eq!(
+            mailmap.resolve("commit name", "shared@example.com"),
+            ("Specific", "specific@example.com")
+        );
+        assert_eq!(
+            mailmap.resolve("Other", "shared@example.com"),
+            ("Other", "shared@example.com")
+        );
+        assert_eq!(
+            mailmap.resolve("Unknown", "unknown@example.com"),
+            ("Unknown", "unknown@example.com")
+        );
+    }
+
+    #[test]
+    fn test_mailmap_later_entries_override() {
+        let mailmap = Mailmap::parse(
+            "\
+First <first@example.com> <old@example.com>
+Second <second@example.com> <old@example.com>
+",
+        );
+        assert_eq!(
+            mailmap.resolve("Old", "old@example.com"),
+            ("Second", "second@example.com")
+        );
+    }
+
+    #[test]
+    fn test_lazy_mailmap() {
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::atomic::Ordering;
+
+        let load_count = Arc::new(AtomicUsize::new(0));
+        let mailmap = LazyMailmap::new({
+            let load_count = load_count.clone();
+            move || {
+                load_count.fetch_add(1, Ordering::Relaxed);
+                Some(Mailmap::parse(
+                    "Proper <proper@example.com> <old@example.com>",
+                ))
+            }
+        });
+        assert_eq!(load_count.load(Ordering::Relaxed), 0);
+        let resolved = mailmap.get().unwrap().resolve("Old", "old@example.com");
+        assert_eq!(resolved, ("Proper", "proper@example.com"));
+        assert!(mailmap.get().is_some());
+        assert_eq!(load_count.load(Ordering::Relaxed), 1);
+
+        // Empty mailmaps aren't applied
+        let mailmap = LazyMailmap::new(|| Some(Mailmap::parse("# comment only")));
+        assert!(mailmap.get().is_none());
+        let mailmap = LazyMailmap::new(|| None);
+        assert!(mailmap.get().is_none());
+    }
+}
@@ -289,6 +289,29 @@ impl<T> Merge<T> {
         self
     }
 
+    /// Simplifies the merge like [`Merge::simplify()`], and removes and
+    /// reorders the terms of `other` the same way. `other` must have the same
+    /// number of terms, e.g. labels describing the terms of this merge.
+    pub fn simplify_with<U>(mut self, other: Merge<U>) -> (Self, Merge<U>)
+    where
+        T: PartialEq + Clone,
+        U: Clone,
+    {
+        assert_eq!(self.values.len(), other.values.len());
+        let mapping = self.get_simplified_mapping();
+        self.values = mapping
+            .iter()
+            .map(|index| self.values[*index].clone())
+            .collect();
+        let other = Merge {
+            values: mapping
+                .iter()
+                .map(|index| other.values[*index].clone())
+                .collect(),
+        };
+        (self, other)
+    }
+
     /// Updates the merge based on the given simplified merge.
     pub fn update_from_simplified(mut self, simplified: Merge<T>) -> Self
     where
@@ -967,6 +990,23 @@ mod tests {
         assert_eq!(c(&[0, 1, 2, 3, 4, 5, 1]).simplify(), c(&[0, 3, 4, 5, 2]));
     }
 
+    #[test]
+    fn test_simplify_with() {
+        let labels = |terms: &[&str]| Merge::from_vec(terms.to_vec());
+        assert_eq!(
+            c(&[0, 0, 1]).simplify_with(labels(&["a", "b", "c"])),
+            (c(&[1]), labels(&["c"]))
+        );
+        assert_eq!(
+            c(&[1, 0, 2]).simplify_with(labels(&["a", "b", "c"])),
+            (c(&[1, 0, 2]), labels(&["a", "b", "c"]))
+        );
+        assert_eq!(
+            c(&[0, 1, 2, 3, 1]).simplify_with(labels(&["a", "b", "c", "d", "e"])),
+            (c(&[0, 3, 2]), labels(&["a", "d", "c"]))
+        );
+    }
+
     #[test]
     fn test_update_from_simplified() {
         // 1-way merge
@@ -0,0 +1,371 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Merge drivers merge the contents of the files matching their patterns
+//! instead of the default line-based merge, similar to Git's `merge`
+//! attribute.
+
+use std::fs;
+use std::io;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::Arc;
+
+use bstr::BString;
+use itertools::Itertools as _;
+use thiserror::Error;
+
+use crate::config::ConfigGetError;
+use crate::files;
+use crate::files::FileMergeSide;
+use crate::files::MergeResult;
+use crate::fileset;
+use crate::fileset::FilesetDiagnostics;
+use crate::fileset::FilesetExpression;
+use crate::matchers::Matcher;
+use crate::merge::Merge;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathUiConverter;
+use crate::settings::UserSettings;
+
+/// Error that may occur while running an external merge driver.
+#[derive(Debug, Error)]
+#[error("Failed to run merge driver '{name}'")]
+pub struct MergeDriverError {
+    name: String,
+    #[source]
+    source: io::Error,
+}
+
+/// Merge drivers built into jj.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuiltinMergeDriver {
+    /// Keeps the contents of all sides of the conflicting hunks, e.g. for
+    /// lists of changes where both sides added entries.
+    Union,
+    /// Keeps the contents of the first side of the conflict. When rebasing,
+    /// that's the destination.
+    Ours,
+    /// Keeps the contents of the last side of the conflict. When rebasing,
+    /// that's the commit being rebased.
+    Theirs,
+}
+
+/// How a merge driver merges the file contents.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MergeDriverKind {
+    /// One of the merge drivers built into jj.
+    Builtin(BuiltinMergeDriver),
+    /// An external program. The `$base`, `$left`, `$right` and `$output`
+    /// arguments are replaced with paths to the contents of the base, the two
+    /// sides, and the merge result. `$path` is replaced with the path of the
+    /// file in the repo.
+    External(Vec<String>),
+}
+
+/// Merge driver for the files matching a fileset.
+#[derive(Clone, Debug)]
+pub struct MergeDriver {
+    name: String,
+    matcher: Arc<dyn Matcher + Send>,
+    kind: MergeDriverKind,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawMergeDriverConfig {
+    patterns: Vec<String>,
+    #[serde(default)]
+    builtin: Option<BuiltinMergeDriver>,
+    #[serde(default)]
+    command: Option<Vec<String>>,
+}
+
+impl MergeDriver {
+    /// Creates a merge driver for the files matching `expression`.
+    pub fn new(name: String, expression: FilesetExpression, kind: MergeDriverKind) -> Self {
+        MergeDriver {
+            name,
+            matcher: expression.to_send_matcher().into(),
+            kind,
+        }
+    }
+
+    /// Loads the merge drivers from the `merge.drivers` table.
+    pub fn load_all(settings: &UserSettings) -> Result<Vec<Self>, ConfigGetError> {
+        settings
+            .table_keys("merge.drivers")
+            // Sort keys so the first matching driver is deterministic.
+            .sorted()
+            .map(|name| {
+                let config_error =
+                    |error: Box<dyn std::error::Error + Send + Sync>| ConfigGetError::Type {
+                        name: format!("merge.drivers.{name}"),
+                        error,
+                        source_path: None,
+                    };
+                let raw: RawMergeDriverConfig = settings.get(["merge", "drivers", name])?;
+                let kind = match (raw.builtin, raw.command) {
+                    (Some(builtin), None) => MergeDriverKind::Builtin(builtin),
+                    (None, Some(command)) if !command.is_empty() => {
+                        MergeDriverKind::External(command)
+                    }
+                    _ => {
+                        return Err(config_error(
+                            "Exactly one of `builtin` or a non-empty `command` must be set".into(),
+                        ))
+                    }
+                };
+                // Patterns are relative to the repo root.
+                let path_converter = RepoPathUiConverter::Fs {
+                    cwd: "".into(),
+                    base: "".into(),
+                };
+                let mut diagnostics = FilesetDiagnostics::new();
+                let expressions: Vec<_> = raw
+                    .patterns
+                    .iter()
+                    .map(|text| fileset::parse(&mut diagnostics, text, &path_converter))
+                    .try_collect()
+                    .map_err(|err| config_error(err.into()))?;
+                Ok(MergeDriver::new(
+                    name.to_owned(),
+                    FilesetExpression::union_all(expressions),
+                    kind,
+                ))
+            })
+            .try_collect()
+    }
+
+    /// Name of the driver in the config.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the contents of `path` should be merged by this driver.
+    pub fn matches(&self, path: &RepoPath) -> bool {
+        self.matcher.matches(path)
+    }
+
+    /// Merges the `contents` of the file at `path`. Returns `None` if the
+    /// driver couldn't resolve the conflict, in which case the default merge
+    /// should be used.
+    pub fn merge<T: AsRef<[u8]>>(
+        &self,
+        path: &RepoPath,
+        contents: &Merge<T>,
+    ) -> Result<Option<BString>, MergeDriverError> {
+        match &self.kind {
+            MergeDriverKind::Builtin(builtin) => Ok(Some(merge_builtin(*builtin, contents))),
+            MergeDriverKind::External(command) => self.merge_external(command, path, contents),
+        }
+    }
+
+    fn merge_external<T: AsRef<[u8]>>(
+        &self,
+        command: &[String],
+        path: &RepoPath,
+        contents: &Merge<T>,
+    ) -> Result<Option<BString>, MergeDriverError> {
+        // External drivers only support 3-way merges
+        let [left, base, right] = contents.as_slice() else {
+            return Ok(None);
+        };
+        let to_error = |source| MergeDriverError {
+            name: self.name.clone(),
+            source,
+        };
+        let temp_dir = tempfile::Builder::new()
+            .prefix("jj-merge-driver-")
+            .tempdir()
+            .map_err(to_error)?;
+        let base_path = temp_dir.path().join("base");
+        let left_path = temp_dir.path().join("left");
+        let right_path = temp_dir.path().join("right");
+        let output_path = temp_dir.path().join("output");
+        for (file_path, content) in [
+            (&base_path, base.as_ref()),
+            (&left_path, left.as_ref()),
+            (&right_path, right.as_ref()),
+            (&output_path, b"".as_slice()),
+        ] {
+            fs::write(file_path, content).map_err(to_error)?;
+        }
+        let args = command[1..].iter().map(|arg| {
+            let file_path = match arg.as_str() {
+                "$base" => &base_path,
+                "$left" => &left_path,
+                "$right" => &right_path,
+                "$output" => &output_path,
+                "$path" => return path.as_internal_file_string().into(),
+                _ => return arg.into(),
+            };
+            file_path.as_os_str().to_owned()
+        });
+        // The output of the driver would be interleaved with the output of jj.
+        let status = Command::new(&command[0])
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        let status = match status {
+            Ok(status) => status,
+            Err(err) => {
+                // A missing program shouldn't prevent the default merge.
+                tracing::warn!(?err, "failed to spawn merge driver {}", self.name);
+                return Ok(None);
+            }
+        };
+        if !status.success() {
+            tracing::info!(?status, "merge driver {} failed", self.name);
+            return Ok(None);
+        }
+        let output = fs::read(&output_path).map_err(to_error)?;
+        Ok(Some(output.into()))
+    }
+}
+
+fn merge_builtin<T: AsRef<[u8]>>(builtin: BuiltinMergeDriver, contents: &Merge<T>) -> BString {
+    match builtin {
+        BuiltinMergeDriver::Union => match files::merge(contents) {
+            MergeResult::Resolved(content) => content,
+            MergeResult::Conflict(hunks) => {
+                let mut content = BString::default();
+                for hunk in &hunks {
+                    if let Some(resolved) = hunk.as_resolved() {
+                        content.extend_from_slice(resolved);
+                    } else {
+                        for side in hunk.adds().unique() {
+                            if !content.is_empty() && !content.ends_with(b"\n") {
+                                content.push(b'\n');
+                            }
+                            content.extend_from_slice(side);
+                        }
+                    }
+                }
+                content
+            }
+        },
+        BuiltinMergeDriver::Ours => FileMergeSide::Ours.select(contents).as_ref().into(),
+        BuiltinMergeDriver::Theirs => FileMergeSide::Theirs.select(contents).as_ref().into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::config::ConfigLayer;
+    use crate::config::ConfigSource;
+    use crate::config::StackedConfig;
+
+    fn load_drivers(text: &str) -> Result<Vec<MergeDriver>, ConfigGetError> {
+        let mut config = StackedConfig::with_defaults();
+        config.add_layer(ConfigLayer::parse(ConfigSource::User, text).unwrap());
+        MergeDriver::load_all(&UserSettings::from_config(config).unwrap())
+    }
+
+    fn merge(builtin: BuiltinMergeDriver, base: &str, left: &str, right: &str) -> BString {
+        merge_builtin(builtin, &Merge::from_vec(vec![left, base, right]))
+    }
+
+    #[test]
+    fn test_merge_builtin_union() {
+        // Both sides added different entries at the same place
+        assert_eq!(
+            merge(
+                BuiltinMergeDriver::Union,
+                "a\nz\n",
+                "a\nb\nz\n",
+                "a\nc\nz\n"
+            ),
+            "a\nb\nc\nz\n"
+        );
+        // Non-conflicting changes are kept
+        assert_eq!(
+            merge(
+                BuiltinMergeDriver::Union,
+                "a\nm\nz\n",
+                "a1\nm\nb\nz\n",
+                "a\nm\nc\nz\n"
+            ),
+            "a1\nm\nb\nc\nz\n"
+        );
+        // Missing newline at the end of a side
+        assert_eq!(merge(BuiltinMergeDriver::Union, "", "b", "c\n"), "b\nc\n");
+    }
+
+    #[test]
+    fn test_merge_builtin_ours_theirs() {
+        assert_eq!(
+            merge(BuiltinMergeDriver::Ours, "a\nb\n", "a1\nb\n", "a2\nb2\n"),
+            "a1\nb\n"
+        );
+        assert_eq!(
+            merge(BuiltinMergeDriver::Theirs, "a\nb\n", "a1\nb\n", "a2\nb2\n"),
+            "a2\nb2\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_external_missing_program() {
+        let driver = MergeDriver::new(
+            "missing".to_owned(),
+            FilesetExpression::all(),
+            MergeDriverKind::External(vec!["jj-test-missing-merge-driver".to_owned()]),
+        );
+        let contents = Merge::from_vec(vec!["a\n", "base\n", "b\n"]);
+        let path = RepoPath::from_internal_string("file");
+        assert_eq!(driver.merge(path, &contents).unwrap(), None);
+    }
+
+    #[test]
+    fn test_load_builtin_drivers() {
+        let drivers = load_drivers(indoc! {"
+            [merge.drivers.a]
+            patterns = ['a']
+            builtin = 'ours'
+            [merge.drivers.b]
+            patterns = ['b']
+            builtin = 'theirs'
+            [merge.drivers.c]
+            patterns = ['c']
+            builtin = 'union'
+        "})
+        .unwrap();
+        assert_eq!(
+            drivers.iter().map(|driver| &driver.kind).collect_vec(),
+            [
+                &MergeDriverKind::Builtin(BuiltinMergeDriver::Ours),
+                &MergeDriverKind::Builtin(BuiltinMergeDriver::Theirs),
+                &MergeDriverKind::Builtin(BuiltinMergeDriver::Union),
+            ]
+        );
+
+        let err = load_drivers(indoc! {"
+            [merge.drivers.a]
+            patterns = ['a']
+            builtin = 'mine'
+        "})
+        .unwrap_err();
+        let source = std::error::Error::source(&err).unwrap().to_string();
+        assert!(
+            source.contains("unknown variant `mine`, expected one of `union`, `ours`, `theirs`"),
+            "{source}"
+        );
+    }
+}
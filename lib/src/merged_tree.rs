@@ -21,6 +21,7 @@ use std::collections::BTreeMap;
 use std::collections::VecDeque;
 use std::iter;
 use std::iter::zip;
+use std::path::Path;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::Context;
@@ -42,20 +43,27 @@ use crate::backend::BackendResult;
 use crate::backend::MergedTreeId;
 use crate::backend::TreeId;
 use crate::backend::TreeValue;
+use crate::config::ConfigGetError;
 use crate::copies::CopiesTreeDiffEntry;
 use crate::copies::CopiesTreeDiffStream;
 use crate::copies::CopyRecords;
+use crate::files::FileMergeOptions;
+use crate::files::FileMergeWhitespace;
 use crate::matchers::EverythingMatcher;
 use crate::matchers::Matcher;
 use crate::merge::Merge;
 use crate::merge::MergeBuilder;
 use crate::merge::MergedTreeVal;
 use crate::merge::MergedTreeValue;
+use crate::merge_driver::MergeDriver;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathComponent;
+use crate::resolution_cache::ResolutionCache;
+use crate::settings::UserSettings;
 use crate::store::Store;
 use crate::tree::try_resolve_file_conflict;
+use crate::tree::try_resolve_file_conflict_with_options;
 use crate::tree::Tree;
 use crate::tree_builder::TreeBuilder;
 
@@ -327,6 +335,30 @@ impl MergedTree {
         self.merge_no_resolve(base, other).resolve()
     }
 
+    /// Like [`MergedTree::merge()`], but also returns the labels of the terms
+    /// of the merged tree if it's conflicted. `labels` contains the labels of
+    /// the terms of this tree, `base`, and `other`, in that order.
+    pub fn merge_with_labels(
+        &self,
+        base: &MergedTree,
+        other: &MergedTree,
+        labels: Merge<Merge<String>>,
+    ) -> BackendResult<(MergedTree, Option<Merge<String>>)> {
+        let nested = Merge::from_vec(vec![
+            self.trees.clone(),
+            base.trees.clone(),
+            other.trees.clone(),
+        ]);
+        let (trees, labels) = nested.flatten().simplify_with(labels.flatten());
+        let merged = merge_trees(&trees)?;
+        if merged.is_resolved() {
+            return Ok((MergedTree { trees: merged }, None));
+        }
+        // Unresolved conflicts keep their arity, so the labels still line up.
+        let (trees, labels) = merged.simplify_with(labels);
+        Ok((MergedTree { trees }, Some(labels)))
+    }
+
     /// Merges this tree with `other`, using `base` as base, without attempting
     /// to resolve file conflicts.
     pub fn merge_no_resolve(&self, base: &MergedTree, other: &MergedTree) -> MergedTree {
@@ -339,6 +371,73 @@ impl MergedTree {
             trees: nested.flatten().simplify(),
         }
     }
+
+    /// Resolves the file conflicts whose conflicting hunks only differ in
+    /// whitespace, comparing whitespace as specified by `whitespace` instead
+    /// of the configured `merge.whitespace`. The other file merge options
+    /// are taken from the store.
+    pub fn resolve_whitespace_conflicts(
+        &self,
+        whitespace: FileMergeWhitespace,
+    ) -> BackendResult<MergedTree> {
+        let store = self.store();
+        let file_merge_options = FileMergeOptions {
+            whitespace,
+            ..store.merge_options().file_merge_options
+        };
+        let mut tree_builder = MergedTreeBuilder::new(self.id());
+        let mut num_resolved = 0;
+        for (path, value) in self.conflicts() {
+            let simplified = value?.simplify();
+            let conflict = simplified.map(|term| term.as_ref());
+            if let Some(resolved) =
+                try_resolve_file_conflict_with_options(store, &path, &conflict, &file_merge_options)
+                    .block_on()?
+            {
+                tree_builder.set_or_remove(path, Merge::normal(resolved));
+                num_resolved += 1;
+            }
+        }
+        if num_resolved == 0 {
+            return Ok(self.clone());
+        }
+        let tree_id = tree_builder.write_tree(store)?;
+        store.get_root_tree(&tree_id)
+    }
+}
+
+/// Options for resolving file conflicts when merging trees.
+#[derive(Clone, Debug, Default)]
+pub struct MergeOptions {
+    /// Options for merging file contents.
+    pub file_merge_options: FileMergeOptions,
+    /// Cache of recorded conflict resolutions to reuse, if enabled.
+    pub resolution_cache: Option<Arc<ResolutionCache>>,
+    /// Drivers to merge the matching files with instead of the line-based
+    /// merge. The first matching driver is used.
+    pub merge_drivers: Vec<MergeDriver>,
+}
+
+impl MergeOptions {
+    /// Loads the merge options from `settings`. Recorded resolutions are
+    /// stored under the `repo_path` directory.
+    pub fn from_settings(
+        settings: &UserSettings,
+        repo_path: &Path,
+    ) -> Result<Self, ConfigGetError> {
+        let resolution_cache = settings
+            .get_bool("merge.reuse-resolutions")?
+            .then(|| Arc::new(ResolutionCache::new(repo_path.join("resolutions"))));
+        Ok(MergeOptions {
+            file_merge_options: FileMergeOptions {
+                hunk_level: settings.get("merge.hunk-level")?,
+                whitespace: settings.get("merge.whitespace")?,
+                whitespace_side: settings.get("merge.whitespace-side")?,
+            },
+            resolution_cache,
+            merge_drivers: MergeDriver::load_all(settings)?,
+        })
+    }
 }
 
 /// A single entry in a tree diff.
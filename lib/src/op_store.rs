@@ -260,6 +260,14 @@ pub struct View {
     // (.jj/working_copy/) has the source of truth about which commit *is* checked out (to be
     // precise: the commit to which we most recently completed an update to).
     pub wc_commit_ids: BTreeMap<WorkspaceNameBuf, CommitId>,
+    /// The commits imported from the `git stash` entries, newest first. They
+    /// aren't visible unless referenced by other commits.
+    #[content_hash(skip_if_default)]
+    pub git_stashes: Vec<CommitId>,
+    /// The Git commit `refs/notes/commits` pointed to when the notes were last
+    /// imported from or exported to Git. This isn't a Jujutsu commit.
+    #[content_hash(skip_if_default)]
+    pub git_notes: Option<CommitId>,
 }
 
 impl View {
@@ -276,6 +284,8 @@ impl View {
             git_refs: BTreeMap::new(),
             git_head: RefTarget::absent(),
             wc_commit_ids: BTreeMap::new(),
+            git_stashes: vec![],
+            git_notes: None,
         }
     }
 
@@ -289,6 +299,8 @@ impl View {
             git_refs: BTreeMap::new(),
             git_head: RefTarget::absent(),
             wc_commit_ids: BTreeMap::new(),
+            git_stashes: vec![],
+            git_notes: None,
         }
     }
 }
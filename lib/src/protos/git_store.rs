@@ -13,6 +13,10 @@ pub struct Commit {
     /// TODO(#1624): delete when we assume that all commits use this format
     #[prost(bool, tag = "10")]
     pub uses_tree_conflict_format: bool,
+    /// Labels of the root_tree terms describing where they came from. Set only
+    /// for labeled conflicts.
+    #[prost(string, repeated, tag = "11")]
+    pub conflict_labels: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
     #[deprecated]
     #[prost(bool, tag = "8")]
     pub is_open: bool,
@@ -122,6 +122,12 @@ pub struct View {
     pub git_head_legacy: ::prost::alloc::vec::Vec<u8>,
     #[prost(message, optional, tag = "9")]
     pub git_head: ::core::option::Option<RefTarget>,
+    /// Commits imported from the `git stash` entries, newest first.
+    #[prost(bytes = "vec", repeated, tag = "11")]
+    pub git_stashes: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// Git commit of `refs/notes/commits` last synced with the Git repo.
+    #[prost(bytes = "vec", tag = "12")]
+    pub git_notes: ::prost::alloc::vec::Vec<u8>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
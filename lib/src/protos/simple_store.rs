@@ -58,6 +58,10 @@ pub struct Commit {
     /// TODO(#1624): delete when all code paths can handle this format
     #[prost(bool, tag = "8")]
     pub uses_tree_conflict_format: bool,
+    /// Labels of the root_tree terms describing where they came from. Empty if
+    /// the terms aren't labeled.
+    #[prost(string, repeated, tag = "10")]
+    pub conflict_labels: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
     #[prost(bytes = "vec", tag = "4")]
     pub change_id: ::prost::alloc::vec::Vec<u8>,
     #[prost(string, tag = "5")]
@@ -20,6 +20,7 @@ use std::collections::HashSet;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fs;
+use std::mem;
 use std::path::Path;
 use std::slice;
 use std::sync::Arc;
@@ -42,6 +43,7 @@ use crate::commit::Commit;
 use crate::commit::CommitByCommitterTimestamp;
 use crate::commit_builder::CommitBuilder;
 use crate::commit_builder::DetachedCommitBuilder;
+use crate::config::ConfigGetError;
 use crate::dag_walk;
 use crate::default_index::DefaultIndexStore;
 use crate::default_index::DefaultMutableIndex;
@@ -55,6 +57,7 @@ use crate::index::IndexStore;
 use crate::index::MutableIndex;
 use crate::index::ReadonlyIndex;
 use crate::merge::MergeBuilder;
+use crate::merged_tree::MergeOptions;
 use crate::object_id::HexPrefix;
 use crate::object_id::ObjectId as _;
 use crate::object_id::PrefixResolution;
@@ -197,7 +200,9 @@ impl ReadonlyRepo {
         let backend = backend_initializer(settings, &store_path)?;
         let backend_path = store_path.join("type");
         fs::write(&backend_path, backend.name()).context(&backend_path)?;
-        let store = Store::new(backend, signer);
+        let merge_options = MergeOptions::from_settings(settings, &repo_path)
+            .map_err(|err| BackendInitError(err.into()))?;
+        let store = Store::new(backend, signer, merge_options);
 
         let op_store_path = repo_path.join("op_store");
         fs::create_dir(&op_store_path).context(&op_store_path)?;
@@ -469,6 +474,8 @@ pub enum StoreLoadError {
     Backend(#[from] BackendLoadError),
     #[error(transparent)]
     Signing(#[from] SignInitError),
+    #[error(transparent)]
+    Config(#[from] ConfigGetError),
 }
 
 impl StoreFactories {
@@ -670,6 +677,7 @@ impl RepoLoader {
         let store = Store::new(
             store_factories.load_backend(settings, &repo_path.join("store"))?,
             Signer::from_settings(settings)?,
+            MergeOptions::from_settings(settings, repo_path)?,
         );
         let root_op_data = RootOperationData {
             root_commit_id: store.root_commit_id().clone(),
@@ -857,6 +865,15 @@ pub struct MutableRepo {
     //   commits. However, if the type is `Abandoned`, a new working-copy commit should be created
     //   on top of all of the new commits instead.
     parent_mapping: HashMap<CommitId, Rewrite>,
+    // Commits rewritten in this transaction, mapped to their new versions. Unlike
+    // `parent_mapping`, this isn't cleared when the descendants are rebased, so
+    // it can be used to carry data attached to the old commits (e.g. Git notes)
+    // over to the new ones.
+    rewritten_commits: HashMap<CommitId, Vec<CommitId>>,
+    // Commits written in this transaction, paired with the commits they were
+    // rewritten from. Their conflict resolutions are recorded when the
+    // transaction is committed.
+    written_commits: Vec<(Commit, Option<Commit>)>,
 }
 
 impl MutableRepo {
@@ -872,6 +889,8 @@ impl MutableRepo {
             index: mut_index,
             view: DirtyCell::with_clean(mut_view),
             parent_mapping: Default::default(),
+            rewritten_commits: Default::default(),
+            written_commits: Default::default(),
         }
     }
 
@@ -919,6 +938,8 @@ impl MutableRepo {
     /// docstring for `record_rewritten_commit` for details.
     pub fn set_rewritten_commit(&mut self, old_id: CommitId, new_id: CommitId) {
         assert_ne!(old_id, *self.store().root_commit_id());
+        self.rewritten_commits
+            .insert(old_id.clone(), vec![new_id.clone()]);
         self.parent_mapping
             .insert(old_id, Rewrite::Rewritten(new_id));
     }
@@ -936,10 +957,11 @@ impl MutableRepo {
         new_ids: impl IntoIterator<Item = CommitId>,
     ) {
         assert_ne!(old_id, *self.store().root_commit_id());
-        self.parent_mapping.insert(
-            old_id.clone(),
-            Rewrite::Divergent(new_ids.into_iter().collect()),
-        );
+        let new_ids = new_ids.into_iter().collect_vec();
+        self.rewritten_commits
+            .insert(old_id.clone(), new_ids.clone());
+        self.parent_mapping
+            .insert(old_id, Rewrite::Divergent(new_ids));
     }
 
     /// Record a commit as having been abandoned in this transaction.
@@ -977,6 +999,30 @@ impl MutableRepo {
         );
     }
 
+    /// Returns the commits rewritten in this transaction, mapped to their new
+    /// versions.
+    ///
+    /// Unlike the rewrites recorded for `rebase_descendants()`, this includes
+    /// the commits whose descendants have already been rebased. Abandoned
+    /// commits aren't included. If a new version is rewritten again, it's
+    /// recorded as another entry.
+    pub fn rewritten_commits(&self) -> &HashMap<CommitId, Vec<CommitId>> {
+        &self.rewritten_commits
+    }
+
+    /// Remembers a commit written in this transaction so the conflict
+    /// resolutions made in it can be recorded when the transaction is
+    /// committed. This is noop if the resolution cache isn't enabled.
+    pub(crate) fn add_written_commit(&mut self, commit: Commit, rewrite_source: Option<Commit>) {
+        if self.store().merge_options().resolution_cache.is_some() {
+            self.written_commits.push((commit, rewrite_source));
+        }
+    }
+
+    pub(crate) fn take_written_commits(&mut self) -> Vec<(Commit, Option<Commit>)> {
+        mem::take(&mut self.written_commits)
+    }
+
     pub fn has_rewrites(&self) -> bool {
         !self.parent_mapping.is_empty()
     }
@@ -1518,6 +1564,77 @@ impl MutableRepo {
         Ok(())
     }
 
+    /// Adds the given `commits` and their ancestors to the index without
+    /// making them visible.
+    ///
+    /// The commits should be referenced by the view, e.g. as Git stashes, so
+    /// they are indexed again if the index is rebuilt.
+    pub fn add_hidden_commits(&mut self, commits: &[Commit]) -> BackendResult<()> {
+        let missing_commits = dag_walk::topo_order_reverse_ord_ok(
+            commits
+                .iter()
+                .filter(|commit| !self.index().has_id(commit.id()))
+                .cloned()
+                .map(CommitByCommitterTimestamp)
+                .map(Ok),
+            |CommitByCommitterTimestamp(commit)| commit.id().clone(),
+            |CommitByCommitterTimestamp(commit)| {
+                commit
+                    .parent_ids()
+                    .iter()
+                    .filter(|id| !self.index().has_id(id))
+                    .map(|id| self.store().get_commit(id))
+                    .map_ok(CommitByCommitterTimestamp)
+                    .collect_vec()
+            },
+        )?;
+        for CommitByCommitterTimestamp(missing_commit) in missing_commits.iter().rev() {
+            self.index.add_commit(missing_commit);
+        }
+        Ok(())
+    }
+
+    /// Updates the index to the current parents of the given `commits`, and
+    /// indexes their new ancestors.
+    ///
+    /// This is needed when the backend starts reporting different parents for
+    /// commits that are already indexed, e.g. when a shallow Git repository is
+    /// deepened. The `commits` should be read from the store after the change.
+    pub fn reparent_commits_in_index(&mut self, commits: &[Commit]) -> BackendResult<()> {
+        if commits.is_empty() {
+            return Ok(());
+        }
+        let new_parents: Vec<_> = commits
+            .iter()
+            .flat_map(|commit| commit.parent_ids())
+            .filter(|id| !self.index().has_id(id))
+            .map(|id| self.store().get_commit(id))
+            .try_collect()?;
+        let missing_commits = dag_walk::topo_order_reverse_ord_ok(
+            new_parents
+                .into_iter()
+                .map(CommitByCommitterTimestamp)
+                .map(Ok),
+            |CommitByCommitterTimestamp(commit)| commit.id().clone(),
+            |CommitByCommitterTimestamp(commit)| {
+                commit
+                    .parent_ids()
+                    .iter()
+                    .filter(|id| !self.index().has_id(id))
+                    .map(|id| self.store().get_commit(id))
+                    .map_ok(CommitByCommitterTimestamp)
+                    .collect_vec()
+            },
+        )?;
+        for CommitByCommitterTimestamp(missing_commit) in missing_commits.iter().rev() {
+            self.index.add_commit(missing_commit);
+        }
+        self.index.reparent_commits(commits);
+        // Heads may now be ancestors of the reparented commits.
+        self.view.mark_dirty();
+        Ok(())
+    }
+
     pub fn remove_head(&mut self, head: &CommitId) {
         self.view_mut().remove_head(head);
         self.view.mark_dirty();
@@ -1640,6 +1757,18 @@ impl MutableRepo {
         self.view_mut().set_git_head_target(target);
     }
 
+    /// Sets the commits imported from the `git stash` entries. The commits
+    /// should be indexed by `add_hidden_commits()` if they aren't visible.
+    pub fn set_git_stashes(&mut self, commit_ids: Vec<CommitId>) {
+        self.view_mut().set_git_stashes(commit_ids);
+    }
+
+    /// Sets the Git commit of `refs/notes/commits`. This should be called
+    /// when the notes are imported from or exported to Git.
+    pub fn set_git_notes(&mut self, commit_id: Option<CommitId>) {
+        self.view_mut().set_git_notes(commit_id);
+    }
+
     pub fn set_view(&mut self, data: op_store::View) {
         self.view_mut().set_view(data);
         self.view.mark_dirty();
@@ -1749,6 +1878,20 @@ impl MutableRepo {
         );
         self.set_git_head_target(new_git_head_target);
 
+        // Concurrent imports create different commits for the same stash
+        // entries, so keep our stashes if both sides changed them.
+        if other.git_stashes() != base.git_stashes()
+            && self.view().git_stashes() == base.git_stashes()
+        {
+            self.set_git_stashes(other.git_stashes().to_vec());
+        }
+
+        // The notes commits can't be merged here, so keep ours if both sides
+        // changed them. The Git ref is authoritative anyway.
+        if other.git_notes() != base.git_notes() && self.view().git_notes() == base.git_notes() {
+            self.set_git_notes(other.git_notes().cloned());
+        }
+
         Ok(())
     }
 
@@ -0,0 +1,317 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cache of conflict resolutions that can be reused when the same conflict
+//! shows up again, e.g. after rebasing a branch another time.
+//!
+//! Resolutions are recorded per conflicting hunk. The key is a hash of the
+//! contents of the hunk's terms, so the same resolution is found regardless of
+//! the surrounding lines or the path of the file.
+
+use std::fs;
+use std::io;
+use std::io::ErrorKind;
+use std::io::Read as _;
+use std::path::PathBuf;
+
+use blake2::Blake2b512;
+use blake2::Digest as _;
+use bstr::BStr;
+use bstr::BString;
+use bstr::ByteSlice as _;
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
+use thiserror::Error;
+
+use crate::backend::BackendError;
+use crate::backend::FileId;
+use crate::backend::TreeValue;
+use crate::conflicts::extract_as_single_hunk;
+use crate::files;
+use crate::files::MergeResult;
+use crate::merge::Merge;
+use crate::merged_tree::MergedTree;
+use crate::repo_path::RepoPath;
+use crate::store::Store;
+
+/// Error that may occur while reading or recording resolutions.
+#[derive(Debug, Error)]
+pub enum ResolutionCacheError {
+    /// Failed to read the conflicted or resolved contents.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+    /// Failed to access the cache directory.
+    #[error("Failed to access the conflict resolution cache")]
+    Io(#[source] io::Error),
+}
+
+/// Stores resolutions of conflicting hunks in a directory, one file per hunk.
+#[derive(Debug)]
+pub struct ResolutionCache {
+    dir: PathBuf,
+}
+
+impl ResolutionCache {
+    /// Creates a cache backed by the given directory. The directory is created
+    /// when the first resolution is recorded.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        ResolutionCache { dir: dir.into() }
+    }
+
+    fn entry_path(&self, hunk: &Merge<BString>) -> PathBuf {
+        // The result of a merge doesn't depend on the order of its removes and
+        // adds, so sort them to find the resolution after e.g. the sides of
+        // the conflict have been swapped.
+        let mut hasher = Blake2b512::new();
+        for terms in [hunk.removes().sorted(), hunk.adds().sorted()] {
+            hasher.update((terms.len() as u64).to_le_bytes());
+            for term in terms {
+                hasher.update((term.len() as u64).to_le_bytes());
+                hasher.update(term);
+            }
+        }
+        self.dir.join(hex::encode(hasher.finalize()))
+    }
+
+    /// Looks up the recorded resolution of a conflicting hunk.
+    pub fn get(&self, hunk: &Merge<BString>) -> Result<Option<BString>, ResolutionCacheError> {
+        match fs::read(self.entry_path(hunk)) {
+            Ok(content) => Ok(Some(content.into())),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(ResolutionCacheError::Io(err)),
+        }
+    }
+
+    /// Records `resolution` as the resolution of a conflicting hunk.
+    pub fn insert(
+        &self,
+        hunk: &Merge<BString>,
+        resolution: &[u8],
+    ) -> Result<(), ResolutionCacheError> {
+        fs::create_dir_all(&self.dir).map_err(ResolutionCacheError::Io)?;
+        let mut temp_file =
+            tempfile::NamedTempFile::new_in(&self.dir).map_err(ResolutionCacheError::Io)?;
+        io::Write::write_all(temp_file.as_file_mut(), resolution)
+            .map_err(ResolutionCacheError::Io)?;
+        temp_file
+            .persist(self.entry_path(hunk))
+            .map_err(|err| ResolutionCacheError::Io(err.error))?;
+        Ok(())
+    }
+
+    /// Resolves the hunks returned by [`files::merge()`] by using the recorded
+    /// resolutions. Returns `None` unless all conflicting hunks have a recorded
+    /// resolution.
+    pub fn resolve_hunks(
+        &self,
+        hunks: &[Merge<BString>],
+    ) -> Result<Option<BString>, ResolutionCacheError> {
+        let mut resolved = BString::default();
+        for hunk in hunks {
+            if let Some(content) = hunk.as_resolved() {
+                resolved.extend_from_slice(content);
+            } else if let Some(content) = self.get(hunk)? {
+                resolved.extend_from_slice(&content);
+            } else {
+                return Ok(None);
+            }
+        }
+        Ok(Some(resolved))
+    }
+
+    /// Records the resolution of each conflicting hunk, given the merge result
+    /// of a file and the content the file was resolved to. Hunks are only
+    /// recorded if the lines around them were left unchanged. Returns the
+    /// number of recorded hunks.
+    pub fn record_hunks(
+        &self,
+        hunks: &[Merge<BString>],
+        resolved: &[u8],
+    ) -> Result<usize, ResolutionCacheError> {
+        let resolutions = split_resolution(hunks, resolved.as_bstr()).unwrap_or_default();
+        for (hunk, resolution) in &resolutions {
+            self.insert(hunk, resolution)?;
+        }
+        Ok(resolutions.len())
+    }
+
+    /// Records the resolutions of the file conflicts in `old_tree` which are
+    /// resolved in `new_tree`. Returns the number of recorded hunks.
+    pub fn record_resolved_conflicts(
+        &self,
+        old_tree: &MergedTree,
+        new_tree: &MergedTree,
+    ) -> Result<usize, ResolutionCacheError> {
+        let store = new_tree.store();
+        let mut count = 0;
+        for (path, old_value) in old_tree.conflicts() {
+            let Some(file_ids) = old_value?.to_file_merge() else {
+                continue;
+            };
+            let new_value = new_tree.path_value(&path)?;
+            let Some(Some(TreeValue::File { id, .. })) = new_value.as_resolved() else {
+                continue;
+            };
+            let contents = extract_as_single_hunk(&file_ids.simplify(), store, &path).block_on()?;
+            let options = &store.merge_options().file_merge_options;
+            if let MergeResult::Conflict(hunks) = files::merge_with_options(&contents, options) {
+                let resolved = read_file(store, &path, id)?;
+                count += self.record_hunks(&hunks, &resolved)?;
+            }
+        }
+        Ok(count)
+    }
+}
+
+fn read_file(store: &Store, path: &RepoPath, id: &FileId) -> Result<Vec<u8>, ResolutionCacheError> {
+    let mut content = vec![];
+    store
+        .read_file(path, id)?
+        .read_to_end(&mut content)
+        .map_err(|err| BackendError::ReadFile {
+            path: path.to_owned(),
+            id: id.clone(),
+            source: err.into(),
+        })?;
+    Ok(content)
+}
+
+/// Splits the `resolved` content into the resolutions of the conflicting
+/// `hunks` by matching the resolved hunks in between. Returns `None` if the
+/// resolved hunks cannot be found in `resolved`, or if a resolved hunk
+/// following a conflict occurs more than once in the rest of the content,
+/// since it would then be ambiguous where the resolution of the conflict
+/// ends.
+fn split_resolution<'a>(
+    hunks: &'a [Merge<BString>],
+    resolved: &'a BStr,
+) -> Option<Vec<(&'a Merge<BString>, &'a BStr)>> {
+    let mut resolutions = vec![];
+    let mut rest = resolved;
+    let mut pending_conflict = None;
+    for hunk in hunks {
+        let Some(content) = hunk.as_resolved() else {
+            pending_conflict = Some(hunk);
+            continue;
+        };
+        let pos = if pending_conflict.is_some() {
+            rest.find_iter(content).exactly_one().ok()?
+        } else if rest.starts_with(content) {
+            0
+        } else {
+            return None;
+        };
+        if let Some(conflict) = pending_conflict.take() {
+            resolutions.push((conflict, &rest[..pos]));
+        }
+        rest = &rest[pos + content.len()..];
+    }
+    if let Some(conflict) = pending_conflict {
+        resolutions.push((conflict, rest));
+    } else if !rest.is_empty() {
+        return None;
+    }
+    Some(resolutions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conflict(base: &str, left: &str, right: &str) -> Merge<BString> {
+        Merge::from_vec(vec![left.into(), base.into(), right.into()])
+    }
+
+    #[test]
+    fn test_split_resolution() {
+        let hunks = vec![
+            Merge::resolved(BString::from("a\n")),
+            conflict("b\n", "b1\n", "b2\n"),
+            Merge::resolved(BString::from("c\n")),
+            conflict("d\n", "d1\n", "d2\n"),
+        ];
+        assert_eq!(
+            split_resolution(&hunks, "a\nb1\nb2\nc\nd2\n".into()),
+            Some(vec![
+                (&hunks[1], BStr::new("b1\nb2\n")),
+                (&hunks[3], BStr::new("d2\n")),
+            ])
+        );
+        // Conflicting hunks can be resolved to nothing
+        assert_eq!(
+            split_resolution(&hunks, "a\nc\n".into()),
+            Some(vec![(&hunks[1], BStr::new("")), (&hunks[3], BStr::new(""))])
+        );
+        // The lines around the conflicts were changed
+        assert_eq!(split_resolution(&hunks, "a2\nb\nc\nd\n".into()), None);
+        assert_eq!(split_resolution(&hunks, "a\nb\nd\n".into()), None);
+
+        let hunks = vec![
+            conflict("a\n", "a1\n", "a2\n"),
+            Merge::resolved(BString::from("b\n")),
+        ];
+        assert_eq!(
+            split_resolution(&hunks, "a1\nb\n".into()),
+            Some(vec![(&hunks[0], BStr::new("a1\n"))])
+        );
+        assert_eq!(split_resolution(&hunks, "a1\nb\nc\n".into()), None);
+        // The resolution contains the following line, so it's unclear where
+        // the resolution ends
+        assert_eq!(split_resolution(&hunks, "b\na1\nb\n".into()), None);
+
+        let hunks = vec![
+            Merge::resolved(BString::from("a\n")),
+            conflict("b\n", "b1\n", "b2\n"),
+            Merge::resolved(BString::from("c\n")),
+            conflict("d\n", "d1\n", "d2\n"),
+            Merge::resolved(BString::from("c\n")),
+        ];
+        assert_eq!(split_resolution(&hunks, "a\nb1\nc\nd1\nc\n".into()), None);
+    }
+
+    #[test]
+    fn test_resolve_hunks() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cache = ResolutionCache::new(temp_dir.path().join("resolutions"));
+        let hunks = vec![
+            Merge::resolved(BString::from("a\n")),
+            conflict("b\n", "b1\n", "b2\n"),
+            Merge::resolved(BString::from("c\n")),
+        ];
+        assert_eq!(cache.resolve_hunks(&hunks).unwrap(), None);
+        assert_eq!(cache.record_hunks(&hunks, b"a\nb3\nc\n").unwrap(), 1);
+        assert_eq!(
+            cache.resolve_hunks(&hunks).unwrap(),
+            Some(BString::from("a\nb3\nc\n"))
+        );
+
+        // The same conflict with different context and swapped sides
+        let hunks = vec![
+            conflict("b\n", "b2\n", "b1\n"),
+            Merge::resolved(BString::from("x\n")),
+        ];
+        assert_eq!(
+            cache.resolve_hunks(&hunks).unwrap(),
+            Some(BString::from("b3\nx\n"))
+        );
+
+        // Another conflicting hunk without resolution
+        let hunks = vec![
+            conflict("b\n", "b1\n", "b2\n"),
+            Merge::resolved(BString::from("x\n")),
+            conflict("y\n", "y1\n", "y2\n"),
+        ];
+        assert_eq!(cache.resolve_hunks(&hunks).unwrap(), None);
+    }
+}
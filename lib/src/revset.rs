@@ -41,6 +41,8 @@ use crate::graph::GraphNode;
 use crate::hex_util::to_forward_hex;
 use crate::id_prefix::IdPrefixContext;
 use crate::id_prefix::IdPrefixIndex;
+use crate::mailmap::LazyMailmap;
+use crate::mailmap::Mailmap;
 use crate::object_id::HexPrefix;
 use crate::object_id::PrefixResolution;
 use crate::op_store::RemoteRefState;
@@ -142,6 +144,7 @@ pub enum RevsetCommitRef {
     Tags(StringPattern),
     GitRefs,
     GitHead,
+    Stashes,
 }
 
 /// A custom revset filter expression, defined by an extension.
@@ -172,6 +175,13 @@ pub enum RevsetFilterPredicate {
     CommitterEmail(StringPattern),
     /// Commits with committer dates matching the given date pattern.
     CommitterDate(DatePattern),
+    /// Commits with author or committer name or email matching the pattern
+    /// after mapping them by the mailmap.
+    MappedSignature {
+        field: SignatureField,
+        pattern: StringPattern,
+        mailmap: Arc<Mailmap>,
+    },
     /// Commits modifying the paths specified by the fileset.
     File(FilesetExpression),
     /// Commits containing diffs matching the `text` pattern within the `files`.
@@ -181,10 +191,26 @@ pub enum RevsetFilterPredicate {
     },
     /// Commits with conflicts
     HasConflict,
+    /// Commits with conflicts at the paths specified by the fileset. If
+    /// `new_only` is set, only conflicts which the parents don't have are
+    /// considered.
+    HasConflictIn {
+        files: FilesetExpression,
+        new_only: bool,
+    },
     /// Custom predicates provided by extensions
     Extension(Rc<dyn RevsetFilterExtension>),
 }
 
+/// Name or email of the author or committer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SignatureField {
+    AuthorName,
+    AuthorEmail,
+    CommitterName,
+    CommitterEmail,
+}
+
 mod private {
     /// Defines [`RevsetExpression`] variants depending on resolution state.
     pub trait ExpressionState {
@@ -367,6 +393,10 @@ impl<St: ExpressionState<CommitRef = RevsetCommitRef>> RevsetExpression<St> {
     pub fn git_head() -> Rc<Self> {
         Rc::new(Self::CommitRef(RevsetCommitRef::GitHead))
     }
+
+    pub fn stashes() -> Rc<Self> {
+        Rc::new(Self::CommitRef(RevsetCommitRef::Stashes))
+    }
 }
 
 // Compound expression
@@ -770,6 +800,10 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         function.expect_no_arguments()?;
         Ok(RevsetExpression::git_head())
     });
+    map.insert("stashes", |_diagnostics, function, _context| {
+        function.expect_no_arguments()?;
+        Ok(RevsetExpression::stashes())
+    });
     map.insert("latest", |diagnostics, function, context| {
         let ([candidates_arg], [count_opt_arg]) = function.expect_arguments()?;
         let candidates = lower_expression(diagnostics, candidates_arg, context)?;
@@ -804,24 +838,25 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         let predicate = RevsetFilterPredicate::Subject(pattern);
         Ok(RevsetExpression::filter(predicate))
     });
-    map.insert("author", |diagnostics, function, _context| {
+    map.insert("author", |diagnostics, function, context| {
         let [arg] = function.expect_exact_arguments()?;
         let pattern = expect_string_pattern(diagnostics, arg)?;
-        let name_predicate = RevsetFilterPredicate::AuthorName(pattern.clone());
-        let email_predicate = RevsetFilterPredicate::AuthorEmail(pattern);
+        let name_predicate =
+            context.signature_predicate(SignatureField::AuthorName, pattern.clone());
+        let email_predicate = context.signature_predicate(SignatureField::AuthorEmail, pattern);
         Ok(RevsetExpression::filter(name_predicate)
             .union(&RevsetExpression::filter(email_predicate)))
     });
-    map.insert("author_name", |diagnostics, function, _context| {
+    map.insert("author_name", |diagnostics, function, context| {
         let [arg] = function.expect_exact_arguments()?;
         let pattern = expect_string_pattern(diagnostics, arg)?;
-        let predicate = RevsetFilterPredicate::AuthorName(pattern);
+        let predicate = context.signature_predicate(SignatureField::AuthorName, pattern);
         Ok(RevsetExpression::filter(predicate))
     });
-    map.insert("author_email", |diagnostics, function, _context| {
+    map.insert("author_email", |diagnostics, function, context| {
         let [arg] = function.expect_exact_arguments()?;
         let pattern = expect_string_pattern(diagnostics, arg)?;
-        let predicate = RevsetFilterPredicate::AuthorEmail(pattern);
+        let predicate = context.signature_predicate(SignatureField::AuthorEmail, pattern);
         Ok(RevsetExpression::filter(predicate))
     });
     map.insert("author_date", |diagnostics, function, context| {
@@ -836,28 +871,35 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         // Email address domains are inherently case‐insensitive, and the local‐parts
         // are generally (although not universally) treated as case‐insensitive too, so
         // we use a case‐insensitive match here.
-        let predicate =
-            RevsetFilterPredicate::AuthorEmail(StringPattern::exact_i(context.user_email));
+        let user_email = match context.mailmap() {
+            Some(mailmap) => mailmap.resolve("", context.user_email).1,
+            None => context.user_email,
+        };
+        let predicate = context.signature_predicate(
+            SignatureField::AuthorEmail,
+            StringPattern::exact_i(user_email),
+        );
         Ok(RevsetExpression::filter(predicate))
     });
-    map.insert("committer", |diagnostics, function, _context| {
+    map.insert("committer", |diagnostics, function, context| {
         let [arg] = function.expect_exact_arguments()?;
         let pattern = expect_string_pattern(diagnostics, arg)?;
-        let name_predicate = RevsetFilterPredicate::CommitterName(pattern.clone());
-        let email_predicate = RevsetFilterPredicate::CommitterEmail(pattern);
+        let name_predicate =
+            context.signature_predicate(SignatureField::CommitterName, pattern.clone());
+        let email_predicate = context.signature_predicate(SignatureField::CommitterEmail, pattern);
         Ok(RevsetExpression::filter(name_predicate)
             .union(&RevsetExpression::filter(email_predicate)))
     });
-    map.insert("committer_name", |diagnostics, function, _context| {
+    map.insert("committer_name", |diagnostics, function, context| {
         let [arg] = function.expect_exact_arguments()?;
         let pattern = expect_string_pattern(diagnostics, arg)?;
-        let predicate = RevsetFilterPredicate::CommitterName(pattern);
+        let predicate = context.signature_predicate(SignatureField::CommitterName, pattern);
         Ok(RevsetExpression::filter(predicate))
     });
-    map.insert("committer_email", |diagnostics, function, _context| {
+    map.insert("committer_email", |diagnostics, function, context| {
         let [arg] = function.expect_exact_arguments()?;
         let pattern = expect_string_pattern(diagnostics, arg)?;
-        let predicate = RevsetFilterPredicate::CommitterEmail(pattern);
+        let predicate = context.signature_predicate(SignatureField::CommitterEmail, pattern);
         Ok(RevsetExpression::filter(predicate))
     });
     map.insert("committer_date", |diagnostics, function, context| {
@@ -902,9 +944,37 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
             RevsetFilterPredicate::DiffContains { text, files },
         ))
     });
-    map.insert("conflicts", |_diagnostics, function, _context| {
-        function.expect_no_arguments()?;
-        Ok(RevsetExpression::filter(RevsetFilterPredicate::HasConflict))
+    map.insert("conflicts", |diagnostics, function, context| {
+        let ([], [opt_arg1, opt_arg2]) = function.expect_arguments()?;
+        // conflicts(new[, files]) only selects conflicts the parents don't have.
+        let (new_only, files_opt_arg) = match opt_arg1 {
+            Some(node) if matches!(node.kind, ExpressionKind::Identifier("new")) => {
+                (true, opt_arg2)
+            }
+            Some(node) if opt_arg2.is_some() => {
+                return Err(RevsetParseError::expression(
+                    "Expected `new` as the first argument",
+                    node.span,
+                ));
+            }
+            _ => (false, opt_arg1),
+        };
+        let files = if let Some(files_arg) = files_opt_arg {
+            let ctx = context.workspace.as_ref().ok_or_else(|| {
+                RevsetParseError::with_span(
+                    RevsetParseErrorKind::FsPathWithoutWorkspace,
+                    files_arg.span,
+                )
+            })?;
+            expect_fileset_expression(diagnostics, files_arg, ctx.path_converter)?
+        } else if new_only {
+            FilesetExpression::all()
+        } else {
+            return Ok(RevsetExpression::filter(RevsetFilterPredicate::HasConflict));
+        };
+        Ok(RevsetExpression::filter(
+            RevsetFilterPredicate::HasConflictIn { files, new_only },
+        ))
     });
     map.insert("present", |diagnostics, function, context| {
         let [arg] = function.expect_exact_arguments()?;
@@ -2151,6 +2221,7 @@ fn resolve_commit_ref(
             Ok(commit_ids)
         }
         RevsetCommitRef::GitHead => Ok(repo.view().git_head().added_ids().cloned().collect()),
+        RevsetCommitRef::Stashes => Ok(repo.view().git_stashes().to_vec()),
     }
 }
 
@@ -2573,6 +2644,9 @@ pub struct RevsetParseContext<'a> {
     pub date_pattern_context: DatePatternContext,
     pub extensions: &'a RevsetExtensions,
     pub workspace: Option<RevsetWorkspaceContext<'a>>,
+    /// Mailmap to apply to author and committer signatures, if any. It's
+    /// loaded only if the expression matches signatures.
+    pub mailmap: Option<&'a LazyMailmap>,
 }
 
 impl<'a> RevsetParseContext<'a> {
@@ -2584,12 +2658,14 @@ impl<'a> RevsetParseContext<'a> {
             date_pattern_context,
             extensions,
             workspace,
+            mailmap,
         } = *self;
         LoweringContext {
             user_email,
             date_pattern_context,
             extensions,
             workspace,
+            mailmap,
         }
     }
 }
@@ -2601,6 +2677,7 @@ pub struct LoweringContext<'a> {
     date_pattern_context: DatePatternContext,
     extensions: &'a RevsetExtensions,
     workspace: Option<RevsetWorkspaceContext<'a>>,
+    mailmap: Option<&'a LazyMailmap>,
 }
 
 impl<'a> LoweringContext<'a> {
@@ -2615,6 +2692,33 @@ impl<'a> LoweringContext<'a> {
     pub fn symbol_resolvers(&self) -> &'a [impl AsRef<dyn SymbolResolverExtension> + use<>] {
         self.extensions.symbol_resolvers()
     }
+
+    /// Returns the mailmap, loading it if needed.
+    fn mailmap(&self) -> Option<&'a Arc<Mailmap>> {
+        self.mailmap.and_then(|mailmap| mailmap.get())
+    }
+
+    /// Returns the predicate matching the `field` of the author or committer
+    /// signature, which is mapped by the mailmap if there's one.
+    fn signature_predicate(
+        &self,
+        field: SignatureField,
+        pattern: StringPattern,
+    ) -> RevsetFilterPredicate {
+        if let Some(mailmap) = self.mailmap() {
+            return RevsetFilterPredicate::MappedSignature {
+                field,
+                pattern,
+                mailmap: mailmap.clone(),
+            };
+        }
+        match field {
+            SignatureField::AuthorName => RevsetFilterPredicate::AuthorName(pattern),
+            SignatureField::AuthorEmail => RevsetFilterPredicate::AuthorEmail(pattern),
+            SignatureField::CommitterName => RevsetFilterPredicate::CommitterName(pattern),
+            SignatureField::CommitterEmail => RevsetFilterPredicate::CommitterEmail(pattern),
+        }
+    }
 }
 
 /// Workspace information needed to parse revset expression.
@@ -2683,6 +2787,7 @@ mod tests {
             date_pattern_context: chrono::Utc::now().fixed_offset().into(),
             extensions: &RevsetExtensions::default(),
             workspace: None,
+            mailmap: None,
         };
         super::parse(&mut RevsetDiagnostics::new(), revset_str, &context)
     }
@@ -2712,6 +2817,7 @@ mod tests {
             date_pattern_context: chrono::Utc::now().fixed_offset().into(),
             extensions: &RevsetExtensions::default(),
             workspace: Some(workspace_ctx),
+            mailmap: None,
         };
         super::parse(&mut RevsetDiagnostics::new(), revset_str, &context)
     }
@@ -2737,6 +2843,7 @@ mod tests {
             date_pattern_context: chrono::Utc::now().fixed_offset().into(),
             extensions: &RevsetExtensions::default(),
             workspace: None,
+            mailmap: None,
         };
         super::parse_with_modifier(&mut RevsetDiagnostics::new(), revset_str, &context)
     }
@@ -3245,6 +3352,49 @@ mod tests {
             ),
         )
         "#);
+        insta::assert_debug_snapshot!(parse("conflicts()").unwrap(), @"Filter(HasConflict)");
+        assert!(parse("conflicts(foo)").is_err());
+        insta::assert_debug_snapshot!(
+            parse_with_workspace("conflicts(foo)", WorkspaceName::DEFAULT).unwrap(), @r#"
+        Filter(
+            HasConflictIn {
+                files: Pattern(PrefixPath("foo")),
+                new_only: false,
+            },
+        )
+        "#);
+        insta::assert_debug_snapshot!(
+            parse("conflicts(new)").unwrap(), @"
+        Filter(
+            HasConflictIn {
+                files: All,
+                new_only: true,
+            },
+        )
+        ");
+        insta::assert_debug_snapshot!(
+            parse_with_workspace("conflicts(new, foo)", WorkspaceName::DEFAULT).unwrap(), @r#"
+        Filter(
+            HasConflictIn {
+                files: Pattern(PrefixPath("foo")),
+                new_only: true,
+            },
+        )
+        "#);
+        insta::assert_debug_snapshot!(
+            parse_with_workspace(r#"conflicts("new")"#, WorkspaceName::DEFAULT).unwrap(), @r#"
+        Filter(
+            HasConflictIn {
+                files: Pattern(PrefixPath("new")),
+                new_only: false,
+            },
+        )
+        "#);
+        insta::assert_debug_snapshot!(
+            parse_with_workspace("conflicts(foo, bar)", WorkspaceName::DEFAULT)
+                .unwrap_err()
+                .kind(),
+            @r#"Expression("Expected `new` as the first argument")"#);
     }
 
     #[test]
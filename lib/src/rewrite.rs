@@ -33,10 +33,12 @@ use crate::commit::Commit;
 use crate::commit::CommitIteratorExt as _;
 use crate::commit_builder::CommitBuilder;
 use crate::dag_walk;
+use crate::files::FileMergeWhitespace;
 use crate::index::Index;
 use crate::index::IndexError;
 use crate::matchers::Matcher;
 use crate::matchers::Visit;
+use crate::merge::Merge;
 use crate::merged_tree::MergedTree;
 use crate::merged_tree::MergedTreeBuilder;
 use crate::merged_tree::TreeDiffEntry;
@@ -224,6 +226,17 @@ impl<'repo> CommitRewriter<'repo> {
     pub fn rebase_with_empty_behavior(
         self,
         empty: EmptyBehaviour,
+    ) -> BackendResult<Option<CommitBuilder<'repo>>> {
+        self.rebase_with_whitespace(empty, FileMergeWhitespace::Exact)
+    }
+
+    /// Like [`Self::rebase_with_empty_behavior()`], but also resolves the
+    /// conflicting hunks whose sides only differ in whitespace as compared by
+    /// `whitespace`.
+    fn rebase_with_whitespace(
+        self,
+        empty: EmptyBehaviour,
+        whitespace: FileMergeWhitespace,
     ) -> BackendResult<Option<CommitBuilder<'repo>>> {
         let old_parents: Vec<_> = self.old_commit.parents().try_collect()?;
         let old_parent_trees = old_parents
@@ -240,21 +253,52 @@ impl<'repo> CommitRewriter<'repo> {
             .map(|parent| parent.tree_id().clone())
             .collect_vec();
 
-        let (was_empty, new_tree_id) = if new_parent_trees == old_parent_trees {
+        let (was_empty, new_tree_id, conflict_labels) = if new_parent_trees == old_parent_trees {
             (
                 // Optimization: was_empty is only used for newly empty, but when the
                 // parents haven't changed it can't be newly empty.
                 true,
                 // Optimization: Skip merging.
                 self.old_commit.tree_id().clone(),
+                self.old_commit.conflict_labels(),
             )
         } else {
             let old_base_tree = merge_commit_trees(self.mut_repo, &old_parents)?;
             let new_base_tree = merge_commit_trees(self.mut_repo, &new_parents)?;
             let old_tree = self.old_commit.tree()?;
+            // The trees of merge commits are merges of unlabeled terms, so only
+            // label conflicts between single-parent commits.
+            let term_labels = match (&old_parents[..], &new_parents[..]) {
+                ([old_parent], [new_parent]) => [new_parent, old_parent, &self.old_commit]
+                    .into_iter()
+                    .map(conflict_term_labels)
+                    .collect::<BackendResult<Option<Vec<_>>>>()?,
+                _ => None,
+            };
+            let (new_tree, conflict_labels) = match term_labels {
+                Some(term_labels) => new_base_tree.merge_with_labels(
+                    &old_base_tree,
+                    &old_tree,
+                    Merge::from_vec(term_labels),
+                )?,
+                None => (new_base_tree.merge(&old_base_tree, &old_tree)?, None),
+            };
+            let (new_tree, conflict_labels) =
+                if whitespace != FileMergeWhitespace::Exact && new_tree.has_conflict() {
+                    let resolved_tree = new_tree.resolve_whitespace_conflicts(whitespace)?;
+                    // Resolving conflicts may simplify the tree, so only keep the
+                    // labels if they still line up with its terms.
+                    let conflict_labels = conflict_labels.filter(|labels| {
+                        resolved_tree.as_merge().as_slice().len() == labels.as_slice().len()
+                    });
+                    (resolved_tree, conflict_labels)
+                } else {
+                    (new_tree, conflict_labels)
+                };
             (
                 old_base_tree.id() == *self.old_commit.tree_id(),
-                new_base_tree.merge(&old_base_tree, &old_tree)?.id(),
+                new_tree.id(),
+                conflict_labels,
             )
         };
         // Ensure we don't abandon commits with multiple parents (merge commits), even
@@ -275,7 +319,10 @@ impl<'repo> CommitRewriter<'repo> {
             .mut_repo
             .rewrite_commit(&self.old_commit)
             .set_parents(self.new_parents)
-            .set_tree_id(new_tree_id);
+            .set_tree_id(new_tree_id)
+            .set_conflict_labels(
+                conflict_labels.map_or_else(Vec::new, |labels| labels.into_iter().collect()),
+            );
         Ok(Some(builder))
     }
 
@@ -295,6 +342,17 @@ impl<'repo> CommitRewriter<'repo> {
     }
 }
 
+/// Returns the labels of the terms of the commit's tree, labeling a resolved
+/// tree with the commit itself. Returns `None` if the tree is conflicted but
+/// its terms aren't labeled.
+fn conflict_term_labels(commit: &Commit) -> BackendResult<Option<Merge<String>>> {
+    if commit.has_conflict()? {
+        Ok(commit.conflict_labels())
+    } else {
+        Ok(Some(Merge::resolved(commit.conflict_label())))
+    }
+}
+
 pub enum RebasedCommit {
     Rewritten(Commit),
     Abandoned { parent_id: CommitId },
@@ -318,7 +376,7 @@ pub fn rebase_commit_with_options(
         _ => None,
     };
     let new_parents_len = rewriter.new_parents.len();
-    if let Some(builder) = rewriter.rebase_with_empty_behavior(options.empty)? {
+    if let Some(builder) = rewriter.rebase_with_whitespace(options.empty, options.whitespace)? {
         let new_commit = builder.write()?;
         Ok(RebasedCommit::Rewritten(new_commit))
     } else {
@@ -378,6 +436,9 @@ pub struct RebaseOptions {
     /// If a merge commit would end up with one parent being an ancestor of the
     /// other, then filter out the ancestor.
     pub simplify_ancestor_merge: bool,
+    /// Resolve conflicting hunks whose sides only differ in whitespace as
+    /// compared by this, in addition to the configured merge options.
+    pub whitespace: FileMergeWhitespace,
 }
 
 /// Configuration for [`MutableRepo::update_rewritten_references()`].
@@ -744,6 +805,7 @@ pub fn move_commits(
         empty: EmptyBehaviour::Keep,
         rewrite_refs: options.rewrite_refs.clone(),
         simplify_ancestor_merge: options.simplify_ancestor_merge,
+        whitespace: options.whitespace,
     };
 
     // Rebase each commit onto its new parents in the reverse topological order
@@ -61,6 +61,7 @@ struct UserSettingsData {
 pub struct GitSettings {
     pub auto_local_bookmark: bool,
     pub abandon_unreachable_commits: bool,
+    pub fetch_notes: bool,
     // TODO: Remove this from the configuration schema when dropping
     // `git2` support.
     #[cfg(feature = "git2")]
@@ -73,6 +74,7 @@ impl GitSettings {
         Ok(GitSettings {
             auto_local_bookmark: settings.get_bool("git.auto-local-bookmark")?,
             abandon_unreachable_commits: settings.get_bool("git.abandon-unreachable-commits")?,
+            fetch_notes: settings.get_bool("git.fetch-notes")?,
             #[cfg(feature = "git2")]
             subprocess: settings.get_bool("git.subprocess")?,
             executable_path: settings.get("git.executable-path")?,
@@ -85,6 +87,7 @@ impl Default for GitSettings {
         GitSettings {
             auto_local_bookmark: false,
             abandon_unreachable_commits: true,
+            fetch_notes: false,
             #[cfg(feature = "git2")]
             subprocess: true,
             executable_path: PathBuf::from("git"),
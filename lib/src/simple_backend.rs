@@ -370,6 +370,7 @@ pub fn commit_to_proto(commit: &Commit) -> crate::protos::simple_store::Commit {
             proto.root_tree = tree_ids.iter().map(|id| id.to_bytes()).collect();
         }
     }
+    proto.conflict_labels = commit.conflict_labels.clone();
     proto.change_id = commit.change_id.to_bytes();
     proto.description = commit.description.clone();
     proto.author = Some(signature_to_proto(&commit.author));
@@ -399,6 +400,7 @@ fn commit_from_proto(mut proto: crate::protos::simple_store::Commit) -> Commit {
         parents,
         predecessors,
         root_tree,
+        conflict_labels: proto.conflict_labels,
         change_id,
         description: proto.description,
         author: signature_from_proto(proto.author.unwrap_or_default()),
@@ -561,6 +563,7 @@ mod tests {
             parents: vec![],
             predecessors: vec![],
             root_tree: MergedTreeId::resolved(backend.empty_tree_id().clone()),
+            conflict_labels: vec![],
             change_id: ChangeId::from_hex("abc123"),
             description: "".to_string(),
             author: create_signature(),
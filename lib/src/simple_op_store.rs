@@ -508,6 +508,11 @@ fn view_to_proto(view: &View) -> crate::protos::op_store::View {
 
     proto.git_head = ref_target_to_proto(&view.git_head);
 
+    proto.git_stashes = view.git_stashes.iter().map(|id| id.to_bytes()).collect();
+    if let Some(id) = &view.git_notes {
+        proto.git_notes = id.to_bytes();
+    }
+
     proto
 }
 
@@ -559,6 +564,11 @@ fn view_from_proto(proto: crate::protos::op_store::View) -> View {
         view.git_head = RefTarget::normal(CommitId::new(proto.git_head_legacy));
     }
 
+    view.git_stashes = proto.git_stashes.into_iter().map(CommitId::new).collect();
+    if !proto.git_notes.is_empty() {
+        view.git_notes = Some(CommitId::new(proto.git_notes));
+    }
+
     view
 }
 
@@ -787,6 +797,8 @@ mod tests {
                 WorkspaceName::DEFAULT.to_owned() => default_wc_commit_id,
                 "test".into() => test_wc_commit_id,
             },
+            git_stashes: vec![],
+            git_notes: None,
         }
     }
 
@@ -854,6 +866,24 @@ mod tests {
         assert_eq!(read_view, view);
     }
 
+    #[test]
+    fn test_read_write_view_with_git_stashes_and_notes() {
+        let temp_dir = new_temp_dir();
+        let root_data = RootOperationData {
+            root_commit_id: CommitId::from_hex("000000"),
+        };
+        let store = SimpleOpStore::init(temp_dir.path(), root_data).unwrap();
+        let view = View {
+            git_stashes: vec![CommitId::from_hex("eee111"), CommitId::from_hex("eee222")],
+            git_notes: Some(CommitId::from_hex("ddd111")),
+            ..create_view()
+        };
+        let view_id = store.write_view(&view).unwrap();
+        assert_ne!(view_id, store.write_view(&create_view()).unwrap());
+        let read_view = store.read_view(&view_id).unwrap();
+        assert_eq!(read_view, view);
+    }
+
     #[test]
     fn test_read_write_operation() {
         let temp_dir = new_temp_dir();
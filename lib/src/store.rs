@@ -42,6 +42,7 @@ use crate::commit::Commit;
 use crate::index::Index;
 use crate::merge::Merge;
 use crate::merge::MergedTreeValue;
+use crate::merged_tree::MergeOptions;
 use crate::merged_tree::MergedTree;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
@@ -59,6 +60,7 @@ const TREE_CACHE_CAPACITY: usize = 1000;
 pub struct Store {
     backend: Box<dyn Backend>,
     signer: Signer,
+    merge_options: MergeOptions,
     commit_cache: Mutex<CLruCache<CommitId, Arc<backend::Commit>>>,
     tree_cache: Mutex<CLruCache<(RepoPathBuf, TreeId), Arc<backend::Tree>>>,
 }
@@ -72,10 +74,15 @@ impl Debug for Store {
 }
 
 impl Store {
-    pub fn new(backend: Box<dyn Backend>, signer: Signer) -> Arc<Self> {
+    pub fn new(
+        backend: Box<dyn Backend>,
+        signer: Signer,
+        merge_options: MergeOptions,
+    ) -> Arc<Self> {
         Arc::new(Store {
             backend,
             signer,
+            merge_options,
             commit_cache: Mutex::new(CLruCache::new(COMMIT_CACHE_CAPACITY.try_into().unwrap())),
             tree_cache: Mutex::new(CLruCache::new(TREE_CACHE_CAPACITY.try_into().unwrap())),
         })
@@ -89,6 +96,10 @@ impl Store {
         &self.signer
     }
 
+    pub fn merge_options(&self) -> &MergeOptions {
+        &self.merge_options
+    }
+
     pub fn get_copy_records(
         &self,
         paths: Option<&[RepoPathBuf]>,
@@ -153,6 +164,15 @@ impl Store {
         Ok(data)
     }
 
+    /// Drops the given commits from the cache so they will be read from the
+    /// backend again.
+    pub(crate) fn forget_commits<'a>(&self, ids: impl IntoIterator<Item = &'a CommitId>) {
+        let mut locked_cache = self.commit_cache.lock().unwrap();
+        for id in ids {
+            locked_cache.pop(id);
+        }
+    }
+
     pub async fn write_commit(
         self: &Arc<Self>,
         commit: backend::Commit,
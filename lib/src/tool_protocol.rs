@@ -0,0 +1,694 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Protocol through which external diff and merge tools can read and write
+//! the files being edited, instead of working on trees materialized into
+//! temporary directories.
+//!
+//! The messages are JSON-RPC 2.0 requests and responses, one per line. The
+//! tool sends requests, and each of them is answered in order. The methods
+//! are:
+//!
+//! * `initialize`: Returns the [mode](ToolSessionMode) of the session and the
+//!   files to edit.
+//! * `read`: Returns the content of a file on the given [side](ToolFileSide).
+//! * `conflict`: Returns the terms and the hunks of a file conflict.
+//! * `write`: Sets the new content of a file.
+//! * `finish`: Ends the session, keeping the written files.
+//! * `abort`: Ends the session, discarding the written files.
+//!
+//! The content of the files is exchanged as text. Files which can't be
+//! represented that way, such as binary files and symbolic links, are marked
+//! as read-only and can't be written.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+use std::sync::Arc;
+
+use bstr::BString;
+use bstr::ByteSlice as _;
+use futures::StreamExt as _;
+use pollster::FutureExt as _;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::MergedTreeId;
+use crate::backend::TreeValue;
+use crate::conflicts::choose_materialized_conflict_marker_len;
+use crate::conflicts::extract_as_single_hunk;
+use crate::conflicts::materialize_merge_result_to_bytes_with_marker_len;
+use crate::conflicts::materialize_tree_value;
+use crate::conflicts::update_from_content;
+use crate::conflicts::ConflictMarkerStyle;
+use crate::conflicts::MaterializedTreeValue;
+use crate::files;
+use crate::files::MergeResult;
+use crate::matchers::Matcher;
+use crate::merge::Merge;
+use crate::merge::MergedTreeValue;
+use crate::merged_tree::MergedTree;
+use crate::merged_tree::MergedTreeBuilder;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::store::Store;
+
+/// Value of the `jsonrpc` field of the messages.
+pub const JSONRPC_VERSION: &str = "2.0";
+
+/// Error code for messages which aren't valid JSON.
+pub const PARSE_ERROR: i64 = -32700;
+/// Error code for messages which aren't valid requests.
+pub const INVALID_REQUEST: i64 = -32600;
+/// Error code for unknown methods.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// Error code for invalid parameters, e.g. paths which aren't being edited.
+pub const INVALID_PARAMS: i64 = -32602;
+/// Error code for errors while reading or writing the files.
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// Request sent by the tool.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Request {
+    /// Must be [`JSONRPC_VERSION`].
+    pub jsonrpc: String,
+    /// Identifier of the request, copied to the response.
+    #[serde(default)]
+    pub id: serde_json::Value,
+    /// Name of the method to call.
+    pub method: String,
+    /// Parameters of the method.
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
+/// Response to a [`Request`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Response {
+    /// Always [`JSONRPC_VERSION`].
+    pub jsonrpc: String,
+    /// Identifier of the request.
+    pub id: serde_json::Value,
+    /// Result of the method if it succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    /// Error if the method failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ResponseError>,
+}
+
+/// Error returned in a [`Response`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ResponseError {
+    /// One of the JSON-RPC error codes, e.g. [`INVALID_PARAMS`].
+    pub code: i64,
+    /// Description of the error.
+    pub message: String,
+}
+
+impl ResponseError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        ResponseError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<BackendError> for ResponseError {
+    fn from(err: BackendError) -> Self {
+        ResponseError::new(INTERNAL_ERROR, err.to_string())
+    }
+}
+
+/// What the tool is asked to do.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ToolSessionMode {
+    /// Edit the right side of a diff.
+    Diff,
+    /// Resolve conflicts.
+    Merge,
+}
+
+/// Side of a file to read.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ToolFileSide {
+    /// The left side of the diff. Only in diff mode.
+    Left,
+    /// The right side of the diff, or the conflict in merge mode.
+    Right,
+    /// The content written so far. Same as the right side until the file is
+    /// written.
+    Output,
+}
+
+/// Type of the value of a file.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ToolFileType {
+    /// The file doesn't exist.
+    Absent,
+    /// Regular file.
+    File,
+    /// Symbolic link. The content is the target of the link.
+    Symlink,
+    /// File conflict. The content has conflict markers.
+    Conflict,
+    /// Anything else, e.g. a conflict involving a directory.
+    Other,
+}
+
+/// Result of the `initialize` method.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct InitializeResult {
+    /// What the tool is asked to do.
+    pub mode: ToolSessionMode,
+    /// Files which can be read and written.
+    pub files: Vec<ToolFileInfo>,
+}
+
+/// File which can be read and written in the session.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ToolFileInfo {
+    /// Path of the file in the repo.
+    pub path: String,
+    /// Number of sides of the conflict, or 1 if the file isn't conflicted.
+    pub num_sides: usize,
+    /// Whether the file can't be written because one of its sides isn't a
+    /// regular file or a file conflict, or has content which isn't valid
+    /// UTF-8.
+    pub read_only: bool,
+}
+
+/// Parameters of the `read` method.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ReadParams {
+    /// Path of the file.
+    pub path: String,
+    /// Side to read.
+    pub side: ToolFileSide,
+}
+
+/// Result of the `read` method.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ReadResult {
+    /// Type of the value.
+    #[serde(rename = "type")]
+    pub file_type: ToolFileType,
+    /// Content of the file, or `None` if the file has no content or isn't
+    /// valid UTF-8. Files which aren't valid UTF-8 are read-only.
+    pub content: Option<String>,
+    /// Whether the file is executable.
+    pub executable: bool,
+}
+
+/// Parameters of the `conflict` method.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ConflictParams {
+    /// Path of the conflicted file.
+    pub path: String,
+}
+
+/// Result of the `conflict` method. Absent terms are empty.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ConflictResult {
+    /// Content of the sides.
+    pub sides: Vec<String>,
+    /// Content of the bases.
+    pub bases: Vec<String>,
+    /// The content split into resolved and conflicting hunks.
+    pub hunks: Vec<ConflictHunk>,
+}
+
+/// Hunk of a [`ConflictResult`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ConflictHunk {
+    /// Content of the hunk if all sides agree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved: Option<String>,
+    /// Content of the sides of a conflicting hunk.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sides: Vec<String>,
+    /// Content of the bases of a conflicting hunk.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bases: Vec<String>,
+}
+
+/// Parameters of the `write` method.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct WriteParams {
+    /// Path of the file.
+    pub path: String,
+    /// New content of the file, or `None` to delete it.
+    pub content: Option<String>,
+    /// Whether conflict markers left in the content of a conflicted file
+    /// should be parsed. The file stays conflicted if there are any.
+    #[serde(default)]
+    pub conflict_markers: bool,
+}
+
+/// State of a [`ToolSession`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ToolSessionState {
+    /// Waiting for requests.
+    Running,
+    /// The tool called `finish`.
+    Finished,
+    /// The tool called `abort`.
+    Aborted,
+    /// The tool closed the connection before finishing the session.
+    Disconnected,
+}
+
+struct ToolSessionFile {
+    path: RepoPathBuf,
+    /// The left side of the diff, or `None` in merge mode.
+    left: Option<MergedTreeValue>,
+    /// The right side of the diff, or the conflict in merge mode.
+    right: MergedTreeValue,
+}
+
+/// Serves the requests of an external tool editing files of a tree.
+pub struct ToolSession {
+    store: Arc<Store>,
+    mode: ToolSessionMode,
+    base_tree_id: MergedTreeId,
+    conflict_marker_style: ConflictMarkerStyle,
+    files: BTreeMap<String, ToolSessionFile>,
+    outputs: BTreeMap<RepoPathBuf, MergedTreeValue>,
+    state: ToolSessionState,
+}
+
+impl ToolSession {
+    /// Creates a session editing the files of the `right_tree` which differ
+    /// from the `left_tree` and match the `matcher`.
+    pub fn for_diff(
+        left_tree: &MergedTree,
+        right_tree: &MergedTree,
+        matcher: &dyn Matcher,
+        conflict_marker_style: ConflictMarkerStyle,
+    ) -> BackendResult<Self> {
+        let mut files = BTreeMap::new();
+        let mut diff_stream = left_tree.diff_stream(right_tree, matcher);
+        async {
+            while let Some(entry) = diff_stream.next().await {
+                let (left, right) = entry.values?;
+                let file = ToolSessionFile {
+                    path: entry.path,
+                    left: Some(left),
+                    right,
+                };
+                files.insert(file.path.as_internal_file_string().to_owned(), file);
+            }
+            Ok::<_, BackendError>(())
+        }
+        .block_on()?;
+        Ok(ToolSession {
+            store: right_tree.store().clone(),
+            mode: ToolSessionMode::Diff,
+            base_tree_id: right_tree.id(),
+            conflict_marker_style,
+            files,
+            outputs: BTreeMap::new(),
+            state: ToolSessionState::Running,
+        })
+    }
+
+    /// Creates a session resolving the given `conflicts` of the `tree`.
+    pub fn for_merge(
+        tree: &MergedTree,
+        conflicts: impl IntoIterator<Item = (RepoPathBuf, MergedTreeValue)>,
+        conflict_marker_style: ConflictMarkerStyle,
+    ) -> Self {
+        let files = conflicts
+            .into_iter()
+            .map(|(path, conflict)| {
+                let file = ToolSessionFile {
+                    path,
+                    left: None,
+                    right: conflict,
+                };
+                (file.path.as_internal_file_string().to_owned(), file)
+            })
+            .collect();
+        ToolSession {
+            store: tree.store().clone(),
+            mode: ToolSessionMode::Merge,
+            base_tree_id: tree.id(),
+            conflict_marker_style,
+            files,
+            outputs: BTreeMap::new(),
+            state: ToolSessionState::Running,
+        }
+    }
+
+    /// State of the session.
+    pub fn state(&self) -> ToolSessionState {
+        self.state
+    }
+
+    /// Reads requests from the `reader` and writes the responses to the
+    /// `writer` until the session is finished or aborted. The session is
+    /// disconnected if the `reader` reaches EOF.
+    pub fn serve(&mut self, reader: &mut dyn BufRead, writer: &mut dyn Write) -> io::Result<()> {
+        let mut line = String::new();
+        while self.state == ToolSessionState::Running {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                self.state = ToolSessionState::Disconnected;
+                break;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = self.handle_message(&line);
+            serde_json::to_writer(&mut *writer, &response)?;
+            writer.write_all(b"\n")?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Parses the `message` as a request and handles it.
+    pub fn handle_message(&mut self, message: &str) -> Response {
+        match serde_json::from_str(message) {
+            Ok(request) => self.handle_request(request),
+            Err(err) => Response {
+                jsonrpc: JSONRPC_VERSION.to_owned(),
+                id: serde_json::Value::Null,
+                result: None,
+                error: Some(ResponseError::new(PARSE_ERROR, err.to_string())),
+            },
+        }
+    }
+
+    /// Handles the `request`.
+    pub fn handle_request(&mut self, request: Request) -> Response {
+        let result = if request.jsonrpc != JSONRPC_VERSION {
+            Err(ResponseError::new(
+                INVALID_REQUEST,
+                format!("Unsupported JSON-RPC version {:?}", request.jsonrpc),
+            ))
+        } else if self.state != ToolSessionState::Running {
+            Err(ResponseError::new(INVALID_REQUEST, "Session has ended"))
+        } else {
+            self.dispatch(&request.method, request.params)
+        };
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Response {
+            jsonrpc: JSONRPC_VERSION.to_owned(),
+            id: request.id,
+            result,
+            error,
+        }
+    }
+
+    /// Writes the tree with the files written in the session. Returns the
+    /// original tree if the session wasn't finished.
+    pub fn write_tree(self) -> BackendResult<MergedTreeId> {
+        if self.state != ToolSessionState::Finished {
+            return Ok(self.base_tree_id);
+        }
+        let mut tree_builder = MergedTreeBuilder::new(self.base_tree_id);
+        for (path, value) in self.outputs {
+            tree_builder.set_or_remove(path, value);
+        }
+        tree_builder.write_tree(&self.store)
+    }
+
+    fn dispatch(
+        &mut self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, ResponseError> {
+        match method {
+            "initialize" => {
+                let result = self.initialize()?;
+                to_value(result)
+            }
+            "read" => {
+                let result = self.read(parse_params(params)?)?;
+                to_value(result)
+            }
+            "conflict" => {
+                let result = self.conflict(parse_params(params)?)?;
+                to_value(result)
+            }
+            "write" => {
+                self.write(parse_params(params)?)?;
+                Ok(serde_json::Value::Null)
+            }
+            "finish" => {
+                self.state = ToolSessionState::Finished;
+                Ok(serde_json::Value::Null)
+            }
+            "abort" => {
+                self.state = ToolSessionState::Aborted;
+                Ok(serde_json::Value::Null)
+            }
+            _ => Err(ResponseError::new(
+                METHOD_NOT_FOUND,
+                format!("Unknown method {method:?}"),
+            )),
+        }
+    }
+
+    fn initialize(&self) -> Result<InitializeResult, ResponseError> {
+        let files = self
+            .files
+            .iter()
+            .map(|(path, file)| {
+                Ok(ToolFileInfo {
+                    path: path.clone(),
+                    num_sides: file.right.clone().simplify().num_sides(),
+                    read_only: self.is_read_only(file)?,
+                })
+            })
+            .collect::<Result<_, ResponseError>>()?;
+        Ok(InitializeResult {
+            mode: self.mode,
+            files,
+        })
+    }
+
+    fn file(&self, path: &str) -> Result<&ToolSessionFile, ResponseError> {
+        self.files.get(path).ok_or_else(|| {
+            ResponseError::new(INVALID_PARAMS, format!("{path:?} isn't being edited"))
+        })
+    }
+
+    /// Returns whether the `file` can't be represented as text on one of its
+    /// sides, so writing it would lose data or change its type.
+    fn is_read_only(&self, file: &ToolSessionFile) -> Result<bool, ResponseError> {
+        for value in [Some(&file.right), file.left.as_ref()]
+            .into_iter()
+            .flatten()
+        {
+            let (file_type, content, _) = self.read_value(&file.path, value)?;
+            let is_text = match file_type {
+                ToolFileType::Absent => true,
+                ToolFileType::File | ToolFileType::Conflict => {
+                    content.is_some_and(|content| content.is_utf8())
+                }
+                ToolFileType::Symlink | ToolFileType::Other => false,
+            };
+            if !is_text {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn read(&self, params: ReadParams) -> Result<ReadResult, ResponseError> {
+        let file = self.file(&params.path)?;
+        let value = match params.side {
+            ToolFileSide::Left => file.left.as_ref().ok_or_else(|| {
+                ResponseError::new(INVALID_PARAMS, "There's no left side in merge mode")
+            })?,
+            ToolFileSide::Right => &file.right,
+            ToolFileSide::Output => self.outputs.get(&file.path).unwrap_or(&file.right),
+        };
+        let (file_type, content, executable) = self.read_value(&file.path, value)?;
+        Ok(ReadResult {
+            file_type,
+            content: content.and_then(|content| String::from_utf8(content.into()).ok()),
+            executable,
+        })
+    }
+
+    /// Returns the type, the raw content, and the executable bit of the
+    /// `value`.
+    fn read_value(
+        &self,
+        path: &RepoPath,
+        value: &MergedTreeValue,
+    ) -> Result<(ToolFileType, Option<BString>, bool), ResponseError> {
+        let materialized = materialize_tree_value(&self.store, path, value.clone())
+            .block_on()
+            .map_err(ResponseError::from)?;
+        let result = match materialized {
+            MaterializedTreeValue::Absent => (ToolFileType::Absent, None, false),
+            MaterializedTreeValue::File(mut file_value) => {
+                let content = file_value.read_all(path)?;
+                (
+                    ToolFileType::File,
+                    Some(content.into()),
+                    file_value.executable,
+                )
+            }
+            MaterializedTreeValue::Symlink { target, .. } => {
+                (ToolFileType::Symlink, Some(target.into()), false)
+            }
+            MaterializedTreeValue::FileConflict {
+                contents,
+                executable,
+                ..
+            } => {
+                let content = materialize_merge_result_to_bytes_with_marker_len(
+                    &contents,
+                    &self.store.merge_options().file_merge_options,
+                    self.conflict_marker_style,
+                    choose_materialized_conflict_marker_len(&contents),
+                );
+                (ToolFileType::Conflict, Some(content), executable)
+            }
+            MaterializedTreeValue::AccessDenied(_)
+            | MaterializedTreeValue::OtherConflict { .. }
+            | MaterializedTreeValue::GitSubmodule(_)
+            | MaterializedTreeValue::Tree(_) => (ToolFileType::Other, None, false),
+        };
+        Ok(result)
+    }
+
+    fn conflict(&self, params: ConflictParams) -> Result<ConflictResult, ResponseError> {
+        let file = self.file(&params.path)?;
+        let file_merge = match file.right.to_file_merge() {
+            Some(file_merge) if !file_merge.is_resolved() => file_merge.simplify(),
+            _ => {
+                return Err(ResponseError::new(
+                    INVALID_PARAMS,
+                    format!("{:?} isn't a file conflict", params.path),
+                ));
+            }
+        };
+        let content = extract_as_single_hunk(&file_merge, &self.store, &file.path).block_on()?;
+        let merge_options = &self.store.merge_options().file_merge_options;
+        let hunks = match files::merge_with_options(&content, merge_options) {
+            MergeResult::Resolved(content) => vec![resolved_hunk(content)?],
+            MergeResult::Conflict(hunks) => hunks
+                .into_iter()
+                .map(|hunk| match hunk.into_resolved() {
+                    Ok(content) => resolved_hunk(content),
+                    Err(hunk) => Ok(ConflictHunk {
+                        resolved: None,
+                        sides: hunk.adds().map(to_text).collect::<Result<_, _>>()?,
+                        bases: hunk.removes().map(to_text).collect::<Result<_, _>>()?,
+                    }),
+                })
+                .collect::<Result<_, _>>()?,
+        };
+        Ok(ConflictResult {
+            sides: content.adds().map(to_text).collect::<Result<_, _>>()?,
+            bases: content.removes().map(to_text).collect::<Result<_, _>>()?,
+            hunks,
+        })
+    }
+
+    fn write(&mut self, params: WriteParams) -> Result<(), ResponseError> {
+        let file = self.file(&params.path)?;
+        if self.is_read_only(file)? {
+            return Err(ResponseError::new(
+                INVALID_PARAMS,
+                format!("{:?} is read-only", params.path),
+            ));
+        }
+        let path = file.path.clone();
+        let Some(content) = &params.content else {
+            self.outputs.insert(path, Merge::absent());
+            return Ok(());
+        };
+        let new_file_ids = match file.right.to_file_merge() {
+            Some(file_merge) if params.conflict_markers && !file_merge.is_resolved() => {
+                let simplified_file_merge = file_merge.clone().simplify();
+                let old_content =
+                    extract_as_single_hunk(&simplified_file_merge, &self.store, &path)
+                        .block_on()?;
+                update_from_content(
+                    &file_merge,
+                    &self.store,
+                    &path,
+                    content.as_bytes(),
+                    self.conflict_marker_style,
+                    choose_materialized_conflict_marker_len(&old_content),
+                )
+                .block_on()?
+            }
+            _ => {
+                let id = self
+                    .store
+                    .write_file(&path, &mut content.as_bytes())
+                    .block_on()?;
+                Merge::normal(id)
+            }
+        };
+        // Keep the executable bit of the edited side, or of the left side if
+        // the file was deleted.
+        let executable = [Some(&file.right), file.left.as_ref()]
+            .into_iter()
+            .flatten()
+            .filter(|value| value.is_present())
+            .find_map(|value| value.to_executable_merge()?.resolve_trivial().copied())
+            .unwrap_or(false);
+        let new_value = new_file_ids.map(|id| {
+            id.as_ref().map(|id| TreeValue::File {
+                id: id.clone(),
+                executable,
+            })
+        });
+        self.outputs.insert(path, new_value);
+        Ok(())
+    }
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(
+    params: serde_json::Value,
+) -> Result<T, ResponseError> {
+    serde_json::from_value(params)
+        .map_err(|err| ResponseError::new(INVALID_PARAMS, err.to_string()))
+}
+
+fn to_value(result: impl Serialize) -> Result<serde_json::Value, ResponseError> {
+    serde_json::to_value(result).map_err(|err| ResponseError::new(INTERNAL_ERROR, err.to_string()))
+}
+
+fn to_text(content: &BString) -> Result<String, ResponseError> {
+    String::from_utf8(content.to_vec())
+        .map_err(|_| ResponseError::new(INVALID_PARAMS, "Binary content isn't supported"))
+}
+
+fn resolved_hunk(content: BString) -> Result<ConflictHunk, ResponseError> {
+    Ok(ConflictHunk {
+        resolved: Some(to_text(&content)?),
+        sides: vec![],
+        bases: vec![],
+    })
+}
@@ -19,6 +19,7 @@ use std::sync::Arc;
 use itertools::Itertools as _;
 
 use crate::backend::Timestamp;
+use crate::commit::Commit;
 use crate::dag_walk;
 use crate::index::ReadonlyIndex;
 use crate::op_heads_store::OpHeadsStore;
@@ -28,9 +29,11 @@ use crate::op_store::OperationMetadata;
 use crate::operation::Operation;
 use crate::repo::MutableRepo;
 use crate::repo::ReadonlyRepo;
-use crate::repo::Repo as _;
+use crate::repo::Repo;
 use crate::repo::RepoLoader;
 use crate::repo::RepoLoaderError;
+use crate::resolution_cache::ResolutionCacheError;
+use crate::rewrite::merge_commit_trees;
 use crate::settings::UserSettings;
 use crate::view::View;
 
@@ -114,12 +117,20 @@ impl Transaction {
     /// That means that a repo can be loaded at the operation, but the
     /// operation will not be seen when loading the repo at head.
     pub fn write(mut self, description: impl Into<String>) -> UnpublishedOperation {
-        let mut_repo = self.mut_repo;
+        let mut mut_repo = self.mut_repo;
         // TODO: Should we instead just do the rebasing here if necessary?
         assert!(
             !mut_repo.has_rewrites(),
             "BUG: Descendants have not been rebased after the last rewrites."
         );
+        for (commit, rewrite_source) in mut_repo.take_written_commits() {
+            // The cache is only an optimization, so failing to update it
+            // shouldn't fail the operation.
+            record_conflict_resolutions(&mut_repo, &commit, rewrite_source.as_ref())
+                .unwrap_or_else(|err| {
+                    tracing::warn!(?err, "Failed to record conflict resolutions");
+                });
+        }
         let base_repo = mut_repo.base_repo().clone();
         let (mut_index, view) = mut_repo.consume();
 
@@ -146,6 +157,32 @@ impl Transaction {
     }
 }
 
+/// Records how the conflicts in the parents, or in the commit rewritten in
+/// place, were resolved in the new `commit` if the resolution cache is
+/// enabled.
+fn record_conflict_resolutions(
+    repo: &dyn Repo,
+    commit: &Commit,
+    rewrite_source: Option<&Commit>,
+) -> Result<(), ResolutionCacheError> {
+    let Some(cache) = &repo.store().merge_options().resolution_cache else {
+        return Ok(());
+    };
+    let parents: Vec<_> = commit.parents().try_collect()?;
+    let mut old_trees = vec![merge_commit_trees(repo, &parents)?];
+    // A rebased commit may be resolved just because its new parents don't
+    // conflict, which isn't a resolution of its old conflicts.
+    if let Some(source) = rewrite_source.filter(|source| source.parent_ids() == commit.parent_ids())
+    {
+        old_trees.push(source.tree()?);
+    }
+    let new_tree = commit.tree()?;
+    for old_tree in old_trees.iter().filter(|tree| tree.has_conflict()) {
+        cache.record_resolved_conflicts(old_tree, &new_tree)?;
+    }
+    Ok(())
+}
+
 pub fn create_op_metadata(
     user_settings: &UserSettings,
     description: String,
@@ -22,6 +22,7 @@ use std::hash::Hasher;
 use std::io::Read as _;
 use std::sync::Arc;
 
+use bstr::BString;
 use futures::future::try_join_all;
 use itertools::Itertools as _;
 use tracing::instrument;
@@ -35,6 +36,7 @@ use crate::backend::TreeEntry;
 use crate::backend::TreeId;
 use crate::backend::TreeValue;
 use crate::files;
+use crate::files::FileMergeOptions;
 use crate::files::MergeResult;
 use crate::matchers::EverythingMatcher;
 use crate::matchers::Matcher;
@@ -274,6 +276,19 @@ pub async fn try_resolve_file_conflict(
     store: &Store,
     filename: &RepoPath,
     conflict: &MergedTreeVal<'_>,
+) -> BackendResult<Option<TreeValue>> {
+    let file_merge_options = &store.merge_options().file_merge_options;
+    try_resolve_file_conflict_with_options(store, filename, conflict, file_merge_options).await
+}
+
+/// Resolves file-level conflict like [`try_resolve_file_conflict()`], but
+/// merges the file contents with the given options instead of the configured
+/// ones.
+pub async fn try_resolve_file_conflict_with_options(
+    store: &Store,
+    filename: &RepoPath,
+    conflict: &MergedTreeVal<'_>,
+    file_merge_options: &FileMergeOptions,
 ) -> BackendResult<Option<TreeValue>> {
     // If there are any non-file or any missing parts in the conflict, we can't
     // merge it. We check early so we don't waste time reading file contents if
@@ -325,14 +340,49 @@ pub async fn try_resolve_file_conflict(
         BackendResult::Ok(content)
     });
     let contents = Merge::from_vec(try_join_all(content_futures).await?);
-    let merge_result = files::merge(&contents);
-    match merge_result {
-        MergeResult::Resolved(merged_content) => {
-            let id = store
-                .write_file(filename, &mut merged_content.as_slice())
-                .await?;
-            Ok(Some(TreeValue::File { id, executable }))
+    let Some(merged_content) = merge_file_contents(store, filename, &contents, file_merge_options)?
+    else {
+        return Ok(None);
+    };
+    let id = store
+        .write_file(filename, &mut merged_content.as_slice())
+        .await?;
+    Ok(Some(TreeValue::File { id, executable }))
+}
+
+/// Merges the contents of a file by using the configured merge driver, the
+/// line-based (or word-based) merge, and the recorded resolutions in that
+/// order.
+fn merge_file_contents(
+    store: &Store,
+    filename: &RepoPath,
+    contents: &Merge<Vec<u8>>,
+    file_merge_options: &FileMergeOptions,
+) -> BackendResult<Option<BString>> {
+    let merge_options = store.merge_options();
+    if let Some(driver) = merge_options
+        .merge_drivers
+        .iter()
+        .find(|driver| driver.matches(filename))
+    {
+        let merged_content = driver
+            .merge(filename, contents)
+            .map_err(|err| BackendError::Other(err.into()))?;
+        if merged_content.is_some() {
+            return Ok(merged_content);
+        }
+    }
+    match files::merge_with_options(contents, file_merge_options) {
+        MergeResult::Resolved(merged_content) => Ok(Some(merged_content)),
+        MergeResult::Conflict(hunks) => {
+            // Reuse the resolutions recorded when the same conflicts were
+            // resolved before.
+            let Some(cache) = &merge_options.resolution_cache else {
+                return Ok(None);
+            };
+            cache
+                .resolve_hunks(&hunks)
+                .map_err(|err| BackendError::Other(err.into()))
         }
-        MergeResult::Conflict(_) => Ok(None),
     }
 }
@@ -94,6 +94,17 @@ impl View {
         &self.data.git_head
     }
 
+    /// The commits imported from the `git stash` entries, newest first.
+    pub fn git_stashes(&self) -> &[CommitId] {
+        &self.data.git_stashes
+    }
+
+    /// The Git commit `refs/notes/commits` pointed to when the notes were last
+    /// synced with Git.
+    pub fn git_notes(&self) -> Option<&CommitId> {
+        self.data.git_notes.as_ref()
+    }
+
     pub fn set_wc_commit(&mut self, name: WorkspaceNameBuf, commit_id: CommitId) {
         self.data.wc_commit_ids.insert(name, commit_id);
     }
@@ -356,6 +367,16 @@ impl View {
         self.data.git_head = target;
     }
 
+    /// Sets the commits imported from the `git stash` entries, newest first.
+    pub fn set_git_stashes(&mut self, commit_ids: Vec<CommitId>) {
+        self.data.git_stashes = commit_ids;
+    }
+
+    /// Sets the Git commit of `refs/notes/commits`.
+    pub fn set_git_notes(&mut self, commit_id: Option<CommitId>) {
+        self.data.git_notes = commit_id;
+    }
+
     /// Iterates all commit ids referenced by this view.
     ///
     /// This can include hidden commits referenced by remote bookmarks, previous
@@ -381,6 +402,9 @@ impl View {
             git_refs,
             git_head,
             wc_commit_ids,
+            git_stashes,
+            // The notes commit isn't a Jujutsu commit.
+            git_notes: _,
         } = &self.data;
         itertools::chain!(
             head_ids,
@@ -394,7 +418,8 @@ impl View {
             }),
             git_refs.values().flat_map(ref_target_ids),
             ref_target_ids(git_head),
-            wc_commit_ids.values()
+            wc_commit_ids.values(),
+            git_stashes
         )
     }
 
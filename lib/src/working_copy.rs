@@ -29,8 +29,12 @@ use crate::backend::BackendError;
 use crate::backend::MergedTreeId;
 use crate::commit::Commit;
 use crate::conflicts::ConflictMarkerStyle;
+use crate::conflicts::UnparsedConflictHunk;
 use crate::dag_walk;
 use crate::fsmonitor::FsmonitorSettings;
+use crate::git_lfs::GitLfsError;
+use crate::git_lfs::LfsObjectStore;
+use crate::gitattributes::GitAttributesError;
 use crate::gitignore::GitIgnoreError;
 use crate::gitignore::GitIgnoreFile;
 use crate::matchers::EverythingMatcher;
@@ -184,6 +188,17 @@ pub enum SnapshotError {
     /// Checking path with ignore patterns failed.
     #[error(transparent)]
     GitIgnoreError(#[from] GitIgnoreError),
+    /// Reading `.gitattributes` files failed.
+    #[error(transparent)]
+    GitAttributesError(#[from] GitAttributesError),
+    /// Moving file contents to the LFS object store failed.
+    #[error("Failed to store LFS object for {path}")]
+    GitLfsError {
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying error.
+        source: GitLfsError,
+    },
     /// Some other error happened while snapshotting the working copy.
     #[error("{message}")]
     Other {
@@ -222,6 +237,15 @@ pub struct SnapshotOptions<'a> {
     pub max_new_file_size: u64,
     /// Expected conflict marker style for checking for changed files.
     pub conflict_marker_style: ConflictMarkerStyle,
+    /// The store to move the contents of LFS files (as specified by
+    /// `.gitattributes`) to. LFS files are committed as pointers to the
+    /// contents. Large LFS files aren't rejected by `max_new_file_size`.
+    pub lfs_store: Option<Arc<LfsObjectStore>>,
+    /// If true, conflicted files with partially edited or malformed conflict
+    /// markers are left conflicted instead of being resolved with the marker
+    /// text in them. The unparsed hunks are reported in
+    /// `SnapshotStats::unparsed_conflicts`.
+    pub validate_conflict_markers: bool,
 }
 
 impl SnapshotOptions<'_> {
@@ -234,6 +258,8 @@ impl SnapshotOptions<'_> {
             start_tracking_matcher: &EverythingMatcher,
             max_new_file_size: u64::MAX,
             conflict_marker_style: ConflictMarkerStyle::default(),
+            lfs_store: None,
+            validate_conflict_markers: false,
         }
     }
 }
@@ -246,6 +272,9 @@ pub type SnapshotProgress<'a> = dyn Fn(&RepoPath) + 'a + Sync;
 pub struct SnapshotStats {
     /// List of new (previously untracked) files which are still untracked.
     pub untracked_paths: BTreeMap<RepoPathBuf, UntrackedReason>,
+    /// Conflicted files which were left unchanged because their conflict
+    /// markers couldn't be parsed.
+    pub unparsed_conflicts: BTreeMap<RepoPathBuf, Vec<UnparsedConflictHunk>>,
 }
 
 /// Reason why the new path isn't tracked.
@@ -267,6 +296,12 @@ pub enum UntrackedReason {
 pub struct CheckoutOptions {
     /// Conflict marker style to use when materializing files
     pub conflict_marker_style: ConflictMarkerStyle,
+    /// The store to read the contents of LFS files from. If unset, or if the
+    /// store doesn't have the object, LFS pointers are checked out as is.
+    pub lfs_store: Option<Arc<LfsObjectStore>>,
+    /// Whether to describe the terms of materialized conflicts with the
+    /// labels recorded in the commit
+    pub show_conflict_labels: bool,
 }
 
 impl CheckoutOptions {
@@ -274,6 +309,8 @@ impl CheckoutOptions {
     pub fn empty_for_test() -> Self {
         CheckoutOptions {
             conflict_marker_style: ConflictMarkerStyle::default(),
+            lfs_store: None,
+            show_conflict_labels: false,
         }
     }
 }
@@ -16,9 +16,7 @@
 
 use std::collections::HashMap;
 use std::fs;
-use std::fs::File;
 use std::io;
-use std::io::Write as _;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -61,6 +59,8 @@ use crate::working_copy::LockedWorkingCopy;
 use crate::working_copy::WorkingCopy;
 use crate::working_copy::WorkingCopyFactory;
 use crate::working_copy::WorkingCopyStateError;
+use crate::workspace_store::WorkspaceStore;
+use crate::workspace_store::WorkspaceStoreError;
 
 #[derive(Error, Debug)]
 pub enum WorkspaceInitError {
@@ -80,6 +80,8 @@ pub enum WorkspaceInitError {
     Backend(#[from] BackendInitError),
     #[error(transparent)]
     SignInit(#[from] SignInitError),
+    #[error(transparent)]
+    WorkspaceStore(#[from] WorkspaceStoreError),
 }
 
 #[derive(Error, Debug)]
@@ -98,6 +100,24 @@ pub enum WorkspaceLoadError {
     Path(#[from] PathError),
 }
 
+#[derive(Error, Debug)]
+pub enum WorkspaceRelocateError {
+    #[error("The destination ({0}) already exists")]
+    DestinationExists(PathBuf),
+    #[error("There is no Jujutsu workspace in {0}")]
+    NoWorkspaceHere(PathBuf),
+    #[error("There is no Jujutsu repo at {0}")]
+    NoRepoHere(PathBuf),
+    #[error("The workspace at {0} contains the repo")]
+    RepoInWorkspace(PathBuf),
+    #[error("Repo path could not be interpreted as Unicode text")]
+    NonUnicodePath,
+    #[error(transparent)]
+    Path(#[from] PathError),
+    #[error(transparent)]
+    WorkspaceStore(#[from] WorkspaceStoreError),
+}
+
 /// The combination of a repo and a working copy.
 ///
 /// Represents the combination of a repo and working copy, i.e. what's typically
@@ -318,6 +338,8 @@ impl Workspace {
             )?;
             let repo_loader = repo.loader().clone();
             let workspace = Workspace::new(workspace_root, repo_dir, working_copy, repo_loader)?;
+            WorkspaceStore::load(workspace.repo_path())
+                .add(workspace.workspace_name(), workspace.workspace_root())?;
             Ok((workspace, repo))
         })()
         .inspect_err(|_err| {
@@ -355,16 +377,12 @@ impl Workspace {
         let jj_dir = create_jj_dir(workspace_root)?;
 
         let repo_dir = dunce::canonicalize(repo_path).context(repo_path)?;
-        let repo_file_path = jj_dir.join("repo");
-        let mut repo_file = File::create(&repo_file_path).context(&repo_file_path)?;
-        repo_file
-            .write_all(
-                repo_dir
-                    .to_str()
-                    .ok_or(WorkspaceInitError::NonUnicodePath)?
-                    .as_bytes(),
-            )
-            .context(&repo_file_path)?;
+        write_repo_path_file(
+            &jj_dir,
+            repo_dir
+                .to_str()
+                .ok_or(WorkspaceInitError::NonUnicodePath)?,
+        )?;
 
         let (working_copy, repo) = init_working_copy(
             repo,
@@ -379,6 +397,8 @@ impl Workspace {
             working_copy,
             repo.loader().clone(),
         )?;
+        WorkspaceStore::load(workspace.repo_path())
+            .add(workspace.workspace_name(), workspace.workspace_root())?;
         Ok((workspace, repo))
     }
 
@@ -565,10 +585,13 @@ impl DefaultWorkspaceLoader {
             let buf = fs::read(&repo_dir).context(&repo_dir)?;
             let repo_path_str =
                 String::from_utf8(buf).map_err(|_| WorkspaceLoadError::NonUnicodePath)?;
-            repo_dir = dunce::canonicalize(jj_dir.join(&repo_path_str)).context(&repo_path_str)?;
-            if !repo_dir.is_dir() {
-                return Err(WorkspaceLoadError::RepoDoesNotExist(repo_dir));
+            let repo_path = jj_dir.join(&repo_path_str);
+            // The pointer goes stale if the workspace containing the repo was
+            // moved.
+            if !repo_path.is_dir() {
+                return Err(WorkspaceLoadError::RepoDoesNotExist(repo_path));
             }
+            repo_dir = dunce::canonicalize(&repo_path).context(&repo_path)?;
         }
         let working_copy_state_path = jj_dir.join("working_copy");
         Ok(Self {
@@ -624,6 +647,118 @@ impl WorkspaceLoader for DefaultWorkspaceLoader {
     }
 }
 
+fn write_repo_path_file(jj_dir: &Path, repo_path_str: &str) -> Result<(), PathError> {
+    let repo_file_path = jj_dir.join("repo");
+    fs::write(&repo_file_path, repo_path_str).context(&repo_file_path)
+}
+
+/// Points the workspace at `workspace_root` to the repo at `repo_path` by
+/// rewriting its `.jj/repo` file.
+///
+/// This can be used to repair a workspace after the workspace containing the
+/// repo was moved. The workspace itself doesn't have to be loadable.
+pub fn set_workspace_repo_path(
+    workspace_root: &Path,
+    repo_path: &Path,
+) -> Result<(), WorkspaceRelocateError> {
+    let jj_dir = workspace_root.join(".jj");
+    if !jj_dir.is_dir() {
+        return Err(WorkspaceRelocateError::NoWorkspaceHere(
+            workspace_root.to_owned(),
+        ));
+    }
+    if jj_dir.join("repo").is_dir() {
+        return Err(WorkspaceRelocateError::RepoInWorkspace(
+            workspace_root.to_owned(),
+        ));
+    }
+    let repo_dir = dunce::canonicalize(repo_path).context(repo_path)?;
+    if !repo_dir.join("store").is_dir() {
+        return Err(WorkspaceRelocateError::NoRepoHere(repo_dir));
+    }
+    let repo_path_str = repo_dir
+        .to_str()
+        .ok_or(WorkspaceRelocateError::NonUnicodePath)?;
+    write_repo_path_file(&jj_dir, repo_path_str)?;
+    Ok(())
+}
+
+/// Rewrites the `.jj/repo` files of the workspaces recorded in the workspace
+/// store of the repo at `repo_path` to point to that repo.
+///
+/// Workspaces which no longer exist at the recorded location are skipped.
+/// Returns the names of the updated workspaces.
+pub fn update_workspace_repo_paths(
+    repo_path: &Path,
+) -> Result<Vec<WorkspaceNameBuf>, WorkspaceRelocateError> {
+    let repo_path_str = repo_path
+        .to_str()
+        .ok_or(WorkspaceRelocateError::NonUnicodePath)?;
+    let mut updated_names = vec![];
+    for (name, workspace_root) in WorkspaceStore::load(repo_path).workspace_paths()? {
+        let jj_dir = workspace_root.join(".jj");
+        let repo_file_path = jj_dir.join("repo");
+        // The workspace containing the repo has no pointer to update.
+        if !repo_file_path.is_file() {
+            continue;
+        }
+        let old_repo_path_str = fs::read(&repo_file_path).context(&repo_file_path)?;
+        if old_repo_path_str == repo_path_str.as_bytes() {
+            continue;
+        }
+        write_repo_path_file(&jj_dir, repo_path_str)?;
+        updated_names.push(name);
+    }
+    Ok(updated_names)
+}
+
+/// Moves the given workspace to `new_root`.
+///
+/// If the repo is stored inside the moved workspace, the other workspaces
+/// recorded in the workspace store are updated to point to the new repo
+/// location. Returns the names of the updated workspaces.
+///
+/// `new_root` shouldn't contain `..` components since the old workspace root
+/// may be one of its ancestors.
+pub fn move_workspace(
+    workspace: Workspace,
+    new_root: &Path,
+) -> Result<Vec<WorkspaceNameBuf>, WorkspaceRelocateError> {
+    let old_root = workspace.workspace_root().to_owned();
+    let old_repo_path = workspace.repo_path().to_owned();
+    let name = workspace.workspace_name().to_owned();
+    drop(workspace);
+
+    if new_root.exists() {
+        return Err(WorkspaceRelocateError::DestinationExists(
+            new_root.to_owned(),
+        ));
+    }
+    fs::rename(&old_root, new_root).context(&old_root)?;
+    let new_root = dunce::canonicalize(new_root).context(new_root)?;
+    let (repo_path, is_repo_moved) = match old_repo_path.strip_prefix(&old_root) {
+        Ok(relative_path) => (new_root.join(relative_path), true),
+        Err(_) => (old_repo_path, false),
+    };
+
+    let store = WorkspaceStore::load(&repo_path);
+    store.add(&name, &new_root)?;
+    // Workspaces nested in the moved directory were moved as well.
+    for (other_name, other_root) in store.workspace_paths()? {
+        if other_name == name {
+            continue;
+        }
+        if let Ok(relative_path) = other_root.strip_prefix(&old_root) {
+            store.add(&other_name, &new_root.join(relative_path))?;
+        }
+    }
+    if is_repo_moved {
+        update_workspace_repo_paths(&repo_path)
+    } else {
+        Ok(vec![])
+    }
+}
+
 pub fn default_working_copy_factories() -> WorkingCopyFactories {
     let mut factories = WorkingCopyFactories::new();
     factories.insert(
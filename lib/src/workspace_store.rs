@@ -0,0 +1,212 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Records where the workspaces of a repo are located on disk.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use itertools::Itertools as _;
+use prost::Message as _;
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::lock::FileLock;
+use crate::lock::FileLockError;
+use crate::ref_name::WorkspaceName;
+use crate::ref_name::WorkspaceNameBuf;
+
+/// Error that may occur while reading or updating the workspace store.
+#[derive(Debug, Error)]
+pub enum WorkspaceStoreError {
+    /// Failed to access the store file.
+    #[error(transparent)]
+    Path(#[from] PathError),
+    /// Failed to lock the store.
+    #[error(transparent)]
+    Lock(#[from] FileLockError),
+    /// The store file is corrupt.
+    #[error("Failed to decode workspace store")]
+    Decode(#[from] prost::DecodeError),
+    /// Workspace path cannot be recorded.
+    #[error("Workspace path could not be interpreted as Unicode text")]
+    NonUnicodePath,
+}
+
+/// Map of workspace names to their root directories.
+///
+/// The map is stored in `<repo>/workspace_store/index`. The view only knows
+/// workspaces by name, so this is what lets commands like `jj workspace move`
+/// find the other workspaces whose `.jj/repo` pointer has to be updated.
+/// Workspaces created by older versions of jj aren't recorded until they are
+/// registered again.
+#[derive(Debug)]
+pub struct WorkspaceStore {
+    store_dir: PathBuf,
+}
+
+impl WorkspaceStore {
+    /// Returns the store of the repo at `repo_path`. The store directory is
+    /// created on first write.
+    pub fn load(repo_path: &Path) -> Self {
+        WorkspaceStore {
+            store_dir: repo_path.join("workspace_store"),
+        }
+    }
+
+    /// Returns the root path of the given workspace if recorded.
+    pub fn get_workspace_path(
+        &self,
+        name: &WorkspaceName,
+    ) -> Result<Option<PathBuf>, WorkspaceStoreError> {
+        let mut paths = self.read_paths()?;
+        Ok(paths.remove(name))
+    }
+
+    /// Returns the root paths of all recorded workspaces.
+    pub fn workspace_paths(
+        &self,
+    ) -> Result<BTreeMap<WorkspaceNameBuf, PathBuf>, WorkspaceStoreError> {
+        self.read_paths()
+    }
+
+    /// Records `path` as the root of the given workspace. The old path will be
+    /// replaced if any.
+    pub fn add(&self, name: &WorkspaceName, path: &Path) -> Result<(), WorkspaceStoreError> {
+        self.update(|paths| {
+            paths.insert(name.to_owned(), path.to_owned());
+        })
+    }
+
+    /// Removes the given workspaces from the store. Unknown names are ignored.
+    pub fn forget(&self, names: &[&WorkspaceName]) -> Result<(), WorkspaceStoreError> {
+        self.update(|paths| {
+            for name in names {
+                paths.remove(*name);
+            }
+        })
+    }
+
+    /// Moves the path recorded for `old_name` to `new_name`.
+    pub fn rename(
+        &self,
+        old_name: &WorkspaceName,
+        new_name: &WorkspaceName,
+    ) -> Result<(), WorkspaceStoreError> {
+        self.update(|paths| {
+            if let Some(path) = paths.remove(old_name) {
+                paths.insert(new_name.to_owned(), path);
+            }
+        })
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.store_dir.join("index")
+    }
+
+    fn read_paths(&self) -> Result<BTreeMap<WorkspaceNameBuf, PathBuf>, WorkspaceStoreError> {
+        let index_path = self.index_path();
+        let buf = match fs::read(&index_path).context(&index_path) {
+            Ok(buf) => buf,
+            Err(err) if err.error.kind() == io::ErrorKind::NotFound => {
+                return Ok(BTreeMap::new());
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let proto = crate::protos::workspace_store::Workspaces::decode(&*buf)?;
+        Ok(proto
+            .workspaces
+            .into_iter()
+            .map(|workspace| (workspace.name.into(), PathBuf::from(workspace.path)))
+            .collect())
+    }
+
+    fn update(
+        &self,
+        f: impl FnOnce(&mut BTreeMap<WorkspaceNameBuf, PathBuf>),
+    ) -> Result<(), WorkspaceStoreError> {
+        fs::create_dir_all(&self.store_dir).context(&self.store_dir)?;
+        let _lock = FileLock::lock(self.store_dir.join("lock"))?;
+        let mut paths = self.read_paths()?;
+        f(&mut paths);
+        let workspaces = paths
+            .into_iter()
+            .map(|(name, path)| {
+                let path = path
+                    .into_os_string()
+                    .into_string()
+                    .map_err(|_| WorkspaceStoreError::NonUnicodePath)?;
+                Ok(crate::protos::workspace_store::Workspace {
+                    name: name.into_string(),
+                    path,
+                })
+            })
+            .try_collect::<_, _, WorkspaceStoreError>()?;
+        let proto = crate::protos::workspace_store::Workspaces { workspaces };
+
+        let index_path = self.index_path();
+        let mut temp_file = NamedTempFile::new_in(&self.store_dir).context(&self.store_dir)?;
+        temp_file
+            .as_file_mut()
+            .write_all(&proto.encode_to_vec())
+            .context(temp_file.path())?;
+        temp_file
+            .persist(&index_path)
+            .map_err(|err| err.error)
+            .context(&index_path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::new_temp_dir;
+
+    #[test]
+    fn test_add_forget_rename() {
+        let temp_dir = new_temp_dir();
+        let store = WorkspaceStore::load(temp_dir.path());
+        let ws1 = WorkspaceName::new("ws1");
+        let ws2 = WorkspaceName::new("ws2");
+        assert_eq!(store.get_workspace_path(ws1).unwrap(), None);
+
+        store.add(ws1, Path::new("/path/one")).unwrap();
+        store.add(ws2, Path::new("/path/two")).unwrap();
+        assert_eq!(
+            store.get_workspace_path(ws1).unwrap(),
+            Some(PathBuf::from("/path/one"))
+        );
+
+        // Adding again replaces the path
+        store.add(ws1, Path::new("/path/moved")).unwrap();
+        assert_eq!(
+            store.get_workspace_path(ws1).unwrap(),
+            Some(PathBuf::from("/path/moved"))
+        );
+
+        store.rename(ws2, WorkspaceName::new("ws3")).unwrap();
+        store.forget(&[ws1]).unwrap();
+        assert_eq!(
+            store.workspace_paths().unwrap(),
+            BTreeMap::from([("ws3".into(), PathBuf::from("/path/two"))])
+        );
+    }
+}
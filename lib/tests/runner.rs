@@ -34,5 +34,6 @@ mod test_rewrite_duplicate;
 mod test_rewrite_transform;
 mod test_signing;
 mod test_ssh_signing;
+mod test_tool_protocol;
 mod test_view;
 mod test_workspace;
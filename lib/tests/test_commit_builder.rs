@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use bstr::BString;
 use futures::StreamExt as _;
 use indoc::indoc;
 use itertools::Itertools as _;
@@ -23,6 +24,7 @@ use jj_lib::config::ConfigLayer;
 use jj_lib::config::ConfigSource;
 use jj_lib::config::StackedConfig;
 use jj_lib::matchers::EverythingMatcher;
+use jj_lib::merge::Merge;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::repo::Repo as _;
 use jj_lib::repo_path::RepoPath;
@@ -396,3 +398,56 @@ fn test_commit_builder_descendants(backend: TestRepoBackend) {
         rebase_descendants_with_options_return_map(tx.repo_mut(), &RebaseOptions::default());
     assert!(rebase_map.is_empty());
 }
+
+#[test]
+fn test_commit_builder_records_conflict_resolutions() {
+    let mut config = testutils::base_user_config();
+    config.add_layer(
+        ConfigLayer::parse(ConfigSource::User, "merge.reuse-resolutions = true").unwrap(),
+    );
+    let settings = UserSettings::from_config(config).unwrap();
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let repo = &test_repo.repo;
+    let store = repo.store().clone();
+    let cache = store.merge_options().resolution_cache.clone().unwrap();
+
+    let file_path = RepoPath::from_internal_string("file");
+    let base_tree = create_tree(repo, &[(file_path, "a\n")]);
+    let side1_tree = create_tree(repo, &[(file_path, "b\n")]);
+    let side2_tree = create_tree(repo, &[(file_path, "c\n")]);
+    let resolved_tree = create_tree(repo, &[(file_path, "d\n")]);
+    let hunk = Merge::from_vec(vec![
+        BString::from("b\n"),
+        BString::from("a\n"),
+        BString::from("c\n"),
+    ]);
+
+    let mut tx = repo.start_transaction();
+    let base = tx
+        .repo_mut()
+        .new_commit(vec![store.root_commit_id().clone()], base_tree.id())
+        .write()
+        .unwrap();
+    let side1 = tx
+        .repo_mut()
+        .new_commit(vec![base.id().clone()], side1_tree.id())
+        .write()
+        .unwrap();
+    let side2 = tx
+        .repo_mut()
+        .new_commit(vec![base.id().clone()], side2_tree.id())
+        .write()
+        .unwrap();
+    tx.repo_mut()
+        .new_commit(
+            vec![side1.id().clone(), side2.id().clone()],
+            resolved_tree.id(),
+        )
+        .write()
+        .unwrap();
+
+    // The resolution is recorded when the transaction is committed
+    assert_eq!(cache.get(&hunk).unwrap(), None);
+    tx.commit("test").unwrap();
+    assert_eq!(cache.get(&hunk).unwrap(), Some(BString::from("d\n")));
+}
@@ -12,19 +12,28 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use bstr::BString;
 use indoc::indoc;
 use itertools::Itertools as _;
 use jj_lib::backend::FileId;
+use jj_lib::config::ConfigLayer;
+use jj_lib::config::ConfigSource;
 use jj_lib::conflicts::choose_materialized_conflict_marker_len;
 use jj_lib::conflicts::extract_as_single_hunk;
+use jj_lib::conflicts::find_unparsed_conflict_hunks;
+use jj_lib::conflicts::materialize_labeled_merge_result;
 use jj_lib::conflicts::materialize_merge_result_to_bytes;
 use jj_lib::conflicts::parse_conflict;
 use jj_lib::conflicts::update_from_content;
 use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::conflicts::UnparsedConflictHunk;
+use jj_lib::conflicts::UnparsedConflictReason;
 use jj_lib::conflicts::MIN_CONFLICT_MARKER_LEN;
+use jj_lib::files::FileMergeOptions;
 use jj_lib::merge::Merge;
 use jj_lib::repo::Repo as _;
 use jj_lib::repo_path::RepoPath;
+use jj_lib::settings::UserSettings;
 use jj_lib::store::Store;
 use pollster::FutureExt as _;
 use testutils::TestRepo;
@@ -811,6 +820,148 @@ fn test_materialize_conflict_two_forward_diffs() {
     );
 }
 
+#[test]
+fn test_materialize_conflict_zdiff3_and_minimal() {
+    let test_repo = TestRepo::init();
+    let store = test_repo.repo.store();
+
+    let path = RepoPath::from_internal_string("file");
+    let base_id = testutils::write_file(store, path, "apple\ngrape\norange\n");
+    let left_id = testutils::write_file(store, path, "apple\nkiwi\nlemon\n");
+    let right_id = testutils::write_file(store, path, "apple\nkiwi\nlime\n");
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_id.clone())],
+        vec![Some(left_id.clone()), Some(right_id.clone())],
+    );
+    // The line both sides added at the start of the conflict is moved out of it
+    insta::assert_snapshot!(
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Zdiff3),
+        @r"
+    apple
+    kiwi
+    <<<<<<< Side #1 (Conflict 1 of 1)
+    lemon
+    ||||||| Base
+    grape
+    orange
+    =======
+    lime
+    >>>>>>> Side #2 (Conflict 1 of 1 ends)
+    "
+    );
+
+    let base_id = testutils::write_file(store, path, "a\n");
+    let left_id = testutils::write_file(store, path, "b1\nc\nd1\n");
+    let right_id = testutils::write_file(store, path, "b2\nc\nd2\n");
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_id.clone())],
+        vec![Some(left_id.clone()), Some(right_id.clone())],
+    );
+    // There are no common lines at the start and end of the conflict
+    insta::assert_snapshot!(
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Zdiff3),
+        @r"
+    <<<<<<< Side #1 (Conflict 1 of 1)
+    b1
+    c
+    d1
+    ||||||| Base
+    a
+    =======
+    b2
+    c
+    d2
+    >>>>>>> Side #2 (Conflict 1 of 1 ends)
+    "
+    );
+    // The conflict is split at the line the sides have in common
+    insta::assert_snapshot!(
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Minimal),
+        @r"
+    <<<<<<< Conflict 1 of 2
+    %%%%%%% Changes from base to side #1
+    -a
+    +b1
+    +++++++ Contents of side #2
+    b2
+    >>>>>>> Conflict 1 of 2 ends
+    c
+    <<<<<<< Conflict 2 of 2
+    %%%%%%% Changes from base to side #1
+    +d1
+    +++++++ Contents of side #2
+    d2
+    >>>>>>> Conflict 2 of 2 ends
+    "
+    );
+}
+
+#[test]
+fn test_materialize_conflict_with_labels() {
+    let contents = Merge::from_vec(vec!["b\n", "a\n", "c\n"]);
+    let labels = Merge::from_vec(
+        ["left commit", "base commit", "right commit"]
+            .map(str::to_owned)
+            .to_vec(),
+    );
+    let materialize = |conflict_marker_style| {
+        let mut output = vec![];
+        materialize_labeled_merge_result(
+            &contents,
+            &FileMergeOptions::default(),
+            &labels,
+            conflict_marker_style,
+            MIN_CONFLICT_MARKER_LEN,
+            &mut output,
+        )
+        .unwrap();
+        String::from_utf8(output).unwrap()
+    };
+
+    let materialized = materialize(ConflictMarkerStyle::Diff);
+    insta::assert_snapshot!(materialized, @r"
+    <<<<<<< Conflict 1 of 1
+    %%%%%%% Changes from base (base commit) to side #1 (left commit)
+    -a
+    +b
+    +++++++ Contents of side #2 (right commit)
+    c
+    >>>>>>> Conflict 1 of 1 ends
+    ");
+    // The labels don't prevent parsing the conflict
+    assert_eq!(
+        parse_conflict(materialized.as_bytes(), 2, MIN_CONFLICT_MARKER_LEN),
+        Some(vec![contents.map(|content| BString::from(*content))])
+    );
+
+    insta::assert_snapshot!(materialize(ConflictMarkerStyle::Snapshot), @r"
+    <<<<<<< Conflict 1 of 1
+    +++++++ Contents of side #1 (left commit)
+    b
+    ------- Contents of base (base commit)
+    a
+    +++++++ Contents of side #2 (right commit)
+    c
+    >>>>>>> Conflict 1 of 1 ends
+    ");
+
+    // Like Git, the labels replace the names of the terms
+    let materialized = materialize(ConflictMarkerStyle::Git);
+    insta::assert_snapshot!(materialized, @r"
+    <<<<<<< left commit (Conflict 1 of 1)
+    b
+    ||||||| base commit
+    a
+    =======
+    c
+    >>>>>>> right commit (Conflict 1 of 1 ends)
+    ");
+    assert_eq!(
+        parse_conflict(materialized.as_bytes(), 2, MIN_CONFLICT_MARKER_LEN),
+        Some(vec![contents.map(|content| BString::from(*content))])
+    );
+}
+
 #[test]
 fn test_parse_conflict_resolved() {
     assert_eq!(
@@ -1635,6 +1786,113 @@ fn test_parse_conflict_mixed_header_styles() {
     );
 }
 
+#[test]
+fn test_find_unparsed_conflict_hunks() {
+    let hunk = |first_line, last_line, reason| UnparsedConflictHunk {
+        first_line,
+        last_line,
+        reason,
+    };
+
+    // Valid conflicts and text which looks like separators are fine
+    assert_eq!(
+        find_unparsed_conflict_hunks(
+            indoc! {b"
+            Title
+            =======
+            <<<<<<<
+            %%%%%%%
+            -base
+            +left
+            +++++++
+            right
+            >>>>>>>
+            -------
+            "},
+            2,
+            7
+        ),
+        vec![]
+    );
+
+    // Markers shorter than the expected length are ignored
+    assert_eq!(
+        find_unparsed_conflict_hunks(b"<<<<<<<\n>>>>>>>\n", 2, 8),
+        vec![]
+    );
+
+    // The end marker of the first conflict and the start marker of the second
+    // conflict were deleted
+    assert_eq!(
+        find_unparsed_conflict_hunks(
+            indoc! {b"
+            <<<<<<<
+            +++++++
+            left
+            -------
+            base
+            +++++++
+            right
+            line 1
+            +++++++
+            left
+            -------
+            base
+            +++++++
+            right
+            >>>>>>>
+            "},
+            2,
+            7
+        ),
+        vec![hunk(1, 15, UnparsedConflictReason::Malformed)]
+    );
+
+    // Missing start and end markers, and a nested start marker
+    assert_eq!(
+        find_unparsed_conflict_hunks(
+            indoc! {b"
+            left
+            >>>>>>>
+            <<<<<<<
+            <<<<<<<
+            |||||||
+            base
+            =======
+            right
+            >>>>>>>
+            <<<<<<<
+            left
+            "},
+            2,
+            7
+        ),
+        vec![
+            hunk(2, 2, UnparsedConflictReason::MissingStart),
+            hunk(3, 3, UnparsedConflictReason::MissingEnd),
+            hunk(10, 10, UnparsedConflictReason::MissingEnd),
+        ]
+    );
+
+    // Valid conflict with an unexpected number of sides
+    assert_eq!(
+        find_unparsed_conflict_hunks(
+            indoc! {b"
+            <<<<<<<
+            %%%%%%%
+            -base
+            +left
+            +++++++
+            right
+            >>>>>>>
+            "},
+            3,
+            7
+        ),
+        vec![hunk(1, 7, UnparsedConflictReason::Malformed)]
+    );
+}
+
 #[test]
 fn test_update_conflict_from_content() {
     let test_repo = TestRepo::init();
@@ -1695,6 +1953,123 @@ fn test_update_conflict_from_content() {
     );
 }
 
+#[test]
+fn test_update_conflict_from_content_zdiff3_and_minimal() {
+    let test_repo = TestRepo::init();
+    let store = test_repo.repo.store();
+
+    let path = RepoPath::from_internal_string("file");
+    let base_file_id = testutils::write_file(store, path, "a\n");
+    let left_file_id = testutils::write_file(store, path, "b1\nc\nd1\n");
+    let right_file_id = testutils::write_file(store, path, "b2\nc\nd2\n");
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_file_id.clone())],
+        vec![Some(left_file_id.clone()), Some(right_file_id.clone())],
+    );
+    let parse = |content: &[u8], conflict_marker_style| {
+        update_from_content(
+            &conflict,
+            store,
+            path,
+            content,
+            conflict_marker_style,
+            MIN_CONFLICT_MARKER_LEN,
+        )
+        .block_on()
+        .unwrap()
+    };
+
+    // Unchanged content gives the old conflict back
+    for conflict_marker_style in [ConflictMarkerStyle::Zdiff3, ConflictMarkerStyle::Minimal] {
+        let materialized =
+            materialize_conflict_string(store, path, &conflict, conflict_marker_style);
+        assert_eq!(
+            parse(materialized.as_bytes(), conflict_marker_style),
+            conflict
+        );
+    }
+
+    // Resolving one of the minimal conflicts keeps the other one. The common
+    // line is included in every term.
+    let new_conflict = parse(
+        indoc! {b"
+            b1
+            c
+            <<<<<<< Conflict 2 of 2
+            %%%%%%% Changes from base to side #1
+            +d1
+            +++++++ Contents of side #2
+            d2
+            >>>>>>> Conflict 2 of 2 ends
+        "},
+        ConflictMarkerStyle::Minimal,
+    );
+    let new_base_file_id = testutils::write_file(store, path, "b1\nc\n");
+    let new_left_file_id = testutils::write_file(store, path, "b1\nc\nd1\n");
+    let new_right_file_id = testutils::write_file(store, path, "b1\nc\nd2\n");
+    assert_eq!(
+        new_conflict,
+        Merge::from_removes_adds(
+            vec![Some(new_base_file_id)],
+            vec![Some(new_left_file_id), Some(new_right_file_id)]
+        )
+    );
+
+    // Resolving all conflicts gives a resolved file
+    let expected_file_id = testutils::write_file(store, path, "b1\nc\nd2\n");
+    assert_eq!(
+        parse(b"b1\nc\nd2\n", ConflictMarkerStyle::Minimal),
+        Merge::normal(expected_file_id)
+    );
+}
+
+#[test]
+fn test_update_conflict_from_content_zdiff3_absent_base() {
+    let test_repo = TestRepo::init();
+    let store = test_repo.repo.store();
+
+    // Both sides added the file with the same first line
+    let path = RepoPath::from_internal_string("file");
+    let left_file_id = testutils::write_file(store, path, "a\nb1\n");
+    let right_file_id = testutils::write_file(store, path, "a\nb2\n");
+    let conflict = Merge::from_removes_adds(
+        vec![None],
+        vec![Some(left_file_id.clone()), Some(right_file_id.clone())],
+    );
+    let materialized =
+        materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Zdiff3);
+    insta::assert_snapshot!(materialized, @r"
+    a
+    <<<<<<< Side #1 (Conflict 1 of 1)
+    b1
+    ||||||| Base
+    =======
+    b2
+    >>>>>>> Side #2 (Conflict 1 of 1 ends)
+    ");
+
+    // Editing the line moved out of the conflict keeps the base absent
+    let new_conflict = update_from_content(
+        &conflict,
+        store,
+        path,
+        materialized.replace("a\n", "A\n").as_bytes(),
+        ConflictMarkerStyle::Zdiff3,
+        MIN_CONFLICT_MARKER_LEN,
+    )
+    .block_on()
+    .unwrap();
+    let new_left_file_id = testutils::write_file(store, path, "A\nb1\n");
+    let new_right_file_id = testutils::write_file(store, path, "A\nb2\n");
+    assert_eq!(
+        new_conflict,
+        Merge::from_removes_adds(
+            vec![None],
+            vec![Some(new_left_file_id), Some(new_right_file_id)]
+        )
+    );
+}
+
 #[test]
 fn test_update_conflict_from_content_modify_delete() {
     let test_repo = TestRepo::init();
@@ -2443,6 +2818,51 @@ fn test_update_from_content_malformed_conflict() {
     assert_eq!(second_snapshot, new_conflict);
 }
 
+#[test]
+fn test_materialize_and_parse_conflict_word_level() {
+    let mut config = testutils::base_user_config();
+    config.add_layer(ConfigLayer::parse(ConfigSource::User, "merge.hunk-level = 'word'").unwrap());
+    let settings = UserSettings::from_config(config).unwrap();
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let store = test_repo.repo.store();
+
+    // The first line can be merged word by word, but the last one can't
+    let path = RepoPath::from_internal_string("file");
+    let base_id = testutils::write_file(store, path, "a b c\nx\ny\n");
+    let left_id = testutils::write_file(store, path, "a1 b c\nx\ny1\n");
+    let right_id = testutils::write_file(store, path, "a b c2\nx\ny2\n");
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_id.clone())],
+        vec![Some(left_id.clone()), Some(right_id.clone())],
+    );
+    let materialized =
+        materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Diff);
+    insta::assert_snapshot!(materialized, @r"
+    a1 b c2
+    x
+    <<<<<<< Conflict 1 of 1
+    %%%%%%% Changes from base to side #1
+    -y
+    +y1
+    +++++++ Contents of side #2
+    y2
+    >>>>>>> Conflict 1 of 1 ends
+    ");
+
+    // The materialized content is parsed back to the same conflict
+    let parsed = update_from_content(
+        &conflict,
+        store,
+        path,
+        materialized.as_bytes(),
+        ConflictMarkerStyle::Diff,
+        MIN_CONFLICT_MARKER_LEN,
+    )
+    .block_on()
+    .unwrap();
+    assert_eq!(parsed, conflict);
+}
+
 fn materialize_conflict_string(
     store: &Store,
     path: &RepoPath,
@@ -2452,6 +2872,9 @@ fn materialize_conflict_string(
     let contents = extract_as_single_hunk(conflict, store, path)
         .block_on()
         .unwrap();
-    String::from_utf8(materialize_merge_result_to_bytes(&contents, conflict_marker_style).into())
-        .unwrap()
+    let merge_options = &store.merge_options().file_merge_options;
+    String::from_utf8(
+        materialize_merge_result_to_bytes(&contents, merge_options, conflict_marker_style).into(),
+    )
+    .unwrap()
 }
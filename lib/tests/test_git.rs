@@ -25,6 +25,7 @@ use std::sync::Barrier;
 use std::thread;
 
 use assert_matches::assert_matches;
+use indoc::indoc;
 use itertools::Itertools as _;
 use jj_lib::backend::BackendError;
 use jj_lib::backend::ChangeId;
@@ -40,9 +41,11 @@ use jj_lib::git;
 use jj_lib::git::FailedRefExportReason;
 use jj_lib::git::GitBranchPushTargets;
 use jj_lib::git::GitFetch;
+use jj_lib::git::GitFetchDepth;
 use jj_lib::git::GitFetchError;
 use jj_lib::git::GitImportError;
 use jj_lib::git::GitPushError;
+use jj_lib::git::GitPushOptions;
 use jj_lib::git::GitPushStats;
 use jj_lib::git::GitRefKind;
 use jj_lib::git::GitRefUpdate;
@@ -62,6 +65,8 @@ use jj_lib::refs::BookmarkPushUpdate;
 use jj_lib::repo::MutableRepo;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo as _;
+use jj_lib::repo::RepoLoader;
+use jj_lib::repo::StoreFactories;
 use jj_lib::repo_path::RepoPath;
 use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::settings::GitSettings;
@@ -156,7 +161,7 @@ fn git_fetch(
         remote_name,
         branch_names,
         git::RemoteCallbacks::default(),
-        None,
+        &GitFetchDepth::Unspecified,
     )?;
     let default_branch =
         git_fetch.get_default_branch(remote_name, git::RemoteCallbacks::default())?;
@@ -2356,6 +2361,58 @@ fn test_export_undo_reexport() {
     );
 }
 
+#[test]
+fn test_export_notes_of_rewritten_commits() {
+    let test_data = GitRepoData::create();
+    let git_repo = test_data.git_repo;
+    let mut tx = test_data.repo.start_transaction();
+    let commit1 = write_random_commit(tx.repo_mut());
+    let repo = tx.commit("test").unwrap();
+    testutils::git::add_note(git_repo.path(), &commit1.id().hex(), "CI: passed");
+    let read_note = |repo: &Arc<ReadonlyRepo>, commit: &Commit| {
+        git::GitNotes::load(repo.store(), git::GIT_NOTES_REF)
+            .unwrap()
+            .get(commit.id())
+            .unwrap()
+    };
+
+    // Import records the notes in the view
+    let mut tx = repo.start_transaction();
+    git::import_refs(tx.repo_mut(), &GitSettings::default()).unwrap();
+    let repo = tx.commit("import").unwrap();
+    assert!(repo.view().git_notes().is_some());
+
+    // Export copies the note to the rewritten commit, including after the
+    // descendants were rebased
+    let mut tx = repo.start_transaction();
+    let commit2 = tx
+        .repo_mut()
+        .rewrite_commit(&commit1)
+        .set_description("rewritten")
+        .write()
+        .unwrap();
+    tx.repo_mut().rebase_descendants().unwrap();
+    git::export_refs(tx.repo_mut()).unwrap();
+    let rewritten_repo = tx.commit("rewrite").unwrap();
+    assert_eq!(
+        read_note(&rewritten_repo, &commit2).as_deref(),
+        Some("CI: passed\n")
+    );
+    assert_ne!(rewritten_repo.view().git_notes(), repo.view().git_notes());
+
+    // Restoring the old view moves the notes ref back
+    let mut tx = rewritten_repo.start_transaction();
+    tx.repo_mut().set_view(repo.view().store_view().clone());
+    git::export_refs(tx.repo_mut()).unwrap();
+    let restored_repo = tx.commit("restore").unwrap();
+    assert_eq!(read_note(&restored_repo, &commit2), None);
+    assert_eq!(
+        read_note(&restored_repo, &commit1).as_deref(),
+        Some("CI: passed\n")
+    );
+    assert_eq!(restored_repo.view().git_notes(), repo.view().git_notes());
+}
+
 #[test]
 fn test_reset_head_to_root() {
     // Create colocated workspace
@@ -3314,6 +3371,7 @@ fn test_push_bookmarks_success(subprocess: bool) {
         &git_settings,
         "origin".as_ref(),
         &targets,
+        &GitPushOptions::default(),
         git::RemoteCallbacks::default(),
     );
     assert_eq!(
@@ -3387,6 +3445,7 @@ fn test_push_bookmarks_deletion(subprocess: bool) {
         &git_settings,
         "origin".as_ref(),
         &targets,
+        &GitPushOptions::default(),
         git::RemoteCallbacks::default(),
     );
     assert_eq!(
@@ -3455,6 +3514,7 @@ fn test_push_bookmarks_mixed_deletion_and_addition(subprocess: bool) {
         &git_settings,
         "origin".as_ref(),
         &targets,
+        &GitPushOptions::default(),
         git::RemoteCallbacks::default(),
     );
     assert_eq!(
@@ -3526,6 +3586,7 @@ fn test_push_bookmarks_not_fast_forward(subprocess: bool) {
         &git_settings,
         "origin".as_ref(),
         &targets,
+        &GitPushOptions::default(),
         git::RemoteCallbacks::default(),
     );
     assert_eq!(
@@ -3576,6 +3637,7 @@ fn test_push_updates_unexpectedly_moved_sideways_on_remote(subprocess: bool) {
             &git_settings,
             "origin".as_ref(),
             &targets,
+            &GitPushOptions::default(),
             git::RemoteCallbacks::default(),
         )
     };
@@ -3655,6 +3717,7 @@ fn test_push_updates_unexpectedly_moved_forward_on_remote(subprocess: bool) {
             &git_settings,
             "origin".as_ref(),
             &targets,
+            &GitPushOptions::default(),
             git::RemoteCallbacks::default(),
         )
     };
@@ -3733,6 +3796,7 @@ fn test_push_updates_unexpectedly_exists_on_remote(subprocess: bool) {
             &git_settings,
             "origin".as_ref(),
             &targets,
+            &GitPushOptions::default(),
             git::RemoteCallbacks::default(),
         )
     };
@@ -3783,6 +3847,7 @@ fn test_push_updates_success(subprocess: bool) {
             expected_current_target: Some(setup.main_commit.id().clone()),
             new_target: Some(setup.child_of_main_commit.id().clone()),
         }],
+        &GitPushOptions::default(),
         git::RemoteCallbacks::default(),
     );
     assert_eq!(
@@ -3808,6 +3873,62 @@ fn test_push_updates_success(subprocess: bool) {
     assert_eq!(new_target.target().id(), new_oid);
 }
 
+// libgit2 doesn't run hooks when pushing to a local repository, so only the
+// subprocess implementation is tested.
+#[test]
+fn test_push_updates_with_push_options() {
+    let settings = testutils::user_settings();
+    let temp_dir = testutils::new_temp_dir();
+    let setup = set_up_push_repos(&settings, &temp_dir);
+    let git_settings = get_git_settings(true);
+    testutils::git::set_config(
+        &setup.source_repo_dir,
+        "receive.advertisePushOptions",
+        "true",
+    );
+    let hook_path = setup.source_repo_dir.join("hooks").join("pre-receive");
+    fs::write(
+        &hook_path,
+        indoc! {r#"
+            #!/bin/sh
+            echo
+            echo "options: $GIT_PUSH_OPTION_0 $GIT_PUSH_OPTION_1"
+            echo
+        "#},
+    )
+    .unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o700)).unwrap();
+    }
+
+    let options = GitPushOptions {
+        remote_push_options: vec!["ci.skip".to_owned(), "merge_request.create".to_owned()],
+    };
+    let result = git::push_updates(
+        setup.jj_repo.as_ref(),
+        &git_settings,
+        "origin".as_ref(),
+        &[GitRefUpdate {
+            qualified_name: "refs/heads/main".into(),
+            expected_current_target: Some(setup.main_commit.id().clone()),
+            new_target: Some(setup.child_of_main_commit.id().clone()),
+        }],
+        &options,
+        git::RemoteCallbacks::default(),
+    );
+    assert_eq!(
+        result.unwrap(),
+        GitPushStats {
+            pushed: vec!["refs/heads/main".into()],
+            remote_messages: vec!["options: ci.skip merge_request.create".to_owned()],
+            ..Default::default()
+        }
+    );
+}
+
 #[cfg_attr(feature = "git2", test_case(false; "use git2 for remote calls"))]
 #[test_case(true; "spawn a git subprocess for remote calls")]
 fn test_push_updates_no_such_remote(subprocess: bool) {
@@ -3824,6 +3945,7 @@ fn test_push_updates_no_such_remote(subprocess: bool) {
             expected_current_target: Some(setup.main_commit.id().clone()),
             new_target: Some(setup.child_of_main_commit.id().clone()),
         }],
+        &GitPushOptions::default(),
         git::RemoteCallbacks::default(),
     );
     assert!(matches!(result, Err(GitPushError::NoSuchRemote(_))));
@@ -3845,11 +3967,108 @@ fn test_push_updates_invalid_remote(subprocess: bool) {
             expected_current_target: Some(setup.main_commit.id().clone()),
             new_target: Some(setup.child_of_main_commit.id().clone()),
         }],
+        &GitPushOptions::default(),
         git::RemoteCallbacks::default(),
     );
     assert!(matches!(result, Err(GitPushError::NoSuchRemote(_))));
 }
 
+/// Reloads the repo set up by `set_up_push_repos()` so the Git config changed
+/// since then is picked up.
+fn reload_push_repo(settings: &UserSettings, temp_dir: &TempDir) -> Arc<ReadonlyRepo> {
+    RepoLoader::init_from_file_system(
+        settings,
+        &temp_dir.path().join("jj"),
+        &StoreFactories::default(),
+    )
+    .unwrap()
+    .load_at_head()
+    .unwrap()
+}
+
+#[cfg_attr(feature = "git2", test_case(false; "use git2 for remote calls"))]
+#[test_case(true; "spawn a git subprocess for remote calls")]
+fn test_fetch_url_insteadof(subprocess: bool) {
+    let settings = testutils::user_settings();
+    let temp_dir = testutils::new_temp_dir();
+    let setup = set_up_push_repos(&settings, &temp_dir);
+    let clone_repo_dir = temp_dir.path().join("clone");
+    let source_repo_dir = setup.source_repo_dir.to_str().unwrap();
+    testutils::git::set_config(&clone_repo_dir, "remote.origin.url", "fake:source");
+    testutils::git::set_config(
+        &clone_repo_dir,
+        &format!("url.{source_repo_dir}.insteadOf"),
+        "fake:source",
+    );
+    let source_repo = testutils::git::open(&setup.source_repo_dir);
+    let new_git_commit = empty_git_commit(
+        &source_repo,
+        "refs/heads/main",
+        &[git_id(&setup.main_commit)],
+    );
+
+    let jj_repo = reload_push_repo(&settings, &temp_dir);
+    let mut tx = jj_repo.start_transaction();
+    git_fetch(
+        tx.repo_mut(),
+        "origin".as_ref(),
+        &[StringPattern::everything()],
+        &get_git_settings(subprocess),
+    )
+    .unwrap();
+    assert_eq!(
+        *tx.repo()
+            .view()
+            .get_git_ref("refs/remotes/origin/main".as_ref()),
+        RefTarget::normal(jj_id(new_git_commit)),
+    );
+}
+
+#[cfg_attr(feature = "git2", test_case(false; "use git2 for remote calls"))]
+#[test_case(true; "spawn a git subprocess for remote calls")]
+fn test_push_updates_url_pushinsteadof(subprocess: bool) {
+    let settings = testutils::user_settings();
+    let temp_dir = testutils::new_temp_dir();
+    let setup = set_up_push_repos(&settings, &temp_dir);
+    let clone_repo_dir = temp_dir.path().join("clone");
+    let source_repo_dir = setup.source_repo_dir.to_str().unwrap();
+    // Only the push URL is rewritten to the source repo.
+    testutils::git::set_config(&clone_repo_dir, "remote.origin.url", "fake:source");
+    testutils::git::set_config(
+        &clone_repo_dir,
+        &format!("url.{source_repo_dir}.pushInsteadOf"),
+        "fake:source",
+    );
+
+    let jj_repo = reload_push_repo(&settings, &temp_dir);
+    let result = git::push_updates(
+        jj_repo.as_ref(),
+        &get_git_settings(subprocess),
+        "origin".as_ref(),
+        &[GitRefUpdate {
+            qualified_name: "refs/heads/main".into(),
+            expected_current_target: Some(setup.main_commit.id().clone()),
+            new_target: Some(setup.child_of_main_commit.id().clone()),
+        }],
+        &GitPushOptions::default(),
+        git::RemoteCallbacks::default(),
+    );
+    assert_eq!(
+        result.unwrap(),
+        GitPushStats {
+            pushed: vec!["refs/heads/main".into()],
+            ..Default::default()
+        }
+    );
+
+    let source_repo = testutils::git::open(&setup.source_repo_dir);
+    let new_target = source_repo.find_reference("refs/heads/main").unwrap();
+    assert_eq!(
+        new_target.target().id(),
+        git_id(&setup.child_of_main_commit)
+    );
+}
+
 #[test]
 fn test_bulk_update_extra_on_import_refs() {
     let git_settings = GitSettings::default();
@@ -4219,9 +4438,17 @@ fn test_shallow_commits_lack_parents() {
     );
 
     // deepen the shallow clone
+    let previously_shallow = get_git_backend(&repo).shallow_commits().unwrap();
+    assert_eq!(previously_shallow, hashset! {jj_id(b), jj_id(c)});
     let repo = make_shallow(&repo, vec![a]);
 
     let mut tx = repo.start_transaction();
+    let unshallowed_ids =
+        git::import_unshallowed_commits(tx.repo_mut(), &previously_shallow).unwrap();
+    assert_eq!(
+        unshallowed_ids.into_iter().collect::<HashSet<_>>(),
+        previously_shallow
+    );
     git::import_refs(tx.repo_mut(), &GitSettings::default()).unwrap();
     let repo = tx.commit("import").unwrap();
     let store = repo.store();
@@ -4242,8 +4469,14 @@ fn test_shallow_commits_lack_parents() {
         vec![jj_id(a)],
         "unshallowed commits have correct parents"
     );
-    // FIXME: new ancestors should be indexed
-    assert!(!repo.index().has_id(&jj_id(a)));
+    assert!(repo.index().has_id(&jj_id(a)));
+    assert!(repo.index().is_ancestor(&jj_id(a), &jj_id(d)));
+    assert!(repo.index().is_ancestor(&jj_id(a), &jj_id(e)));
+    assert_eq!(*repo.view().heads(), expected_heads);
+
+    // the reparented index should be usable after reloading
+    let repo = test_env.load_repo_at_head(&settings, test_repo.repo_path());
+    assert!(repo.index().is_ancestor(&jj_id(a), &jj_id(d)));
 }
 
 #[test]
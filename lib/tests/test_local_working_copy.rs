@@ -26,9 +26,13 @@ use itertools::Itertools as _;
 use jj_lib::backend::MergedTreeId;
 use jj_lib::backend::TreeId;
 use jj_lib::backend::TreeValue;
+use jj_lib::conflicts::UnparsedConflictHunk;
+use jj_lib::conflicts::UnparsedConflictReason;
 use jj_lib::file_util::check_symlink_support;
 use jj_lib::file_util::try_symlink;
 use jj_lib::fsmonitor::FsmonitorSettings;
+use jj_lib::git_lfs::LfsObjectStore;
+use jj_lib::git_lfs::LfsPointer;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::local_working_copy::LocalWorkingCopy;
 use jj_lib::merge::Merge;
@@ -958,6 +962,85 @@ fn test_materialize_snapshot_conflicted_files() {
     );
 }
 
+#[test]
+fn test_snapshot_validate_conflict_markers() {
+    let mut test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo.clone();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let file1_path = RepoPath::from_internal_string("file1");
+    let file2_path = RepoPath::from_internal_string("file2");
+    let base_tree = create_tree(repo, &[(file1_path, "a\n"), (file2_path, "a\n")]);
+    let side1_tree = create_tree(repo, &[(file1_path, "b\n"), (file2_path, "b\n")]);
+    let side2_tree = create_tree(repo, &[(file1_path, "c\n"), (file2_path, "c\n")]);
+    let merged_tree = side1_tree.merge(&base_tree, &side2_tree).unwrap();
+    let commit = commit_with_tree(repo.store(), merged_tree.id());
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+
+    // The end marker of file1 was deleted, and file2 was resolved
+    testutils::write_working_copy_file(
+        &workspace_root,
+        file1_path,
+        indoc! {"
+            <<<<<<< Conflict 1 of 1
+            %%%%%%% Changes from base to side #1
+            -a
+            +b
+            +++++++ Contents of side #2
+            c
+        "},
+    );
+    testutils::write_working_copy_file(&workspace_root, file2_path, "b\n");
+
+    let options = SnapshotOptions {
+        validate_conflict_markers: true,
+        ..SnapshotOptions::empty_for_test()
+    };
+    let (tree, stats) = test_workspace.snapshot_with_options(&options).unwrap();
+    assert_eq!(
+        tree.path_value(file1_path).unwrap(),
+        merged_tree.path_value(file1_path).unwrap()
+    );
+    assert!(tree.path_value(file2_path).unwrap().is_resolved());
+    assert_eq!(
+        stats.unparsed_conflicts.keys().collect_vec(),
+        [&file1_path.to_owned()]
+    );
+    assert_eq!(
+        stats.unparsed_conflicts[file1_path],
+        [UnparsedConflictHunk {
+            first_line: 1,
+            last_line: 1,
+            reason: UnparsedConflictReason::MissingEnd,
+        }]
+    );
+
+    // The file state of file1 isn't updated, so the next snapshot examines the
+    // file again instead of treating it as unchanged.
+    let (tree, stats) = test_workspace.snapshot_with_options(&options).unwrap();
+    assert_eq!(
+        tree.path_value(file1_path).unwrap(),
+        merged_tree.path_value(file1_path).unwrap()
+    );
+    assert_eq!(
+        stats.unparsed_conflicts.keys().collect_vec(),
+        [&file1_path.to_owned()]
+    );
+
+    // Once the markers are fixed, the file is snapshotted as usual
+    testutils::write_working_copy_file(&workspace_root, file1_path, "b\n");
+    let (tree, stats) = test_workspace.snapshot_with_options(&options).unwrap();
+    assert!(tree.path_value(file1_path).unwrap().is_resolved());
+    assert!(stats.unparsed_conflicts.is_empty());
+}
+
 #[test]
 fn test_snapshot_racy_timestamps() {
     // Tests that file modifications are detected even if they happen the same
@@ -2231,3 +2314,171 @@ fn test_snapshot_max_new_file_size() {
         UntrackedReason::FileTooLarge { .. }
     );
 }
+
+#[test]
+fn test_snapshot_and_checkout_lfs_files() {
+    let mut test_workspace = TestWorkspace::init();
+    let repo = test_workspace.repo.clone();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+    let lfs_store = Arc::new(LfsObjectStore::new(test_workspace.root_dir().join("lfs")));
+
+    let attributes_path = RepoPath::from_internal_string(".gitattributes");
+    let lfs_path = RepoPath::from_internal_string("sub/large.bin");
+    let normal_path = RepoPath::from_internal_string("sub/normal.txt");
+    let lfs_content = vec![b'x'; 2048];
+    std::fs::write(
+        attributes_path.to_fs_path_unchecked(&workspace_root),
+        "*.bin filter=lfs\n",
+    )
+    .unwrap();
+    std::fs::create_dir(workspace_root.join("sub")).unwrap();
+    std::fs::write(lfs_path.to_fs_path_unchecked(&workspace_root), &lfs_content).unwrap();
+    std::fs::write(
+        normal_path.to_fs_path_unchecked(&workspace_root),
+        "normal\n",
+    )
+    .unwrap();
+
+    // LFS files are committed as pointers, and aren't rejected because of
+    // their size
+    let options = SnapshotOptions {
+        max_new_file_size: 1024,
+        lfs_store: Some(lfs_store.clone()),
+        ..SnapshotOptions::empty_for_test()
+    };
+    let (tree, stats) = test_workspace.snapshot_with_options(&options).unwrap();
+    assert!(stats.untracked_paths.is_empty());
+    let TreeValue::File { id, .. } = tree
+        .path_value(lfs_path)
+        .unwrap()
+        .into_resolved()
+        .unwrap()
+        .unwrap()
+    else {
+        panic!("LFS file should be a file");
+    };
+    let pointer = LfsPointer::parse(&testutils::read_file(repo.store(), lfs_path, &id)).unwrap();
+    assert_eq!(pointer.size, 2048);
+    assert!(lfs_store.has_object(&pointer));
+    let TreeValue::File { id, .. } = tree
+        .path_value(normal_path)
+        .unwrap()
+        .into_resolved()
+        .unwrap()
+        .unwrap()
+    else {
+        panic!("normal file should be a file");
+    };
+    assert_eq!(
+        testutils::read_file(repo.store(), normal_path, &id),
+        b"normal\n"
+    );
+
+    // Snapshotting again doesn't change the tree
+    let (tree_again, _stats) = test_workspace.snapshot_with_options(&options).unwrap();
+    assert_eq!(tree_again.id(), tree.id());
+
+    // Pointers are smudged on checkout
+    let commit = commit_with_tree(repo.store(), tree.id());
+    let empty_commit = commit_with_tree(repo.store(), repo.store().empty_merged_tree_id());
+    let checkout_options = CheckoutOptions {
+        lfs_store: Some(lfs_store.clone()),
+        ..CheckoutOptions::empty_for_test()
+    };
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(repo.op_id().clone(), None, &empty_commit, &checkout_options)
+        .unwrap();
+    assert!(!lfs_path.to_fs_path_unchecked(&workspace_root).exists());
+    ws.check_out(repo.op_id().clone(), None, &commit, &checkout_options)
+        .unwrap();
+    assert_eq!(
+        std::fs::read(lfs_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        lfs_content
+    );
+    let (tree_again, _stats) = test_workspace.snapshot_with_options(&options).unwrap();
+    assert_eq!(tree_again.id(), tree.id());
+
+    // Without the LFS store, pointers are checked out as is
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &empty_commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+    assert_eq!(
+        std::fs::read(lfs_path.to_fs_path_unchecked(&workspace_root)).unwrap(),
+        pointer.to_bytes()
+    );
+}
+
+#[test]
+fn test_snapshot_and_checkout_eol_conversion() {
+    let mut test_workspace = TestWorkspace::init();
+    let repo = test_workspace.repo.clone();
+    let workspace_root = test_workspace.workspace.workspace_root().to_owned();
+
+    let attributes_path = RepoPath::from_internal_string(".gitattributes");
+    let crlf_path = RepoPath::from_internal_string("crlf.txt");
+    let lf_path = RepoPath::from_internal_string("lf.txt");
+    let binary_path = RepoPath::from_internal_string("crlf.bin");
+    let attributes = "*.txt text\ncrlf.* eol=crlf\n*.bin binary\n";
+    let tree = create_tree(
+        &repo,
+        &[
+            (attributes_path, attributes),
+            (crlf_path, "a\nb\n"),
+            (lf_path, "a\nb\n"),
+            (binary_path, "a\nb\n"),
+        ],
+    );
+    let commit = commit_with_tree(repo.store(), tree.id());
+
+    // Files with eol=crlf are checked out with CRLF line endings
+    let ws = &mut test_workspace.workspace;
+    ws.check_out(
+        repo.op_id().clone(),
+        None,
+        &commit,
+        &CheckoutOptions::empty_for_test(),
+    )
+    .unwrap();
+    let read = |path: &RepoPath| std::fs::read(path.to_fs_path_unchecked(&workspace_root)).unwrap();
+    assert_eq!(read(crlf_path), b"a\r\nb\r\n");
+    if !cfg!(windows) {
+        assert_eq!(read(lf_path), b"a\nb\n");
+    }
+    assert_eq!(read(binary_path), b"a\nb\n");
+
+    // Unmodified files are snapshotted as they were in the store
+    let new_tree = test_workspace.snapshot().unwrap();
+    assert_eq!(new_tree.id(), tree.id());
+
+    // CRLF line endings of text files are normalized when snapshotting
+    std::fs::write(crlf_path.to_fs_path_unchecked(&workspace_root), "a\r\nc\n").unwrap();
+    std::fs::write(lf_path.to_fs_path_unchecked(&workspace_root), "a\r\nc\r\n").unwrap();
+    std::fs::write(
+        binary_path.to_fs_path_unchecked(&workspace_root),
+        "a\r\nc\r\n",
+    )
+    .unwrap();
+    let new_tree = test_workspace.snapshot().unwrap();
+    let expected_tree = create_tree(
+        &repo,
+        &[
+            (attributes_path, attributes),
+            (crlf_path, "a\nc\n"),
+            (lf_path, "a\nc\n"),
+            (binary_path, "a\r\nc\r\n"),
+        ],
+    );
+    assert_eq!(new_tree.id(), expected_tree.id());
+}
@@ -89,6 +89,7 @@ fn resolve_symbol_with_extensions(
         date_pattern_context: chrono::Local::now().into(),
         extensions,
         workspace: None,
+        mailmap: None,
     };
     let expression = parse(&mut RevsetDiagnostics::new(), symbol, &context).unwrap();
     assert_matches!(*expression, RevsetExpression::CommitRef(_));
@@ -230,6 +231,7 @@ fn test_resolve_symbol_commit_id() {
         date_pattern_context: chrono::Utc::now().fixed_offset().into(),
         extensions: &RevsetExtensions::default(),
         workspace: None,
+        mailmap: None,
     };
     assert_matches!(
         parse(&mut RevsetDiagnostics::new(), "present(04)", &context).unwrap()
@@ -930,6 +932,7 @@ fn try_resolve_commit_ids(
         date_pattern_context: chrono::Utc::now().fixed_offset().into(),
         extensions: &RevsetExtensions::default(),
         workspace: None,
+        mailmap: None,
     };
     let expression = parse(&mut RevsetDiagnostics::new(), revset_str, &context).unwrap();
     let symbol_resolver = DefaultSymbolResolver::new(repo, context.extensions.symbol_resolvers());
@@ -964,6 +967,7 @@ fn resolve_commit_ids_in_workspace(
         date_pattern_context: chrono::Utc::now().fixed_offset().into(),
         extensions: &RevsetExtensions::default(),
         workspace: Some(workspace_ctx),
+        mailmap: None,
     };
     let expression = parse(&mut RevsetDiagnostics::new(), revset_str, &context).unwrap();
     let symbol_resolver =
@@ -3968,6 +3972,71 @@ fn test_evaluate_expression_conflict() {
         resolve_commit_ids(mut_repo, "conflicts()"),
         vec![commit4.id().clone()]
     );
+
+    // commit5 inherits the conflict in `file1`, and commit6 adds another
+    // conflict in `file2`
+    let tree5 = create_tree(repo, &[(file_path1, "3"), (file_path2, "3")]);
+    let tree6 = tree2.merge(&tree1, &tree5).unwrap();
+    let commit5 = mut_repo
+        .new_commit(vec![commit4.id().clone()], tree4.id())
+        .write()
+        .unwrap();
+    let commit6 = mut_repo
+        .new_commit(vec![commit5.id().clone()], tree6.id())
+        .write()
+        .unwrap();
+    let workspace = &test_workspace.workspace;
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "conflicts()"),
+        vec![
+            commit6.id().clone(),
+            commit5.id().clone(),
+            commit4.id().clone(),
+        ]
+    );
+    assert_eq!(
+        resolve_commit_ids_in_workspace(mut_repo, "conflicts(file2)", workspace, None),
+        vec![commit6.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids_in_workspace(mut_repo, "conflicts(nonexistent)", workspace, None),
+        vec![]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "conflicts(new)"),
+        vec![commit6.id().clone(), commit4.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids_in_workspace(mut_repo, "conflicts(new, file1)", workspace, None),
+        vec![commit4.id().clone()]
+    );
+
+    // A merge commit keeping the conflict of one of its parents doesn't have a
+    // new conflict, but one whose non-conflicted parents merge with conflicts
+    // does
+    let commit7 = mut_repo
+        .new_commit(vec![commit4.id().clone(), commit1.id().clone()], tree4.id())
+        .write()
+        .unwrap();
+    let commit8 = mut_repo
+        .new_commit(vec![commit1.id().clone()], tree3.id())
+        .write()
+        .unwrap();
+    let commit9 = mut_repo
+        .new_commit(vec![commit2.id().clone(), commit8.id().clone()], tree4.id())
+        .write()
+        .unwrap();
+    let conflicts = resolve_commit_ids(mut_repo, "conflicts()");
+    assert!(conflicts.contains(commit7.id()));
+    assert!(conflicts.contains(commit9.id()));
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "conflicts(new)"),
+        vec![
+            commit9.id().clone(),
+            commit6.id().clone(),
+            commit4.id().clone(),
+        ]
+    );
 }
 
 #[test]
@@ -14,6 +14,7 @@
 
 use itertools::Itertools as _;
 use jj_lib::commit::Commit;
+use jj_lib::files::FileMergeWhitespace;
 use jj_lib::matchers::EverythingMatcher;
 use jj_lib::matchers::FilesMatcher;
 use jj_lib::merge::Merge;
@@ -34,6 +35,7 @@ use jj_lib::rewrite::CommitRewriter;
 use jj_lib::rewrite::CommitWithSelection;
 use jj_lib::rewrite::EmptyBehaviour;
 use jj_lib::rewrite::RebaseOptions;
+use jj_lib::rewrite::RebasedCommit;
 use jj_lib::rewrite::RewriteRefsOptions;
 use maplit::hashmap;
 use maplit::hashset;
@@ -925,6 +927,88 @@ fn test_rebase_descendants_contents() {
     );
 }
 
+#[test]
+fn test_rebase_records_conflict_labels() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // Rebase C onto B, then C' onto B2. The conflicts in C' and C'' should be
+    // labeled with the commits their terms came from.
+    //
+    // B2 B C
+    //  \ | /
+    //    A
+    let mut tx = repo.start_transaction();
+    let path = RepoPath::from_internal_string("file");
+    let mut new_commit = |parent: &Commit, content: &str, description: &str| {
+        let tree = create_tree(repo, &[(path, content)]);
+        tx.repo_mut()
+            .new_commit(vec![parent.id().clone()], tree.id())
+            .set_description(description)
+            .write()
+            .unwrap()
+    };
+    let root_commit = repo.store().root_commit();
+    let commit_a = new_commit(&root_commit, "a\n", "A");
+    let commit_b = new_commit(&commit_a, "b\n", "B");
+    let commit_b2 = new_commit(&commit_a, "b2\n", "");
+    let commit_c = new_commit(&commit_a, "c\n", "C");
+    let labels = |commits: &[&Commit]| {
+        Some(Merge::from_vec(
+            commits
+                .iter()
+                .map(|commit| commit.conflict_label())
+                .collect_vec(),
+        ))
+    };
+    assert_eq!(commit_c.conflict_labels(), None);
+    assert_eq!(
+        commit_b.conflict_label(),
+        format!("{:.12} {:.12} \"B\"", commit_b.change_id(), commit_b.id())
+    );
+    assert_eq!(
+        commit_b2.conflict_label(),
+        format!(
+            "{:.12} {:.12} (no description set)",
+            commit_b2.change_id(),
+            commit_b2.id()
+        )
+    );
+
+    let new_commit_c =
+        CommitRewriter::new(tx.repo_mut(), commit_c.clone(), vec![commit_b.id().clone()])
+            .rebase()
+            .unwrap()
+            .write()
+            .unwrap();
+    assert!(new_commit_c.has_conflict().unwrap());
+    assert_eq!(
+        new_commit_c.conflict_labels(),
+        labels(&[&commit_b, &commit_a, &commit_c])
+    );
+
+    // The labels of the conflicted commit are reused for its terms
+    let new_commit_c2 =
+        CommitRewriter::new(tx.repo_mut(), new_commit_c, vec![commit_b2.id().clone()])
+            .rebase()
+            .unwrap()
+            .write()
+            .unwrap();
+    assert_eq!(
+        new_commit_c2.conflict_labels(),
+        labels(&[&commit_b2, &commit_a, &commit_c])
+    );
+
+    // The labels are dropped when the conflict is resolved
+    let resolved_commit = tx
+        .repo_mut()
+        .rewrite_commit(&new_commit_c2)
+        .set_tree_id(commit_c.tree_id().clone())
+        .write()
+        .unwrap();
+    assert_eq!(resolved_commit.conflict_labels(), None);
+}
+
 #[test]
 fn test_rebase_descendants_basic_bookmark_update() {
     let test_repo = TestRepo::init();
@@ -1706,6 +1790,7 @@ fn test_empty_commit_option(empty_behavior: EmptyBehaviour) {
                 delete_abandoned_bookmarks: false,
             },
             simplify_ancestor_merge: true,
+            whitespace: FileMergeWhitespace::Exact,
         },
     );
 
@@ -1841,6 +1926,7 @@ fn test_rebase_abandoning_empty() {
             delete_abandoned_bookmarks: false,
         },
         simplify_ancestor_merge: true,
+        whitespace: FileMergeWhitespace::Exact,
     };
     let rewriter = CommitRewriter::new(tx.repo_mut(), commit_b, vec![commit_b2.id().clone()]);
     rebase_commit_with_options(rewriter, &rebase_options).unwrap();
@@ -1892,3 +1978,57 @@ fn test_commit_with_selection() {
     assert!(!full_selection.is_empty_selection());
     assert!(full_selection.is_full_selection());
 }
+
+#[test]
+fn test_rebase_resolving_whitespace_conflicts() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let path = RepoPath::from_internal_string("file");
+
+    // B and C reformatted the same line differently
+    let mut tx = repo.start_transaction();
+    let tree_a = create_tree(repo, &[(path, "a\nf(x,y)\n")]);
+    let tree_b = create_tree(repo, &[(path, "a\nf(x, y)\n")]);
+    let tree_c = create_tree(repo, &[(path, "a\nf( x, y )\n")]);
+    let commit_a = tx
+        .repo_mut()
+        .new_commit(vec![repo.store().root_commit_id().clone()], tree_a.id())
+        .write()
+        .unwrap();
+    let commit_b = tx
+        .repo_mut()
+        .new_commit(vec![commit_a.id().clone()], tree_b.id())
+        .write()
+        .unwrap();
+    let commit_c = tx
+        .repo_mut()
+        .new_commit(vec![commit_a.id().clone()], tree_c.id())
+        .write()
+        .unwrap();
+    let repo = tx.commit("test").unwrap();
+
+    let rebase_c_onto_b = |whitespace| {
+        let mut tx = repo.start_transaction();
+        let rewriter =
+            CommitRewriter::new(tx.repo_mut(), commit_c.clone(), vec![commit_b.id().clone()]);
+        let options = RebaseOptions {
+            whitespace,
+            ..Default::default()
+        };
+        let RebasedCommit::Rewritten(new_commit) =
+            rebase_commit_with_options(rewriter, &options).unwrap()
+        else {
+            panic!("commit shouldn't be abandoned");
+        };
+        new_commit.tree().unwrap()
+    };
+    assert!(rebase_c_onto_b(FileMergeWhitespace::Exact).has_conflict());
+    // The destination's formatting is taken by default
+    let expected_tree = create_tree(&repo, &[(path, "a\nf(x, y)\n")]);
+    assert_eq!(
+        rebase_c_onto_b(FileMergeWhitespace::IgnoreAllSpace).id(),
+        expected_tree.id()
+    );
+    // "f(x, y)" and "f( x, y )" differ in more than the amount of whitespace
+    assert!(rebase_c_onto_b(FileMergeWhitespace::IgnoreSpaceChange).has_conflict());
+}
@@ -0,0 +1,414 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::backend::TreeValue;
+use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::tool_protocol::ConflictHunk;
+use jj_lib::tool_protocol::ReadResult;
+use jj_lib::tool_protocol::ResponseError;
+use jj_lib::tool_protocol::ToolFileInfo;
+use jj_lib::tool_protocol::ToolFileSide;
+use jj_lib::tool_protocol::ToolFileType;
+use jj_lib::tool_protocol::ToolSession;
+use jj_lib::tool_protocol::ToolSessionMode;
+use jj_lib::tool_protocol::ToolSessionState;
+use jj_lib::tool_protocol::INVALID_PARAMS;
+use jj_lib::tool_protocol::INVALID_REQUEST;
+use jj_lib::tool_protocol::METHOD_NOT_FOUND;
+use jj_lib::tool_protocol::PARSE_ERROR;
+use pollster::FutureExt as _;
+use serde_json::json;
+use testutils::create_tree;
+use testutils::tool_protocol::ToolClient;
+use testutils::TestRepo;
+
+fn file_info(path: &str, num_sides: usize) -> ToolFileInfo {
+    ToolFileInfo {
+        path: path.to_owned(),
+        num_sides,
+        read_only: false,
+    }
+}
+
+fn file_content(file_type: ToolFileType, content: Option<&str>) -> ReadResult {
+    ReadResult {
+        file_type,
+        content: content.map(ToOwned::to_owned),
+        executable: false,
+    }
+}
+
+#[test]
+fn test_diff_session() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let path_a = RepoPath::from_internal_string("a");
+    let path_b = RepoPath::from_internal_string("b");
+    let path_c = RepoPath::from_internal_string("c");
+    let path_d = RepoPath::from_internal_string("d");
+    let left_tree = create_tree(
+        repo,
+        &[(path_a, "a\n"), (path_b, "b\n"), (path_d, "unchanged\n")],
+    );
+    let right_tree = create_tree(
+        repo,
+        &[(path_a, "a2\n"), (path_c, "c\n"), (path_d, "unchanged\n")],
+    );
+
+    let mut session = ToolSession::for_diff(
+        &left_tree,
+        &right_tree,
+        &EverythingMatcher,
+        ConflictMarkerStyle::Diff,
+    )
+    .unwrap();
+    let mut client = ToolClient::in_process(&mut session);
+    let init = client.initialize();
+    assert_eq!(init.mode, ToolSessionMode::Diff);
+    assert_eq!(
+        init.files,
+        vec![file_info("a", 1), file_info("b", 1), file_info("c", 1)]
+    );
+
+    assert_eq!(
+        client.read("a", ToolFileSide::Left),
+        file_content(ToolFileType::File, Some("a\n"))
+    );
+    assert_eq!(
+        client.read("a", ToolFileSide::Right),
+        file_content(ToolFileType::File, Some("a2\n"))
+    );
+    assert_eq!(
+        client.read("b", ToolFileSide::Right),
+        file_content(ToolFileType::Absent, None)
+    );
+
+    client.write("a", Some("edited\n"));
+    client.write("b", Some("restored\n"));
+    client.write("c", None);
+    assert_eq!(
+        client.read("a", ToolFileSide::Output),
+        file_content(ToolFileType::File, Some("edited\n"))
+    );
+    assert_eq!(
+        client.read("a", ToolFileSide::Right),
+        file_content(ToolFileType::File, Some("a2\n"))
+    );
+    client.finish();
+    assert_eq!(session.state(), ToolSessionState::Finished);
+
+    let expected_tree = create_tree(
+        repo,
+        &[
+            (path_a, "edited\n"),
+            (path_b, "restored\n"),
+            (path_d, "unchanged\n"),
+        ],
+    );
+    assert_eq!(session.write_tree().unwrap(), expected_tree.id());
+}
+
+#[test]
+fn test_merge_session() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let path = RepoPath::from_internal_string("file");
+    let base_tree = create_tree(repo, &[(path, "a\nb\nc\n")]);
+    let side1_tree = create_tree(repo, &[(path, "a1\nb\nc1\n")]);
+    let side2_tree = create_tree(repo, &[(path, "a2\nb\nc2\n")]);
+    let tree = side1_tree.merge(&base_tree, &side2_tree).unwrap();
+    let conflict = tree.path_value(path).unwrap();
+    assert!(!conflict.is_resolved());
+
+    let mut session = ToolSession::for_merge(
+        &tree,
+        [(path.to_owned(), conflict)],
+        ConflictMarkerStyle::Git,
+    );
+    let mut client = ToolClient::in_process(&mut session);
+    let init = client.initialize();
+    assert_eq!(init.mode, ToolSessionMode::Merge);
+    assert_eq!(init.files, vec![file_info("file", 2)]);
+
+    let conflict = client.conflict("file");
+    assert_eq!(conflict.sides, vec!["a1\nb\nc1\n", "a2\nb\nc2\n"]);
+    assert_eq!(conflict.bases, vec!["a\nb\nc\n"]);
+    let conflict_hunk = |side1: &str, base: &str, side2: &str| ConflictHunk {
+        resolved: None,
+        sides: vec![side1.to_owned(), side2.to_owned()],
+        bases: vec![base.to_owned()],
+    };
+    assert_eq!(
+        conflict.hunks,
+        vec![
+            conflict_hunk("a1\n", "a\n", "a2\n"),
+            ConflictHunk {
+                resolved: Some("b\n".to_owned()),
+                sides: vec![],
+                bases: vec![],
+            },
+            conflict_hunk("c1\n", "c\n", "c2\n"),
+        ]
+    );
+
+    let output = client.read("file", ToolFileSide::Output);
+    assert_eq!(output.file_type, ToolFileType::Conflict);
+    insta::assert_snapshot!(output.content.unwrap(), @r"
+    <<<<<<< Side #1 (Conflict 1 of 2)
+    a1
+    ||||||| Base
+    a
+    =======
+    a2
+    >>>>>>> Side #2 (Conflict 1 of 2 ends)
+    b
+    <<<<<<< Side #1 (Conflict 2 of 2)
+    c1
+    ||||||| Base
+    c
+    =======
+    c2
+    >>>>>>> Side #2 (Conflict 2 of 2 ends)
+    ");
+
+    client.write("file", Some("a1\nb\nc2\n"));
+    client.finish();
+
+    let expected_tree = create_tree(repo, &[(path, "a1\nb\nc2\n")]);
+    assert_eq!(session.write_tree().unwrap(), expected_tree.id());
+}
+
+#[test]
+fn test_merge_session_conflict_markers() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let path = RepoPath::from_internal_string("file");
+    let base_tree = create_tree(repo, &[(path, "a\n")]);
+    let side1_tree = create_tree(repo, &[(path, "a1\n")]);
+    let side2_tree = create_tree(repo, &[(path, "a2\n")]);
+    let tree = side1_tree.merge(&base_tree, &side2_tree).unwrap();
+    let conflict = tree.path_value(path).unwrap();
+
+    // Writing back the conflict markers leaves the file conflicted
+    let mut session = ToolSession::for_merge(
+        &tree,
+        [(path.to_owned(), conflict)],
+        ConflictMarkerStyle::Diff,
+    );
+    let mut client = ToolClient::in_process(&mut session);
+    let output = client.read("file", ToolFileSide::Output);
+    client.write_with_conflict_markers("file", output.content.as_deref().unwrap());
+    assert_eq!(client.read("file", ToolFileSide::Output), output);
+    client.finish();
+    assert_eq!(session.write_tree().unwrap(), tree.id());
+}
+
+#[test]
+fn test_diff_session_read_only_files() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+    let store = repo.store();
+
+    let binary_path = RepoPath::from_internal_string("binary");
+    let symlink_path = RepoPath::from_internal_string("symlink");
+    let text_path = RepoPath::from_internal_string("text");
+    let left_tree = create_tree(repo, &[(text_path, "a\n")]);
+    let create_right_tree = |text: &str| {
+        let mut tree_builder = store.tree_builder(store.empty_tree_id().clone());
+        let binary_id = store
+            .write_file(binary_path, &mut &b"\xff\xfe\n"[..])
+            .block_on()
+            .unwrap();
+        tree_builder.set(
+            binary_path.to_owned(),
+            TreeValue::File {
+                id: binary_id,
+                executable: false,
+            },
+        );
+        testutils::write_symlink(&mut tree_builder, symlink_path, "target");
+        testutils::write_normal_file(&mut tree_builder, text_path, text);
+        let tree_id = tree_builder.write_tree().unwrap();
+        MergedTree::resolved(store.get_tree(RepoPathBuf::root(), &tree_id).unwrap())
+    };
+    let right_tree = create_right_tree("b\n");
+
+    let mut session = ToolSession::for_diff(
+        &left_tree,
+        &right_tree,
+        &EverythingMatcher,
+        ConflictMarkerStyle::Diff,
+    )
+    .unwrap();
+    let mut client = ToolClient::in_process(&mut session);
+    let read_only_file_info = |path: &str| ToolFileInfo {
+        read_only: true,
+        ..file_info(path, 1)
+    };
+    assert_eq!(
+        client.initialize().files,
+        vec![
+            read_only_file_info("binary"),
+            read_only_file_info("symlink"),
+            file_info("text", 1),
+        ]
+    );
+    assert_eq!(
+        client.read("binary", ToolFileSide::Right),
+        file_content(ToolFileType::File, None)
+    );
+    assert_eq!(
+        client.read("symlink", ToolFileSide::Right),
+        file_content(ToolFileType::Symlink, Some("target"))
+    );
+
+    // Read-only files can't be written or deleted
+    let error_code = |result: Result<_, ResponseError>| result.unwrap_err().code;
+    assert_eq!(
+        error_code(client.call("write", json!({ "path": "binary", "content": "" }))),
+        INVALID_PARAMS
+    );
+    assert_eq!(
+        error_code(client.call("write", json!({ "path": "binary", "content": null }))),
+        INVALID_PARAMS
+    );
+    assert_eq!(
+        error_code(client.call("write", json!({ "path": "symlink", "content": "other" }))),
+        INVALID_PARAMS
+    );
+    client.write("text", Some("c\n"));
+    client.finish();
+
+    assert_eq!(session.write_tree().unwrap(), create_right_tree("c\n").id());
+}
+
+#[test]
+fn test_session_errors() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let path = RepoPath::from_internal_string("file");
+    let base_tree = create_tree(repo, &[(path, "a\n")]);
+    let side1_tree = create_tree(repo, &[(path, "a1\n")]);
+    let side2_tree = create_tree(repo, &[(path, "a2\n")]);
+    let tree = side1_tree.merge(&base_tree, &side2_tree).unwrap();
+    let conflict = tree.path_value(path).unwrap();
+
+    let mut session = ToolSession::for_merge(
+        &tree,
+        [(path.to_owned(), conflict)],
+        ConflictMarkerStyle::Diff,
+    );
+    let mut client = ToolClient::in_process(&mut session);
+    let error_code = |result: Result<_, ResponseError>| result.unwrap_err().code;
+    assert_eq!(
+        error_code(client.call("unknown", json!(null))),
+        METHOD_NOT_FOUND
+    );
+    assert_eq!(
+        error_code(client.call("read", json!({ "path": "other", "side": "right" }))),
+        INVALID_PARAMS
+    );
+    assert_eq!(
+        error_code(client.call("read", json!({ "path": "file", "side": "left" }))),
+        INVALID_PARAMS
+    );
+    assert_eq!(
+        error_code(client.call("write", json!({ "content": "" }))),
+        INVALID_PARAMS
+    );
+    let response = client.send_message("not json");
+    assert!(response.contains(&PARSE_ERROR.to_string()), "{response}");
+
+    // Nothing is written if the session is aborted
+    client.write("file", Some("resolved\n"));
+    client.abort();
+    assert_eq!(
+        error_code(client.call("finish", json!(null))),
+        INVALID_REQUEST
+    );
+    assert_eq!(session.state(), ToolSessionState::Aborted);
+    assert_eq!(session.write_tree().unwrap(), tree.id());
+}
+
+#[test]
+fn test_session_serve() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let path = RepoPath::from_internal_string("file");
+    let left_tree = create_tree(repo, &[(path, "a\n")]);
+    let right_tree = create_tree(repo, &[(path, "b\n")]);
+    let mut session = ToolSession::for_diff(
+        &left_tree,
+        &right_tree,
+        &EverythingMatcher,
+        ConflictMarkerStyle::Diff,
+    )
+    .unwrap();
+
+    let requests = indoc::indoc! {r#"
+        {"jsonrpc": "2.0", "id": 1, "method": "write", "params": {"path": "file", "content": "c\n"}}
+
+        {"jsonrpc": "2.0", "id": 2, "method": "finish"}
+        {"jsonrpc": "2.0", "id": 3, "method": "initialize"}
+    "#};
+    let mut output = vec![];
+    session
+        .serve(&mut requests.as_bytes(), &mut output)
+        .unwrap();
+    insta::assert_snapshot!(String::from_utf8(output).unwrap(), @r#"
+    {"jsonrpc":"2.0","id":1,"result":null}
+    {"jsonrpc":"2.0","id":2,"result":null}
+    "#);
+    assert_eq!(session.state(), ToolSessionState::Finished);
+    let expected_tree = create_tree(repo, &[(path, "c\n")]);
+    assert_eq!(session.write_tree().unwrap(), expected_tree.id());
+}
+
+#[test]
+fn test_session_serve_disconnected() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    let path = RepoPath::from_internal_string("file");
+    let left_tree = create_tree(repo, &[(path, "a\n")]);
+    let right_tree = create_tree(repo, &[(path, "b\n")]);
+    let mut session = ToolSession::for_diff(
+        &left_tree,
+        &right_tree,
+        &EverythingMatcher,
+        ConflictMarkerStyle::Diff,
+    )
+    .unwrap();
+
+    // The tool exits without finishing or aborting the session
+    let requests = indoc::indoc! {r#"
+        {"jsonrpc": "2.0", "id": 1, "method": "write", "params": {"path": "file", "content": "c\n"}}
+    "#};
+    let mut output = vec![];
+    session
+        .serve(&mut requests.as_bytes(), &mut output)
+        .unwrap();
+    assert_eq!(session.state(), ToolSessionState::Disconnected);
+    assert_eq!(session.write_tree().unwrap(), right_tree.id());
+}
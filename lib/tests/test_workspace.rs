@@ -19,6 +19,7 @@ use jj_lib::ref_name::WorkspaceNameBuf;
 use jj_lib::repo::Repo as _;
 use jj_lib::workspace::default_working_copy_factories;
 use jj_lib::workspace::default_working_copy_factory;
+use jj_lib::workspace::set_workspace_repo_path;
 use jj_lib::workspace::Workspace;
 use jj_lib::workspace::WorkspaceLoadError;
 use testutils::TestEnvironment;
@@ -92,6 +93,50 @@ fn test_init_additional_workspace() {
     assert_eq!(same_workspace.workspace_root(), ws2.workspace_root());
 }
 
+#[test]
+fn test_load_stale_repo_path() {
+    let settings = testutils::user_settings();
+    let test_workspace = TestWorkspace::init_with_settings(&settings);
+
+    let ws2_root = test_workspace.root_dir().join("ws2_root");
+    std::fs::create_dir(&ws2_root).unwrap();
+    Workspace::init_workspace_with_existing_repo(
+        &ws2_root,
+        test_workspace.repo_path(),
+        &test_workspace.repo,
+        &*default_working_copy_factory(),
+        WorkspaceNameBuf::from("ws2"),
+    )
+    .unwrap();
+
+    // Simulate that the workspace containing the repo was moved
+    let stale_repo_path = test_workspace.root_dir().join("moved").join(".jj").join("repo");
+    std::fs::write(
+        ws2_root.join(".jj").join("repo"),
+        stale_repo_path.to_str().unwrap(),
+    )
+    .unwrap();
+    let result = Workspace::load(
+        &settings,
+        &ws2_root,
+        &test_workspace.env.default_store_factories(),
+        &default_working_copy_factories(),
+    );
+    assert_matches!(
+        result.err(),
+        Some(WorkspaceLoadError::RepoDoesNotExist(path)) if path == stale_repo_path
+    );
+
+    set_workspace_repo_path(&ws2_root, test_workspace.repo_path()).unwrap();
+    let result = Workspace::load(
+        &settings,
+        &ws2_root,
+        &test_workspace.env.default_store_factories(),
+        &default_working_copy_factories(),
+    );
+    assert!(result.is_ok());
+}
+
 /// Test cross-thread access to a workspace, which requires it to be Send
 #[test]
 fn test_sendable() {
@@ -355,6 +355,26 @@ impl<'a> IndexManager<'a> {
     }
 }
 
+/// Attaches a note to the `object` in `refs/notes/commits` using `git notes`.
+pub fn add_note(repo_dir: impl AsRef<Path>, object: &str, message: &str) {
+    let output = std::process::Command::new("git")
+        .current_dir(repo_dir)
+        .args(["notes", "add", "-m", message, object])
+        .env("GIT_AUTHOR_NAME", "Test User")
+        .env("GIT_AUTHOR_EMAIL", "test.user@example.com")
+        .env("GIT_COMMITTER_NAME", "Test User")
+        .env("GIT_COMMITTER_EMAIL", "test.user@example.com")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git notes add {object} failed with {}:\n{}\n----- stderr -----\n{}",
+        output.status,
+        bstr::BString::from(output.stdout),
+        bstr::BString::from(output.stderr),
+    );
+}
+
 pub fn add_remote(repo_dir: impl AsRef<Path>, remote_name: &str, url: &str) {
     let output = std::process::Command::new("git")
         .current_dir(repo_dir)
@@ -370,6 +390,21 @@ pub fn add_remote(repo_dir: impl AsRef<Path>, remote_name: &str, url: &str) {
     );
 }
 
+pub fn set_config(repo_dir: impl AsRef<Path>, key: &str, value: &str) {
+    let output = std::process::Command::new("git")
+        .current_dir(repo_dir)
+        .args(["config", key, value])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git config {key} {value} failed with {}:\n{}\n----- stderr -----\n{}",
+        output.status,
+        bstr::BString::from(output.stdout),
+        bstr::BString::from(output.stderr),
+    );
+}
+
 pub fn rename_remote(repo_dir: impl AsRef<Path>, original: &str, new: &str) {
     let output = std::process::Command::new("git")
         .current_dir(repo_dir)
@@ -70,6 +70,7 @@ use crate::test_backend::TestBackendFactory;
 
 pub mod git;
 pub mod test_backend;
+pub mod tool_protocol;
 
 // TODO: Consider figuring out a way to make `GitBackend` and `git(1)` calls in
 // tests ignore external configuration and removing this function. This is
@@ -454,6 +455,7 @@ pub fn commit_with_tree(store: &Arc<Store>, tree_id: MergedTreeId) -> Commit {
         parents: vec![store.root_commit_id().clone()],
         predecessors: vec![],
         root_tree: tree_id,
+        conflict_labels: vec![],
         change_id: ChangeId::from_hex("abcd"),
         description: "description".to_string(),
         author: signature.clone(),
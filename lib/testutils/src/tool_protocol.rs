@@ -0,0 +1,171 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reference implementation of the tool side of
+//! [`jj_lib::tool_protocol`].
+
+use std::io::BufRead;
+use std::io::Write;
+
+use jj_lib::tool_protocol::ConflictParams;
+use jj_lib::tool_protocol::ConflictResult;
+use jj_lib::tool_protocol::InitializeResult;
+use jj_lib::tool_protocol::ReadParams;
+use jj_lib::tool_protocol::ReadResult;
+use jj_lib::tool_protocol::Request;
+use jj_lib::tool_protocol::Response;
+use jj_lib::tool_protocol::ResponseError;
+use jj_lib::tool_protocol::ToolFileSide;
+use jj_lib::tool_protocol::ToolSession;
+use jj_lib::tool_protocol::WriteParams;
+use jj_lib::tool_protocol::JSONRPC_VERSION;
+use serde_json::Value;
+
+enum Transport<'a> {
+    InProcess(&'a mut ToolSession),
+    Stream {
+        reader: Box<dyn BufRead + 'a>,
+        writer: Box<dyn Write + 'a>,
+    },
+}
+
+/// Client sending requests to a [`ToolSession`] like an external tool would.
+///
+/// The typed methods panic if the request fails. Use [`ToolClient::call()`]
+/// to test errors.
+pub struct ToolClient<'a> {
+    transport: Transport<'a>,
+    next_id: u64,
+}
+
+impl<'a> ToolClient<'a> {
+    /// Creates a client talking to the `session` in the same process. The
+    /// messages are still serialized to exercise the protocol.
+    pub fn in_process(session: &'a mut ToolSession) -> Self {
+        ToolClient {
+            transport: Transport::InProcess(session),
+            next_id: 0,
+        }
+    }
+
+    /// Creates a client reading responses from the `reader` and writing
+    /// requests to the `writer`, e.g. the stdout and stdin of a process
+    /// serving the session.
+    pub fn with_streams(reader: impl BufRead + 'a, writer: impl Write + 'a) -> Self {
+        ToolClient {
+            transport: Transport::Stream {
+                reader: Box::new(reader),
+                writer: Box::new(writer),
+            },
+            next_id: 0,
+        }
+    }
+
+    /// Sends a request and returns the result or the error of the response.
+    pub fn call(&mut self, method: &str, params: Value) -> Result<Value, ResponseError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = Request {
+            jsonrpc: JSONRPC_VERSION.to_owned(),
+            id: id.into(),
+            method: method.to_owned(),
+            params,
+        };
+        let message = serde_json::to_string(&request).unwrap();
+        let response_message = self.send_message(&message);
+        let response: Response = serde_json::from_str(&response_message).unwrap();
+        assert_eq!(response.jsonrpc, JSONRPC_VERSION);
+        assert_eq!(response.id, Value::from(id));
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(error),
+            (result, None) => Ok(result.unwrap_or(Value::Null)),
+        }
+    }
+
+    /// Sends a raw `message` and returns the raw response.
+    pub fn send_message(&mut self, message: &str) -> String {
+        match &mut self.transport {
+            Transport::InProcess(session) => {
+                let response = session.handle_message(message);
+                serde_json::to_string(&response).unwrap()
+            }
+            Transport::Stream { reader, writer } => {
+                writeln!(writer, "{message}").unwrap();
+                writer.flush().unwrap();
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                line
+            }
+        }
+    }
+
+    fn call_ok(&mut self, method: &str, params: Value) -> Value {
+        self.call(method, params).unwrap()
+    }
+
+    /// Calls the `initialize` method.
+    pub fn initialize(&mut self) -> InitializeResult {
+        serde_json::from_value(self.call_ok("initialize", Value::Null)).unwrap()
+    }
+
+    /// Calls the `read` method.
+    pub fn read(&mut self, path: &str, side: ToolFileSide) -> ReadResult {
+        let params = ReadParams {
+            path: path.to_owned(),
+            side,
+        };
+        let params = serde_json::to_value(params).unwrap();
+        serde_json::from_value(self.call_ok("read", params)).unwrap()
+    }
+
+    /// Calls the `conflict` method.
+    pub fn conflict(&mut self, path: &str) -> ConflictResult {
+        let params = ConflictParams {
+            path: path.to_owned(),
+        };
+        let params = serde_json::to_value(params).unwrap();
+        serde_json::from_value(self.call_ok("conflict", params)).unwrap()
+    }
+
+    /// Calls the `write` method. The file is deleted if `content` is `None`.
+    pub fn write(&mut self, path: &str, content: Option<&str>) {
+        let params = WriteParams {
+            path: path.to_owned(),
+            content: content.map(ToOwned::to_owned),
+            conflict_markers: false,
+        };
+        self.call_ok("write", serde_json::to_value(params).unwrap());
+    }
+
+    /// Calls the `write` method, asking for the conflict markers in the
+    /// `content` to be parsed.
+    pub fn write_with_conflict_markers(&mut self, path: &str, content: &str) {
+        let params = WriteParams {
+            path: path.to_owned(),
+            content: Some(content.to_owned()),
+            conflict_markers: true,
+        };
+        self.call_ok("write", serde_json::to_value(params).unwrap());
+    }
+
+    /// Calls the `finish` method.
+    pub fn finish(&mut self) {
+        self.call_ok("finish", Value::Null);
+    }
+
+    /// Calls the `abort` method.
+    pub fn abort(&mut self) {
+        self.call_ok("abort", Value::Null);
+    }
+}